@@ -35,7 +35,7 @@ fn main() -> std::io::Result<()> {
 
         // alternativeley we can create a InputEvent, which will be any variant of InputEvent
         // depending on the type_ value
-        let up_event = InputEvent::new(EventType::KEY.0, code, 0);
+        let up_event = InputEvent::new(EventType::KEY, code, 0);
         device.emit(&[up_event]).unwrap();
         println!("Released.");
         sleep(Duration::from_secs(2));
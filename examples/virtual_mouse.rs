@@ -81,7 +81,7 @@ fn new_move_mouse_event(direction: MoveDirection, distance: u16) -> InputEvent {
         MoveDirection::Left => (RelativeAxisCode::REL_X, -i32::from(distance)),
         MoveDirection::Right => (RelativeAxisCode::REL_X, i32::from(distance)),
     };
-    InputEvent::new_now(EventType::RELATIVE.0, axis.0, distance)
+    InputEvent::new_now(EventType::RELATIVE, axis.0, distance)
 }
 
 fn new_scroll_mouse_event(direction: MoveDirection, distance: u16) -> InputEvent {
@@ -91,5 +91,5 @@ fn new_scroll_mouse_event(direction: MoveDirection, distance: u16) -> InputEvent
         MoveDirection::Left => (RelativeAxisCode::REL_HWHEEL.0, -i32::from(distance)),
         MoveDirection::Right => (RelativeAxisCode::REL_HWHEEL.0, i32::from(distance)),
     };
-    InputEvent::new_now(EventType::RELATIVE.0, axis, distance)
+    InputEvent::new_now(EventType::RELATIVE, axis, distance)
 }
@@ -0,0 +1,193 @@
+use crate::compat::input_absinfo;
+use crate::{AbsoluteAxisCode, InputId};
+
+/// Corrections to the `AbsInfo` of an axis. Fields that are `None` are left as reported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AbsFix {
+    pub minimum: Option<i32>,
+    pub maximum: Option<i32>,
+    pub fuzz: Option<i32>,
+    pub flat: Option<i32>,
+    pub resolution: Option<i32>,
+}
+
+impl AbsFix {
+    pub(crate) fn apply(&self, info: &mut input_absinfo) {
+        let fields = [
+            (self.minimum, &mut info.minimum),
+            (self.maximum, &mut info.maximum),
+            (self.fuzz, &mut info.fuzz),
+            (self.flat, &mut info.flat),
+            (self.resolution, &mut info.resolution),
+        ];
+        for (fix, field) in fields {
+            if let Some(value) = fix {
+                *field = value;
+            }
+        }
+    }
+}
+
+/// A correction for an axis of a specific device, identified by its vendor and product ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbsQuirk {
+    pub vendor: u16,
+    pub product: u16,
+    pub axis: AbsoluteAxisCode,
+    pub fix: AbsFix,
+}
+
+/// A table of corrections for devices that report wrong axis ranges or resolutions.
+///
+/// Some devices report ranges they never reach, no resolution at all, or a dead zone on an axis
+/// that shouldn't have one, such as the throttle of a joystick. Once a table is applied to a
+/// device with [`Device::apply_abs_quirks`], the corrections show up in everything derived from
+/// the axis information of the device: [`Device::get_absinfo`], the cached state, and helpers
+/// such as [`AxisNormalizer`](crate::AxisNormalizer).
+///
+/// [`AbsQuirks::builtin`] contains corrections for a few known devices, to which more can be
+/// added with [`AbsQuirks::insert`].
+///
+/// ```no_run
+/// use evdev::{AbsFix, AbsQuirk, AbsQuirks, AbsoluteAxisCode, Device};
+///
+/// let mut quirks = AbsQuirks::builtin();
+/// quirks.insert(AbsQuirk {
+///     vendor: 0x1234,
+///     product: 0x5678,
+///     axis: AbsoluteAxisCode::ABS_X,
+///     fix: AbsFix { maximum: Some(4000), ..AbsFix::default() },
+/// });
+/// let mut device = Device::open("/dev/input/event0")?;
+/// device.apply_abs_quirks(&quirks)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`Device::apply_abs_quirks`]: crate::Device::apply_abs_quirks
+/// [`Device::get_absinfo`]: crate::Device::get_absinfo
+#[derive(Debug, Clone, Default)]
+pub struct AbsQuirks {
+    quirks: Vec<AbsQuirk>,
+}
+
+/// A correction removing the dead zone of an axis.
+const fn no_dead_zone(vendor: u16, product: u16, axis: AbsoluteAxisCode) -> AbsQuirk {
+    AbsQuirk {
+        vendor,
+        product,
+        axis,
+        fix: AbsFix {
+            minimum: None,
+            maximum: None,
+            fuzz: None,
+            flat: Some(0),
+            resolution: None,
+        },
+    }
+}
+
+/// A correction setting the resolution of an axis.
+const fn resolution(vendor: u16, product: u16, axis: AbsoluteAxisCode, res: i32) -> AbsQuirk {
+    AbsQuirk {
+        vendor,
+        product,
+        axis,
+        fix: AbsFix {
+            minimum: None,
+            maximum: None,
+            fuzz: None,
+            flat: None,
+            resolution: Some(res),
+        },
+    }
+}
+
+const BUILTIN: &[AbsQuirk] = &[
+    // the HID driver gives the throttle of joysticks the same dead zone in its center as the
+    // sticks, so the middle of its range can't be used
+    // Logitech Extreme 3D Pro
+    no_dead_zone(0x046d, 0xc215, AbsoluteAxisCode::ABS_THROTTLE),
+    // Thrustmaster T.16000M
+    no_dead_zone(0x044f, 0xb10a, AbsoluteAxisCode::ABS_THROTTLE),
+    // Apple Magic Trackpad, whose resolution older kernels don't report
+    resolution(0x05ac, 0x030e, AbsoluteAxisCode::ABS_X, 46),
+    resolution(0x05ac, 0x030e, AbsoluteAxisCode::ABS_Y, 45),
+    resolution(0x05ac, 0x030e, AbsoluteAxisCode::ABS_MT_POSITION_X, 46),
+    resolution(0x05ac, 0x030e, AbsoluteAxisCode::ABS_MT_POSITION_Y, 45),
+];
+
+impl AbsQuirks {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a table with the corrections that ship with this crate.
+    pub fn builtin() -> Self {
+        Self {
+            quirks: BUILTIN.to_vec(),
+        }
+    }
+
+    /// Add a correction. Later corrections of the same field of an axis take precedence.
+    pub fn insert(&mut self, quirk: AbsQuirk) {
+        self.quirks.push(quirk);
+    }
+
+    /// Returns the corrections for a device, combined per axis.
+    pub fn lookup(&self, id: &InputId) -> Vec<(AbsoluteAxisCode, AbsFix)> {
+        let mut fixes: Vec<(AbsoluteAxisCode, AbsFix)> = Vec::new();
+        let matching = self
+            .quirks
+            .iter()
+            .filter(|q| q.vendor == id.vendor() && q.product == id.product());
+        for quirk in matching {
+            let fix = match fixes.iter_mut().find(|(axis, _)| *axis == quirk.axis) {
+                Some((_, fix)) => fix,
+                None => {
+                    fixes.push((quirk.axis, AbsFix::default()));
+                    &mut fixes.last_mut().unwrap().1
+                }
+            };
+            let new = quirk.fix;
+            *fix = AbsFix {
+                minimum: new.minimum.or(fix.minimum),
+                maximum: new.maximum.or(fix.maximum),
+                fuzz: new.fuzz.or(fix.fuzz),
+                flat: new.flat.or(fix.flat),
+                resolution: new.resolution.or(fix.resolution),
+            };
+        }
+        fixes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbsInfo, BusType};
+
+    #[test]
+    fn lookup_and_apply() {
+        let mut quirks = AbsQuirks::builtin();
+        quirks.insert(AbsQuirk {
+            vendor: 0x046d,
+            product: 0xc215,
+            axis: AbsoluteAxisCode::ABS_THROTTLE,
+            fix: AbsFix {
+                maximum: Some(250),
+                ..AbsFix::default()
+            },
+        });
+        let id = InputId::new(BusType::BUS_USB, 0x046d, 0xc215, 0x0110);
+        let fixes = quirks.lookup(&id);
+        assert_eq!(fixes.len(), 1);
+
+        let mut info = *AbsInfo::new(128, 0, 255, 0, 15, 0).as_ref();
+        fixes[0].1.apply(&mut info);
+        assert_eq!((info.value, info.maximum, info.flat), (128, 250, 0));
+
+        let other = InputId::new(BusType::BUS_USB, 0x046d, 0xc216, 0x0110);
+        assert!(quirks.lookup(&other).is_empty());
+    }
+}
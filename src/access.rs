@@ -0,0 +1,268 @@
+use crate::Device;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::{Path, PathBuf};
+
+/// Why an event node could not be opened, see [`DeviceAccess::problem`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessProblem {
+    /// The user lacks permission to open the node (`EACCES` or `EPERM`).
+    PermissionDenied,
+    /// The node or its device disappeared (`ENOENT`, `ENODEV` or `ENXIO`).
+    NotFound,
+    /// The device is in exclusive use (`EBUSY`).
+    Busy,
+    /// Any other error.
+    Other(io::ErrorKind),
+}
+
+impl AccessProblem {
+    fn from_error(error: &io::Error) -> Self {
+        match error.raw_os_error() {
+            Some(libc::EACCES | libc::EPERM) => Self::PermissionDenied,
+            Some(libc::ENOENT | libc::ENODEV | libc::ENXIO) => Self::NotFound,
+            Some(libc::EBUSY) => Self::Busy,
+            _ => Self::Other(error.kind()),
+        }
+    }
+}
+
+impl fmt::Display for AccessProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PermissionDenied => f.write_str("permission denied"),
+            Self::NotFound => f.write_str("device not found"),
+            Self::Busy => f.write_str("device busy"),
+            Self::Other(kind) => write!(f, "{kind:?}"),
+        }
+    }
+}
+
+/// The result of trying to open an event node, returned by [`enumerate_with_diagnostics`].
+#[derive(Debug)]
+pub struct DeviceAccess {
+    path: PathBuf,
+    device: io::Result<Device>,
+    gid: Option<u32>,
+    group: Option<String>,
+}
+
+impl DeviceAccess {
+    /// Returns the path of the event node.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the opened device, or the error that occurred while opening it.
+    pub fn device(&self) -> Result<&Device, &io::Error> {
+        self.device.as_ref()
+    }
+
+    /// Returns the opened device, or the error that occurred while opening it.
+    pub fn into_device(self) -> io::Result<Device> {
+        self.device
+    }
+
+    /// Returns why the node could not be opened, or `None` if it was opened.
+    pub fn problem(&self) -> Option<AccessProblem> {
+        self.device.as_ref().err().map(AccessProblem::from_error)
+    }
+
+    /// Returns the ID of the group owning the node.
+    pub fn gid(&self) -> Option<u32> {
+        self.gid
+    }
+
+    /// Returns the name of the group owning the node, typically `input`.
+    pub fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    /// Returns a suggestion for how the user can fix the problem, if any.
+    pub fn hint(&self) -> Option<String> {
+        hint(self.problem()?, self.group.as_deref())
+    }
+}
+
+fn hint(problem: AccessProblem, group: Option<&str>) -> Option<String> {
+    match (problem, group) {
+        (AccessProblem::PermissionDenied, Some(group)) if group != "root" => Some(format!(
+            "add your user to the '{group}' group and log in again, or run as root"
+        )),
+        (AccessProblem::PermissionDenied, _) => {
+            Some("run as root or grant access to the device with a udev rule".to_owned())
+        }
+        (AccessProblem::Busy, _) => {
+            Some("close the program that is using the device exclusively".to_owned())
+        }
+        _ => None,
+    }
+}
+
+/// A process that has an event node open, as reported by [`device_holders`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceHolder {
+    /// The ID of the process.
+    pub pid: u32,
+    /// The name of the process' executable, as in `/proc/<pid>/comm`.
+    pub name: String,
+}
+
+impl fmt::Display for DeviceHolder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (pid {})", self.name, self.pid)
+    }
+}
+
+/// Finds the processes other than this one that have the event node at `path` open.
+///
+/// When [`Device::grab`] fails with `EBUSY`, another process has grabbed the device. The kernel
+/// doesn't say which one, so this scans the open files of every process in `/proc` for the node
+/// as a best guess of the culprit. Processes whose files this process isn't allowed to inspect,
+/// usually those of other users unless running as root, are skipped.
+///
+/// ```no_run
+/// let mut device = evdev::Device::open("/dev/input/event0")?;
+/// if let Err(e) = device.grab() {
+///     if e.raw_os_error() == Some(libc::EBUSY) {
+///         for holder in evdev::device_holders("/dev/input/event0")? {
+///             eprintln!("held by {holder}");
+///         }
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn device_holders(path: impl AsRef<Path>) -> io::Result<Vec<DeviceHolder>> {
+    let meta = fs::metadata(path)?;
+    if !meta.file_type().is_char_device() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "not a character device",
+        ));
+    }
+    Ok(holders_of(meta.rdev()))
+}
+
+pub(crate) fn holders_of(rdev: u64) -> Vec<DeviceHolder> {
+    let own = std::process::id();
+    let mut holders = Vec::new();
+    let procs = match fs::read_dir("/proc") {
+        Ok(procs) => procs,
+        Err(_) => return holders,
+    };
+    for entry in procs.filter_map(|entry| entry.ok()) {
+        let pid = match entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<u32>().ok())
+        {
+            Some(pid) if pid != own => pid,
+            _ => continue,
+        };
+        let fds = match fs::read_dir(entry.path().join("fd")) {
+            Ok(fds) => fds,
+            Err(_) => continue,
+        };
+        let holds = fds.filter_map(|fd| fd.ok()).any(|fd| {
+            fs::metadata(fd.path()).map_or(false, |meta| {
+                meta.file_type().is_char_device() && meta.rdev() == rdev
+            })
+        });
+        if holds {
+            let name = fs::read_to_string(entry.path().join("comm"))
+                .map(|comm| comm.trim_end().to_owned())
+                .unwrap_or_default();
+            holders.push(DeviceHolder { pid, name });
+        }
+    }
+    holders.sort_by_key(|holder| holder.pid);
+    holders
+}
+
+/// Tries to open every event node in `/dev/input`, reporting for each whether it could be opened
+/// and why not.
+///
+/// Unlike [`enumerate`](crate::enumerate), which silently skips nodes that can't be opened, this
+/// lets applications explain to users how to gain access to their devices.
+///
+/// ```no_run
+/// for access in evdev::enumerate_with_diagnostics() {
+///     if let Some(problem) = access.problem() {
+///         print!("{}: {problem}", access.path().display());
+///         match access.hint() {
+///             Some(hint) => println!(" ({hint})"),
+///             None => println!(),
+///         }
+///     }
+/// }
+/// ```
+pub fn enumerate_with_diagnostics() -> Vec<DeviceAccess> {
+    let mut paths: Vec<_> = match fs::read_dir("/dev/input") {
+        Ok(dir) => dir
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .map_or(false, |name| name.to_string_lossy().starts_with("event"))
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let gid = fs::metadata(&path).ok().map(|meta| meta.gid());
+            let group = gid
+                .and_then(|gid| nix::unistd::Group::from_gid(gid.into()).ok().flatten())
+                .map(|group| group.name);
+            DeviceAccess {
+                device: Device::open(&path),
+                path,
+                gid,
+                group,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_and_hint() {
+        let err = io::Error::from_raw_os_error(libc::EACCES);
+        let problem = AccessProblem::from_error(&err);
+        assert_eq!(problem, AccessProblem::PermissionDenied);
+        assert!(hint(problem, Some("input"))
+            .unwrap()
+            .contains("'input' group"));
+        assert!(hint(problem, Some("root")).unwrap().contains("udev"));
+
+        let busy = AccessProblem::from_error(&io::Error::from_raw_os_error(libc::EBUSY));
+        assert_eq!(busy, AccessProblem::Busy);
+        let gone = AccessProblem::from_error(&io::Error::from_raw_os_error(libc::ENODEV));
+        assert_eq!(gone, AccessProblem::NotFound);
+        assert_eq!(hint(gone, None), None);
+    }
+
+    #[test]
+    fn holders_of_null() {
+        // keep /dev/null open in a child process, which must then be among the holders
+        let mut child = std::process::Command::new("sleep")
+            .arg("10")
+            .stdin(fs::File::open("/dev/null").unwrap())
+            .spawn()
+            .unwrap();
+        let holders = device_holders("/dev/null").unwrap();
+        child.kill().unwrap();
+        child.wait().unwrap();
+        let holder = holders.iter().find(|h| h.pid == child.id()).unwrap();
+        assert_eq!(holder.to_string(), format!("sleep (pid {})", child.id()));
+        assert!(device_holders("/proc").is_err());
+    }
+}
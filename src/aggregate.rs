@@ -0,0 +1,171 @@
+use crate::evemu::Description;
+use crate::uinput::VirtualDevice;
+use crate::{Device, EventSummary, EventType, InputEvent, KeyCode, SynchronizationCode};
+use std::collections::HashSet;
+use std::io;
+use std::os::fd::AsFd;
+
+/// Merges several devices into a single virtual device.
+///
+/// This joins devices that belong together but show up separately, such as the two halves of a
+/// split keyboard or a keyboard and a macropad, so that applications that only look at one
+/// keyboard see everything. The virtual device supports the union of the capabilities of the
+/// devices, and the devices are grabbed so their events are only seen once.
+///
+/// Events are forwarded frame by frame, so frames of different devices are never mixed up. A key
+/// held on several devices at once is only reported as released once all of them released it.
+///
+/// ```no_run
+/// use evdev::{AggregateDevice, Device};
+///
+/// let left = Device::open("/dev/input/event10")?;
+/// let right = Device::open("/dev/input/event11")?;
+/// let mut keyboard = AggregateDevice::new("Split Keyboard", [left, right])?;
+/// keyboard.forward()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct AggregateDevice {
+    output: VirtualDevice,
+    sources: Vec<Source>,
+}
+
+#[derive(Debug)]
+struct Source {
+    device: Device,
+    frame: Vec<InputEvent>,
+    held: HashSet<KeyCode>,
+}
+
+impl AggregateDevice {
+    /// Grab `devices` and create a virtual device named `name` combining them.
+    ///
+    /// The virtual device uses the ID of the first device. Where several devices support the
+    /// same absolute axis, the range of the first one is used.
+    pub fn new(name: &str, devices: impl IntoIterator<Item = Device>) -> io::Result<Self> {
+        let mut description: Option<Description> = None;
+        let mut sources = Vec::new();
+        for mut device in devices {
+            let other = Description::from_device(&device)?;
+            match &mut description {
+                Some(description) => description.merge(&other),
+                None => description = Some(other),
+            }
+            device.grab()?;
+            device.set_nonblocking(true)?;
+            sources.push(Source {
+                device,
+                frame: Vec::new(),
+                held: HashSet::new(),
+            });
+        }
+        let mut description = description
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no devices to combine"))?;
+        description.name = name.to_owned();
+        Ok(Self {
+            output: description.create_virtual_device()?,
+            sources,
+        })
+    }
+
+    /// Returns the devices that are combined. Unplugged devices are removed.
+    pub fn devices(&self) -> impl Iterator<Item = &Device> + '_ {
+        self.sources.iter().map(|source| &source.device)
+    }
+
+    /// Returns the virtual device combining the devices.
+    pub fn virtual_device(&mut self) -> &mut VirtualDevice {
+        &mut self.output
+    }
+
+    /// Wait for events from any of the devices and forward them, blocking until at least one
+    /// device had events.
+    ///
+    /// Fails with [`InvalidInput`](io::ErrorKind::InvalidInput) once all devices are unplugged.
+    pub fn forward_once(&mut self) -> io::Result<()> {
+        if self.sources.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "all devices were removed",
+            ));
+        }
+        let fds: Vec<_> = self.sources.iter().map(|s| s.device.as_fd()).collect();
+        let ready = match crate::poll_any(&fds, None) {
+            Ok(ready) => ready,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let mut unplugged = Vec::new();
+        for i in ready {
+            match self.read_source(i) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) if e.raw_os_error() == Some(libc::ENODEV) => unplugged.push(i),
+                result => result?,
+            }
+        }
+        for i in unplugged.into_iter().rev() {
+            self.remove_source(i)?;
+        }
+        Ok(())
+    }
+
+    /// Forward events until all devices are unplugged, or reading or writing events fails.
+    pub fn forward(&mut self) -> io::Result<()> {
+        while !self.sources.is_empty() {
+            self.forward_once()?;
+        }
+        Ok(())
+    }
+
+    fn read_source(&mut self, index: usize) -> io::Result<()> {
+        let Self { output, sources } = self;
+        let (source, others) = split(sources, index);
+        for ev in source.device.fetch_events()? {
+            match ev.destructure() {
+                EventSummary::Synchronization(_, SynchronizationCode::SYN_REPORT, _) => {
+                    if !source.frame.is_empty() {
+                        source.frame.push(ev);
+                        output.write_raw(&source.frame)?;
+                        source.frame.clear();
+                    }
+                }
+                EventSummary::Key(_, key, value) => {
+                    let held_elsewhere = others.iter().any(|s| s.held.contains(&key));
+                    let changed = match value {
+                        0 => source.held.remove(&key),
+                        1 => source.held.insert(key),
+                        _ => true,
+                    };
+                    if changed && !held_elsewhere {
+                        source.frame.push(ev);
+                    }
+                }
+                _ => source.frame.push(ev),
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes an unplugged device, releasing the keys only it held.
+    fn remove_source(&mut self, index: usize) -> io::Result<()> {
+        let source = self.sources.remove(index);
+        let released: Vec<_> = source
+            .held
+            .iter()
+            .filter(|key| !self.sources.iter().any(|s| s.held.contains(key)))
+            .map(|key| InputEvent::new(EventType::KEY, key.code(), 0))
+            .collect();
+        if released.is_empty() {
+            Ok(())
+        } else {
+            self.output.emit(&released)
+        }
+    }
+}
+
+/// Returns the element at `index` and all other elements.
+fn split<T>(slice: &mut [T], index: usize) -> (&mut T, Vec<&T>) {
+    let (before, rest) = slice.split_at_mut(index);
+    let (item, after) = rest.split_first_mut().expect("index out of bounds");
+    (item, before.iter().chain(after.iter()).collect())
+}
@@ -1,35 +1,54 @@
-use bitvec::prelude::*;
 use std::fmt;
-use std::ops::{Deref, DerefMut};
+use std::marker::PhantomData;
+use std::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Deref, DerefMut, Sub, SubAssign,
+};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Returns the number of words needed to store `bits` bits.
+pub(crate) const fn words_for(bits: usize) -> usize {
+    (bits + WORD_BITS - 1) / WORD_BITS
+}
 
 /// A collection of bits representing either device capability or state.
 ///
 /// This can be used to iterate across all keys supported by a keyboard, or all buttons supported
 /// by a joystick. You can also query directly whether a specific bit is set (corresponding to
 /// whether a key or button is depressed).
+///
+/// The bits are stored in 64-bit words, so that iterating over a set and comparing sets looks at
+/// 64 codes at a time.
 #[repr(transparent)]
 pub struct AttributeSetRef<T> {
-    _indexer: std::marker::PhantomData<T>,
-    bitslice: BitSlice<u8>,
+    _indexer: PhantomData<T>,
+    words: [u64],
 }
 
 impl<T: EvdevEnum> AttributeSetRef<T> {
     #[inline]
-    fn new(bitslice: &BitSlice<u8>) -> &Self {
-        // SAFETY: for<T> AttributeSet<T> is repr(transparent) over BitSlice<u8>
-        unsafe { &*(bitslice as *const BitSlice<u8> as *const Self) }
+    fn new(words: &[u64]) -> &Self {
+        // SAFETY: for<T> AttributeSetRef<T> is repr(transparent) over [u64]
+        unsafe { &*(words as *const [u64] as *const Self) }
     }
 
     #[inline]
-    fn new_mut(bitslice: &mut BitSlice<u8>) -> &mut Self {
-        // SAFETY: for<T> AttributeSet<T> is repr(transparent) over BitSlice<u8>
-        unsafe { &mut *(bitslice as *mut BitSlice<u8> as *mut Self) }
+    fn new_mut(words: &mut [u64]) -> &mut Self {
+        // SAFETY: for<T> AttributeSetRef<T> is repr(transparent) over [u64]
+        unsafe { &mut *(words as *mut [u64] as *mut Self) }
+    }
+
+    /// Returns the word at `index`, or 0 past the end of the set.
+    #[inline]
+    fn word(&self, index: usize) -> u64 {
+        self.words.get(index).copied().unwrap_or(0)
     }
 
     /// Returns `true` if this AttributeSet contains the passed T.
     #[inline]
     pub fn contains(&self, attr: T) -> bool {
-        self.bitslice.get(attr.to_index()).map_or(false, |b| *b)
+        let i = attr.to_index();
+        self.word(i / WORD_BITS) & (1 << (i % WORD_BITS)) != 0
     }
 
     /// Provides an iterator over all "set" bits in the collection.
@@ -38,6 +57,18 @@ impl<T: EvdevEnum> AttributeSetRef<T> {
         self.into_iter()
     }
 
+    /// Returns the number of elements in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Returns `true` if the set contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
     pub fn insert(&mut self, attr: T) {
         self.set(attr, true)
     }
@@ -49,18 +80,66 @@ impl<T: EvdevEnum> AttributeSetRef<T> {
     // TODO: figure out a good name for this if we make it public
     #[inline]
     pub(crate) fn set(&mut self, attr: T, on: bool) {
-        self.bitslice.set(attr.to_index(), on)
+        let i = attr.to_index();
+        let word = &mut self.words[i / WORD_BITS];
+        let bit = 1 << (i % WORD_BITS);
+        if on {
+            *word |= bit;
+        } else {
+            *word &= !bit;
+        }
     }
 
     #[inline]
-    pub(crate) fn slice_iter(&self, start: T) -> AttributeSetRefIter<T> {
-        let slice = Self::new(&self.bitslice[start.to_index()..]);
+    pub(crate) fn slice_iter(&self, start: T) -> AttributeSetRefIter<'_, T> {
+        AttributeSetRefIter::new(&self.words, start.to_index())
+    }
+}
 
-        AttributeSetRefIter {
-            _indexer: std::marker::PhantomData,
-            inner: slice.bitslice.iter_ones(),
-            offset: start.to_index(),
+impl<T: EvdevEnum> AttributeSetRef<T> {
+    /// Returns `true` if every element of this set is also in `other`.
+    pub fn is_subset(&self, other: &AttributeSetRef<T>) -> bool {
+        (self.words.iter().enumerate()).all(|(i, &w)| w & !other.word(i) == 0)
+    }
+
+    /// Returns `true` if every element of `other` is also in this set.
+    pub fn is_superset(&self, other: &AttributeSetRef<T>) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Returns `true` if this set and `other` have no elements in common.
+    pub fn is_disjoint(&self, other: &AttributeSetRef<T>) -> bool {
+        (self.words.iter().enumerate()).all(|(i, &w)| w & other.word(i) == 0)
+    }
+}
+
+impl<T: ArrayedEvdevEnum> AttributeSetRef<T> {
+    fn combine(&self, other: &AttributeSetRef<T>, op: impl Fn(u64, u64) -> u64) -> AttributeSet<T> {
+        let mut set = AttributeSet::new();
+        for (i, word) in set.as_mut_words().iter_mut().enumerate() {
+            *word = op(self.word(i), other.word(i));
         }
+        set
+    }
+
+    /// Returns the elements that are in this set, `other`, or both.
+    pub fn union(&self, other: &AttributeSetRef<T>) -> AttributeSet<T> {
+        self.combine(other, |a, b| a | b)
+    }
+
+    /// Returns the elements that are in both this set and `other`.
+    pub fn intersection(&self, other: &AttributeSetRef<T>) -> AttributeSet<T> {
+        self.combine(other, |a, b| a & b)
+    }
+
+    /// Returns the elements that are in this set but not in `other`.
+    pub fn difference(&self, other: &AttributeSetRef<T>) -> AttributeSet<T> {
+        self.combine(other, |a, b| a & !b)
+    }
+
+    /// Returns the elements that are in either this set or `other`, but not in both.
+    pub fn symmetric_difference(&self, other: &AttributeSetRef<T>) -> AttributeSet<T> {
+        self.combine(other, |a, b| a ^ b)
     }
 }
 
@@ -72,12 +151,12 @@ impl<T: EvdevEnum + fmt::Debug> fmt::Debug for AttributeSetRef<T> {
 
 impl<T: EvdevEnum> Default for &AttributeSetRef<T> {
     fn default() -> Self {
-        AttributeSetRef::new(BitSlice::empty())
+        AttributeSetRef::new(&[])
     }
 }
 impl<T: EvdevEnum> Default for &mut AttributeSetRef<T> {
     fn default() -> Self {
-        AttributeSetRef::new_mut(BitSlice::empty_mut())
+        AttributeSetRef::new_mut(&mut [])
     }
 }
 
@@ -86,39 +165,89 @@ impl<'a, T: EvdevEnum> IntoIterator for &'a AttributeSetRef<T> {
     type IntoIter = AttributeSetRefIter<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
-        AttributeSetRefIter {
-            _indexer: std::marker::PhantomData,
-            inner: self.bitslice.iter_ones(),
-            offset: 0,
-        }
+        AttributeSetRefIter::new(&self.words, 0)
     }
 }
 
 pub struct AttributeSetRefIter<'a, T> {
-    _indexer: std::marker::PhantomData<&'a T>,
-    inner: bitvec::slice::IterOnes<'a, u8, Lsb0>,
-    offset: usize,
+    _indexer: PhantomData<&'a T>,
+    words: &'a [u64],
+    /// The range of bits that are left, `front..back`.
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T> AttributeSetRefIter<'a, T> {
+    fn new(words: &'a [u64], front: usize) -> Self {
+        Self {
+            _indexer: PhantomData,
+            words,
+            front,
+            back: words.len() * WORD_BITS,
+        }
+    }
+
+    /// Returns the word at `index` with the bits outside of the remaining range cleared.
+    fn word(&self, index: usize) -> u64 {
+        let start = index * WORD_BITS;
+        let mut word = self.words[index];
+        if self.front > start {
+            word &= !0 << (self.front - start);
+        }
+        if self.back < start + WORD_BITS {
+            word &= !(!0 << (self.back - start));
+        }
+        word
+    }
+
+    fn remaining(&self) -> usize {
+        if self.front >= self.back {
+            return 0;
+        }
+        (self.front / WORD_BITS..=(self.back - 1) / WORD_BITS)
+            .map(|i| self.word(i).count_ones() as usize)
+            .sum()
+    }
 }
 
 impl<T: EvdevEnum> Iterator for AttributeSetRefIter<'_, T> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner
-            .next()
-            .map(|idx| T::from_index(self.offset + idx))
+        while self.front < self.back {
+            let index = self.front / WORD_BITS;
+            let word = self.word(index);
+            if word != 0 {
+                let bit = index * WORD_BITS + word.trailing_zeros() as usize;
+                self.front = bit + 1;
+                return Some(T::from_index(bit));
+            }
+            self.front = (index + 1) * WORD_BITS;
+        }
+        None
     }
-}
 
-impl<T: EvdevEnum> ExactSizeIterator for AttributeSetRefIter<'_, T> {
-    fn len(&self) -> usize {
-        self.inner.len()
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining();
+        (remaining, Some(remaining))
     }
 }
 
+impl<T: EvdevEnum> ExactSizeIterator for AttributeSetRefIter<'_, T> {}
+
 impl<T: EvdevEnum> DoubleEndedIterator for AttributeSetRefIter<'_, T> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.inner.next_back().map(T::from_index)
+        while self.front < self.back {
+            let index = (self.back - 1) / WORD_BITS;
+            let word = self.word(index);
+            if word != 0 {
+                let bit = index * WORD_BITS + (WORD_BITS - 1 - word.leading_zeros() as usize);
+                self.back = bit;
+                return Some(T::from_index(bit));
+            }
+            self.back = index * WORD_BITS;
+        }
+        None
     }
 }
 
@@ -133,17 +262,29 @@ impl<T: ArrayedEvdevEnum> AttributeSet<T> {
         }
     }
 
-    fn as_bitslice(&self) -> &BitSlice<u8> {
-        T::array_as_slice(&self.container)
+    fn as_words(&self) -> &[u64] {
+        T::array_as_words(&self.container)
     }
 
-    fn as_mut_bitslice(&mut self) -> &mut BitSlice<u8> {
-        T::array_as_slice_mut(&mut self.container)
+    fn as_mut_words(&mut self) -> &mut [u64] {
+        T::array_as_words_mut(&mut self.container)
     }
 
+    /// Returns the bytes of the set, to be filled in with a bitmap from the kernel.
+    ///
+    /// The kernel's bitmaps are arrays of `unsigned long`, which have the same layout as the
+    /// words of the set on little-endian and 64-bit targets.
     #[inline]
     pub(crate) fn as_mut_raw_slice(&mut self) -> &mut [u8] {
-        T::array_as_buf(&mut self.container)
+        let words = self.as_mut_words();
+        // SAFETY: the bytes cover exactly the words, u8 has no alignment requirement, and any
+        // bytes make up valid words
+        unsafe {
+            std::slice::from_raw_parts_mut(
+                words.as_mut_ptr().cast::<u8>(),
+                std::mem::size_of_val(words),
+            )
+        }
     }
 }
 
@@ -167,16 +308,28 @@ impl<'a, T: ArrayedEvdevEnum> FromIterator<&'a T> for AttributeSet<T> {
     }
 }
 
+impl<T: ArrayedEvdevEnum> Extend<T> for AttributeSet<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|el| self.insert(el));
+    }
+}
+
+impl<'a, T: ArrayedEvdevEnum> Extend<&'a T> for AttributeSet<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
 impl<T: ArrayedEvdevEnum> Deref for AttributeSet<T> {
     type Target = AttributeSetRef<T>;
     fn deref(&self) -> &AttributeSetRef<T> {
-        AttributeSetRef::new(self.as_bitslice())
+        AttributeSetRef::new(self.as_words())
     }
 }
 
 impl<T: ArrayedEvdevEnum> DerefMut for AttributeSet<T> {
     fn deref_mut(&mut self) -> &mut AttributeSetRef<T> {
-        AttributeSetRef::new_mut(self.as_mut_bitslice())
+        AttributeSetRef::new_mut(self.as_mut_words())
     }
 }
 
@@ -189,6 +342,64 @@ impl<'a, T: ArrayedEvdevEnum> IntoIterator for &'a AttributeSet<T> {
     }
 }
 
+impl<T: ArrayedEvdevEnum> IntoIterator for AttributeSet<T> {
+    type Item = T;
+    type IntoIter = AttributeSetIntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let back = self.as_words().len() * WORD_BITS;
+        AttributeSetIntoIter {
+            set: self,
+            front: 0,
+            back,
+        }
+    }
+}
+
+/// An owning iterator over the elements of an [`AttributeSet`].
+pub struct AttributeSetIntoIter<T: ArrayedEvdevEnum> {
+    set: AttributeSet<T>,
+    front: usize,
+    back: usize,
+}
+
+impl<T: ArrayedEvdevEnum> AttributeSetIntoIter<T> {
+    fn iter(&self) -> AttributeSetRefIter<'_, T> {
+        AttributeSetRefIter {
+            _indexer: PhantomData,
+            words: self.set.as_words(),
+            front: self.front,
+            back: self.back,
+        }
+    }
+}
+
+impl<T: ArrayedEvdevEnum> Iterator for AttributeSetIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let mut iter = self.iter();
+        let next = iter.next();
+        self.front = iter.front;
+        next
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter().size_hint()
+    }
+}
+
+impl<T: ArrayedEvdevEnum> DoubleEndedIterator for AttributeSetIntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        let mut iter = self.iter();
+        let next = iter.next_back();
+        self.back = iter.back;
+        next
+    }
+}
+
+impl<T: ArrayedEvdevEnum> ExactSizeIterator for AttributeSetIntoIter<T> {}
+
 impl<T: ArrayedEvdevEnum> Clone for AttributeSet<T>
 where
     T::Array: Clone,
@@ -209,6 +420,49 @@ impl<T: ArrayedEvdevEnum + fmt::Debug> fmt::Debug for AttributeSet<T> {
     }
 }
 
+macro_rules! set_operator {
+    ($op:ident, $method:ident, $assign_op:ident, $assign_method:ident, $set_method:ident) => {
+        impl<T: ArrayedEvdevEnum> $op for &AttributeSetRef<T> {
+            type Output = AttributeSet<T>;
+
+            fn $method(self, other: Self) -> AttributeSet<T> {
+                self.$set_method(other)
+            }
+        }
+
+        impl<T: ArrayedEvdevEnum> $op for &AttributeSet<T> {
+            type Output = AttributeSet<T>;
+
+            fn $method(self, other: Self) -> AttributeSet<T> {
+                self.$set_method(other)
+            }
+        }
+
+        impl<T: ArrayedEvdevEnum> $assign_op<&AttributeSetRef<T>> for AttributeSet<T> {
+            fn $assign_method(&mut self, other: &AttributeSetRef<T>) {
+                *self = self.$set_method(other);
+            }
+        }
+
+        impl<T: ArrayedEvdevEnum> $assign_op<&AttributeSet<T>> for AttributeSet<T> {
+            fn $assign_method(&mut self, other: &AttributeSet<T>) {
+                *self = self.$set_method(other);
+            }
+        }
+    };
+}
+
+set_operator!(BitOr, bitor, BitOrAssign, bitor_assign, union);
+set_operator!(BitAnd, bitand, BitAndAssign, bitand_assign, intersection);
+set_operator!(Sub, sub, SubAssign, sub_assign, difference);
+set_operator!(
+    BitXor,
+    bitxor,
+    BitXorAssign,
+    bitxor_assign,
+    symmetric_difference
+);
+
 pub trait EvdevEnum: Copy + 'static {
     fn from_index(i: usize) -> Self;
     fn to_index(self) -> usize;
@@ -216,9 +470,8 @@ pub trait EvdevEnum: Copy + 'static {
 
 pub trait ArrayedEvdevEnum: EvdevEnum {
     type Array;
-    fn array_as_slice(arr: &Self::Array) -> &BitSlice<u8>;
-    fn array_as_slice_mut(arr: &mut Self::Array) -> &mut BitSlice<u8>;
-    fn array_as_buf(arr: &mut Self::Array) -> &mut [u8];
+    fn array_as_words(arr: &Self::Array) -> &[u64];
+    fn array_as_words_mut(arr: &mut Self::Array) -> &mut [u64];
     fn zeroed_array() -> Self::Array;
 }
 
@@ -226,36 +479,31 @@ macro_rules! evdev_enum {
     ($t:ty, Array, $($(#[$attr:meta])* $c:ident = $val:expr,)*) => {
         evdev_enum!(
             $t,
-            Array: bitvec::BitArr!(for <$t>::COUNT, in u8),
-            bitvec::array::BitArray::as_raw_mut_slice,
-            bitvec::array::BitArray::ZERO,
+            Array: [u64; $crate::attribute_set::words_for(<$t>::COUNT)],
+            [0; $crate::attribute_set::words_for(<$t>::COUNT)],
             $($(#[$attr])* $c = $val,)*
         );
     };
     ($t:ty, box Array, $($(#[$attr:meta])* $c:ident = $val:expr,)*) => {
         evdev_enum!(
             $t,
-            Array: Box<bitvec::BitArr!(for <$t>::COUNT, in u8)>,
-            bitvec::array::BitArray::as_raw_mut_slice,
-            Box::new(bitvec::array::BitArray::ZERO),
+            Array: Box<[u64; $crate::attribute_set::words_for(<$t>::COUNT)]>,
+            Box::new([0; $crate::attribute_set::words_for(<$t>::COUNT)]),
             $($(#[$attr])* $c = $val,)*
         );
     };
     (
         $t:ty,
-        Array: $Array:ty, $arr_as_buf:expr, $zero:expr,
+        Array: $Array:ty, $zero:expr,
         $($(#[$attr:meta])* $c:ident = $val:expr,)*
     ) => {
         impl $crate::attribute_set::ArrayedEvdevEnum for $t {
             type Array = $Array;
-            fn array_as_slice(arr: &Self::Array) -> &bitvec::slice::BitSlice<u8> {
-                arr
+            fn array_as_words(arr: &Self::Array) -> &[u64] {
+                &arr[..]
             }
-            fn array_as_slice_mut(arr: &mut Self::Array) -> &mut bitvec::slice::BitSlice<u8> {
-                arr
-            }
-            fn array_as_buf(arr: &mut Self::Array) -> &mut [u8] {
-                $arr_as_buf(arr)
+            fn array_as_words_mut(arr: &mut Self::Array) -> &mut [u64] {
+                &mut arr[..]
             }
             fn zeroed_array() -> Self::Array {
                 $zero
@@ -270,23 +518,56 @@ macro_rules! evdev_enum {
             const NAME_MAP: &'static [(&'static str, $t)] = &[
                 $((stringify!($c), Self::$c),)*
             ];
+
+            /// All named codes of this type, in the order of their definition.
+            pub const ALL: &'static [Self] = &[$(Self::$c,)*];
+
+            /// Returns an iterator over all named codes of this type.
+            pub fn iter() -> impl Iterator<Item = Self> {
+                Self::ALL.iter().copied()
+            }
         }
         impl std::str::FromStr for $t {
             type Err = crate::EnumParseError;
 
             fn from_str(s: &str) -> Result<Self, Self::Err> {
-                match Self::NAME_MAP.iter().find(|e| e.0 == s) {
-                    Some(e) => Ok(e.1),
-                    None => Err(crate::EnumParseError(())),
+                let name = crate::constants::resolve_alias(s);
+                if let Some(e) = Self::NAME_MAP.iter().find(|e| e.0 == name) {
+                    return Ok(e.1);
+                }
+                // fall back to the numeric code, for codes without a name
+                use $crate::attribute_set::EvdevEnum;
+                let prefix = crate::constants::unknown_code_prefix(stringify!($t));
+                let s = s
+                    .strip_prefix(prefix)
+                    .and_then(|s| s.strip_prefix("_UNKNOWN("))
+                    .and_then(|s| s.strip_suffix(')'))
+                    .unwrap_or(s);
+                let code = match s.strip_prefix("0x") {
+                    Some(hex) => u64::from_str_radix(hex, 16),
+                    None => s.parse(),
+                }
+                .map_err(|_| crate::EnumParseError(()))?;
+                let value = Self::from_index(code as usize);
+                if value.to_index() as u64 == code {
+                    Ok(value)
+                } else {
+                    Err(crate::EnumParseError(()))
                 }
             }
         }
+
         impl std::fmt::Debug for $t {
             fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
                 #[allow(unreachable_patterns)]
                 match *self {
                     $(Self::$c => f.pad(stringify!($c)),)*
-                    _ => write!(f, "unknown key: {}", self.0),
+                    _ => write!(
+                        f,
+                        "{}_UNKNOWN({:#x})",
+                        crate::constants::unknown_code_prefix(stringify!($t)),
+                        self.0
+                    ),
                 }
             }
         }
@@ -307,9 +588,13 @@ macro_rules! evdev_enum {
             where
                 S: serde::ser::Serializer,
             {
+                // compact formats get the number, as do codes without a name
+                if !serializer.is_human_readable() {
+                    return serializer.serialize_u16(self.0);
+                }
                 let value = match *self {
                     $(Self::$c => stringify!($c),)*
-                    _ => unreachable!(),
+                    _ => return serializer.serialize_u16(self.0),
                 };
 
                 serializer.serialize_str(value)
@@ -326,21 +611,81 @@ macro_rules! evdev_enum {
                     type Value = $t;
 
                     fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                        write!(formatter, "a string with any of the constants in {}", stringify!($t))
+                        write!(formatter, "any of the constants in {} or a numeric code", stringify!($t))
                     }
 
                     fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
                     where
                         E: serde::de::Error,
                     {
-                        match <$t>::NAME_MAP.iter().find(|(key, _)| s.eq_ignore_ascii_case(key)) {
+                        let name = crate::constants::resolve_alias(s);
+                        match <$t>::NAME_MAP.iter().find(|(key, _)| name.eq_ignore_ascii_case(key)) {
                             Some((_, v)) => Ok(*v),
-                            None => Err(serde::de::Error::invalid_value(serde::de::Unexpected::Str(s), &self)),
+                            None => match s.parse::<u64>() {
+                                Ok(code) => self.visit_u64(code),
+                                Err(_) => Err(serde::de::Error::invalid_value(serde::de::Unexpected::Str(s), &self)),
+                            },
+                        }
+                    }
+
+                    fn visit_u64<E>(self, code: u64) -> Result<Self::Value, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        use $crate::attribute_set::EvdevEnum;
+                        let value = <$t>::from_index(code as usize);
+                        if value.to_index() as u64 == code {
+                            Ok(value)
+                        } else {
+                            Err(serde::de::Error::invalid_value(serde::de::Unexpected::Unsigned(code), &self))
                         }
                     }
+
+                    fn visit_i64<E>(self, code: i64) -> Result<Self::Value, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match u64::try_from(code) {
+                            Ok(code) => self.visit_u64(code),
+                            Err(_) => Err(serde::de::Error::invalid_value(serde::de::Unexpected::Signed(code), &self)),
+                        }
+                    }
+                }
+                if deserializer.is_human_readable() {
+                    deserializer.deserialize_any(Visitor)
+                } else {
+                    deserializer.deserialize_u16(Visitor)
+                }
+            }
+        }
+        #[cfg(feature = "arbitrary")]
+        impl<'a> arbitrary::Arbitrary<'a> for $t {
+            fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+                // mostly named codes, which are the ones devices use
+                if u.ratio(7, 8)? {
+                    Ok(*u.choose(Self::ALL)?)
+                } else {
+                    use $crate::attribute_set::EvdevEnum;
+                    Ok(Self::from_index(u.arbitrary::<u16>()?.into()))
                 }
-                deserializer.deserialize_str(Visitor)
             }
         }
     }
 }
+
+/// Implements `Display` for types defined with `evdev_enum!`, showing the name of the constant
+/// or, for values without a name, the name `Debug` shows, e.g. `KEY_UNKNOWN(0x2e7)`.
+macro_rules! evdev_enum_display {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl std::fmt::Display for $t {
+                fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    match Self::NAME_MAP.iter().find(|e| e.1 == *self) {
+                        Some(e) => f.pad(e.0),
+                        None => write!(f, "{:?}", self),
+                    }
+                }
+            }
+        )*
+    };
+}
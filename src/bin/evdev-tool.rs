@@ -0,0 +1,117 @@
+//! A command line tool for inspecting input devices, built with the `cli` feature.
+//!
+//! Besides being useful for debugging, it shows how the pieces of the library fit together:
+//! enumerating devices, printing them like `evtest`, recording and replaying events in the evemu
+//! format, and finding out who holds a grab.
+
+use evdev::evemu::{Description, Recording, Writer};
+use evdev::{fmt, Device};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+usage: evdev-tool <command> [args]
+
+commands:
+    list                     list the input devices
+    show <device>            describe a device like evtest does
+    monitor <device>         describe a device and print its events
+    record <device> [file]   record events in the evemu format, to stdout by default
+    replay <file>            replay an evemu recording through a virtual device
+    grab-test <device>       check whether a device can be grabbed, and by whom it is held";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args[..] {
+        ["list"] => list(),
+        ["show", device] => show(device),
+        ["monitor", device] => monitor(device),
+        ["record", device] => record(device, io::stdout().lock()),
+        ["record", device, file] => File::create(file).and_then(|f| record(device, f)),
+        ["replay", file] => replay(file),
+        ["grab-test", device] => grab_test(device),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("evdev-tool: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn list() -> io::Result<()> {
+    let mut devices: Vec<_> = evdev::enumerate().collect();
+    devices.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (path, device) in devices {
+        println!(
+            "{}:\t{}",
+            path.display(),
+            device.name().unwrap_or("Unnamed device")
+        );
+    }
+    Ok(())
+}
+
+fn show(path: &str) -> io::Result<()> {
+    let device = Device::open(path)?;
+    print!("{}", fmt::device_header(&device));
+    Ok(())
+}
+
+fn monitor(path: &str) -> io::Result<()> {
+    let mut device = Device::open(path)?;
+    print!("{}", fmt::device_header(&device));
+    println!("Testing ... (interrupt to exit)");
+    loop {
+        for ev in device.fetch_events()? {
+            println!("{}", fmt::pretty_print(&ev.destructure()));
+        }
+    }
+}
+
+fn record(path: &str, out: impl Write) -> io::Result<()> {
+    let mut device = Device::open(path)?;
+    let description = Description::from_device(&device)?;
+    let mut writer = Writer::new(BufWriter::new(out), &description)?;
+    eprintln!("Recording {path} (interrupt to exit)");
+    loop {
+        for ev in device.fetch_events()? {
+            writer.write_event(&ev)?;
+        }
+        writer.flush()?;
+    }
+}
+
+fn replay(file: &str) -> io::Result<()> {
+    let recording = Recording::load(file)?;
+    let mut device = recording.description.create_virtual_device()?;
+    for path in device.enumerate_dev_nodes_blocking()? {
+        eprintln!("Replaying through {}", path?.display());
+    }
+    recording.replay(&mut device)
+}
+
+fn grab_test(path: &str) -> io::Result<()> {
+    let mut device = Device::open(path)?;
+    match device.grab() {
+        Ok(()) => {
+            println!("{path} can be grabbed");
+            device.ungrab()
+        }
+        Err(e) if e.raw_os_error() == Some(libc::EBUSY) => {
+            println!("{path} is grabbed by another process");
+            for holder in device.holders()? {
+                println!("    held open by {holder}");
+            }
+            Ok(())
+        }
+        Err(e) => Err(e),
+    }
+}
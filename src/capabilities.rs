@@ -0,0 +1,274 @@
+use crate::raw_stream::RawDevice;
+use crate::{
+    AbsInfo, AbsoluteAxisCode, AttributeSetRef, BusType, EventType, FFEffectCode, KeyCode, LedCode,
+    MiscCode, PropType, RelativeAxisCode, SoundCode, SwitchCode,
+};
+use std::{fmt, io};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A description of everything a device supports, returned by
+/// [`Device::capabilities_report`](crate::Device::capabilities_report).
+///
+/// With the `serde` feature enabled, the report can be serialized, for example to dump a
+/// machine-readable description of a device for diagnostics. Codes are serialized by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CapabilitiesReport {
+    pub name: Option<String>,
+    pub physical_path: Option<String>,
+    pub unique_name: Option<String>,
+    pub bus_type: BusType,
+    pub vendor: u16,
+    pub product: u16,
+    pub version: u16,
+    /// The version of the evdev driver, as `(major, minor, patch)`.
+    pub driver_version: (u8, u8, u8),
+    pub properties: Vec<PropType>,
+    pub event_types: Vec<EventType>,
+    pub keys: Vec<KeyCode>,
+    pub relative_axes: Vec<RelativeAxisCode>,
+    /// The supported absolute axes along with their ranges and current values.
+    pub absolute_axes: Vec<(AbsoluteAxisCode, AbsInfo)>,
+    pub misc: Vec<MiscCode>,
+    pub switches: Vec<SwitchCode>,
+    pub leds: Vec<LedCode>,
+    pub sounds: Vec<SoundCode>,
+    pub ff_effects: Vec<FFEffectCode>,
+    /// The number of force feedback effects the device can play simultaneously.
+    pub max_ff_effects: usize,
+}
+
+fn to_vec<T: crate::attribute_set::ArrayedEvdevEnum>(set: Option<&AttributeSetRef<T>>) -> Vec<T> {
+    set.map_or_else(Vec::new, |set| set.iter().collect())
+}
+
+impl CapabilitiesReport {
+    pub(crate) fn new(device: &RawDevice) -> io::Result<Self> {
+        let id = device.input_id();
+        Ok(Self {
+            name: device.name().map(str::to_owned),
+            physical_path: device.physical_path().map(str::to_owned),
+            unique_name: device.unique_name().map(str::to_owned),
+            bus_type: id.bus_type(),
+            vendor: id.vendor(),
+            product: id.product(),
+            version: id.version(),
+            driver_version: device.driver_version(),
+            properties: device.properties().iter().collect(),
+            event_types: device.supported_events().iter().collect(),
+            keys: to_vec(device.supported_keys()),
+            relative_axes: to_vec(device.supported_relative_axes()),
+            absolute_axes: device.get_absinfo()?.collect(),
+            misc: to_vec(device.misc_properties()),
+            switches: to_vec(device.supported_switches()),
+            leds: to_vec(device.supported_leds()),
+            sounds: to_vec(device.supported_sounds()),
+            ff_effects: to_vec(device.supported_ff()),
+            max_ff_effects: device.max_ff_effects(),
+        })
+    }
+}
+
+/// Codes present in only one of two [`CapabilitiesReport`]s, see [`CapabilitiesDiff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CodeChanges<T> {
+    /// The codes only supported by the other device.
+    pub added: Vec<T>,
+    /// The codes only supported by this device.
+    pub removed: Vec<T>,
+}
+
+impl<T: Copy + PartialEq> CodeChanges<T> {
+    fn new(old: &[T], new: &[T]) -> Self {
+        Self {
+            added: new.iter().filter(|c| !old.contains(c)).copied().collect(),
+            removed: old.iter().filter(|c| !new.contains(c)).copied().collect(),
+        }
+    }
+
+    /// Returns `true` if no codes were added or removed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// The differences between two [`CapabilitiesReport`]s, returned by
+/// [`CapabilitiesReport::diff`].
+///
+/// The [`Display`](fmt::Display) implementation lists one difference per line, prefixed with
+/// `+` for added codes, `-` for removed codes and `~` for changed axes, which is handy for quirk
+/// debugging and in the messages of failed test assertions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CapabilitiesDiff {
+    pub properties: CodeChanges<PropType>,
+    pub event_types: CodeChanges<EventType>,
+    pub keys: CodeChanges<KeyCode>,
+    pub relative_axes: CodeChanges<RelativeAxisCode>,
+    pub absolute_axes: CodeChanges<AbsoluteAxisCode>,
+    /// The axes supported by both devices whose range, fuzz, flat or resolution differ, along
+    /// with the `AbsInfo` of this device and of the other one. Current values aren't compared.
+    pub changed_absinfo: Vec<(AbsoluteAxisCode, AbsInfo, AbsInfo)>,
+    pub misc: CodeChanges<MiscCode>,
+    pub switches: CodeChanges<SwitchCode>,
+    pub leds: CodeChanges<LedCode>,
+    pub sounds: CodeChanges<SoundCode>,
+    pub ff_effects: CodeChanges<FFEffectCode>,
+}
+
+impl CapabilitiesDiff {
+    /// Returns `true` if the two devices support the same events and properties.
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+            && self.event_types.is_empty()
+            && self.keys.is_empty()
+            && self.relative_axes.is_empty()
+            && self.absolute_axes.is_empty()
+            && self.changed_absinfo.is_empty()
+            && self.misc.is_empty()
+            && self.switches.is_empty()
+            && self.leds.is_empty()
+            && self.sounds.is_empty()
+            && self.ff_effects.is_empty()
+    }
+}
+
+impl CapabilitiesReport {
+    /// Compare the capabilities of this device to those of `other`, e.g. another device or a
+    /// later report of the same device.
+    ///
+    /// Codes only `other` supports are reported as added, codes only this device supports as
+    /// removed.
+    pub fn diff(&self, other: &CapabilitiesReport) -> CapabilitiesDiff {
+        let axes = |report: &CapabilitiesReport| -> Vec<AbsoluteAxisCode> {
+            report.absolute_axes.iter().map(|&(axis, _)| axis).collect()
+        };
+        let changed_absinfo = self
+            .absolute_axes
+            .iter()
+            .filter_map(|&(axis, old)| {
+                let &(_, new) = other.absolute_axes.iter().find(|&&(a, _)| a == axis)?;
+                let limits =
+                    |i: AbsInfo| (i.minimum(), i.maximum(), i.fuzz(), i.flat(), i.resolution());
+                (limits(old) != limits(new)).then_some((axis, old, new))
+            })
+            .collect();
+        CapabilitiesDiff {
+            properties: CodeChanges::new(&self.properties, &other.properties),
+            event_types: CodeChanges::new(&self.event_types, &other.event_types),
+            keys: CodeChanges::new(&self.keys, &other.keys),
+            relative_axes: CodeChanges::new(&self.relative_axes, &other.relative_axes),
+            absolute_axes: CodeChanges::new(&axes(self), &axes(other)),
+            changed_absinfo,
+            misc: CodeChanges::new(&self.misc, &other.misc),
+            switches: CodeChanges::new(&self.switches, &other.switches),
+            leds: CodeChanges::new(&self.leds, &other.leds),
+            sounds: CodeChanges::new(&self.sounds, &other.sounds),
+            ff_effects: CodeChanges::new(&self.ff_effects, &other.ff_effects),
+        }
+    }
+}
+
+fn write_changes<T: fmt::Debug>(
+    f: &mut fmt::Formatter<'_>,
+    changes: &CodeChanges<T>,
+) -> fmt::Result {
+    for code in &changes.added {
+        writeln!(f, "+ {code:?}")?;
+    }
+    for code in &changes.removed {
+        writeln!(f, "- {code:?}")?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for CapabilitiesDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_changes(f, &self.properties)?;
+        write_changes(f, &self.event_types)?;
+        write_changes(f, &self.keys)?;
+        write_changes(f, &self.relative_axes)?;
+        write_changes(f, &self.absolute_axes)?;
+        for (axis, old, new) in &self.changed_absinfo {
+            write!(f, "~ {axis:?}:")?;
+            let fields = [
+                ("min", old.minimum(), new.minimum()),
+                ("max", old.maximum(), new.maximum()),
+                ("fuzz", old.fuzz(), new.fuzz()),
+                ("flat", old.flat(), new.flat()),
+                ("resolution", old.resolution(), new.resolution()),
+            ];
+            for (name, old, new) in fields {
+                if old != new {
+                    write!(f, " {name} {old} -> {new}")?;
+                }
+            }
+            writeln!(f)?;
+        }
+        write_changes(f, &self.misc)?;
+        write_changes(f, &self.switches)?;
+        write_changes(f, &self.leds)?;
+        write_changes(f, &self.sounds)?;
+        write_changes(f, &self.ff_effects)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> CapabilitiesReport {
+        CapabilitiesReport {
+            name: Some("test".to_owned()),
+            physical_path: None,
+            unique_name: None,
+            bus_type: BusType::BUS_USB,
+            vendor: 1,
+            product: 2,
+            version: 3,
+            driver_version: (1, 0, 1),
+            properties: vec![],
+            event_types: vec![
+                EventType::SYNCHRONIZATION,
+                EventType::KEY,
+                EventType::ABSOLUTE,
+            ],
+            keys: vec![KeyCode::KEY_A, KeyCode::KEY_B],
+            relative_axes: vec![],
+            absolute_axes: vec![
+                (AbsoluteAxisCode::ABS_X, AbsInfo::new(0, 0, 100, 0, 0, 10)),
+                (AbsoluteAxisCode::ABS_Y, AbsInfo::new(0, 0, 100, 0, 0, 10)),
+            ],
+            misc: vec![],
+            switches: vec![],
+            leds: vec![],
+            sounds: vec![],
+            ff_effects: vec![],
+            max_ff_effects: 0,
+        }
+    }
+
+    #[test]
+    fn diff_reports() {
+        let old = report();
+        assert!(old.diff(&old).is_empty());
+
+        let mut new = report();
+        new.properties.push(PropType::POINTER);
+        new.keys = vec![KeyCode::KEY_B, KeyCode::KEY_C];
+        // only the current value differs
+        new.absolute_axes[0].1 = AbsInfo::new(50, 0, 100, 0, 0, 10);
+        new.absolute_axes[1].1 = AbsInfo::new(0, 0, 200, 4, 0, 10);
+        let diff = old.diff(&new);
+        assert_eq!(diff.keys.added, [KeyCode::KEY_C]);
+        assert_eq!(diff.keys.removed, [KeyCode::KEY_A]);
+        assert_eq!(diff.changed_absinfo.len(), 1);
+        assert_eq!(
+            diff.to_string(),
+            "+ POINTER\n+ KEY_C\n- KEY_A\n~ ABS_Y: max 100 -> 200 fuzz 0 -> 4\n"
+        );
+    }
+}
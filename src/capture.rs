@@ -0,0 +1,383 @@
+//! A compact binary capture format for long recording sessions.
+//!
+//! A capture starts with a header holding the description of the device, followed by fixed-size
+//! event records. Events are written as they arrive, so a capture can be streamed and a capture
+//! file can be appended to with [`Writer::append`]. Captures are several times smaller than evemu
+//! recordings and much faster to write and read, at the cost of not being human-readable. They
+//! can be converted to an evemu [`Recording`] with [`Reader::into_recording`].
+//!
+//! All numbers are stored in little-endian byte order. The header consists of:
+//!
+//! - the magic bytes `EVDEVCAP` and the format version as a `u32`,
+//! - the device name as a `u32` length followed by UTF-8 bytes,
+//! - the bus type, vendor, product and version as `u16`s,
+//! - the bitmasks of properties, event types, keys, relative axes, misc events, switches, LEDs,
+//!   sounds and force feedback effects, each as a `u32` length followed by the bytes,
+//! - the number of absolute axes as a `u32`, each followed by its code as a `u16` and the value,
+//!   minimum, maximum, fuzz, flat and resolution as `i32`s.
+//!
+//! Each event record is 20 bytes: the seconds of its timestamp as an `i64`, the microseconds as a
+//! `u32`, the type and code as `u16`s and the value as an `i32`.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use evdev::capture::Writer;
+//! use evdev::evemu::Description;
+//! let mut device = evdev::Device::open("/dev/input/event0")?;
+//! let file = std::io::BufWriter::new(std::fs::File::create("session.cap")?);
+//! let mut writer = Writer::new(file, &Description::from_device(&device)?)?;
+//! loop {
+//!     for ev in device.fetch_events()? {
+//!         writer.write_event(&ev)?;
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! [`Recording`]: crate::evemu::Recording
+
+use crate::compat::input_event;
+use crate::evemu::{bitmap, from_bitmap, Description, Recording, DEFAULT_MAX_FF_EFFECTS};
+use crate::{
+    AbsInfo, AbsoluteAxisCode, BusType, EventType, FFEffectCode, InputEvent, InputId, KeyCode,
+    LedCode, MiscCode, PropType, RelativeAxisCode, SoundCode, SwitchCode,
+};
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 8] = b"EVDEVCAP";
+const VERSION: u32 = 1;
+const RECORD_SIZE: usize = 20;
+
+/// Writes a device description and events in the capture format.
+#[derive(Debug)]
+pub struct Writer<W: Write> {
+    out: W,
+}
+
+impl<W: Write> Writer<W> {
+    /// Create a writer, writing the header with the description of the device right away.
+    pub fn new(mut out: W, description: &Description) -> io::Result<Self> {
+        write_header(&mut out, description)?;
+        Ok(Self { out })
+    }
+
+    /// Create a writer that appends events to an existing capture, without writing a header.
+    ///
+    /// `out` is usually a file opened in append mode.
+    pub fn append(out: W) -> Self {
+        Self { out }
+    }
+
+    /// Write an event.
+    pub fn write_event(&mut self, event: &InputEvent) -> io::Result<()> {
+        let raw = event.as_ref();
+        let mut record = [0u8; RECORD_SIZE];
+        // the fields are 32 bits wide on some platforms
+        #[allow(clippy::useless_conversion)]
+        let secs = i64::from(raw.time.tv_sec);
+        record[0..8].copy_from_slice(&secs.to_le_bytes());
+        record[8..12].copy_from_slice(&(raw.time.tv_usec as u32).to_le_bytes());
+        record[12..14].copy_from_slice(&raw.type_.to_le_bytes());
+        record[14..16].copy_from_slice(&raw.code.to_le_bytes());
+        record[16..20].copy_from_slice(&raw.value.to_le_bytes());
+        self.out.write_all(&record)
+    }
+
+    /// Flush the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+fn write_header(out: &mut impl Write, desc: &Description) -> io::Result<()> {
+    out.write_all(MAGIC)?;
+    out.write_all(&VERSION.to_le_bytes())?;
+    write_bytes(out, desc.name.as_bytes())?;
+    let id = &desc.input_id;
+    for n in [id.bus_type().0, id.vendor(), id.product(), id.version()] {
+        out.write_all(&n.to_le_bytes())?;
+    }
+    let masks = [
+        bitmap(&desc.properties, PropType::COUNT),
+        bitmap(&desc.event_types, EventType::COUNT),
+        bitmap(&desc.keys, KeyCode::COUNT),
+        bitmap(&desc.relative_axes, RelativeAxisCode::COUNT),
+        bitmap(&desc.misc, MiscCode::COUNT),
+        bitmap(&desc.switches, SwitchCode::COUNT),
+        bitmap(&desc.leds, LedCode::COUNT),
+        bitmap(&desc.sounds, SoundCode::COUNT),
+        bitmap(&desc.ff, FFEffectCode::COUNT),
+    ];
+    for mask in &masks {
+        write_bytes(out, mask)?;
+    }
+    out.write_all(&(desc.absolute_axes.len() as u32).to_le_bytes())?;
+    for (axis, info) in &desc.absolute_axes {
+        out.write_all(&axis.0.to_le_bytes())?;
+        for n in [
+            info.value(),
+            info.minimum(),
+            info.maximum(),
+            info.fuzz(),
+            info.flat(),
+            info.resolution(),
+        ] {
+            out.write_all(&n.to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+fn write_bytes(out: &mut impl Write, bytes: &[u8]) -> io::Result<()> {
+    out.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    out.write_all(bytes)
+}
+
+/// Reads a capture, starting with the description of the device and followed by its events.
+///
+/// The reader is an iterator over the events of the capture.
+#[derive(Debug)]
+pub struct Reader<R: Read> {
+    input: R,
+    description: Description,
+}
+
+impl<R: Read> Reader<R> {
+    /// Create a reader, reading the header right away.
+    ///
+    /// Fails with [`InvalidData`](io::ErrorKind::InvalidData) if `input` doesn't start with a
+    /// capture header.
+    pub fn new(mut input: R) -> io::Result<Self> {
+        let description = read_header(&mut input)?;
+        Ok(Self { input, description })
+    }
+
+    /// Returns the description of the captured device.
+    pub fn description(&self) -> &Description {
+        &self.description
+    }
+
+    /// Read the next event, returning `None` at the end of the capture.
+    ///
+    /// Fails with [`UnexpectedEof`](io::ErrorKind::UnexpectedEof) if the capture ends in the
+    /// middle of an event, such as when the writing process was killed.
+    pub fn read_event(&mut self) -> io::Result<Option<InputEvent>> {
+        let mut record = [0u8; RECORD_SIZE];
+        let mut filled = 0;
+        while filled < RECORD_SIZE {
+            match self.input.read(&mut record[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "capture ends with a partial event",
+                    ))
+                }
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let secs = i64::from_le_bytes(record[0..8].try_into().unwrap());
+        let usecs = u32::from_le_bytes(record[8..12].try_into().unwrap());
+        let time = crate::checked_systime(secs, usecs.into())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid event timestamp"))?;
+        Ok(Some(InputEvent::from(input_event {
+            time: crate::systime_to_timeval(&time),
+            type_: u16::from_le_bytes(record[12..14].try_into().unwrap()),
+            code: u16::from_le_bytes(record[14..16].try_into().unwrap()),
+            value: i32::from_le_bytes(record[16..20].try_into().unwrap()),
+        })))
+    }
+
+    /// Read the remaining events into an evemu recording.
+    pub fn into_recording(mut self) -> io::Result<Recording> {
+        let mut events = Vec::new();
+        while let Some(ev) = self.read_event()? {
+            events.push(ev);
+        }
+        Ok(Recording {
+            description: self.description,
+            events,
+        })
+    }
+}
+
+impl<R: Read> Iterator for Reader<R> {
+    type Item = io::Result<InputEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_event().transpose()
+    }
+}
+
+fn invalid() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid capture header")
+}
+
+fn read_array<const N: usize>(input: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    input.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn read_u16(input: &mut impl Read) -> io::Result<u16> {
+    read_array(input).map(u16::from_le_bytes)
+}
+
+fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    read_array(input).map(u32::from_le_bytes)
+}
+
+fn read_bytes(input: &mut impl Read) -> io::Result<Vec<u8>> {
+    let len = read_u32(input)? as u64;
+    let mut bytes = Vec::new();
+    input.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(invalid());
+    }
+    Ok(bytes)
+}
+
+fn read_header(input: &mut impl Read) -> io::Result<Description> {
+    if &read_array::<8>(input)? != MAGIC || read_u32(input)? != VERSION {
+        return Err(invalid());
+    }
+    let name = String::from_utf8(read_bytes(input)?).map_err(|_| invalid())?;
+    let input_id = InputId::new(
+        BusType(read_u16(input)?),
+        read_u16(input)?,
+        read_u16(input)?,
+        read_u16(input)?,
+    );
+    let properties = from_bitmap(&read_bytes(input)?);
+    let event_types = from_bitmap(&read_bytes(input)?);
+    let keys = from_bitmap(&read_bytes(input)?);
+    let relative_axes = from_bitmap(&read_bytes(input)?);
+    let misc = from_bitmap(&read_bytes(input)?);
+    let switches = from_bitmap(&read_bytes(input)?);
+    let leds = from_bitmap(&read_bytes(input)?);
+    let sounds = from_bitmap(&read_bytes(input)?);
+    let ff = from_bitmap(&read_bytes(input)?);
+    let mut absolute_axes = Vec::new();
+    for _ in 0..read_u32(input)? {
+        let axis = AbsoluteAxisCode(read_u16(input)?);
+        let mut n = [0i32; 6];
+        for n in &mut n {
+            *n = read_array(input).map(i32::from_le_bytes)?;
+        }
+        let [value, min, max, fuzz, flat, res] = n;
+        absolute_axes.push((axis, AbsInfo::new(value, min, max, fuzz, flat, res)));
+    }
+    Ok(Description {
+        name,
+        input_id,
+        properties,
+        event_types,
+        keys,
+        relative_axes,
+        absolute_axes,
+        misc,
+        switches,
+        leds,
+        sounds,
+        ff,
+        max_ff_effects: DEFAULT_MAX_FF_EFFECTS,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AttributeSet, KeyEvent};
+    use std::time::{Duration, SystemTime};
+
+    fn description() -> Description {
+        Description {
+            name: "Test Tablet".to_owned(),
+            input_id: InputId::new(BusType::BUS_USB, 0x056a, 0x0001, 0x0100),
+            properties: [PropType::DIRECT].into_iter().collect(),
+            event_types: [EventType::KEY, EventType::ABSOLUTE].into_iter().collect(),
+            keys: [KeyCode::BTN_TOUCH].into_iter().collect(),
+            relative_axes: AttributeSet::new(),
+            absolute_axes: vec![(AbsoluteAxisCode::ABS_X, AbsInfo::new(5, 0, 4096, 1, 2, 40))],
+            misc: AttributeSet::new(),
+            switches: AttributeSet::new(),
+            leds: AttributeSet::new(),
+            sounds: AttributeSet::new(),
+            ff: AttributeSet::new(),
+            max_ff_effects: 0,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_micros(1_500_042);
+        let press = InputEvent::from(input_event {
+            time: crate::systime_to_timeval(&time),
+            ..*KeyEvent::new(KeyCode::BTN_TOUCH, 1).as_ref()
+        });
+        let mut writer = Writer::new(Vec::new(), &description()).unwrap();
+        writer.write_event(&press).unwrap();
+        let mut bytes = writer.into_inner();
+        let mut appender = Writer::append(&mut bytes);
+        appender
+            .write_event(&KeyEvent::new(KeyCode::BTN_TOUCH, 0))
+            .unwrap();
+
+        let reader = Reader::new(&bytes[..]).unwrap();
+        let desc = reader.description();
+        assert_eq!(desc.name, "Test Tablet");
+        assert_eq!(desc.input_id, description().input_id);
+        assert!(desc.properties.contains(PropType::DIRECT));
+        assert!(desc.keys.contains(KeyCode::BTN_TOUCH));
+        assert_eq!(desc.absolute_axes[0].1.resolution(), 40);
+
+        let events: Vec<_> = reader.collect::<io::Result<_>>().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].code(), KeyCode::BTN_TOUCH.0);
+        assert_eq!(events[1].value(), 0);
+        assert_eq!(events[0].timestamp(), time);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut writer = Writer::new(Vec::new(), &description()).unwrap();
+        writer
+            .write_event(&KeyEvent::new(KeyCode::BTN_TOUCH, 1))
+            .unwrap();
+        let mut bytes = writer.into_inner();
+        bytes.pop();
+        let mut reader = Reader::new(&bytes[..]).unwrap();
+        let err = reader.read_event().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        assert!(Reader::new(&b"EVEMU 1.3"[..]).is_err());
+    }
+
+    #[test]
+    fn timestamps() {
+        let mut writer = Writer::new(Vec::new(), &description()).unwrap();
+        let event = KeyEvent::new(KeyCode::BTN_TOUCH, 1);
+        writer.write_event(&event).unwrap();
+        let mut bytes = writer.into_inner();
+        let record = bytes.len() - 20;
+
+        // before the epoch
+        bytes[record..record + 8].copy_from_slice(&(-1i64).to_le_bytes());
+        let mut reader = Reader::new(&bytes[..]).unwrap();
+        let time = reader.read_event().unwrap().unwrap().timestamp();
+        assert!(time < SystemTime::UNIX_EPOCH);
+
+        bytes[record..record + 8].copy_from_slice(&i64::MAX.to_le_bytes());
+        bytes[record + 8..record + 12].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut reader = Reader::new(&bytes[..]).unwrap();
+        let err = reader.read_event().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,304 @@
+use crate::{EnumParseError, EventSummary, InputEvent, KeyCode, Modifiers};
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// A key combination, such as Ctrl+Alt+Delete.
+///
+/// A chord consists of modifiers and other keys. Generic modifiers such as [`Modifiers::CTRL`]
+/// match either the left or the right key, while sided modifiers such as
+/// [`Modifiers::LEFT_CTRL`] only match that key. The lock modifiers are ignored.
+///
+/// Chords can be parsed from strings like `"Ctrl+Alt+Delete"` or `"SUPER+K"`. Modifier names
+/// (`ctrl`/`control`, `shift`, `alt`, `super`/`meta`/`logo`, optionally prefixed with `left`
+/// or `right`) are matched case-insensitively, and other keys are either key names like
+/// `KEY_K` or the same without the `KEY_` prefix.
+///
+/// ```
+/// use evdev::{Chord, KeyCode, Modifiers};
+///
+/// let chord: Chord = "Ctrl+Alt+Delete".parse().unwrap();
+/// assert_eq!(chord, Chord::new(Modifiers::CTRL | Modifiers::ALT, [KeyCode::KEY_DELETE]));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chord {
+    modifiers: Modifiers,
+    keys: Vec<KeyCode>,
+}
+
+const GROUPS: [Modifiers; 4] = [
+    Modifiers::SHIFT,
+    Modifiers::CTRL,
+    Modifiers::ALT,
+    Modifiers::META,
+];
+
+impl Chord {
+    /// Create a chord of the given modifiers and keys.
+    ///
+    /// Modifier keys in `keys` are treated as sided modifiers.
+    pub fn new(modifiers: Modifiers, keys: impl IntoIterator<Item = KeyCode>) -> Self {
+        let mut chord = Self {
+            modifiers: modifiers
+                & (Modifiers::SHIFT | Modifiers::CTRL | Modifiers::ALT | Modifiers::META),
+            keys: Vec::new(),
+        };
+        for key in keys {
+            match Modifiers::from_key(key) {
+                Some(m) => chord.modifiers |= m,
+                None => chord.keys.push(key),
+            }
+        }
+        chord.keys.sort_unstable();
+        chord.keys.dedup();
+        chord
+    }
+
+    /// Returns the modifiers of this chord.
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// Returns the non-modifier keys of this chord.
+    pub fn keys(&self) -> &[KeyCode] {
+        &self.keys
+    }
+
+    /// Returns `true` if exactly the keys of this chord are held.
+    fn matches(&self, modifiers: Modifiers, keys: &[KeyCode]) -> bool {
+        let modifiers_match = GROUPS.iter().all(|&group| {
+            let want = self.modifiers & group;
+            let held = modifiers & group;
+            if want.is_empty() {
+                held.is_empty()
+            } else if want == group {
+                !held.is_empty()
+            } else {
+                held == want
+            }
+        });
+        modifiers_match && self.keys == keys
+    }
+
+    /// Returns `true` if releasing `key` breaks this chord.
+    fn uses(&self, key: KeyCode) -> bool {
+        match Modifiers::from_key(key) {
+            Some(m) => self.modifiers.intersects(m),
+            None => self.keys.binary_search(&key).is_ok(),
+        }
+    }
+}
+
+impl FromStr for Chord {
+    type Err = EnumParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = Modifiers::NONE;
+        let mut keys = Vec::new();
+        for part in s.split('+').map(str::trim) {
+            let lower = part.to_ascii_lowercase();
+            let (side, name) = if let Some(name) = lower.strip_prefix("left") {
+                (Some(false), name)
+            } else if let Some(name) = lower.strip_prefix("right") {
+                (Some(true), name)
+            } else {
+                (None, lower.as_str())
+            };
+            let name = name.trim_start_matches('_');
+            let group = match name {
+                "ctrl" | "control" => Some(0),
+                "shift" => Some(1),
+                "alt" => Some(2),
+                "super" | "meta" | "logo" => Some(3),
+                _ => None,
+            };
+            if let Some(group) = group {
+                let [generic, left, right] = [
+                    [Modifiers::CTRL, Modifiers::LEFT_CTRL, Modifiers::RIGHT_CTRL],
+                    [
+                        Modifiers::SHIFT,
+                        Modifiers::LEFT_SHIFT,
+                        Modifiers::RIGHT_SHIFT,
+                    ],
+                    [Modifiers::ALT, Modifiers::LEFT_ALT, Modifiers::RIGHT_ALT],
+                    [Modifiers::META, Modifiers::LEFT_META, Modifiers::RIGHT_META],
+                ][group];
+                modifiers |= match side {
+                    None => generic,
+                    Some(false) => left,
+                    Some(true) => right,
+                };
+                continue;
+            }
+            let upper = part.to_ascii_uppercase();
+            let key = upper
+                .parse::<KeyCode>()
+                .or_else(|_| format!("KEY_{}", upper).parse::<KeyCode>())?;
+            keys.push(key);
+        }
+        if modifiers.is_empty() && keys.is_empty() {
+            return Err(EnumParseError(()));
+        }
+        Ok(Self::new(modifiers, keys))
+    }
+}
+
+/// An activation or release of a chord, reported by [`ChordDetector::process`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChordEvent<T> {
+    /// All keys of the chord are held, and nothing else.
+    Activated(T),
+    /// A key of a previously activated chord was released.
+    Released(T),
+}
+
+/// Detects when configured [`Chord`]s are pressed and released.
+///
+/// A chord is activated when a key press results in exactly the keys of the chord being held,
+/// regardless of the order in which they were pressed. Holding additional keys prevents the
+/// activation, so Ctrl+K does not trigger while Ctrl+Shift+K is being pressed. Key repeats
+/// never activate a chord. Once activated, a chord stays active until one of its keys is
+/// released, even if other keys are pressed in the meantime.
+///
+/// Each chord is associated with a value of type `T`, which is reported in [`ChordEvent`]s.
+///
+/// ```
+/// use evdev::{ChordDetector, ChordEvent, KeyCode, KeyEvent};
+///
+/// let mut chords = ChordDetector::new();
+/// chords.add("Super+K".parse().unwrap(), "launcher");
+/// chords.process(&KeyEvent::new(KeyCode::KEY_LEFTMETA, 1));
+/// let events = chords.process(&KeyEvent::new(KeyCode::KEY_K, 1));
+/// assert_eq!(events, [ChordEvent::Activated("launcher")]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChordDetector<T> {
+    chords: Vec<(Chord, T)>,
+    active: Vec<bool>,
+    held: HashSet<KeyCode>,
+}
+
+impl<T: Clone> ChordDetector<T> {
+    /// Create a detector without any chords.
+    pub fn new() -> Self {
+        Self {
+            chords: Vec::new(),
+            active: Vec::new(),
+            held: HashSet::new(),
+        }
+    }
+
+    /// Add a chord, reported with `value`.
+    pub fn add(&mut self, chord: Chord, value: T) {
+        self.chords.push((chord, value));
+        self.active.push(false);
+    }
+
+    /// Returns the values of the chords that are currently active.
+    pub fn active(&self) -> impl Iterator<Item = &T> + '_ {
+        self.chords
+            .iter()
+            .zip(&self.active)
+            .filter(|(_, &active)| active)
+            .map(|((_, value), _)| value)
+    }
+
+    /// Update the detector with an event, returning the chords it activated or released.
+    pub fn process(&mut self, event: &InputEvent) -> Vec<ChordEvent<T>> {
+        let mut out = Vec::new();
+        let (key, value) = match event.destructure() {
+            EventSummary::Key(_, key, value) => (key, value),
+            _ => return out,
+        };
+        match value {
+            0 => {
+                self.held.remove(&key);
+                for ((chord, value), active) in self.chords.iter().zip(&mut self.active) {
+                    if *active && chord.uses(key) {
+                        *active = false;
+                        out.push(ChordEvent::Released(value.clone()));
+                    }
+                }
+            }
+            1 => {
+                if !self.held.insert(key) {
+                    return out;
+                }
+                let mut modifiers = Modifiers::NONE;
+                let mut keys = Vec::new();
+                for &key in &self.held {
+                    match Modifiers::from_key(key) {
+                        Some(m) => modifiers |= m,
+                        None => keys.push(key),
+                    }
+                }
+                keys.sort_unstable();
+                for ((chord, value), active) in self.chords.iter().zip(&mut self.active) {
+                    if !*active && chord.matches(modifiers, &keys) {
+                        *active = true;
+                        out.push(ChordEvent::Activated(value.clone()));
+                    }
+                }
+            }
+            _ => {}
+        }
+        out
+    }
+}
+
+impl<T: Clone> Default for ChordDetector<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyEvent;
+
+    fn press(chords: &mut ChordDetector<u8>, key: KeyCode, value: i32) -> Vec<ChordEvent<u8>> {
+        chords.process(&KeyEvent::new(key, value))
+    }
+
+    #[test]
+    fn parse() {
+        let chord: Chord = "leftctrl + shift + k".parse().unwrap();
+        assert_eq!(
+            chord,
+            Chord::new(Modifiers::LEFT_CTRL | Modifiers::SHIFT, [KeyCode::KEY_K])
+        );
+        assert_eq!("KEY_F1".parse::<Chord>().unwrap().keys(), [KeyCode::KEY_F1]);
+        assert!("Ctrl+Nope".parse::<Chord>().is_err());
+    }
+
+    #[test]
+    fn exact_match_and_order() {
+        let mut chords = ChordDetector::new();
+        chords.add("Ctrl+K".parse().unwrap(), 1);
+        chords.add("Ctrl+Shift+K".parse().unwrap(), 2);
+
+        // order doesn't matter, and repeats don't activate
+        assert!(press(&mut chords, KeyCode::KEY_K, 1).is_empty());
+        assert_eq!(
+            press(&mut chords, KeyCode::KEY_RIGHTCTRL, 1),
+            [ChordEvent::Activated(1)]
+        );
+        assert!(press(&mut chords, KeyCode::KEY_K, 2).is_empty());
+        assert_eq!(
+            press(&mut chords, KeyCode::KEY_K, 0),
+            [ChordEvent::Released(1)]
+        );
+
+        // the longer chord doesn't activate the shorter one
+        assert!(press(&mut chords, KeyCode::KEY_LEFTSHIFT, 1).is_empty());
+        assert_eq!(
+            press(&mut chords, KeyCode::KEY_K, 1),
+            [ChordEvent::Activated(2)]
+        );
+        assert_eq!(chords.active().collect::<Vec<_>>(), [&2]);
+        assert_eq!(
+            press(&mut chords, KeyCode::KEY_LEFTSHIFT, 0),
+            [ChordEvent::Released(2)]
+        );
+    }
+}
@@ -19,10 +19,23 @@ cfg_if! {
     ))] {
         pub(crate) use libc::{
             ff_condition_effect, ff_constant_effect, ff_envelope, ff_periodic_effect, ff_ramp_effect,
-            ff_replay, ff_rumble_effect, ff_trigger, input_absinfo, input_event, input_id,
+            ff_replay, ff_rumble_effect, ff_trigger, input_absinfo, input_id,
             input_keymap_entry, uinput_abs_setup, uinput_setup, ABS_CNT, EV_CNT, FF_CNT, INPUT_PROP_CNT,
             KEY_CNT, LED_CNT, MSC_CNT, REL_CNT, SND_CNT, SW_CNT, UINPUT_MAX_NAME_SIZE,
         };
+
+        cfg_if! {
+            // x32 is the one 32-bit target whose kernel uses 64-bit longs
+            if #[cfg(any(target_pointer_width = "64", target_arch = "x86_64"))] {
+                pub(crate) use libc::{input_event, timeval};
+                // compiled for tests so its layout is checked on 64-bit hosts too
+                #[cfg(test)]
+                pub(crate) mod time64;
+            } else {
+                pub(crate) mod time64;
+                pub(crate) use time64::{input_event, timeval};
+            }
+        }
     } else {
         mod non_linux;
         pub(crate) use non_linux::{
@@ -31,5 +44,6 @@ cfg_if! {
             input_keymap_entry, uinput_abs_setup, uinput_setup, ABS_CNT, EV_CNT, FF_CNT, INPUT_PROP_CNT,
             KEY_CNT, LED_CNT, MSC_CNT, REL_CNT, SND_CNT, SW_CNT, UINPUT_MAX_NAME_SIZE,
         };
+        pub(crate) use libc::timeval;
     }
 }
@@ -1,7 +1,12 @@
 //! FreeBSD and other non-Linux targets don't have these available in libc, because they're in
 //! the "linux-like" impl directory. They are copied here for convenience and compatibility.
 //!
-//! BSD-likes are only minimally supported by evdev. Use at your own risk.
+//! FreeBSD and DragonFly implement the evdev protocol natively, with the same structures and
+//! `/dev/input/eventN` device nodes as Linux. Their ioctl requests are encoded the BSD way, which
+//! nix takes care of, except for the few requests whose direction differs, see `sys.rs`. There
+//! is no sysfs, so the sysfs-based parts of the crate are unavailable there.
+//!
+//! Other BSD-likes don't implement evdev, see [`is_supported`](crate::is_supported).
 
 #![allow(non_camel_case_types)]
 
@@ -0,0 +1,26 @@
+//! The kernel stores the timestamp of an `input_event` as two `long`s. libc declares it as a
+//! `struct timeval` instead, which only matches as long as `time_t` is as wide as `long`. On
+//! 32-bit targets built with a 64-bit `time_t`, such as musl since 1.2 or glibc with
+//! `_TIME_BITS=64`, libc's declaration either has a different layout or lacks the `time` field
+//! altogether, so the kernel's layout is declared here for all 32-bit targets. The exception is
+//! x32, where the kernel's `long` is 64 bits wide unlike C's, which libc's `timeval` matches.
+
+#![allow(non_camel_case_types)]
+
+use libc::c_long;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct timeval {
+    pub tv_sec: c_long,
+    pub tv_usec: c_long,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct input_event {
+    pub time: timeval,
+    pub type_: u16,
+    pub code: u16,
+    pub value: i32,
+}
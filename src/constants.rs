@@ -92,6 +92,9 @@ evdev_enum!(
     POINTING_STICK = 0x05,
     /// Has an accelerometer. Probably reports relative events in that case?
     ACCELEROMETER = 0x06,
+    /// Is a touchpad that senses the pressure applied to the whole pad instead of having a
+    /// physical button under it.
+    PRESSUREPAD = 0x07,
 );
 
 impl PropType {
@@ -156,6 +159,8 @@ evdev_enum!(
     ABS_TILT_Y = 0x1b,
     ABS_TOOL_WIDTH = 0x1c,
     ABS_VOLUME = 0x20,
+    /// The selected profile of a device with several, such as a gaming mouse.
+    ABS_PROFILE = 0x21,
     ABS_MISC = 0x28,
     /// "MT slot being modified"
     ABS_MT_SLOT = 0x2f,
@@ -191,6 +196,46 @@ evdev_enum!(
 
 impl AbsoluteAxisCode {
     pub(crate) const COUNT: usize = ABS_CNT;
+
+    /// Returns the unit the resolution of this axis is in, following the conventions of the
+    /// kernel, or `None` for axes without a physical unit such as `ABS_PRESSURE`.
+    ///
+    /// On devices with the `ACCELEROMETER` property, the resolution of `ABS_X`, `ABS_Y` and
+    /// `ABS_Z` is in units per g, and that of `ABS_RX`, `ABS_RY` and `ABS_RZ` in units per degree
+    /// per second instead.
+    pub fn unit(self) -> Option<AxisUnit> {
+        match self {
+            Self::ABS_X
+            | Self::ABS_Y
+            | Self::ABS_Z
+            | Self::ABS_MT_POSITION_X
+            | Self::ABS_MT_POSITION_Y
+            | Self::ABS_MT_TOOL_X
+            | Self::ABS_MT_TOOL_Y
+            | Self::ABS_MT_TOUCH_MAJOR
+            | Self::ABS_MT_TOUCH_MINOR
+            | Self::ABS_MT_WIDTH_MAJOR
+            | Self::ABS_MT_WIDTH_MINOR => Some(AxisUnit::Millimeter),
+            Self::ABS_RX
+            | Self::ABS_RY
+            | Self::ABS_RZ
+            | Self::ABS_WHEEL
+            | Self::ABS_TILT_X
+            | Self::ABS_TILT_Y => Some(AxisUnit::Radian),
+            _ => None,
+        }
+    }
+}
+
+/// The physical unit of the [resolution](crate::AbsInfo::resolution) of an absolute axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AxisUnit {
+    /// The resolution is in units per millimeter, see
+    /// [`AbsInfo::to_mm`](crate::AbsInfo::to_mm).
+    Millimeter,
+    /// The resolution is in units per radian, see
+    /// [`AbsInfo::to_radians`](crate::AbsInfo::to_radians).
+    Radian,
 }
 
 /// An event type corresponding to a physical or virtual switch.
@@ -404,3 +449,93 @@ pub struct PowerCode(pub u16);
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OtherCode(pub u16, pub u16);
+
+impl std::fmt::Display for PowerCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::str::FromStr for PowerCode {
+    type Err = crate::EnumParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("0x") {
+            Some(hex) => u16::from_str_radix(hex, 16),
+            None => s.parse(),
+        }
+        .map(Self)
+        .map_err(|_| crate::EnumParseError(()))
+    }
+}
+
+evdev_enum_display!(
+    EventType,
+    SynchronizationCode,
+    PropType,
+    RelativeAxisCode,
+    AbsoluteAxisCode,
+    SwitchCode,
+    LedCode,
+    MiscCode,
+    FFEffectCode,
+    FFStatusCode,
+    RepeatCode,
+    SoundCode,
+    UInputCode,
+);
+
+/// Alternative names of codes from the kernel headers, along with the name used by this crate.
+const ALIASES: &[(&str, &str)] = &[
+    ("BTN_MISC", "BTN_0"),
+    ("BTN_MOUSE", "BTN_LEFT"),
+    ("BTN_JOYSTICK", "BTN_TRIGGER"),
+    ("BTN_GAMEPAD", "BTN_SOUTH"),
+    ("BTN_A", "BTN_SOUTH"),
+    ("BTN_B", "BTN_EAST"),
+    ("BTN_X", "BTN_NORTH"),
+    ("BTN_Y", "BTN_WEST"),
+    ("BTN_DIGI", "BTN_TOOL_PEN"),
+    ("BTN_WHEEL", "BTN_GEAR_DOWN"),
+    ("BTN_TRIGGER_HAPPY", "BTN_TRIGGER_HAPPY1"),
+    ("KEY_HANGUEL", "KEY_HANGEUL"),
+    ("KEY_SCREENLOCK", "KEY_COFFEE"),
+    ("KEY_ROTATE_DISPLAY", "KEY_DIRECTION"),
+    ("KEY_FULL_SCREEN", "KEY_ZOOM"),
+    ("KEY_BRIGHTNESS_ZERO", "KEY_BRIGHTNESS_AUTO"),
+    ("KEY_WIMAX", "KEY_WWAN"),
+    ("KEY_MIN_INTERESTING", "KEY_MUTE"),
+    ("KEY_ALL_APPLICATIONS", "KEY_DASHBOARD"),
+    ("SW_RADIO", "SW_RFKILL_ALL"),
+];
+
+/// Returns the name used by this crate for an alternative name of a code, or the name itself.
+pub(crate) fn resolve_alias(name: &str) -> &str {
+    ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |&(_, canonical)| canonical)
+}
+
+/// Returns the prefix of the kernel's names for the codes of a type, used to name codes that
+/// don't have a constant, e.g. `KEY_UNKNOWN(0x2e7)`.
+pub(crate) fn unknown_code_prefix(type_name: &str) -> &'static str {
+    match type_name {
+        "EventType" => "EV",
+        "SynchronizationCode" => "SYN",
+        "PropType" => "INPUT_PROP",
+        "KeyCode" => "KEY",
+        "RelativeAxisCode" => "REL",
+        "AbsoluteAxisCode" => "ABS",
+        "SwitchCode" => "SW",
+        "LedCode" => "LED",
+        "MiscCode" => "MSC",
+        "FFEffectCode" => "FF",
+        "FFStatusCode" => "FF_STATUS",
+        "RepeatCode" => "REP",
+        "SoundCode" => "SND",
+        "UInputCode" => "UI",
+        "BusType" => "BUS",
+        _ => "CODE",
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{FFEffect, FFEffectCode, FFEffectData, FFEvent, InputEvent, LedCode, LedEvent};
+use std::io;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The control half of a device: a handle for sending events to it and managing its force
+/// feedback effects, returned by [`Device::control`].
+///
+/// Reading events requires `&mut Device`, so a device whose events are read on one thread
+/// couldn't otherwise be controlled from another without wrapping it in a mutex, which the
+/// reading thread holds while it waits for events. The control handle instead only needs
+/// `&self`, so it can be cloned or shared between threads and used while the reading thread is
+/// blocked.
+///
+/// The handle shares the file descriptor of the device, which stays open until the device and
+/// all handles are dropped. Effects uploaded through the handle are erased when the device is
+/// dropped, like those uploaded through the device itself.
+///
+/// ```no_run
+/// use evdev::{Device, LedCode};
+///
+/// let mut device = Device::open("/dev/input/event0")?;
+/// let control = device.control();
+/// std::thread::spawn(move || control.set_led(LedCode::LED_CAPSL, true));
+/// loop {
+///     for ev in device.fetch_events()? {
+///         println!("{ev:?}");
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`Device::control`]: crate::Device::control
+#[derive(Debug, Clone)]
+pub struct DeviceControl {
+    fd: Arc<OwnedFd>,
+    uploaded_effects: Arc<Mutex<Vec<u16>>>,
+}
+
+impl DeviceControl {
+    pub(crate) fn new(fd: &Arc<OwnedFd>, uploaded_effects: &Arc<Mutex<Vec<u16>>>) -> Self {
+        Self {
+            fd: fd.clone(),
+            uploaded_effects: uploaded_effects.clone(),
+        }
+    }
+
+    /// Send events to the device, see [`RawDevice::send_events`].
+    ///
+    /// [`RawDevice::send_events`]: crate::raw_stream::RawDevice::send_events
+    pub fn send_events(&self, events: &[InputEvent]) -> io::Result<()> {
+        crate::write_events(self.fd.as_fd(), events)?;
+        Ok(())
+    }
+
+    /// Turn an LED of the device on or off.
+    pub fn set_led(&self, led: LedCode, on: bool) -> io::Result<()> {
+        self.send_events(&[*LedEvent::new(led, on.into())])
+    }
+
+    /// Uploads a force feedback effect to the device.
+    pub fn upload_ff_effect(&self, data: FFEffectData) -> io::Result<FFEffect> {
+        FFEffect::upload(&self.fd, &self.uploaded_effects, data)
+    }
+
+    /// Rumbles the device for `duration`, see [`RawDevice::rumble`].
+    ///
+    /// [`RawDevice::rumble`]: crate::raw_stream::RawDevice::rumble
+    pub fn rumble(&self, strong: u16, weak: u16, duration: Duration) -> io::Result<()> {
+        FFEffect::rumble(&self.fd, &self.uploaded_effects, strong, weak, duration)
+    }
+
+    /// Sets the force feedback gain, see [`RawDevice::set_ff_gain`].
+    ///
+    /// [`RawDevice::set_ff_gain`]: crate::raw_stream::RawDevice::set_ff_gain
+    pub fn set_ff_gain(&self, value: u16) -> io::Result<()> {
+        self.send_events(&[*FFEvent::new(FFEffectCode::FF_GAIN, value.into())])
+    }
+
+    /// Enables or disables autocenter for the force feedback device.
+    pub fn set_ff_autocenter(&self, value: u16) -> io::Result<()> {
+        self.send_events(&[*FFEvent::new(FFEffectCode::FF_AUTOCENTER, value.into())])
+    }
+}
+
+impl AsFd for DeviceControl {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+impl AsRawFd for DeviceControl {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
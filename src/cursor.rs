@@ -0,0 +1,162 @@
+use crate::{EventSummary, InputEvent, RelativeAxisCode, SynchronizationCode};
+
+/// What an [`AbsoluteCursor`] does when motion would take it past the edge of the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EdgeBehavior {
+    /// Stop at the edge.
+    #[default]
+    Clamp,
+    /// Continue at the opposite edge.
+    Wrap,
+}
+
+/// Integrates the relative motion of a mouse into a position on a screen of a given size.
+///
+/// This is what framebuffer UIs without a display server need to draw a pointer. The position
+/// stays within `0..width` and `0..height`, and motion is scaled by a configurable speed, with
+/// fractions carried over so slow movements aren't lost.
+///
+/// ```
+/// use evdev::{
+///     AbsoluteCursor, RelativeAxisCode, RelativeAxisEvent, SynchronizationCode,
+///     SynchronizationEvent,
+/// };
+///
+/// let mut cursor = AbsoluteCursor::new(1920, 1080);
+/// cursor.process(&RelativeAxisEvent::new(RelativeAxisCode::REL_X, -2000));
+/// let moved = cursor.process(&SynchronizationEvent::new(SynchronizationCode::SYN_REPORT, 0));
+/// assert_eq!(moved, Some((0, 540)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct AbsoluteCursor {
+    width: u32,
+    height: u32,
+    edge: EdgeBehavior,
+    speed: f64,
+    x: f64,
+    y: f64,
+    moved: bool,
+}
+
+impl AbsoluteCursor {
+    /// Create a cursor in the middle of a screen of `width` by `height` pixels, stopping at the
+    /// edges.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width: width.max(1),
+            height: height.max(1),
+            edge: EdgeBehavior::Clamp,
+            speed: 1.0,
+            x: f64::from(width / 2),
+            y: f64::from(height / 2),
+            moved: false,
+        }
+    }
+
+    /// Set what the cursor does at the edges of the screen.
+    pub fn edge_behavior(mut self, edge: EdgeBehavior) -> Self {
+        self.edge = edge;
+        self
+    }
+
+    /// Set the number of pixels the cursor moves per unit of relative motion.
+    pub fn speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Returns the position of the cursor.
+    pub fn position(&self) -> (u32, u32) {
+        (self.x as u32, self.y as u32)
+    }
+
+    /// Move the cursor to a position, such as after the size of the screen changed. The
+    /// position is kept within the screen.
+    pub fn set_position(&mut self, x: u32, y: u32) {
+        self.x = f64::from(x.min(self.width - 1));
+        self.y = f64::from(y.min(self.height - 1));
+    }
+
+    /// Change the size of the screen, keeping the cursor within it.
+    pub fn set_screen_size(&mut self, width: u32, height: u32) {
+        self.width = width.max(1);
+        self.height = height.max(1);
+        let (x, y) = self.position();
+        self.set_position(x, y);
+    }
+
+    fn apply(value: f64, delta: f64, size: u32, edge: EdgeBehavior) -> f64 {
+        let size = f64::from(size);
+        match edge {
+            EdgeBehavior::Clamp => (value + delta).clamp(0.0, size - 1.0),
+            EdgeBehavior::Wrap => (value + delta).rem_euclid(size),
+        }
+    }
+
+    /// Update the cursor with an event.
+    ///
+    /// Returns the new position if `event` is a `SYN_REPORT` ending a frame that moved the
+    /// cursor.
+    pub fn process(&mut self, event: &InputEvent) -> Option<(u32, u32)> {
+        match event.destructure() {
+            EventSummary::RelativeAxis(_, RelativeAxisCode::REL_X, value) => {
+                let delta = f64::from(value) * self.speed;
+                self.x = Self::apply(self.x, delta, self.width, self.edge);
+                self.moved = true;
+            }
+            EventSummary::RelativeAxis(_, RelativeAxisCode::REL_Y, value) => {
+                let delta = f64::from(value) * self.speed;
+                self.y = Self::apply(self.y, delta, self.height, self.edge);
+                self.moved = true;
+            }
+            EventSummary::Synchronization(_, SynchronizationCode::SYN_REPORT, _)
+                if std::mem::take(&mut self.moved) =>
+            {
+                return Some(self.position());
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Apply the cursor to an iterator of events, yielding the position after every frame that
+    /// moved it.
+    pub fn positions<'a, I>(&'a mut self, events: I) -> impl Iterator<Item = (u32, u32)> + 'a
+    where
+        I: IntoIterator<Item = InputEvent>,
+        I::IntoIter: 'a,
+    {
+        events.into_iter().filter_map(move |ev| self.process(&ev))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RelativeAxisEvent, SynchronizationEvent};
+
+    fn frame(x: i32, y: i32) -> [InputEvent; 3] {
+        [
+            *RelativeAxisEvent::new(RelativeAxisCode::REL_X, x),
+            *RelativeAxisEvent::new(RelativeAxisCode::REL_Y, y),
+            *SynchronizationEvent::new(SynchronizationCode::SYN_REPORT, 0),
+        ]
+    }
+
+    #[test]
+    fn clamp_wrap_and_speed() {
+        let mut cursor = AbsoluteCursor::new(100, 50);
+        let pos: Vec<_> = cursor.positions(frame(500, -10)).collect();
+        assert_eq!(pos, [(99, 15)]);
+
+        let mut cursor = AbsoluteCursor::new(100, 50).edge_behavior(EdgeBehavior::Wrap);
+        let pos: Vec<_> = cursor.positions(frame(60, -30)).collect();
+        assert_eq!(pos, [(10, 45)]);
+
+        let mut cursor = AbsoluteCursor::new(100, 50).speed(0.5);
+        let pos: Vec<_> = cursor
+            .positions([frame(1, 0), frame(1, 0)].concat())
+            .collect();
+        assert_eq!(pos, [(50, 25), (51, 25)]);
+    }
+}
@@ -0,0 +1,120 @@
+use crate::Device;
+use std::path::{Path, PathBuf};
+
+/// Event nodes that belong to the same physical device, returned by [`enumerate_grouped`].
+///
+/// Many devices expose several event nodes, for example a keyboard with separate nodes for its
+/// "Consumer Control" and "System Control" keys, or a gamepad with a separate node for its
+/// motion sensors.
+#[derive(Debug)]
+pub struct DeviceGroup {
+    key: String,
+    devices: Vec<(PathBuf, Device)>,
+}
+
+impl DeviceGroup {
+    /// Returns the identifier the nodes were grouped by.
+    ///
+    /// This is the physical path of the nodes without the trailing `/inputN`, or the sysfs path
+    /// of their parent device if they have no physical path.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the name of the first device of the group.
+    pub fn name(&self) -> Option<&str> {
+        self.devices.first().and_then(|(_, dev)| dev.name())
+    }
+
+    /// Returns the event nodes of the group along with their paths, ordered by path.
+    pub fn devices(&self) -> &[(PathBuf, Device)] {
+        &self.devices
+    }
+
+    /// Returns the event nodes of the group along with their paths, ordered by path.
+    pub fn into_devices(self) -> Vec<(PathBuf, Device)> {
+        self.devices
+    }
+}
+
+/// Crawls `/dev/input` for evdev devices like [`enumerate`](crate::enumerate), grouping the
+/// event nodes that belong to the same physical device.
+///
+/// Nodes are grouped by their physical path, ignoring the trailing `/inputN` which distinguishes
+/// the interfaces of a device. Nodes without a physical path are grouped by their parent device in
+/// sysfs.
+///
+/// ```no_run
+/// for group in evdev::enumerate_grouped() {
+///     println!("{}:", group.name().unwrap_or("Unnamed device"));
+///     for (path, _) in group.devices() {
+///         println!("  {}", path.display());
+///     }
+/// }
+/// ```
+pub fn enumerate_grouped() -> Vec<DeviceGroup> {
+    let mut devices: Vec<_> = crate::enumerate().collect();
+    devices.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut groups: Vec<DeviceGroup> = Vec::new();
+    for (path, dev) in devices {
+        let key = group_key(&path, &dev);
+        match groups.iter_mut().find(|group| group.key == key) {
+            Some(group) => group.devices.push((path, dev)),
+            None => groups.push(DeviceGroup {
+                key,
+                devices: vec![(path, dev)],
+            }),
+        }
+    }
+    groups
+}
+
+fn group_key(path: &Path, dev: &Device) -> String {
+    if let Some(phys) = dev.physical_path().filter(|phys| !phys.is_empty()) {
+        return strip_interface(phys).to_owned();
+    }
+    // /sys/class/input/eventN/device is the input device, its parent is the physical device
+    let parent = path
+        .file_name()
+        .map(|node| {
+            Path::new("/sys/class/input")
+                .join(node)
+                .join("device/device")
+        })
+        .and_then(|parent| parent.canonicalize().ok());
+    match parent {
+        Some(parent) => parent.to_string_lossy().into_owned(),
+        None => path.to_string_lossy().into_owned(),
+    }
+}
+
+/// Strips the `/inputN` suffix the kernel appends to the physical path of each interface.
+fn strip_interface(phys: &str) -> &str {
+    match phys.rsplit_once('/') {
+        Some((base, last))
+            if last.len() > "input".len()
+                && last.starts_with("input")
+                && last["input".len()..].bytes().all(|b| b.is_ascii_digit()) =>
+        {
+            base
+        }
+        _ => phys,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_interface_suffix() {
+        assert_eq!(
+            strip_interface("usb-0000:00:14.0-2/input1"),
+            "usb-0000:00:14.0-2"
+        );
+        assert_eq!(strip_interface("isa0060/serio0/input0"), "isa0060/serio0");
+        assert_eq!(strip_interface("ALSA"), "ALSA");
+        assert_eq!(strip_interface("usb-1/inputs"), "usb-1/inputs");
+    }
+}
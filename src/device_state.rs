@@ -1,6 +1,6 @@
-use crate::compat::input_absinfo;
+use crate::compat::{input_absinfo, input_event};
 use crate::{constants::*, raw_stream::RawDevice};
-use crate::{AttributeSet, AttributeSetRef, EventSummary, InputEvent, KeyCode};
+use crate::{AttributeSet, AttributeSetRef, EvdevEnum, EventSummary, InputEvent, KeyCode};
 use std::time::SystemTime;
 
 /// A **cached** representation of device state at a certain time.
@@ -84,6 +84,37 @@ impl DeviceState {
         self.key_vals.as_deref()
     }
 
+    /// Returns `true` if `key` was pressed when the snapshot was taken.
+    ///
+    /// For the [cached state](crate::Device::cached_state) of a device this is kept up to date
+    /// as events are fetched, so there is no need to track pressed keys separately.
+    ///
+    /// ```no_run
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use evdev::{Device, KeyCode};
+    /// let mut device = Device::open("/dev/input/event0")?;
+    /// for ev in device.fetch_events()? {
+    ///     // ...
+    /// }
+    /// let shift = device.cached_state().key_is_pressed(KeyCode::KEY_LEFTSHIFT);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn key_is_pressed(&self, key: KeyCode) -> bool {
+        self.key_vals().map_or(false, |keys| keys.contains(key))
+    }
+
+    /// Returns `true` if `switch` was enabled when the snapshot was taken.
+    pub fn switch_is_on(&self, switch: SwitchCode) -> bool {
+        self.switch_vals()
+            .map_or(false, |switches| switches.contains(switch))
+    }
+
+    /// Returns `true` if `led` was lit when the snapshot was taken.
+    pub fn led_is_lit(&self, led: LedCode) -> bool {
+        self.led_vals().map_or(false, |leds| leds.contains(led))
+    }
+
     /// Returns the set of absolute axis measurements when the snapshot was taken.
     ///
     /// Returns `None` if not supported by this device.
@@ -105,6 +136,67 @@ impl DeviceState {
         self.led_vals.as_deref()
     }
 
+    /// Returns the events that would transition a device from this state to `other`.
+    ///
+    /// For every key, absolute axis, switch, and LED whose value differs between the two states,
+    /// an event carrying the value from `other` is returned, timestamped with
+    /// `other.timestamp()`. Event types that aren't present in both states are skipped. The
+    /// events are not terminated by a `SYN_REPORT`, so they can be passed directly to
+    /// [`VirtualDevice::emit`](crate::uinput::VirtualDevice::emit).
+    pub fn diff(&self, other: &DeviceState) -> Vec<InputEvent> {
+        let time = crate::systime_to_timeval(&other.timestamp);
+        let event = |type_: EventType, code: usize, value: i32| {
+            InputEvent::from(input_event {
+                time,
+                type_: type_.0,
+                code: code as u16,
+                value,
+            })
+        };
+
+        fn diff_sets<'a, T: EvdevEnum>(
+            old: &'a AttributeSetRef<T>,
+            new: &'a AttributeSetRef<T>,
+        ) -> impl Iterator<Item = (usize, i32)> + 'a {
+            let changed = move |&code: &T| old.contains(code) != new.contains(code);
+            old.iter()
+                .filter(changed)
+                .chain(new.iter().filter(changed))
+                .map(move |code| (code.to_index(), new.contains(code) as i32))
+        }
+
+        let mut events = Vec::new();
+        if let (Some(old), Some(new)) = (self.key_vals(), other.key_vals()) {
+            let mut keys: Vec<_> = diff_sets(old, new).collect();
+            keys.sort_unstable();
+            events.extend(keys.into_iter().map(|(c, v)| event(EventType::KEY, c, v)));
+        }
+        if let (Some(old), Some(new)) = (self.abs_vals(), other.abs_vals()) {
+            events.extend(
+                old.iter()
+                    .zip(new)
+                    .enumerate()
+                    .filter(|(_, (old, new))| old.value != new.value)
+                    .map(|(c, (_, new))| event(EventType::ABSOLUTE, c, new.value)),
+            );
+        }
+        if let (Some(old), Some(new)) = (self.switch_vals(), other.switch_vals()) {
+            let mut switches: Vec<_> = diff_sets(old, new).collect();
+            switches.sort_unstable();
+            events.extend(
+                switches
+                    .into_iter()
+                    .map(|(c, v)| event(EventType::SWITCH, c, v)),
+            );
+        }
+        if let (Some(old), Some(new)) = (self.led_vals(), other.led_vals()) {
+            let mut leds: Vec<_> = diff_sets(old, new).collect();
+            leds.sort_unstable();
+            events.extend(leds.into_iter().map(|(c, v)| event(EventType::LED, c, v)));
+        }
+        events
+    }
+
     #[inline]
     pub(crate) fn process_event(&mut self, ev: InputEvent) {
         match ev.destructure() {
@@ -122,7 +214,71 @@ impl DeviceState {
                     .expect("got an abs event despite not supporting absolute axes");
                 axes[axis.0 as usize].value = ev.value();
             }
+            EventSummary::Switch(_, code, value) => {
+                if let Some(switches) = self.switch_vals.as_deref_mut() {
+                    switches.set(code, value != 0);
+                }
+            }
+            EventSummary::Led(_, code, value) => {
+                if let Some(leds) = self.led_vals.as_deref_mut() {
+                    leds.set(code, value != 0);
+                }
+            }
             _ => {}
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_state() -> DeviceState {
+        DeviceState {
+            timestamp: SystemTime::UNIX_EPOCH,
+            key_vals: Some(AttributeSet::new()),
+            abs_vals: Some(Box::new(crate::raw_stream::ABS_VALS_INIT)),
+            switch_vals: Some(AttributeSet::new()),
+            led_vals: None,
+        }
+    }
+
+    #[test]
+    fn diff_produces_transitions() {
+        let mut old = empty_state();
+        let mut new = empty_state();
+        old.key_vals.as_mut().unwrap().insert(KeyCode::KEY_A);
+        new.key_vals.as_mut().unwrap().insert(KeyCode::KEY_B);
+        new.abs_vals.as_mut().unwrap()[AbsoluteAxisCode::ABS_Y.0 as usize].value = 7;
+        new.switch_vals.as_mut().unwrap().insert(SwitchCode::SW_LID);
+
+        let summary: Vec<_> = old
+            .diff(&new)
+            .into_iter()
+            .map(|ev| (ev.event_type(), ev.code(), ev.value()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (EventType::KEY, KeyCode::KEY_A.0, 0),
+                (EventType::KEY, KeyCode::KEY_B.0, 1),
+                (EventType::ABSOLUTE, AbsoluteAxisCode::ABS_Y.0, 7),
+                (EventType::SWITCH, SwitchCode::SW_LID.0, 1),
+            ]
+        );
+        assert!(new.diff(&new).is_empty());
+    }
+
+    #[test]
+    fn events_update_state() {
+        let mut state = empty_state();
+        state.process_event(*crate::KeyEvent::new(KeyCode::KEY_LEFTSHIFT, 1));
+        state.process_event(*crate::SwitchEvent::new(SwitchCode::SW_LID, 1));
+        assert!(state.key_is_pressed(KeyCode::KEY_LEFTSHIFT));
+        assert!(state.switch_is_on(SwitchCode::SW_LID));
+
+        state.process_event(*crate::KeyEvent::new(KeyCode::KEY_LEFTSHIFT, 0));
+        assert!(!state.key_is_pressed(KeyCode::KEY_LEFTSHIFT));
+        assert!(!state.led_is_lit(LedCode::LED_CAPSL));
+    }
+}
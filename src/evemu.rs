@@ -0,0 +1,759 @@
+//! Recording in the [evemu](https://gitlab.freedesktop.org/libevdev/evemu) data format.
+//!
+//! An evemu recording consists of a description of the device (its name, ID, properties,
+//! supported events and axis ranges) followed by the recorded events, one per line. Recordings
+//! written by a [`Writer`] can be replayed with `evemu-play` and are commonly attached to
+//! kernel and libinput bug reports. A [`Recording`] parses such a recording and replays it
+//! through a virtual device created from its description.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use evdev::evemu::{Description, Writer};
+//! let mut device = evdev::Device::open("/dev/input/event0")?;
+//! let file = std::fs::File::create("recording.evemu")?;
+//! let mut writer = Writer::new(file, &Description::from_device(&device)?)?;
+//! loop {
+//!     for ev in device.fetch_events()? {
+//!         writer.write_event(&ev)?;
+//!     }
+//! }
+//! # }
+//! ```
+
+use crate::attribute_set::ArrayedEvdevEnum;
+use crate::compat::input_event;
+use crate::uinput::VirtualDevice;
+use crate::{
+    AbsInfo, AbsoluteAxisCode, AttributeSet, AttributeSetRef, BusType, Device, EventPacer,
+    EventSummary, EventType, FFEffectCode, InputEvent, InputId, KeyCode, LedCode, MiscCode,
+    PropType, RelativeAxisCode, SoundCode, SwitchCode, SynchronizationCode, UinputAbsSetup,
+};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// The description of a device in an evemu recording.
+#[derive(Debug, Clone)]
+pub struct Description {
+    pub name: String,
+    pub input_id: InputId,
+    pub properties: AttributeSet<PropType>,
+    pub event_types: AttributeSet<EventType>,
+    pub keys: AttributeSet<KeyCode>,
+    pub relative_axes: AttributeSet<RelativeAxisCode>,
+    /// The supported absolute axes along with their ranges.
+    pub absolute_axes: Vec<(AbsoluteAxisCode, AbsInfo)>,
+    pub misc: AttributeSet<MiscCode>,
+    pub switches: AttributeSet<SwitchCode>,
+    pub leds: AttributeSet<LedCode>,
+    pub sounds: AttributeSet<SoundCode>,
+    pub ff: AttributeSet<FFEffectCode>,
+    /// The number of force feedback effects the device can hold at a time, only used if it
+    /// supports force feedback. Recordings don't include it, so parsed descriptions use
+    /// [`DEFAULT_MAX_FF_EFFECTS`].
+    pub max_ff_effects: usize,
+}
+
+/// The number of force feedback effects assumed for devices whose recordings don't say, as
+/// many drivers support.
+pub const DEFAULT_MAX_FF_EFFECTS: usize = 16;
+
+fn to_owned_set<T: ArrayedEvdevEnum>(set: Option<&AttributeSetRef<T>>) -> AttributeSet<T> {
+    set.map_or_else(AttributeSet::new, |set| set.iter().collect())
+}
+
+impl Description {
+    /// Describe a device.
+    pub fn from_device(device: &Device) -> io::Result<Self> {
+        Ok(Self {
+            name: device.name().unwrap_or_default().to_owned(),
+            input_id: device.input_id(),
+            properties: device.properties().iter().collect(),
+            event_types: device.supported_events().iter().collect(),
+            keys: to_owned_set(device.supported_keys()),
+            relative_axes: to_owned_set(device.supported_relative_axes()),
+            absolute_axes: device.get_absinfo()?.collect(),
+            misc: to_owned_set(device.misc_properties()),
+            switches: to_owned_set(device.supported_switches()),
+            leds: to_owned_set(device.supported_leds()),
+            sounds: to_owned_set(device.supported_sounds()),
+            ff: to_owned_set(device.supported_ff()),
+            max_ff_effects: device.max_ff_effects(),
+        })
+    }
+
+    /// Add the capabilities of `other` to this description, keeping the name and ID.
+    ///
+    /// Absolute axes that both descriptions support keep the range of this description.
+    pub fn merge(&mut self, other: &Description) {
+        self.properties.extend(other.properties.iter());
+        self.event_types.extend(other.event_types.iter());
+        self.keys.extend(other.keys.iter());
+        self.relative_axes.extend(other.relative_axes.iter());
+        for &(axis, info) in &other.absolute_axes {
+            if !self.absolute_axes.iter().any(|&(a, _)| a == axis) {
+                self.absolute_axes.push((axis, info));
+            }
+        }
+        self.misc.extend(other.misc.iter());
+        self.switches.extend(other.switches.iter());
+        self.leds.extend(other.leds.iter());
+        self.sounds.extend(other.sounds.iter());
+        self.ff.extend(other.ff.iter());
+        self.max_ff_effects = self.max_ff_effects.max(other.max_ff_effects);
+    }
+
+    /// Write the description in the evemu format.
+    pub fn write_to(&self, mut out: impl Write) -> io::Result<()> {
+        let id = &self.input_id;
+        writeln!(out, "# EVEMU 1.3")?;
+        writeln!(out, "# Input device name: \"{}\"", self.name)?;
+        writeln!(
+            out,
+            "# Input device ID: bus {:#x} vendor {:#x} product {:#x} version {:#x}",
+            id.bus_type().0,
+            id.vendor(),
+            id.product(),
+            id.version()
+        )?;
+        writeln!(out, "N: {}", self.name)?;
+        writeln!(
+            out,
+            "I: {:04x} {:04x} {:04x} {:04x}",
+            id.bus_type().0,
+            id.vendor(),
+            id.product(),
+            id.version()
+        )?;
+
+        write_mask(&mut out, "P:", &self.properties, PropType::COUNT)?;
+        let masks = [
+            (
+                EventType::SYNCHRONIZATION,
+                bitmap(&self.event_types, EventType::COUNT),
+            ),
+            (EventType::KEY, bitmap(&self.keys, KeyCode::COUNT)),
+            (
+                EventType::RELATIVE,
+                bitmap(&self.relative_axes, RelativeAxisCode::COUNT),
+            ),
+            (
+                EventType::ABSOLUTE,
+                bitmap(
+                    &self.absolute_axes.iter().map(|&(axis, _)| axis).collect(),
+                    AbsoluteAxisCode::COUNT,
+                ),
+            ),
+            (EventType::MISC, bitmap(&self.misc, MiscCode::COUNT)),
+            (EventType::SWITCH, bitmap(&self.switches, SwitchCode::COUNT)),
+            (EventType::LED, bitmap(&self.leds, LedCode::COUNT)),
+            (EventType::SOUND, bitmap(&self.sounds, SoundCode::COUNT)),
+            (
+                EventType::FORCEFEEDBACK,
+                bitmap(&self.ff, FFEffectCode::COUNT),
+            ),
+        ];
+        for (ty, bytes) in masks {
+            write_bytes(&mut out, &format!("B: {:02x}", ty.0), &bytes)?;
+        }
+
+        for (axis, info) in &self.absolute_axes {
+            writeln!(
+                out,
+                "A: {:02x} {} {} {} {} {}",
+                axis.0,
+                info.minimum(),
+                info.maximum(),
+                info.fuzz(),
+                info.flat(),
+                info.resolution()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the bytes of a bitmask of `count` bits, padded to a multiple of 8 bytes like the
+/// kernel's bitmasks on 64-bit platforms.
+pub(crate) fn bitmap<T: ArrayedEvdevEnum>(set: &AttributeSet<T>, count: usize) -> Vec<u8> {
+    let len = (count + 63) / 64 * 8;
+    let mut bytes = vec![0u8; len];
+    for code in set.iter() {
+        let i = code.to_index();
+        if let Some(byte) = bytes.get_mut(i / 8) {
+            *byte |= 1 << (i % 8);
+        }
+    }
+    bytes
+}
+
+fn write_mask<T: ArrayedEvdevEnum>(
+    out: &mut impl Write,
+    prefix: &str,
+    set: &AttributeSet<T>,
+    count: usize,
+) -> io::Result<()> {
+    write_bytes(out, prefix, &bitmap(set, count))
+}
+
+fn write_bytes(out: &mut impl Write, prefix: &str, bytes: &[u8]) -> io::Result<()> {
+    for chunk in bytes.chunks(8) {
+        write!(out, "{}", prefix)?;
+        for byte in chunk {
+            write!(out, " {:02x}", byte)?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Returns the name of an event type as used in the kernel headers, e.g. `EV_KEY`.
+pub(crate) fn type_name(ty: EventType) -> String {
+    let name = match ty {
+        EventType::SYNCHRONIZATION => "EV_SYN",
+        EventType::KEY => "EV_KEY",
+        EventType::RELATIVE => "EV_REL",
+        EventType::ABSOLUTE => "EV_ABS",
+        EventType::MISC => "EV_MSC",
+        EventType::SWITCH => "EV_SW",
+        EventType::LED => "EV_LED",
+        EventType::SOUND => "EV_SND",
+        EventType::REPEAT => "EV_REP",
+        EventType::FORCEFEEDBACK => "EV_FF",
+        EventType::POWER => "EV_PWR",
+        EventType::FORCEFEEDBACKSTATUS => "EV_FF_STATUS",
+        _ => return format!("EV_{:#x}", ty.0),
+    };
+    name.to_owned()
+}
+
+/// Returns the name of the code of an event as used in the kernel headers, e.g. `KEY_A`.
+pub(crate) fn code_name(event: &InputEvent) -> String {
+    match event.destructure() {
+        EventSummary::Synchronization(_, code, _) => format!("{:?}", code),
+        EventSummary::Key(_, code, _) => format!("{:?}", code),
+        EventSummary::RelativeAxis(_, code, _) => format!("{:?}", code),
+        EventSummary::AbsoluteAxis(_, code, _) => format!("{:?}", code),
+        EventSummary::Misc(_, code, _) => format!("{:?}", code),
+        EventSummary::Switch(_, code, _) => format!("{:?}", code),
+        EventSummary::Led(_, code, _) => format!("{:?}", code),
+        EventSummary::Sound(_, code, _) => format!("{:?}", code),
+        EventSummary::Repeat(_, code, _) => format!("{:?}", code),
+        _ => format!("{:#x}", event.code()),
+    }
+}
+
+/// Writes a device description and events in the evemu format.
+#[derive(Debug)]
+pub struct Writer<W: Write> {
+    out: W,
+    start: Option<SystemTime>,
+}
+
+impl<W: Write> Writer<W> {
+    /// Create a writer, writing the description of the device right away.
+    pub fn new(mut out: W, description: &Description) -> io::Result<Self> {
+        description.write_to(&mut out)?;
+        Ok(Self { out, start: None })
+    }
+
+    /// Write an event.
+    ///
+    /// Timestamps are written relative to the first event, as done by `evemu-record`.
+    pub fn write_event(&mut self, event: &InputEvent) -> io::Result<()> {
+        let start = *self.start.get_or_insert_with(|| event.timestamp());
+        let time = event.timestamp().duration_since(start).unwrap_or_default();
+        write!(
+            self.out,
+            "E: {}.{:06} {:04x} {:04x} {:04}\t",
+            time.as_secs(),
+            time.subsec_micros(),
+            event.event_type().0,
+            event.code(),
+            event.value()
+        )?;
+        match event.destructure() {
+            EventSummary::Synchronization(_, code, _) => writeln!(
+                self.out,
+                "# ------------ {:?} ({}) ---------- +{}ms",
+                code,
+                event.value(),
+                time.as_millis()
+            ),
+            _ => writeln!(
+                self.out,
+                "# {} / {:<20} {}",
+                type_name(event.event_type()),
+                code_name(event),
+                event.value()
+            ),
+        }
+    }
+
+    /// Flush the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// An evemu recording: a device description and the recorded events.
+#[derive(Debug, Clone)]
+pub struct Recording {
+    pub description: Description,
+    /// The recorded events. Their timestamps are relative to the Unix epoch, i.e. the first
+    /// event of a recording made by `evemu-record` is at [`UNIX_EPOCH`](SystemTime::UNIX_EPOCH).
+    pub events: Vec<InputEvent>,
+}
+
+impl Recording {
+    /// Parse a recording in the evemu format.
+    ///
+    /// Fails with [`InvalidData`](io::ErrorKind::InvalidData) if a line can't be parsed.
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut name = String::new();
+        let mut input_id = InputId::new(BusType(0), 0, 0, 0);
+        let mut props = Vec::new();
+        let mut masks: Vec<Vec<u8>> = vec![Vec::new(); EventType::COUNT];
+        let mut absolute_axes = Vec::new();
+        let mut events = Vec::new();
+
+        for (lineno, line) in text.lines().enumerate() {
+            let invalid = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid evemu data on line {}", lineno + 1),
+                )
+            };
+            let line = match line.find('#') {
+                Some(i) => &line[..i],
+                None => line,
+            };
+            let (kind, rest) = match line.split_once(':') {
+                Some((kind, rest)) => (kind.trim(), rest.trim()),
+                None if line.trim().is_empty() => continue,
+                None => return Err(invalid()),
+            };
+            let mut fields = rest.split_whitespace();
+            let mut hex = || {
+                fields
+                    .next()
+                    .and_then(|f| u16::from_str_radix(f, 16).ok())
+                    .ok_or_else(invalid)
+            };
+            match kind {
+                "N" => name = rest.to_owned(),
+                "I" => {
+                    let (bus, vendor, product, version) = (hex()?, hex()?, hex()?, hex()?);
+                    input_id = InputId::new(BusType(bus), vendor, product, version);
+                }
+                "P" => {
+                    for byte in fields {
+                        props.push(u8::from_str_radix(byte, 16).map_err(|_| invalid())?);
+                    }
+                }
+                "B" => {
+                    let ty = hex()? as usize;
+                    let mask = masks.get_mut(ty).ok_or_else(invalid)?;
+                    for byte in fields {
+                        mask.push(u8::from_str_radix(byte, 16).map_err(|_| invalid())?);
+                    }
+                }
+                "A" => {
+                    let code = hex()?;
+                    let values = fields
+                        .map(|f| f.parse::<i32>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| invalid())?;
+                    // evemu before 1.1 didn't record the resolution
+                    let info = match values[..] {
+                        [min, max, fuzz, flat] => AbsInfo::new(0, min, max, fuzz, flat, 0),
+                        [min, max, fuzz, flat, res] => AbsInfo::new(0, min, max, fuzz, flat, res),
+                        _ => return Err(invalid()),
+                    };
+                    absolute_axes.push((AbsoluteAxisCode(code), info));
+                }
+                "E" => {
+                    let time = fields.next().ok_or_else(invalid)?;
+                    let (secs, micros) = time.split_once('.').ok_or_else(invalid)?;
+                    let secs: u64 = secs.parse().map_err(|_| invalid())?;
+                    let micros: u32 = micros.parse().map_err(|_| invalid())?;
+                    let mut hex = || {
+                        fields
+                            .next()
+                            .and_then(|f| u16::from_str_radix(f, 16).ok())
+                            .ok_or_else(invalid)
+                    };
+                    let (ty, code) = (hex()?, hex()?);
+                    let value = fields
+                        .next()
+                        .and_then(|f| f.parse().ok())
+                        .ok_or_else(invalid)?;
+                    let time = i64::try_from(secs)
+                        .ok()
+                        .and_then(|secs| crate::checked_systime(secs, micros.into()))
+                        .ok_or_else(invalid)?;
+                    events.push(InputEvent::from(input_event {
+                        time: crate::systime_to_timeval(&time),
+                        type_: ty,
+                        code,
+                        value,
+                    }));
+                }
+                // LED and switch states, which can't be set on a virtual device
+                "L" | "S" => {}
+                _ => return Err(invalid()),
+            }
+        }
+
+        let mut mask = |ty: EventType| std::mem::take(&mut masks[ty.0 as usize]);
+        let description = Description {
+            name,
+            input_id,
+            properties: from_bitmap(&props),
+            event_types: from_bitmap(&mask(EventType::SYNCHRONIZATION)),
+            keys: from_bitmap(&mask(EventType::KEY)),
+            relative_axes: from_bitmap(&mask(EventType::RELATIVE)),
+            absolute_axes,
+            misc: from_bitmap(&mask(EventType::MISC)),
+            switches: from_bitmap(&mask(EventType::SWITCH)),
+            leds: from_bitmap(&mask(EventType::LED)),
+            sounds: from_bitmap(&mask(EventType::SOUND)),
+            ff: from_bitmap(&mask(EventType::FORCEFEEDBACK)),
+            max_ff_effects: DEFAULT_MAX_FF_EFFECTS,
+        };
+        Ok(Self {
+            description,
+            events,
+        })
+    }
+
+    /// Read and parse a recording from a file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Replay the events through a virtual device with their original timing, blocking until
+    /// all events were emitted.
+    ///
+    /// The virtual device is usually created with [`Description::create_virtual_device`]. Use a
+    /// [`Player`] to replay at a different speed, or to replay only part of the recording.
+    pub fn replay(&self, device: &mut VirtualDevice) -> io::Result<()> {
+        self.player().play(device)
+    }
+
+    /// Returns a [`Player`] positioned at the start of the recording.
+    pub fn player(&self) -> Player<'_> {
+        Player {
+            recording: self,
+            position: 0,
+            speed: 1.0,
+        }
+    }
+
+    /// Returns the time of an event relative to the first event of the recording.
+    fn offset(&self, index: usize) -> Duration {
+        match (self.events.first(), self.events.get(index)) {
+            (Some(first), Some(ev)) => ev
+                .timestamp()
+                .duration_since(first.timestamp())
+                .unwrap_or_default(),
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+/// Replays a [`Recording`] step by step, or at a different speed.
+///
+/// The player keeps a position in the recording, which is always at the start of a frame of
+/// events. Frames can be emitted one at a time with [`step`](Self::step), which helps with
+/// reproducing bugs that depend on the order or timing of events, or played with their
+/// original timing scaled by the [speed](Self::set_speed) of the player.
+///
+/// ```no_run
+/// use evdev::evemu::Recording;
+/// use std::time::Duration;
+///
+/// let recording = Recording::load("recording.evemu")?;
+/// let mut device = recording.description.create_virtual_device()?;
+/// let mut player = recording.player();
+/// player.set_speed(0.5);
+/// player.fast_forward(&mut device, Duration::from_secs(10))?;
+/// while player.step(&mut device)? {
+///     std::thread::sleep(Duration::from_secs(1));
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Player<'a> {
+    recording: &'a Recording,
+    position: usize,
+    speed: f64,
+}
+
+impl Player<'_> {
+    /// Set the speed at which events are played, e.g. `2.0` to play twice as fast. The default
+    /// is `1.0`, the original speed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `speed` isn't a positive number.
+    pub fn set_speed(&mut self, speed: f64) {
+        assert!(speed > 0.0, "replay speed must be positive");
+        self.speed = speed;
+    }
+
+    /// Returns the index of the next event to be emitted.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the time of the next frame relative to the start of the recording.
+    pub fn time(&self) -> Duration {
+        self.recording.offset(self.position)
+    }
+
+    /// Returns `true` if all events were emitted.
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.recording.events.len()
+    }
+
+    /// Move to the first frame at or after `time`, relative to the start of the recording,
+    /// without emitting anything.
+    ///
+    /// Skipped events aren't emitted, so keys that are held at that point of the recording are
+    /// not held on the device. Use [`fast_forward`](Self::fast_forward) to keep the state of
+    /// the device consistent with the recording.
+    pub fn seek(&mut self, time: Duration) {
+        self.position = 0;
+        while !self.is_finished() && self.time() < time {
+            self.next_frame();
+        }
+    }
+
+    /// Emit the events up to the first frame at or after `time` without delay.
+    ///
+    /// Unlike [`seek`](Self::seek), this only moves forward.
+    pub fn fast_forward(&mut self, device: &mut VirtualDevice, time: Duration) -> io::Result<()> {
+        while !self.is_finished() && self.time() < time {
+            self.step(device)?;
+        }
+        Ok(())
+    }
+
+    /// Emit the next frame right away, returning `false` if there were no events left.
+    pub fn step(&mut self, device: &mut VirtualDevice) -> io::Result<bool> {
+        let frame = self.next_frame();
+        if frame.is_empty() {
+            return Ok(false);
+        }
+        let events: Vec<_> = frame
+            .iter()
+            .map(|ev| InputEvent::new(ev.event_type(), ev.code(), ev.value()))
+            .collect();
+        device.write_raw(&events)?;
+        Ok(true)
+    }
+
+    /// Emit the remaining events with their original timing scaled by the speed of the player,
+    /// blocking until all events were emitted.
+    ///
+    /// Frames that are late are written right away to catch up, as with an [`EventPacer`] with
+    /// an unlimited tolerance.
+    pub fn play(&mut self, device: &mut VirtualDevice) -> io::Result<()> {
+        let mut pacer = EventPacer::new(Duration::MAX);
+        pacer.set_speed(self.speed);
+        while !self.is_finished() {
+            let start = self.position;
+            self.next_frame();
+            pacer.emit(device, &self.recording.events[start..self.position])?;
+        }
+        Ok(())
+    }
+
+    /// Advance to the next frame, returning the events of the current one.
+    fn next_frame(&mut self) -> &[InputEvent] {
+        let events = &self.recording.events[self.position.min(self.recording.events.len())..];
+        let len = events
+            .iter()
+            .position(|ev| {
+                ev.event_type() == EventType::SYNCHRONIZATION
+                    && ev.code() == SynchronizationCode::SYN_REPORT.0
+            })
+            .map_or(events.len(), |i| i + 1);
+        self.position += len;
+        &events[..len]
+    }
+}
+
+impl Description {
+    /// Create a virtual device matching this description.
+    ///
+    /// LEDs and sounds can't be set up through uinput and are left out.
+    pub fn create_virtual_device(&self) -> io::Result<VirtualDevice> {
+        let mut builder = VirtualDevice::builder()?
+            .name(&self.name)
+            .input_id(self.input_id.clone())
+            .with_properties(&self.properties)?;
+        if self.keys.iter().next().is_some() {
+            builder = builder.with_keys(&self.keys)?;
+        }
+        if self.relative_axes.iter().next().is_some() {
+            builder = builder.with_relative_axes(&self.relative_axes)?;
+        }
+        for &(axis, info) in &self.absolute_axes {
+            builder = builder.with_absolute_axis(&UinputAbsSetup::new(axis, info))?;
+        }
+        if self.misc.iter().next().is_some() {
+            builder = builder.with_msc(&self.misc)?;
+        }
+        if self.switches.iter().next().is_some() {
+            builder = builder.with_switches(&self.switches)?;
+        }
+        // uinput rejects force feedback without room for any effects
+        if self.ff.iter().next().is_some() && self.max_ff_effects > 0 {
+            builder = builder
+                .with_ff(&self.ff)?
+                .with_ff_effects_max(self.max_ff_effects.try_into().unwrap_or(u32::MAX));
+        }
+        builder.build()
+    }
+}
+
+pub(crate) fn from_bitmap<T: ArrayedEvdevEnum>(bytes: &[u8]) -> AttributeSet<T> {
+    let mut set = AttributeSet::new();
+    let len = T::array_as_words(&T::zeroed_array()).len() * u64::BITS as usize;
+    for (i, byte) in bytes.iter().enumerate() {
+        for bit in 0..8 {
+            let index = i * 8 + bit;
+            if byte & (1 << bit) != 0 && index < len {
+                set.insert(T::from_index(index));
+            }
+        }
+    }
+    set
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyEvent;
+
+    fn description() -> Description {
+        Description {
+            name: "Test Keyboard".to_owned(),
+            input_id: InputId::new(BusType::BUS_USB, 0x1234, 0x5678, 0x0111),
+            properties: AttributeSet::new(),
+            event_types: [
+                EventType::SYNCHRONIZATION,
+                EventType::KEY,
+                EventType::ABSOLUTE,
+            ]
+            .into_iter()
+            .collect(),
+            keys: [KeyCode::KEY_A].into_iter().collect(),
+            relative_axes: AttributeSet::new(),
+            absolute_axes: vec![(AbsoluteAxisCode::ABS_X, AbsInfo::new(0, 0, 1000, 4, 8, 12))],
+            misc: AttributeSet::new(),
+            switches: AttributeSet::new(),
+            leds: AttributeSet::new(),
+            sounds: AttributeSet::new(),
+            ff: AttributeSet::new(),
+            max_ff_effects: 0,
+        }
+    }
+
+    #[test]
+    fn description_format() {
+        let mut out = Vec::new();
+        description().write_to(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = text.lines().filter(|l| !l.starts_with('#')).collect();
+        assert_eq!(lines[0], "N: Test Keyboard");
+        assert_eq!(lines[1], "I: 0003 1234 5678 0111");
+        assert_eq!(lines[2], "P: 00 00 00 00 00 00 00 00");
+        assert_eq!(lines[3], "B: 00 0b 00 00 00 00 00 00 00");
+        // KEY_A is 30
+        assert_eq!(lines[4], "B: 01 00 00 00 40 00 00 00 00");
+        assert_eq!(*lines.last().unwrap(), "A: 00 0 1000 4 8 12");
+    }
+
+    #[test]
+    fn merge() {
+        let mut merged = description();
+        let mut other = description();
+        other.keys = [KeyCode::KEY_B].into_iter().collect();
+        other.absolute_axes = vec![
+            (AbsoluteAxisCode::ABS_X, AbsInfo::new(0, 0, 10, 0, 0, 0)),
+            (AbsoluteAxisCode::ABS_Y, AbsInfo::new(0, 0, 20, 0, 0, 0)),
+        ];
+        merged.merge(&other);
+        let keys: Vec<_> = merged.keys.iter().collect();
+        assert_eq!(keys, [KeyCode::KEY_A, KeyCode::KEY_B]);
+        assert_eq!(merged.absolute_axes.len(), 2);
+        assert_eq!(merged.absolute_axes[0].1.maximum(), 1000);
+    }
+
+    #[test]
+    fn event_format() {
+        let mut writer = Writer::new(Vec::new(), &description()).unwrap();
+        writer
+            .write_event(&KeyEvent::new(KeyCode::KEY_A, 1))
+            .unwrap();
+        let text = String::from_utf8(writer.into_inner()).unwrap();
+        let last = text.lines().last().unwrap();
+        assert!(last.starts_with("E: 0.000000 0001 001e 0001\t# EV_KEY / KEY_A"));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut writer = Writer::new(Vec::new(), &description()).unwrap();
+        writer
+            .write_event(&KeyEvent::new(KeyCode::KEY_A, 1))
+            .unwrap();
+        let text = String::from_utf8(writer.into_inner()).unwrap();
+        let recording = Recording::parse(&text).unwrap();
+        let desc = recording.description;
+        assert_eq!(desc.name, "Test Keyboard");
+        assert_eq!(desc.input_id, description().input_id);
+        assert!(desc.keys.contains(KeyCode::KEY_A));
+        assert!(desc.event_types.contains(EventType::ABSOLUTE));
+        assert_eq!(desc.absolute_axes[0].1.resolution(), 12);
+        assert_eq!(recording.events.len(), 1);
+        assert_eq!(recording.events[0].code(), KeyCode::KEY_A.0);
+        assert_eq!(recording.events[0].timestamp(), SystemTime::UNIX_EPOCH);
+
+        assert!(Recording::parse("E: nonsense").is_err());
+        assert!(Recording::parse("E: 18446744073709551615.000000 0001 001e 0001").is_err());
+    }
+
+    #[test]
+    fn player_frames() {
+        let text = "\
+E: 0.000000 0001 001e 0001
+E: 0.000000 0000 0000 0000
+E: 0.500000 0001 001e 0000
+E: 0.500000 0000 0000 0000
+E: 1.000000 0001 0030 0001
+E: 1.000000 0000 0000 0000
+";
+        let recording = Recording::parse(text).unwrap();
+        let mut player = recording.player();
+        assert_eq!(player.next_frame().len(), 2);
+        assert_eq!(player.time(), Duration::from_millis(500));
+
+        player.seek(Duration::from_millis(600));
+        assert_eq!(player.position(), 4);
+        assert_eq!(player.next_frame()[0].code(), KeyCode::KEY_B.0);
+        assert!(player.is_finished());
+        assert!(player.next_frame().is_empty());
+
+        player.seek(Duration::ZERO);
+        assert_eq!(player.position(), 0);
+    }
+}
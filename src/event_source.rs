@@ -0,0 +1,82 @@
+use crate::raw_stream::RawDevice;
+use crate::uinput::VirtualDevice;
+use crate::{CapabilitiesReport, Device, InputEvent, InputId};
+use std::io;
+use std::os::fd::AsFd;
+
+/// Functionality shared by [`Device`], [`RawDevice`] and [`VirtualDevice`].
+///
+/// This allows writing code that works with any of them, for example to substitute a virtual
+/// device for a physical one in tests.
+///
+/// ```no_run
+/// use evdev::EventSource;
+///
+/// fn print_events(source: &mut impl EventSource) -> std::io::Result<()> {
+///     let name = source.capabilities_report()?.name;
+///     for ev in source.fetch_events()? {
+///         println!("{name:?}: {ev:?}");
+///     }
+///     Ok(())
+/// }
+/// ```
+pub trait EventSource: AsFd {
+    /// Fetches the events that are available, blocking until there are some unless the file
+    /// descriptor is in non-blocking mode.
+    ///
+    /// For a [`VirtualDevice`], these are the events sent to it, such as LED changes and force
+    /// feedback requests.
+    fn fetch_events(&mut self) -> io::Result<Box<dyn Iterator<Item = InputEvent> + '_>>;
+
+    /// Returns a report of everything the device supports.
+    fn capabilities_report(&self) -> io::Result<CapabilitiesReport>;
+
+    /// Returns the bustype, vendor, product, and version identifiers of the device.
+    fn input_id(&self) -> io::Result<InputId> {
+        let report = self.capabilities_report()?;
+        Ok(InputId::new(
+            report.bus_type,
+            report.vendor,
+            report.product,
+            report.version,
+        ))
+    }
+}
+
+impl EventSource for Device {
+    fn fetch_events(&mut self) -> io::Result<Box<dyn Iterator<Item = InputEvent> + '_>> {
+        Ok(Box::new(Device::fetch_events(self)?))
+    }
+
+    fn capabilities_report(&self) -> io::Result<CapabilitiesReport> {
+        Device::capabilities_report(self)
+    }
+
+    fn input_id(&self) -> io::Result<InputId> {
+        Ok(Device::input_id(self))
+    }
+}
+
+impl EventSource for RawDevice {
+    fn fetch_events(&mut self) -> io::Result<Box<dyn Iterator<Item = InputEvent> + '_>> {
+        Ok(Box::new(RawDevice::fetch_events(self)?))
+    }
+
+    fn capabilities_report(&self) -> io::Result<CapabilitiesReport> {
+        RawDevice::capabilities_report(self)
+    }
+
+    fn input_id(&self) -> io::Result<InputId> {
+        Ok(RawDevice::input_id(self))
+    }
+}
+
+impl EventSource for VirtualDevice {
+    fn fetch_events(&mut self) -> io::Result<Box<dyn Iterator<Item = InputEvent> + '_>> {
+        Ok(Box::new(VirtualDevice::fetch_events(self)?))
+    }
+
+    fn capabilities_report(&self) -> io::Result<CapabilitiesReport> {
+        VirtualDevice::capabilities_report(self)
+    }
+}
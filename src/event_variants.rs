@@ -17,8 +17,9 @@ use crate::constants::{
     AbsoluteAxisCode, FFStatusCode, LedCode, MiscCode, OtherCode, PowerCode, RelativeAxisCode,
     RepeatCode, SoundCode, SwitchCode, SynchronizationCode, UInputCode,
 };
+use crate::gamepad::Deadzone;
 use crate::scancodes::KeyCode;
-use crate::{systime_to_timeval, EventType, FFEffectCode};
+use crate::{systime_to_timeval, AbsInfo, EventType, FFEffectCode};
 use crate::{EventSummary, InputEvent};
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash)]
@@ -120,7 +121,7 @@ macro_rules! input_event_newtype {
         impl $name {
             pub fn new($kind(code): $kind, value: i32) -> Self {
                 let raw = input_event {
-                    time: libc::timeval {
+                    time: crate::compat::timeval {
                         tv_sec: 0,
                         tv_usec: 0,
                     },
@@ -160,6 +161,17 @@ macro_rules! input_event_newtype {
                 }
             }
         }
+        /// Fails with the original event if it has a different type.
+        impl TryFrom<InputEvent> for $name {
+            type Error = InputEvent;
+
+            fn try_from(event: InputEvent) -> Result<Self, InputEvent> {
+                match event.event_type() {
+                    $evdev_type => Ok(Self(event)),
+                    _ => Err(event),
+                }
+            }
+        }
         impl fmt::Debug for $name {
             fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
                 let mut debug = f.debug_struct(stringify!($name));
@@ -236,6 +248,111 @@ input_event_newtype!(
 );
 input_event_newtype!(OtherEvent);
 
+/// The meaning of the value of a [`KeyEvent`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum KeyAction {
+    /// The key was released, value 0.
+    Release,
+    /// The key was pressed, value 1.
+    Press,
+    /// The key is held and is being repeated by the kernel, value 2.
+    Repeat,
+    /// Any other value.
+    Other(i32),
+}
+
+impl KeyAction {
+    /// Returns the value of a key event with this action.
+    pub fn value(self) -> i32 {
+        match self {
+            Self::Release => 0,
+            Self::Press => 1,
+            Self::Repeat => 2,
+            Self::Other(value) => value,
+        }
+    }
+}
+
+impl From<i32> for KeyAction {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => Self::Release,
+            1 => Self::Press,
+            2 => Self::Repeat,
+            value => Self::Other(value),
+        }
+    }
+}
+
+impl KeyEvent {
+    /// Returns whether the key was pressed, released or repeated.
+    pub fn action(&self) -> KeyAction {
+        self.value().into()
+    }
+
+    /// Create an event pressing `key`.
+    pub fn press(key: KeyCode) -> Self {
+        Self::new(key, KeyAction::Press.value())
+    }
+
+    /// Create an event releasing `key`.
+    pub fn release(key: KeyCode) -> Self {
+        Self::new(key, KeyAction::Release.value())
+    }
+}
+
+impl AbsoluteAxisEvent {
+    /// Returns the value mapped into `[-1, 1]` for axes whose range includes negative values,
+    /// such as joystick axes, or into `[0, 1]` otherwise, such as for triggers and touch
+    /// positions.
+    ///
+    /// Values of centered axes within `flat` or `fuzz` of the center are reported as `0`, and
+    /// the remaining range is rescaled to still cover `[-1, 1]`. Use
+    /// [`Deadzone`](crate::gamepad::Deadzone) for more control over this.
+    ///
+    /// [`AxisNormalizer`](crate::AxisNormalizer) looks up the `AbsInfo` of the axis for you.
+    pub fn normalized(&self, info: &AbsInfo) -> f32 {
+        let info = AbsInfo::new(
+            info.value(),
+            info.minimum(),
+            info.maximum(),
+            info.fuzz(),
+            info.flat().max(info.fuzz()),
+            info.resolution(),
+        );
+        if info.minimum() < 0 {
+            Deadzone::FLAT.normalize(&info, self.value())
+        } else {
+            Deadzone::FLAT.normalize_trigger(&info, self.value())
+        }
+    }
+}
+
+impl SwitchEvent {
+    /// Returns `true` if the switch is on (closed), such as when the lid is shut for
+    /// [`SwitchCode::SW_LID`].
+    pub fn is_on(&self) -> bool {
+        self.value() != 0
+    }
+
+    /// Create an event turning the switch on or off.
+    pub fn with_state(code: SwitchCode, on: bool) -> Self {
+        Self::new(code, on as i32)
+    }
+}
+
+impl LedEvent {
+    /// Returns `true` if the LED is lit.
+    pub fn is_on(&self) -> bool {
+        self.value() != 0
+    }
+
+    /// Create an event turning the LED on or off.
+    pub fn with_state(code: LedCode, on: bool) -> Self {
+        Self::new(code, on as i32)
+    }
+}
+
 impl OtherEvent {
     pub fn kind(&self) -> OtherCode {
         OtherCode(self.event_type().0, self.code())
@@ -244,6 +361,17 @@ impl OtherEvent {
         (self.kind(), self.value())
     }
 }
+/// Fails with the original event if its type has a dedicated event variant.
+impl TryFrom<InputEvent> for OtherEvent {
+    type Error = InputEvent;
+
+    fn try_from(event: InputEvent) -> Result<Self, InputEvent> {
+        match event.destructure() {
+            EventSummary::Other(event, ..) => Ok(event),
+            _ => Err(event),
+        }
+    }
+}
 impl From<OtherEvent> for EventSummary {
     fn from(event: OtherEvent) -> Self {
         let (kind, value) = event.destructure();
@@ -259,3 +387,52 @@ impl fmt::Debug for OtherEvent {
         debug.field("value", &self.value()).finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_from_event() {
+        let event = InputEvent::new(EventType::KEY, KeyCode::KEY_A.0, 1);
+        let key = KeyEvent::try_from(event).unwrap();
+        assert_eq!(key.code(), KeyCode::KEY_A);
+        assert_eq!(RelativeAxisEvent::try_from(event), Err(event));
+        assert_eq!(OtherEvent::try_from(event), Err(event));
+
+        let vendor = InputEvent::new(EventType(0x1e), 0, 1);
+        assert!(OtherEvent::try_from(vendor).is_ok());
+    }
+
+    #[test]
+    fn key_action() {
+        assert_eq!(KeyEvent::press(KeyCode::KEY_A).value(), 1);
+        assert_eq!(
+            KeyEvent::release(KeyCode::KEY_A).action(),
+            KeyAction::Release
+        );
+        assert_eq!(KeyEvent::new(KeyCode::KEY_A, 2).action(), KeyAction::Repeat);
+        assert_eq!(KeyAction::from(7), KeyAction::Other(7));
+    }
+
+    #[test]
+    fn normalized() {
+        let stick = AbsInfo::new(0, -100, 100, 2, 0, 0);
+        let event = AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_X, 51);
+        assert!((event.normalized(&stick) - 0.5).abs() < 1e-6);
+        let event = AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_X, -1);
+        assert_eq!(event.normalized(&stick), 0.0);
+
+        let trigger = AbsInfo::new(0, 0, 255, 0, 0, 0);
+        let event = AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_Z, 255);
+        assert_eq!(event.normalized(&trigger), 1.0);
+    }
+
+    #[test]
+    fn on_off() {
+        let lid = SwitchEvent::with_state(SwitchCode::SW_LID, true);
+        assert_eq!(lid.value(), 1);
+        assert!(lid.is_on());
+        assert!(!LedEvent::with_state(LedCode::LED_CAPSL, false).is_on());
+    }
+}
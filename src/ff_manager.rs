@@ -0,0 +1,174 @@
+use crate::{Device, DeviceControl, FFEffect, FFEffectData};
+use std::io;
+
+/// Keeps track of the force feedback effects uploaded to a device, evicting the least recently
+/// played ones when the device runs out of room.
+///
+/// Devices can only hold a few effects at a time, often no more than 16, and uploading one more
+/// fails. The manager identifies effects by a key of your choosing, such as the name of the game
+/// event they belong to. Playing an effect uploads it on first use and reuses the uploaded
+/// effect afterwards, updating it if its data changed. When all slots are taken, the effect that
+/// was played the longest time ago is erased to make room, even if it is still playing.
+///
+/// The manager uses a [`DeviceControl`] handle, so it can be used while another thread reads
+/// events from the device.
+///
+/// ```no_run
+/// use evdev::{Device, FFEffectData, FFEffectKind, FFManager, FFReplay, FFTrigger};
+///
+/// let device = Device::open("/dev/input/event0")?;
+/// let mut effects = FFManager::new(&device);
+/// let hit = FFEffectData {
+///     direction: 0,
+///     trigger: FFTrigger::default(),
+///     replay: FFReplay { length: 150, delay: 0 },
+///     kind: FFEffectKind::Rumble { strong_magnitude: 0xffff, weak_magnitude: 0 },
+/// };
+/// effects.play("hit", hit, 1)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct FFManager<K> {
+    control: DeviceControl,
+    capacity: usize,
+    slots: Vec<Slot<K>>,
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct Slot<K> {
+    key: K,
+    data: FFEffectData,
+    effect: FFEffect,
+    last_played: u64,
+}
+
+impl<K: Eq> FFManager<K> {
+    /// Create a manager for `device`, using as many slots as the device supports.
+    pub fn new(device: &Device) -> Self {
+        Self::with_capacity(device.control(), device.max_ff_effects())
+    }
+
+    /// Create a manager using at most `capacity` slots of the device behind `control`.
+    ///
+    /// Use this to leave room for effects uploaded elsewhere.
+    pub fn with_capacity(control: DeviceControl, capacity: usize) -> Self {
+        Self {
+            control,
+            capacity,
+            slots: Vec::new(),
+            clock: 0,
+        }
+    }
+
+    /// Returns the maximum number of effects the manager keeps uploaded.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of effects currently uploaded.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns `true` if no effects are uploaded.
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Returns `true` if an effect is uploaded for `key`.
+    pub fn contains(&self, key: &K) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// Upload the effect for `key` if necessary, and play it `count` times.
+    ///
+    /// If an effect is already uploaded for `key` with different data, it is updated. If the
+    /// device has no free slot, the least recently played effect is erased first. This also
+    /// happens if the device reports that it's full before the manager's capacity is reached,
+    /// e.g. because other effects were uploaded elsewhere.
+    pub fn play(&mut self, key: K, data: FFEffectData, count: i32) -> io::Result<()> {
+        let index = match self.position(&key) {
+            Some(i) => {
+                let slot = &mut self.slots[i];
+                if slot.data != data {
+                    slot.effect.update(data)?;
+                    slot.data = data;
+                }
+                i
+            }
+            None => {
+                let effect = self.upload(data)?;
+                self.slots.push(Slot {
+                    key,
+                    data,
+                    effect,
+                    last_played: 0,
+                });
+                self.slots.len() - 1
+            }
+        };
+        self.clock += 1;
+        let slot = &mut self.slots[index];
+        slot.last_played = self.clock;
+        slot.effect.play(count)
+    }
+
+    /// Stop the effect for `key`, if one is uploaded. It stays uploaded for later use.
+    pub fn stop(&mut self, key: &K) -> io::Result<()> {
+        match self.position(key) {
+            Some(i) => self.slots[i].effect.stop(),
+            None => Ok(()),
+        }
+    }
+
+    /// Erase the effect for `key` from the device, returning `true` if one was uploaded.
+    pub fn remove(&mut self, key: &K) -> bool {
+        match self.position(key) {
+            Some(i) => {
+                self.slots.swap_remove(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Erase all effects uploaded by the manager.
+    pub fn clear(&mut self) {
+        self.slots.clear();
+    }
+
+    fn position(&self, key: &K) -> Option<usize> {
+        self.slots.iter().position(|slot| slot.key == *key)
+    }
+
+    fn upload(&mut self, data: FFEffectData) -> io::Result<FFEffect> {
+        if self.capacity == 0 {
+            return Err(io::Error::from_raw_os_error(libc::ENOSPC));
+        }
+        if self.slots.len() >= self.capacity {
+            self.evict();
+        }
+        loop {
+            match self.control.upload_ff_effect(data) {
+                Err(e) if e.raw_os_error() == Some(libc::ENOSPC) && !self.slots.is_empty() => {
+                    self.evict();
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Erases the least recently played effect.
+    fn evict(&mut self) {
+        let oldest = self
+            .slots
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, slot)| slot.last_played)
+            .map(|(i, _)| i);
+        if let Some(i) = oldest {
+            self.slots.swap_remove(i);
+        }
+    }
+}
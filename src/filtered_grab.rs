@@ -0,0 +1,195 @@
+use crate::evemu::Description;
+use crate::uinput::VirtualDevice;
+use crate::{
+    AbsoluteAxisCode, Chord, ChordDetector, ChordEvent, Device, EventSummary, EventType,
+    InputEvent, KeyCode, RelativeAxisCode, SynchronizationCode,
+};
+use std::collections::HashSet;
+use std::io;
+
+/// Grabs a device, but only consumes some of its events and forwards the rest.
+///
+/// Grabbing a device hides all of its events from everyone else, which is too much for a hotkey
+/// daemon that only wants Super+K. A filtered grab grabs the device and creates a virtual copy of
+/// it, then forwards every event that isn't consumed through the copy, so other applications
+/// keep working as usual. Consumed events are returned from [`FilteredGrab::fetch_events`].
+///
+/// Keys can be consumed always, or only as part of a [`Chord`]. For a chord, the key press that
+/// completes it is consumed together with its repeats and release, while the other keys of the
+/// chord (typically the modifiers) are still forwarded. Applications will therefore see Super
+/// being pressed and released on its own, which some desktops treat as a shortcut of its own.
+///
+/// Forwarded events keep their frames. Frames left with nothing but `MSC_SCAN` events after
+/// filtering are dropped.
+///
+/// ```no_run
+/// use evdev::{Device, FilteredGrab};
+///
+/// let device = Device::open("/dev/input/event0")?;
+/// let mut grab = FilteredGrab::new(device)?;
+/// grab.consume_chord("Super+K".parse().unwrap());
+/// loop {
+///     for ev in grab.fetch_events()? {
+///         println!("consumed {ev:?}");
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct FilteredGrab {
+    device: Device,
+    proxy: VirtualDevice,
+    filter: Filter,
+    consumed: Vec<InputEvent>,
+    forwarded: Vec<InputEvent>,
+}
+
+impl FilteredGrab {
+    /// Grab `device` and create the virtual device its events are forwarded through.
+    ///
+    /// Nothing is consumed until keys or axes are configured.
+    pub fn new(mut device: Device) -> io::Result<Self> {
+        let proxy = Description::from_device(&device)?.create_virtual_device()?;
+        device.grab()?;
+        Ok(Self {
+            device,
+            proxy,
+            filter: Filter::default(),
+            consumed: Vec::new(),
+            forwarded: Vec::new(),
+        })
+    }
+
+    /// Consume every event of `key`.
+    pub fn consume_key(&mut self, key: KeyCode) {
+        self.filter.keys.insert(key);
+    }
+
+    /// Consume the key that completes `chord`, but only while the rest of the chord is held.
+    pub fn consume_chord(&mut self, chord: Chord) {
+        self.filter.chords.add(chord, ());
+    }
+
+    /// Consume every event of a relative axis.
+    pub fn consume_relative_axis(&mut self, axis: RelativeAxisCode) {
+        self.filter.relative_axes.insert(axis);
+    }
+
+    /// Consume every event of an absolute axis.
+    pub fn consume_absolute_axis(&mut self, axis: AbsoluteAxisCode) {
+        self.filter.absolute_axes.insert(axis);
+    }
+
+    /// Returns the grabbed device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Returns the virtual device the remaining events are forwarded through.
+    pub fn proxy(&mut self) -> &mut VirtualDevice {
+        &mut self.proxy
+    }
+
+    /// Read events from the device, forward those that aren't consumed, and return the consumed
+    /// ones.
+    ///
+    /// This blocks until events are available, like [`Device::fetch_events`]. The returned
+    /// events are followed by `SYN_REPORT`s like the original frames, so they can be processed
+    /// with the usual tools.
+    pub fn fetch_events(&mut self) -> io::Result<impl Iterator<Item = InputEvent> + '_> {
+        self.consumed.clear();
+        for ev in self.device.fetch_events()? {
+            if is_report(&ev) {
+                if self.consumed.last().map_or(false, |last| !is_report(last)) {
+                    self.consumed.push(ev);
+                }
+                if self
+                    .forwarded
+                    .iter()
+                    .any(|ev| ev.event_type() != EventType::MISC)
+                {
+                    self.forwarded.push(ev);
+                    self.proxy.write_raw(&self.forwarded)?;
+                }
+                self.forwarded.clear();
+            } else if self.filter.consumes(&ev) {
+                self.consumed.push(ev);
+            } else {
+                self.forwarded.push(ev);
+            }
+        }
+        Ok(self.consumed.drain(..))
+    }
+
+    /// Release the grab and return the device. The virtual device is destroyed.
+    pub fn into_inner(mut self) -> io::Result<Device> {
+        self.device.ungrab()?;
+        Ok(self.device)
+    }
+}
+
+fn is_report(ev: &InputEvent) -> bool {
+    ev.event_type() == EventType::SYNCHRONIZATION && ev.code() == SynchronizationCode::SYN_REPORT.0
+}
+
+/// Decides which events are consumed, independently of any device.
+#[derive(Debug, Default)]
+struct Filter {
+    keys: HashSet<KeyCode>,
+    chords: ChordDetector<()>,
+    relative_axes: HashSet<RelativeAxisCode>,
+    absolute_axes: HashSet<AbsoluteAxisCode>,
+    /// Keys whose press was consumed, so their repeats and release are consumed as well.
+    held: HashSet<KeyCode>,
+}
+
+impl Filter {
+    fn consumes(&mut self, event: &InputEvent) -> bool {
+        match event.destructure() {
+            EventSummary::Key(_, key, value) => {
+                let activated = self
+                    .chords
+                    .process(event)
+                    .iter()
+                    .any(|ev| matches!(ev, ChordEvent::Activated(())));
+                match value {
+                    0 => self.held.remove(&key) || self.keys.contains(&key),
+                    1 if activated || self.keys.contains(&key) => {
+                        self.held.insert(key);
+                        true
+                    }
+                    _ => self.held.contains(&key) || self.keys.contains(&key),
+                }
+            }
+            EventSummary::RelativeAxis(_, axis, _) => self.relative_axes.contains(&axis),
+            EventSummary::AbsoluteAxis(_, axis, _) => self.absolute_axes.contains(&axis),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KeyEvent, RelativeAxisEvent};
+
+    #[test]
+    fn chord_and_axes() {
+        let mut filter = Filter::default();
+        filter.chords.add("Super+K".parse().unwrap(), ());
+        filter.relative_axes.insert(RelativeAxisCode::REL_WHEEL);
+        let mut consumes = |key, value| filter.consumes(&KeyEvent::new(key, value));
+
+        assert!(!consumes(KeyCode::KEY_K, 1));
+        assert!(!consumes(KeyCode::KEY_K, 0));
+        assert!(!consumes(KeyCode::KEY_LEFTMETA, 1));
+        assert!(consumes(KeyCode::KEY_K, 1));
+        assert!(consumes(KeyCode::KEY_K, 2));
+        // releasing the modifier first doesn't leak the release of K
+        assert!(!consumes(KeyCode::KEY_LEFTMETA, 0));
+        assert!(consumes(KeyCode::KEY_K, 0));
+
+        assert!(filter.consumes(&RelativeAxisEvent::new(RelativeAxisCode::REL_WHEEL, 1)));
+        assert!(!filter.consumes(&RelativeAxisEvent::new(RelativeAxisCode::REL_X, 1)));
+    }
+}
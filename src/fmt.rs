@@ -0,0 +1,233 @@
+//! Printing devices and events the way `evtest` does.
+//!
+//! `evtest` is the tool most people reach for to see what an input device reports, and its
+//! output is what bug reports and forum posts are full of. Diagnostic tools built on this crate
+//! can use these functions to produce the same format, so their output is familiar and can be
+//! compared to that of `evtest` with `diff`.
+//!
+//! ```no_run
+//! use evdev::Device;
+//!
+//! let mut device = Device::open("/dev/input/event0")?;
+//! print!("{}", evdev::fmt::device_header(&device));
+//! println!("Testing ... (interrupt to exit)");
+//! loop {
+//!     for ev in device.fetch_events()? {
+//!         println!("{}", evdev::fmt::pretty_print(&ev.destructure()));
+//!     }
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use crate::evemu::{code_name, type_name};
+use crate::{
+    AttributeSetRef, Device, EvdevEnum, EventSummary, EventType, InputEvent, MiscCode, PropType,
+    SynchronizationCode,
+};
+use std::fmt::Write;
+
+/// Formats an event like `evtest` does, e.g.
+/// `Event: time 1407342150.462234, type 1 (EV_KEY), code 30 (KEY_A), value 1`.
+///
+/// `SYN_REPORT`s are formatted as separators, and the values of `MSC_SCAN` and `MSC_RAW` events
+/// in hexadecimal. The timestamp is printed as stored in the event, whichever clock it is from.
+pub fn pretty_print(summary: &EventSummary) -> String {
+    let event = event_of(summary);
+    let time = event.as_ref().time;
+    let mut out = format!("Event: time {}.{:06}, ", time.tv_sec, time.tv_usec);
+    let ty = event.event_type();
+    let code = code_name(&event);
+    let _ = match summary {
+        EventSummary::Synchronization(_, SynchronizationCode::SYN_MT_REPORT, _) => {
+            write!(out, "++++++++++++++ {code} ++++++++++++")
+        }
+        EventSummary::Synchronization(_, SynchronizationCode::SYN_DROPPED, _) => {
+            write!(out, ">>>>>>>>>>>>>> {code} <<<<<<<<<<<<")
+        }
+        EventSummary::Synchronization(..) => write!(out, "-------------- {code} ------------"),
+        EventSummary::Misc(_, MiscCode::MSC_SCAN | MiscCode::MSC_RAW, value) => write!(
+            out,
+            "type {} ({}), code {} ({code}), value {value:02x}",
+            ty.0,
+            type_name(ty),
+            event.code()
+        ),
+        _ => write!(
+            out,
+            "type {} ({}), code {} ({code}), value {}",
+            ty.0,
+            type_name(ty),
+            event.code(),
+            event.value()
+        ),
+    };
+    out
+}
+
+fn event_of(summary: &EventSummary) -> InputEvent {
+    match summary {
+        EventSummary::Synchronization(ev, ..) => **ev,
+        EventSummary::Key(ev, ..) => **ev,
+        EventSummary::RelativeAxis(ev, ..) => **ev,
+        EventSummary::AbsoluteAxis(ev, ..) => **ev,
+        EventSummary::Misc(ev, ..) => **ev,
+        EventSummary::Switch(ev, ..) => **ev,
+        EventSummary::Led(ev, ..) => **ev,
+        EventSummary::Sound(ev, ..) => **ev,
+        EventSummary::Repeat(ev, ..) => **ev,
+        EventSummary::ForceFeedback(ev, ..) => **ev,
+        EventSummary::Power(ev, ..) => **ev,
+        EventSummary::ForceFeedbackStatus(ev, ..) => **ev,
+        EventSummary::UInput(ev, ..) => **ev,
+        EventSummary::Other(ev, ..) => **ev,
+    }
+}
+
+/// Formats the description of a device that `evtest` prints before the events: the driver
+/// version, the ID and name of the device, the supported events with the state of axes, switches
+/// and LEDs, the key repeat settings, and the properties.
+///
+/// The state is taken from [`Device::cached_state`].
+pub fn device_header(device: &Device) -> String {
+    let mut out = String::new();
+    let (major, minor, patch) = device.driver_version();
+    let id = device.input_id();
+    let _ = writeln!(out, "Input driver version is {major}.{minor}.{patch}");
+    let _ = writeln!(
+        out,
+        "Input device ID: bus {:#x} vendor {:#x} product {:#x} version {:#x}",
+        id.bus_type().0,
+        id.vendor(),
+        id.product(),
+        id.version()
+    );
+    let _ = writeln!(
+        out,
+        "Input device name: \"{}\"",
+        device.name().unwrap_or("")
+    );
+    out.push_str("Supported events:\n");
+
+    let state = device.cached_state();
+    for ty in device.supported_events().iter() {
+        let _ = writeln!(out, "  Event type {} ({})", ty.0, type_name(ty));
+        match ty {
+            EventType::KEY => codes(&mut out, ty, device.supported_keys(), |_| None),
+            EventType::RELATIVE => codes(&mut out, ty, device.supported_relative_axes(), |_| None),
+            EventType::ABSOLUTE => {
+                for axis in device.supported_absolute_axes().into_iter().flatten() {
+                    code_line(&mut out, ty, axis.0);
+                    out.push('\n');
+                    if let Some(info) = state.abs_vals().and_then(|abs| abs.get(axis.0 as usize)) {
+                        let fields = [
+                            ("Value", info.value),
+                            ("Min  ", info.minimum),
+                            ("Max  ", info.maximum),
+                            ("Fuzz ", info.fuzz),
+                            ("Flat ", info.flat),
+                            ("Resolution ", info.resolution),
+                        ];
+                        for (i, (name, value)) in fields.into_iter().enumerate() {
+                            if i < 3 || value != 0 {
+                                let _ = writeln!(out, "      {name} {value:6}");
+                            }
+                        }
+                    }
+                }
+            }
+            EventType::MISC => codes(&mut out, ty, device.misc_properties(), |_| None),
+            EventType::SWITCH => codes(&mut out, ty, device.supported_switches(), |sw| {
+                state.switch_vals().map(|vals| vals.contains(sw))
+            }),
+            EventType::LED => codes(&mut out, ty, device.supported_leds(), |led| {
+                state.led_vals().map(|vals| vals.contains(led))
+            }),
+            EventType::SOUND => codes(&mut out, ty, device.supported_sounds(), |_| None),
+            EventType::FORCEFEEDBACK => {
+                for effect in device.supported_ff().into_iter().flatten() {
+                    let _ = writeln!(out, "    Event code {} ({effect:?})", effect.0);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(repeat) = device.get_auto_repeat() {
+        out.push_str("Key repeat handling:\n");
+        let ty = EventType::REPEAT;
+        let _ = writeln!(out, "  Repeat type {} ({})", ty.0, type_name(ty));
+        for (code, name, value) in [
+            (0, "REP_DELAY", repeat.delay),
+            (1, "REP_PERIOD", repeat.period),
+        ] {
+            let _ = writeln!(out, "    Repeat code {code} ({name})");
+            let _ = writeln!(out, "      Value {value:6}");
+        }
+    }
+
+    out.push_str("Properties:\n");
+    for prop in device.properties().iter() {
+        let _ = writeln!(out, "  Property type {} ({})", prop.0, prop_name(prop));
+    }
+    out
+}
+
+fn codes<T: EvdevEnum>(
+    out: &mut String,
+    ty: EventType,
+    codes: Option<&AttributeSetRef<T>>,
+    state: impl Fn(T) -> Option<bool>,
+) {
+    for code in codes.into_iter().flatten() {
+        code_line(out, ty, code.to_index() as u16);
+        if let Some(on) = state(code) {
+            let _ = write!(out, " state {}", on as u8);
+        }
+        out.push('\n');
+    }
+}
+
+fn code_line(out: &mut String, ty: EventType, code: u16) {
+    let name = code_name(&InputEvent::new(ty, code, 0));
+    let _ = write!(out, "    Event code {code} ({name})");
+}
+
+fn prop_name(prop: PropType) -> String {
+    let name = format!("{prop:?}");
+    if name.starts_with("INPUT_PROP_") {
+        name
+    } else {
+        format!("INPUT_PROP_{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KeyCode, KeyEvent, MiscEvent, SynchronizationEvent};
+
+    #[test]
+    fn event_lines() {
+        let key = InputEvent::from(crate::compat::input_event {
+            time: crate::compat::timeval {
+                tv_sec: 1407342150,
+                tv_usec: 462234,
+            },
+            ..*KeyEvent::new(KeyCode::KEY_A, 1).as_ref()
+        });
+        assert_eq!(
+            pretty_print(&key.destructure()),
+            "Event: time 1407342150.462234, type 1 (EV_KEY), code 30 (KEY_A), value 1"
+        );
+        let scan = *MiscEvent::new(MiscCode::MSC_SCAN, 0x70004);
+        assert_eq!(
+            pretty_print(&scan.destructure()),
+            "Event: time 0.000000, type 4 (EV_MSC), code 4 (MSC_SCAN), value 70004"
+        );
+        let report = *SynchronizationEvent::new(SynchronizationCode::SYN_REPORT, 0);
+        assert_eq!(
+            pretty_print(&report.destructure()),
+            "Event: time 0.000000, -------------- SYN_REPORT ------------"
+        );
+    }
+}
@@ -0,0 +1,243 @@
+use crate::{EventSummary, InputEvent, MiscCode, SynchronizationCode};
+use std::time::Duration;
+
+/// An iterator adapter grouping events into frames, i.e. the events between two `SYN_REPORT`s.
+///
+/// Each item holds the events of one frame in order, without the terminating `SYN_REPORT`, so
+/// it can be handed to [`VirtualDevice::emit`] as-is. Events after the last `SYN_REPORT` of the
+/// underlying iterator are incomplete and are discarded.
+///
+/// As described in the kernel documentation, a `SYN_DROPPED` invalidates the frame it appears
+/// in: all events up to and including the next `SYN_REPORT` are discarded. Use a synchronized
+/// [`Device`] if you need to know what the dropped events changed.
+///
+/// Usually created via [`Device::fetch_frames`] or [`RawDevice::fetch_frames`].
+///
+/// [`VirtualDevice::emit`]: crate::uinput::VirtualDevice::emit
+/// [`Device`]: crate::Device
+/// [`Device::fetch_frames`]: crate::Device::fetch_frames
+/// [`RawDevice::fetch_frames`]: crate::raw_stream::RawDevice::fetch_frames
+#[derive(Debug)]
+pub struct Frames<I> {
+    events: I,
+}
+
+impl<I: Iterator<Item = InputEvent>> Frames<I> {
+    /// Group the events of `events` into frames.
+    pub fn new(events: impl IntoIterator<IntoIter = I>) -> Self {
+        Self {
+            events: events.into_iter(),
+        }
+    }
+
+    /// Attach the hardware timestamp (`MSC_TIMESTAMP`) of each frame to it.
+    pub fn timestamped(self) -> TimestampedFrames<I> {
+        TimestampedFrames {
+            frames: self,
+            clock: HardwareClock::new(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = InputEvent>> Iterator for Frames<I> {
+    type Item = Vec<InputEvent>;
+
+    fn next(&mut self) -> Option<Vec<InputEvent>> {
+        let mut frame = Vec::new();
+        let mut dropped = false;
+        for ev in self.events.by_ref() {
+            match ev.destructure() {
+                EventSummary::Synchronization(_, SynchronizationCode::SYN_REPORT, _) => {
+                    if !dropped {
+                        return Some(frame);
+                    }
+                    dropped = false;
+                    frame.clear();
+                }
+                EventSummary::Synchronization(_, SynchronizationCode::SYN_DROPPED, _) => {
+                    dropped = true;
+                }
+                _ if !dropped => frame.push(ev),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// A frame of events along with its hardware timestamp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimestampedFrame {
+    /// The events of the frame, without the terminating `SYN_REPORT`.
+    pub events: Vec<InputEvent>,
+    /// The time of the frame according to the device's own clock, if it reports one, as
+    /// extended by a [`HardwareClock`].
+    pub hardware_time: Option<Duration>,
+}
+
+/// An iterator adapter yielding frames along with their hardware timestamp.
+///
+/// Created by [`Frames::timestamped`].
+#[derive(Debug)]
+pub struct TimestampedFrames<I> {
+    frames: Frames<I>,
+    clock: HardwareClock,
+}
+
+impl<I> TimestampedFrames<I> {
+    /// Returns the clock used to extend the hardware timestamps.
+    pub fn clock(&self) -> &HardwareClock {
+        &self.clock
+    }
+}
+
+impl<I: Iterator<Item = InputEvent>> Iterator for TimestampedFrames<I> {
+    type Item = TimestampedFrame;
+
+    fn next(&mut self) -> Option<TimestampedFrame> {
+        let events = self.frames.next()?;
+        let hardware_time = self.clock.update_from_frame(&events);
+        Some(TimestampedFrame {
+            events,
+            hardware_time,
+        })
+    }
+}
+
+/// Tracks the hardware timestamps reported with `MSC_TIMESTAMP`.
+///
+/// Many touch controllers report the time at which they scanned each frame in microseconds.
+/// This is more precise than the time the kernel received the frame, but the counter is only 32
+/// bits wide and wraps around after about 71 minutes. The clock detects wraparounds and extends
+/// the timestamps into a monotonic [`Duration`] since the counter was last zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HardwareClock {
+    last: Option<u32>,
+    wraps: u64,
+}
+
+impl HardwareClock {
+    /// Create a clock that hasn't seen a timestamp yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if the counter wrapped around between the timestamps `prev` and `next`.
+    pub fn wrapped(prev: u32, next: u32) -> bool {
+        next < prev
+    }
+
+    /// Update the clock with the value of an `MSC_TIMESTAMP` event, returning the extended
+    /// timestamp.
+    pub fn update(&mut self, value: i32) -> Duration {
+        let value = value as u32;
+        if let Some(last) = self.last {
+            if Self::wrapped(last, value) {
+                self.wraps += 1;
+            }
+        }
+        self.last = Some(value);
+        self.now().unwrap_or_default()
+    }
+
+    /// Update the clock with the `MSC_TIMESTAMP` of a frame, if it contains one.
+    pub fn update_from_frame(&mut self, events: &[InputEvent]) -> Option<Duration> {
+        let value = events.iter().rev().find_map(|ev| match ev.destructure() {
+            EventSummary::Misc(_, MiscCode::MSC_TIMESTAMP, value) => Some(value),
+            _ => None,
+        })?;
+        Some(self.update(value))
+    }
+
+    /// Returns the last extended timestamp, if any.
+    pub fn now(&self) -> Option<Duration> {
+        let last = self.last?;
+        Some(Duration::from_micros((self.wraps << 32) + last as u64))
+    }
+
+    /// Forget all timestamps, e.g. after the device was reset.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Returns the index just past the last `SYN_REPORT` in `events`, or 0 if there is none.
+pub(crate) fn complete_frames_len(events: &[crate::compat::input_event]) -> usize {
+    events
+        .iter()
+        .rposition(|ev| {
+            ev.type_ == crate::EventType::SYNCHRONIZATION.0
+                && ev.code == SynchronizationCode::SYN_REPORT.0
+        })
+        .map_or(0, |i| i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventType, KeyCode, KeyEvent, MiscEvent, SynchronizationEvent};
+
+    fn key(code: KeyCode) -> InputEvent {
+        *KeyEvent::new(code, 1)
+    }
+
+    fn syn(code: SynchronizationCode) -> InputEvent {
+        *SynchronizationEvent::new(code, 0)
+    }
+
+    #[test]
+    fn groups_by_report() {
+        let report = syn(SynchronizationCode::SYN_REPORT);
+        let events = [
+            key(KeyCode::KEY_A),
+            key(KeyCode::KEY_B),
+            report,
+            report,
+            key(KeyCode::KEY_C),
+        ];
+        let frames: Vec<_> = Frames::new(events).collect();
+        assert_eq!(
+            frames,
+            [vec![key(KeyCode::KEY_A), key(KeyCode::KEY_B)], vec![]]
+        );
+    }
+
+    #[test]
+    fn skips_dropped_frames() {
+        let report = syn(SynchronizationCode::SYN_REPORT);
+        let events = [
+            key(KeyCode::KEY_A),
+            syn(SynchronizationCode::SYN_DROPPED),
+            key(KeyCode::KEY_B),
+            report,
+            key(KeyCode::KEY_C),
+            report,
+        ];
+        let frames: Vec<_> = Frames::new(events).collect();
+        assert_eq!(frames, [vec![key(KeyCode::KEY_C)]]);
+        assert_eq!(frames[0][0].event_type(), EventType::KEY);
+    }
+
+    #[test]
+    fn hardware_timestamps_wrap() {
+        let report = syn(SynchronizationCode::SYN_REPORT);
+        let ts = |v: u32| *MiscEvent::new(MiscCode::MSC_TIMESTAMP, v as i32);
+        let events = [
+            ts(u32::MAX - 9),
+            report,
+            key(KeyCode::KEY_A),
+            report,
+            ts(10),
+            report,
+        ];
+        let times: Vec<_> = Frames::new(events)
+            .timestamped()
+            .map(|f| f.hardware_time)
+            .collect();
+        let first = Duration::from_micros(u32::MAX as u64 - 9);
+        assert_eq!(
+            times,
+            [Some(first), None, Some(first + Duration::from_micros(20))]
+        );
+    }
+}
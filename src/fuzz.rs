@@ -0,0 +1,124 @@
+//! Generation of realistic events for fuzzing, shared by the `arbitrary` and `proptest`
+//! features.
+//!
+//! Events get a named code of their type most of the time, and a value in the range used by
+//! their type, so that fuzzed code sees the events devices actually send rather than mostly
+//! unknown codes.
+
+use crate::constants::*;
+use crate::scancodes::KeyCode;
+use crate::InputEvent;
+use std::ops::RangeInclusive;
+use std::time::{Duration, SystemTime};
+
+/// Returns the number of named codes of `ty`, and a function returning the code at an index.
+pub(crate) fn named_codes(ty: EventType) -> (usize, fn(usize) -> u16) {
+    macro_rules! codes {
+        ($t:ty) => {
+            (<$t>::ALL.len(), |i| <$t>::ALL[i].0)
+        };
+    }
+    match ty {
+        EventType::SYNCHRONIZATION => codes!(SynchronizationCode),
+        EventType::KEY => codes!(KeyCode),
+        EventType::RELATIVE => codes!(RelativeAxisCode),
+        EventType::ABSOLUTE => codes!(AbsoluteAxisCode),
+        EventType::MISC => codes!(MiscCode),
+        EventType::SWITCH => codes!(SwitchCode),
+        EventType::LED => codes!(LedCode),
+        EventType::SOUND => codes!(SoundCode),
+        EventType::REPEAT => codes!(RepeatCode),
+        EventType::FORCEFEEDBACK => codes!(FFEffectCode),
+        EventType::FORCEFEEDBACKSTATUS => codes!(FFStatusCode),
+        EventType::UINPUT => codes!(UInputCode),
+        _ => (0, |i| i as u16),
+    }
+}
+
+/// Returns the range of values events of type `ty` have.
+pub(crate) fn value_range(ty: EventType) -> RangeInclusive<i32> {
+    match ty {
+        EventType::SYNCHRONIZATION => 0..=0,
+        // released, pressed, and repeated
+        EventType::KEY => 0..=2,
+        EventType::SWITCH | EventType::LED | EventType::SOUND => 0..=1,
+        EventType::RELATIVE => -255..=255,
+        _ => i32::MIN..=i32::MAX,
+    }
+}
+
+/// Returns the range of seconds of timestamps, which fits a 32-bit `time_t`.
+pub(crate) const SECONDS: RangeInclusive<u32> = 0..=i32::MAX as u32;
+
+pub(crate) fn event(ty: EventType, code: u16, value: i32, sec: u32, usec: u32) -> InputEvent {
+    let time = SystemTime::UNIX_EPOCH + Duration::new(sec.into(), usec * 1000);
+    InputEvent::from(crate::compat::input_event {
+        time: crate::systime_to_timeval(&time),
+        type_: ty.0,
+        code,
+        value,
+    })
+}
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls {
+    use super::*;
+    use crate::EventSummary;
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    impl<'a> Arbitrary<'a> for InputEvent {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let ty = *u.choose(EventType::ALL)?;
+            let (count, code_at) = named_codes(ty);
+            let code = if count > 0 && u.ratio(15, 16)? {
+                code_at(u.choose_index(count)?)
+            } else {
+                u.arbitrary()?
+            };
+            let value = u.int_in_range(value_range(ty))?;
+            let sec = u.int_in_range(SECONDS)?;
+            let usec = u.int_in_range(0..=999_999)?;
+            Ok(event(ty, code, value, sec, usec))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for EventSummary {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(InputEvent::arbitrary(u)?.destructure())
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for PowerCode {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(Self(u.arbitrary()?))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for OtherCode {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(Self(u.arbitrary()?, u.arbitrary()?))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn realistic_events() {
+            let bytes: Vec<u8> = (0..4096u32)
+                .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+                .collect();
+            let mut u = Unstructured::new(&bytes);
+            let mut named = 0;
+            for _ in 0..100 {
+                let ev = InputEvent::arbitrary(&mut u).unwrap();
+                assert!(value_range(ev.event_type()).contains(&ev.value()));
+                if !format!("{:?}", ev.destructure()).contains("UNKNOWN") {
+                    named += 1;
+                }
+            }
+            assert!(named > 50);
+        }
+    }
+}
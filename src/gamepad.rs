@@ -0,0 +1,331 @@
+//! A standard layout for gamepads.
+//!
+//! Gamepads report their buttons and sticks with a wide range of `BTN_*` and `ABS_*` codes. The
+//! kernel's [gamepad specification](https://www.kernel.org/doc/html/latest/input/gamepad.html)
+//! describes the codes that well-behaved drivers use; a [`GamepadMapping`] translates these (or
+//! any other codes) into the positional [`Button`]s and [`Axis`]es of a typical controller, and a
+//! [`Gamepad`] uses a mapping to turn raw events into [`GamepadEvent`]s.
+//!
+//! With the `gamecontrollerdb` feature, mappings can also be loaded from SDL's
+//! `gamecontrollerdb.txt`, which covers thousands of controllers that don't follow the kernel's
+//! specification.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use evdev::gamepad::{Button, Gamepad, GamepadEvent};
+//! let mut device = evdev::Device::open("/dev/input/event0")?;
+//! let mut gamepad = Gamepad::from_device(&device).expect("not a gamepad");
+//! loop {
+//!     for event in gamepad.gamepad_events(device.fetch_events()?) {
+//!         if let GamepadEvent::Button(Button::South, true) = event {
+//!             println!("jump!");
+//!         }
+//!     }
+//! }
+//! # }
+//! ```
+
+use crate::{AbsoluteAxisCode, Device, EventSummary, InputEvent, KeyCode};
+use std::collections::HashMap;
+
+mod deadzone;
+#[cfg(feature = "gamecontrollerdb")]
+mod sdl;
+
+pub use deadzone::Deadzone;
+
+#[cfg(feature = "gamecontrollerdb")]
+pub use sdl::{sdl_guid, SdlDatabase, SdlMapping};
+
+/// A button of a gamepad, named after its position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Button {
+    /// The bottom face button ("A" on Xbox controllers, "Cross" on PlayStation controllers).
+    South,
+    /// The right face button.
+    East,
+    /// The left face button.
+    West,
+    /// The top face button.
+    North,
+    LeftBumper,
+    RightBumper,
+    /// The digital part of the left trigger.
+    LeftTrigger,
+    /// The digital part of the right trigger.
+    RightTrigger,
+    /// The left center button ("Back", "Share", "Select").
+    Select,
+    /// The right center button ("Start", "Options").
+    Start,
+    /// The central button ("Home", "Guide", "PS").
+    Mode,
+    /// Pressing down the left stick.
+    LeftThumb,
+    /// Pressing down the right stick.
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+/// An analog axis of a gamepad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Axis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+/// An event of a [`Gamepad`], in terms of its standard layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadEvent {
+    /// A button was pressed (`true`) or released (`false`).
+    Button(Button, bool),
+    /// An axis changed to the given raw value. The range of the value is given by the
+    /// [`AbsInfo`](crate::AbsInfo) of the axis the event originated from.
+    Axis(Axis, i32),
+}
+
+/// A mapping from the codes of a device to the standard gamepad layout.
+///
+/// Besides plain buttons and axes, an absolute axis can be mapped to a pair of buttons, pressed
+/// when the axis is negative or positive respectively. This is how d-pads reporting through
+/// `ABS_HAT0X`/`ABS_HAT0Y` are handled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GamepadMapping {
+    buttons: HashMap<KeyCode, Button>,
+    axes: HashMap<AbsoluteAxisCode, Axis>,
+    axis_buttons: HashMap<AbsoluteAxisCode, (Option<Button>, Option<Button>)>,
+}
+
+impl GamepadMapping {
+    /// Create a mapping that doesn't map anything.
+    pub fn empty() -> Self {
+        Self {
+            buttons: HashMap::new(),
+            axes: HashMap::new(),
+            axis_buttons: HashMap::new(),
+        }
+    }
+
+    /// Create the mapping described by the kernel's gamepad specification.
+    pub fn standard() -> Self {
+        let buttons = [
+            (KeyCode::BTN_SOUTH, Button::South),
+            (KeyCode::BTN_EAST, Button::East),
+            (KeyCode::BTN_WEST, Button::West),
+            (KeyCode::BTN_NORTH, Button::North),
+            (KeyCode::BTN_TL, Button::LeftBumper),
+            (KeyCode::BTN_TR, Button::RightBumper),
+            (KeyCode::BTN_TL2, Button::LeftTrigger),
+            (KeyCode::BTN_TR2, Button::RightTrigger),
+            (KeyCode::BTN_SELECT, Button::Select),
+            (KeyCode::BTN_START, Button::Start),
+            (KeyCode::BTN_MODE, Button::Mode),
+            (KeyCode::BTN_THUMBL, Button::LeftThumb),
+            (KeyCode::BTN_THUMBR, Button::RightThumb),
+            (KeyCode::BTN_DPAD_UP, Button::DPadUp),
+            (KeyCode::BTN_DPAD_DOWN, Button::DPadDown),
+            (KeyCode::BTN_DPAD_LEFT, Button::DPadLeft),
+            (KeyCode::BTN_DPAD_RIGHT, Button::DPadRight),
+        ];
+        let axes = [
+            (AbsoluteAxisCode::ABS_X, Axis::LeftStickX),
+            (AbsoluteAxisCode::ABS_Y, Axis::LeftStickY),
+            (AbsoluteAxisCode::ABS_RX, Axis::RightStickX),
+            (AbsoluteAxisCode::ABS_RY, Axis::RightStickY),
+            (AbsoluteAxisCode::ABS_Z, Axis::LeftTrigger),
+            (AbsoluteAxisCode::ABS_RZ, Axis::RightTrigger),
+        ];
+        let mut mapping = Self::empty();
+        mapping.buttons.extend(buttons);
+        mapping.axes.extend(axes);
+        mapping.map_axis_to_buttons(
+            AbsoluteAxisCode::ABS_HAT0X,
+            Some(Button::DPadLeft),
+            Some(Button::DPadRight),
+        );
+        mapping.map_axis_to_buttons(
+            AbsoluteAxisCode::ABS_HAT0Y,
+            Some(Button::DPadUp),
+            Some(Button::DPadDown),
+        );
+        mapping
+    }
+
+    /// Map a key code to a button, replacing any previous mapping of the key.
+    pub fn map_button(&mut self, key: KeyCode, button: Button) {
+        self.buttons.insert(key, button);
+    }
+
+    /// Map an absolute axis to an axis, replacing any previous mapping of the axis.
+    pub fn map_axis(&mut self, abs: AbsoluteAxisCode, axis: Axis) {
+        self.axis_buttons.remove(&abs);
+        self.axes.insert(abs, axis);
+    }
+
+    /// Map the negative and positive halves of an absolute axis to buttons, replacing any
+    /// previous mapping of the axis.
+    pub fn map_axis_to_buttons(
+        &mut self,
+        abs: AbsoluteAxisCode,
+        negative: Option<Button>,
+        positive: Option<Button>,
+    ) {
+        self.axes.remove(&abs);
+        self.axis_buttons.insert(abs, (negative, positive));
+    }
+
+    /// Returns the button a key code is mapped to.
+    pub fn button(&self, key: KeyCode) -> Option<Button> {
+        self.buttons.get(&key).copied()
+    }
+
+    /// Returns the axis an absolute axis is mapped to.
+    pub fn axis(&self, abs: AbsoluteAxisCode) -> Option<Axis> {
+        self.axes.get(&abs).copied()
+    }
+}
+
+impl Default for GamepadMapping {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// Returns `true` if the device looks like a gamepad.
+///
+/// Following the kernel's gamepad specification, a gamepad is a device that supports
+/// `BTN_SOUTH` (also known as `BTN_GAMEPAD` or `BTN_A`).
+pub fn is_gamepad(device: &Device) -> bool {
+    device
+        .supported_keys()
+        .map_or(false, |keys| keys.contains(KeyCode::BTN_SOUTH))
+}
+
+/// Translates the events of a gamepad into [`GamepadEvent`]s.
+#[derive(Debug, Clone)]
+pub struct Gamepad {
+    mapping: GamepadMapping,
+    /// The sign of each axis that is mapped to buttons, to detect presses and releases.
+    axis_signs: HashMap<AbsoluteAxisCode, i32>,
+}
+
+impl Gamepad {
+    /// Create a gamepad using the given mapping.
+    pub fn new(mapping: GamepadMapping) -> Self {
+        Self {
+            mapping,
+            axis_signs: HashMap::new(),
+        }
+    }
+
+    /// Create a gamepad for a device with the standard mapping, or `None` if the device does not
+    /// look like a gamepad.
+    pub fn from_device(device: &Device) -> Option<Self> {
+        if is_gamepad(device) {
+            Some(Self::new(GamepadMapping::standard()))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the mapping in use.
+    pub fn mapping(&self) -> &GamepadMapping {
+        &self.mapping
+    }
+
+    /// Translate an event, returning the resulting gamepad events.
+    ///
+    /// Events that aren't mapped are ignored. Key repeats are ignored as well.
+    pub fn process(&mut self, event: &InputEvent) -> Vec<GamepadEvent> {
+        let mut out = Vec::new();
+        match event.destructure() {
+            EventSummary::Key(_, key, value @ (0 | 1)) => {
+                if let Some(button) = self.mapping.button(key) {
+                    out.push(GamepadEvent::Button(button, value == 1));
+                }
+            }
+            EventSummary::AbsoluteAxis(_, abs, value) => {
+                if let Some(axis) = self.mapping.axis(abs) {
+                    out.push(GamepadEvent::Axis(axis, value));
+                } else if let Some(&(negative, positive)) = self.mapping.axis_buttons.get(&abs) {
+                    let sign = value.signum();
+                    let old = self.axis_signs.insert(abs, sign).unwrap_or(0);
+                    if sign != old {
+                        let button = |sign| if sign < 0 { negative } else { positive };
+                        if let (true, Some(b)) = (old != 0, button(old)) {
+                            out.push(GamepadEvent::Button(b, false));
+                        }
+                        if let (true, Some(b)) = (sign != 0, button(sign)) {
+                            out.push(GamepadEvent::Button(b, true));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        out
+    }
+
+    /// Translate an iterator of events into an iterator of gamepad events.
+    pub fn gamepad_events<'a, I>(&'a mut self, events: I) -> impl Iterator<Item = GamepadEvent> + 'a
+    where
+        I: IntoIterator<Item = InputEvent>,
+        I::IntoIter: 'a,
+    {
+        events.into_iter().flat_map(move |ev| self.process(&ev))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbsoluteAxisEvent, KeyEvent};
+
+    #[test]
+    fn standard_buttons_and_axes() {
+        let mut pad = Gamepad::new(GamepadMapping::standard());
+        let events = [
+            *KeyEvent::new(KeyCode::BTN_SOUTH, 1),
+            *KeyEvent::new(KeyCode::BTN_SOUTH, 2),
+            *KeyEvent::new(KeyCode::KEY_A, 1),
+            *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_RX, -300),
+            *KeyEvent::new(KeyCode::BTN_SOUTH, 0),
+        ];
+        let out: Vec<_> = pad.gamepad_events(events).collect();
+        assert_eq!(
+            out,
+            [
+                GamepadEvent::Button(Button::South, true),
+                GamepadEvent::Axis(Axis::RightStickX, -300),
+                GamepadEvent::Button(Button::South, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn hat_to_dpad() {
+        let mut pad = Gamepad::new(GamepadMapping::standard());
+        let events = [
+            *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_HAT0X, -1),
+            *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_HAT0X, 1),
+            *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_HAT0X, 0),
+        ];
+        let out: Vec<_> = pad.gamepad_events(events).collect();
+        assert_eq!(
+            out,
+            [
+                GamepadEvent::Button(Button::DPadLeft, true),
+                GamepadEvent::Button(Button::DPadLeft, false),
+                GamepadEvent::Button(Button::DPadRight, true),
+                GamepadEvent::Button(Button::DPadRight, false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use crate::AbsInfo;
+
+/// Normalization of raw joystick values with a deadzone.
+///
+/// Sticks rarely rest exactly at their center, so small deflections are treated as no
+/// deflection at all. The deadzone is given as a fraction of the axis range; by default, the
+/// kernel's `flat` value of the axis is used as a lower bound for it. Values outside the
+/// deadzone are rescaled so the output still covers the whole range.
+///
+/// ```
+/// use evdev::gamepad::Deadzone;
+/// use evdev::AbsInfo;
+///
+/// let info = AbsInfo::new(0, -32768, 32767, 16, 128, 0);
+/// let deadzone = Deadzone::new(0.1);
+/// assert_eq!(deadzone.normalize(&info, 1000), 0.0);
+/// assert_eq!(deadzone.normalize(&info, 32767), 1.0);
+/// assert_eq!(deadzone.normalize(&info, -32768), -1.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Deadzone {
+    inner: f32,
+    outer: f32,
+    use_flat: bool,
+}
+
+impl Deadzone {
+    /// No deadzone besides the `flat` value of the axis.
+    pub const FLAT: Self = Self::new(0.0);
+
+    /// Create a deadzone covering the given fraction of the axis range around its center.
+    pub const fn new(inner: f32) -> Self {
+        Self {
+            inner,
+            outer: 0.0,
+            use_flat: true,
+        }
+    }
+
+    /// Treat values within the given fraction of the ends of the axis range as fully deflected.
+    pub const fn outer(mut self, outer: f32) -> Self {
+        self.outer = outer;
+        self
+    }
+
+    /// Don't use the `flat` value reported by the kernel as a minimum deadzone.
+    pub const fn ignore_flat(mut self) -> Self {
+        self.use_flat = false;
+        self
+    }
+
+    fn inner_for(&self, flat: f32) -> f32 {
+        if self.use_flat {
+            self.inner.max(flat)
+        } else {
+            self.inner
+        }
+    }
+
+    fn rescale(&self, magnitude: f32, inner: f32) -> f32 {
+        let live = 1.0 - inner - self.outer;
+        if magnitude <= inner {
+            0.0
+        } else if live <= 0.0 {
+            1.0
+        } else {
+            ((magnitude - inner) / live).min(1.0)
+        }
+    }
+
+    /// Normalize the value of a centered axis (such as a stick axis) into `[-1, 1]`.
+    pub fn normalize(&self, info: &AbsInfo, value: i32) -> f32 {
+        let (raw, flat) = centered(info, value);
+        let inner = self.inner_for(flat);
+        raw.signum() * self.rescale(raw.abs(), inner)
+    }
+
+    /// Normalize the value of a one-sided axis (such as a trigger) into `[0, 1]`.
+    pub fn normalize_trigger(&self, info: &AbsInfo, value: i32) -> f32 {
+        let range = (info.maximum() as f32 - info.minimum() as f32).max(1.0);
+        let raw = ((value as f32 - info.minimum() as f32) / range).clamp(0.0, 1.0);
+        let inner = self.inner_for(info.flat() as f32 / range);
+        self.rescale(raw, inner)
+    }
+
+    /// Normalize the values of a pair of axes (such as the X and Y axes of a stick) with a
+    /// circular deadzone, returning a vector of at most length 1.
+    ///
+    /// Unlike normalizing each axis on its own, this doesn't snap diagonal movement to the
+    /// axes near the center.
+    pub fn normalize_stick(
+        &self,
+        (x_info, x): (&AbsInfo, i32),
+        (y_info, y): (&AbsInfo, i32),
+    ) -> (f32, f32) {
+        let (x, x_flat) = centered(x_info, x);
+        let (y, y_flat) = centered(y_info, y);
+        let inner = self.inner_for(x_flat.max(y_flat));
+        let magnitude = x.hypot(y);
+        if magnitude == 0.0 {
+            return (0.0, 0.0);
+        }
+        let scale = self.rescale(magnitude.min(1.0), inner) / magnitude;
+        (x * scale, y * scale)
+    }
+}
+
+impl Default for Deadzone {
+    fn default() -> Self {
+        Self::FLAT
+    }
+}
+
+/// Returns the value mapped into `[-1, 1]`, and the flat value as a fraction of half the range.
+fn centered(info: &AbsInfo, value: i32) -> (f32, f32) {
+    let min = info.minimum() as f32;
+    let max = info.maximum() as f32;
+    let half = ((max - min) / 2.0).max(1.0);
+    let center = (min + max) / 2.0;
+    let raw = ((value as f32 - center) / half).clamp(-1.0, 1.0);
+    (raw, info.flat() as f32 / half)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_and_rescaling() {
+        let info = AbsInfo::new(0, 0, 200, 0, 10, 0);
+        // the flat value covers 10% of the half range
+        assert_eq!(Deadzone::FLAT.normalize(&info, 105), 0.0);
+        assert_eq!(Deadzone::FLAT.ignore_flat().normalize(&info, 105), 0.05);
+        assert!((Deadzone::FLAT.normalize(&info, 155) - 0.5).abs() < 1e-6);
+        assert_eq!(Deadzone::new(0.1).outer(0.1).normalize(&info, 5), -1.0);
+        assert_eq!(Deadzone::FLAT.normalize_trigger(&info, 200), 1.0);
+        assert_eq!(Deadzone::FLAT.normalize_trigger(&info, 5), 0.0);
+    }
+
+    #[test]
+    fn circular_deadzone() {
+        let info = AbsInfo::new(0, -100, 100, 0, 0, 0);
+        let deadzone = Deadzone::new(0.2);
+        assert_eq!(
+            deadzone.normalize_stick((&info, 10), (&info, -10)),
+            (0.0, 0.0)
+        );
+        let (x, y) = deadzone.normalize_stick((&info, 100), (&info, 100));
+        assert!((x.hypot(y) - 1.0).abs() < 1e-6);
+        assert!((x - y).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,375 @@
+//! Support for the mapping database of SDL, `gamecontrollerdb.txt`.
+//!
+//! Each line of the database describes one controller, identified by a GUID derived from its
+//! [`InputId`], and maps SDL's button and axis indices to SDL's standard gamepad layout. See
+//! <https://github.com/gabomdq/SDL_GameControllerDB> for the community-maintained database.
+
+use super::{Axis, Button, Gamepad, GamepadMapping};
+use crate::{AbsoluteAxisCode, AttributeSetRef, Device, InputId, KeyCode};
+use std::collections::HashMap;
+use std::path::Path;
+use std::{fs, io};
+
+const BTN_JOYSTICK: u16 = 0x120;
+const ABS_HAT0X: u16 = AbsoluteAxisCode::ABS_HAT0X.0;
+const ABS_HAT3Y: u16 = AbsoluteAxisCode::ABS_HAT3Y.0;
+
+/// Returns the GUID SDL uses to identify a device with the given ID on Linux.
+///
+/// The GUID is formatted as 32 lowercase hexadecimal digits, as used in `gamecontrollerdb.txt`.
+pub fn sdl_guid(id: InputId) -> String {
+    let words = [
+        id.bus_type().0,
+        0,
+        id.vendor(),
+        0,
+        id.product(),
+        0,
+        id.version(),
+        0,
+    ];
+    words
+        .iter()
+        .flat_map(|w| w.to_le_bytes())
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// A single line of an SDL mapping database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SdlMapping {
+    guid: String,
+    name: String,
+    fields: Vec<(String, String)>,
+}
+
+impl SdlMapping {
+    /// Parse a single mapping line, such as
+    /// `030000005e0400008e02000014010000,Xbox 360 Controller,a:b0,b:b1,...,platform:Linux,`.
+    ///
+    /// Returns `None` if the line is empty, a comment, or malformed.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let mut parts = line.split(',');
+        let guid = parts.next()?.trim().to_ascii_lowercase();
+        if guid.len() != 32 || !guid.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let name = parts.next()?.trim().to_owned();
+        let fields = parts
+            .filter_map(|field| {
+                let (key, value) = field.split_once(':')?;
+                Some((key.trim().to_owned(), value.trim().to_owned()))
+            })
+            .collect();
+        Some(Self { guid, name, fields })
+    }
+
+    /// Returns the GUID of the controller this mapping applies to.
+    pub fn guid(&self) -> &str {
+        &self.guid
+    }
+
+    /// Returns the human-readable name of the controller.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the platform this mapping is intended for, if specified.
+    pub fn platform(&self) -> Option<&str> {
+        self.field("platform")
+    }
+
+    fn field(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Translate this mapping into a [`GamepadMapping`] for a device with the given capabilities.
+    ///
+    /// SDL refers to buttons and axes by their index among the capabilities of the device, so
+    /// the capabilities have to match those of the device the mapping is used with. Inverted
+    /// axes and half-axis mappings of analog axes are mapped as if they were plain axes.
+    pub fn to_gamepad_mapping(
+        &self,
+        keys: &AttributeSetRef<KeyCode>,
+        axes: &AttributeSetRef<AbsoluteAxisCode>,
+    ) -> GamepadMapping {
+        let buttons: Vec<KeyCode> = keys
+            .iter()
+            .filter(|k| k.0 >= BTN_JOYSTICK)
+            .chain(keys.iter().filter(|k| k.0 < BTN_JOYSTICK))
+            .collect();
+        let abs: Vec<AbsoluteAxisCode> = axes
+            .iter()
+            .filter(|a| !(ABS_HAT0X..=ABS_HAT3Y).contains(&a.0))
+            .collect();
+
+        let mut mapping = GamepadMapping::empty();
+        for (target, source) in &self.fields {
+            let source = match parse_source(source) {
+                Some(source) => source,
+                None => continue,
+            };
+            if let Some(button) = sdl_button(target) {
+                match source {
+                    Source::Button(i) => {
+                        if let Some(&key) = buttons.get(i) {
+                            mapping.map_button(key, button);
+                        }
+                    }
+                    Source::Axis(i, half) => {
+                        if let Some(&axis) = abs.get(i) {
+                            map_half(&mut mapping, axis, half >= 0, button);
+                        }
+                    }
+                    Source::Hat(hat, mask) => {
+                        let x = AbsoluteAxisCode(ABS_HAT0X + 2 * hat as u16);
+                        let y = AbsoluteAxisCode(x.0 + 1);
+                        match mask {
+                            1 => map_half(&mut mapping, y, false, button),
+                            2 => map_half(&mut mapping, x, true, button),
+                            4 => map_half(&mut mapping, y, true, button),
+                            8 => map_half(&mut mapping, x, false, button),
+                            _ => {}
+                        }
+                    }
+                }
+            } else if let Some(axis) = sdl_axis(target) {
+                match (source, axis) {
+                    (Source::Axis(i, _), _) => {
+                        if let Some(&abs) = abs.get(i) {
+                            mapping.map_axis(abs, axis);
+                        }
+                    }
+                    // digital triggers
+                    (Source::Button(i), Axis::LeftTrigger | Axis::RightTrigger) => {
+                        if let Some(&key) = buttons.get(i) {
+                            let button = if axis == Axis::LeftTrigger {
+                                Button::LeftTrigger
+                            } else {
+                                Button::RightTrigger
+                            };
+                            mapping.map_button(key, button);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        mapping
+    }
+}
+
+fn map_half(mapping: &mut GamepadMapping, abs: AbsoluteAxisCode, positive: bool, button: Button) {
+    let (mut neg, mut pos) = mapping
+        .axis_buttons
+        .get(&abs)
+        .copied()
+        .unwrap_or((None, None));
+    if positive {
+        pos = Some(button);
+    } else {
+        neg = Some(button);
+    }
+    mapping.map_axis_to_buttons(abs, neg, pos);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Button(usize),
+    /// An axis index, and which half of it is used (`0` for the full axis).
+    Axis(usize, i8),
+    /// A hat index and direction mask.
+    Hat(usize, u8),
+}
+
+fn parse_source(s: &str) -> Option<Source> {
+    let (half, s) = match s.as_bytes().first()? {
+        b'+' => (1, &s[1..]),
+        b'-' => (-1, &s[1..]),
+        _ => (0, s),
+    };
+    let s = s.trim_end_matches('~');
+    if let Some(i) = s.strip_prefix('b') {
+        i.parse().ok().map(Source::Button)
+    } else if let Some(i) = s.strip_prefix('a') {
+        i.parse().ok().map(|i| Source::Axis(i, half))
+    } else if let Some(hat) = s.strip_prefix('h') {
+        let (hat, mask) = hat.split_once('.')?;
+        Some(Source::Hat(hat.parse().ok()?, mask.parse().ok()?))
+    } else {
+        None
+    }
+}
+
+fn sdl_button(name: &str) -> Option<Button> {
+    Some(match name {
+        "a" => Button::South,
+        "b" => Button::East,
+        "x" => Button::West,
+        "y" => Button::North,
+        "back" => Button::Select,
+        "guide" => Button::Mode,
+        "start" => Button::Start,
+        "leftstick" => Button::LeftThumb,
+        "rightstick" => Button::RightThumb,
+        "leftshoulder" => Button::LeftBumper,
+        "rightshoulder" => Button::RightBumper,
+        "dpup" => Button::DPadUp,
+        "dpdown" => Button::DPadDown,
+        "dpleft" => Button::DPadLeft,
+        "dpright" => Button::DPadRight,
+        _ => return None,
+    })
+}
+
+fn sdl_axis(name: &str) -> Option<Axis> {
+    Some(match name {
+        "leftx" => Axis::LeftStickX,
+        "lefty" => Axis::LeftStickY,
+        "rightx" => Axis::RightStickX,
+        "righty" => Axis::RightStickY,
+        "lefttrigger" => Axis::LeftTrigger,
+        "righttrigger" => Axis::RightTrigger,
+        _ => return None,
+    })
+}
+
+/// A collection of SDL controller mappings, keyed by GUID.
+///
+/// Only mappings for Linux (or without a platform) are kept.
+#[derive(Debug, Clone, Default)]
+pub struct SdlDatabase {
+    mappings: HashMap<String, SdlMapping>,
+}
+
+impl SdlDatabase {
+    /// Create an empty database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a database in the `gamecontrollerdb.txt` format, skipping invalid lines.
+    pub fn parse(text: &str) -> Self {
+        let mut db = Self::new();
+        db.extend(text);
+        db
+    }
+
+    /// Load a database from a file in the `gamecontrollerdb.txt` format.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    /// Add the mappings of a text in the `gamecontrollerdb.txt` format, replacing existing
+    /// mappings with the same GUID.
+    pub fn extend(&mut self, text: &str) {
+        for mapping in text.lines().filter_map(SdlMapping::parse) {
+            if mapping.platform().map_or(true, |p| p == "Linux") {
+                self.mappings.insert(mapping.guid.clone(), mapping);
+            }
+        }
+    }
+
+    /// Returns the number of mappings in the database.
+    pub fn len(&self) -> usize {
+        self.mappings.len()
+    }
+
+    /// Returns `true` if the database contains no mappings.
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+
+    /// Look up the mapping of a device by its ID.
+    ///
+    /// Like SDL, this falls back to a mapping that ignores the version of the device.
+    pub fn get(&self, id: InputId) -> Option<&SdlMapping> {
+        let guid = sdl_guid(id);
+        self.mappings.get(&guid).or_else(|| {
+            // bytes 12 and 13 are the version
+            let versionless = format!("{}0000{}", &guid[..24], &guid[28..]);
+            self.mappings.get(&versionless)
+        })
+    }
+
+    /// Returns the gamepad mapping of a device, if the database contains it.
+    pub fn mapping_for(&self, device: &Device) -> Option<GamepadMapping> {
+        let mapping = self.get(device.input_id())?;
+        let keys = device.supported_keys().unwrap_or_default();
+        let axes = device.supported_absolute_axes().unwrap_or_default();
+        Some(mapping.to_gamepad_mapping(keys, axes))
+    }
+}
+
+impl Gamepad {
+    /// Create a gamepad for a device, using its mapping from an SDL database if there is one.
+    ///
+    /// Otherwise, this behaves like [`Gamepad::from_device`].
+    pub fn from_device_with_db(device: &Device, db: &SdlDatabase) -> Option<Self> {
+        match db.mapping_for(device) {
+            Some(mapping) => Some(Self::new(mapping)),
+            None => Self::from_device(device),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AttributeSet, BusType};
+
+    const XBOX: &str = "030000005e0400008e02000014010000,Xbox 360 Controller,a:b0,b:b1,\
+        x:b2,y:b3,back:b6,dpdown:h0.4,dpleft:h0.8,dpright:h0.2,dpup:h0.1,leftx:a0,lefty:a1,\
+        lefttrigger:a2,platform:Linux,";
+
+    #[test]
+    fn guid() {
+        let id = InputId::new(BusType::BUS_USB, 0x045e, 0x028e, 0x0114);
+        assert_eq!(sdl_guid(id), "030000005e0400008e02000014010000");
+    }
+
+    #[test]
+    fn lookup_and_translate() {
+        let db = SdlDatabase::parse(&format!(
+            "# comment\n{}\n{}",
+            XBOX, "030000005e0400008e02000014010000,Other,a:b1,platform:Windows,"
+        ));
+        assert_eq!(db.len(), 1);
+        let id = InputId::new(BusType::BUS_USB, 0x045e, 0x028e, 0x0114);
+        let mapping = db.get(id).unwrap();
+        assert_eq!(mapping.name(), "Xbox 360 Controller");
+
+        let keys: AttributeSet<_> = [KeyCode::BTN_SOUTH, KeyCode::BTN_EAST, KeyCode::KEY_A]
+            .into_iter()
+            .collect();
+        let axes: AttributeSet<_> = [
+            AbsoluteAxisCode::ABS_X,
+            AbsoluteAxisCode::ABS_Y,
+            AbsoluteAxisCode::ABS_Z,
+            AbsoluteAxisCode::ABS_HAT0X,
+            AbsoluteAxisCode::ABS_HAT0Y,
+        ]
+        .into_iter()
+        .collect();
+        let mapping = mapping.to_gamepad_mapping(&keys, &axes);
+        assert_eq!(mapping.button(KeyCode::BTN_SOUTH), Some(Button::South));
+        assert_eq!(mapping.button(KeyCode::BTN_EAST), Some(Button::East));
+        // buttons below BTN_JOYSTICK come last
+        assert_eq!(mapping.button(KeyCode::KEY_A), Some(Button::West));
+        assert_eq!(
+            mapping.axis(AbsoluteAxisCode::ABS_Z),
+            Some(Axis::LeftTrigger)
+        );
+        assert_eq!(
+            mapping.axis_buttons[&AbsoluteAxisCode::ABS_HAT0X],
+            (Some(Button::DPadLeft), Some(Button::DPadRight))
+        );
+    }
+}
@@ -0,0 +1,458 @@
+//! Recognition of simple touch gestures from multitouch contacts.
+//!
+//! A [`GestureRecognizer`] consumes the [`ContactUpdate`]s produced by an
+//! [`MTStateTracker`](crate::multitouch::MTStateTracker) and detects taps, double taps,
+//! two-finger scrolling, swipes and pinches. All distances are in device units, so the
+//! thresholds in [`GestureConfig`] usually need to be adjusted to the resolution of the device.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use evdev::gestures::{GestureConfig, GestureRecognizer};
+//! use evdev::multitouch::MTStateTracker;
+//! let mut device = evdev::Device::open("/dev/input/event0")?;
+//! let mut tracker = MTStateTracker::from_device(&device)?;
+//! let mut gestures = GestureRecognizer::new(GestureConfig::default());
+//! loop {
+//!     for ev in device.fetch_events()? {
+//!         let updates = tracker.process(&ev);
+//!         for gesture in gestures.update(&updates, ev.timestamp()) {
+//!             println!("{:?}", gesture);
+//!         }
+//!     }
+//! }
+//! # }
+//! ```
+
+use crate::multitouch::ContactUpdate;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+/// Thresholds used by a [`GestureRecognizer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GestureConfig {
+    /// The longest a finger may touch the device to count as a tap.
+    pub tap_max_duration: Duration,
+    /// The farthest a finger may move during a tap, and the farthest apart the two taps of a
+    /// double tap may be.
+    pub tap_max_distance: i32,
+    /// The longest time between two taps that still counts as a double tap.
+    pub double_tap_interval: Duration,
+    /// How far two fingers have to move together before scrolling starts.
+    pub scroll_min_distance: i32,
+    /// How much the distance between two fingers has to change before pinching starts.
+    pub pinch_min_distance: i32,
+    /// How far fingers have to move to count as a swipe.
+    pub swipe_min_distance: i32,
+    /// The longest a swipe may take, from the first finger down to the last finger up.
+    pub swipe_max_duration: Duration,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            tap_max_duration: Duration::from_millis(200),
+            tap_max_distance: 20,
+            double_tap_interval: Duration::from_millis(300),
+            scroll_min_distance: 30,
+            pinch_min_distance: 50,
+            swipe_min_distance: 200,
+            swipe_max_duration: Duration::from_millis(500),
+        }
+    }
+}
+
+/// The direction of a [`Gesture::Swipe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SwipeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// A gesture detected by a [`GestureRecognizer`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// A single finger briefly touched the device.
+    Tap { position: (i32, i32) },
+    /// A second tap shortly after and close to a previous one. The first tap is reported as a
+    /// [`Tap`](Gesture::Tap).
+    DoubleTap { position: (i32, i32) },
+    /// Two fingers moved together by `(dx, dy)` since the last scroll.
+    Scroll { dx: i32, dy: i32 },
+    /// One or more fingers quickly moved in one direction and were lifted.
+    Swipe {
+        fingers: usize,
+        direction: SwipeDirection,
+    },
+    /// Two fingers moved apart or together. `scale` is the distance between the fingers relative
+    /// to the distance when they were placed.
+    Pinch { scale: f64 },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Track {
+    start: (i32, i32),
+    last: (i32, i32),
+}
+
+impl Track {
+    fn delta(&self) -> (i32, i32) {
+        (self.last.0 - self.start.0, self.last.1 - self.start.1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Undecided,
+    Scroll,
+    Pinch,
+    /// The session can no longer produce a gesture, e.g. because too many fingers were placed.
+    Ignored,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TwoFinger {
+    start_distance: f64,
+    start_centroid: (i32, i32),
+    last_centroid: (i32, i32),
+}
+
+/// The time from the first finger touching the device until the last finger is lifted.
+#[derive(Debug, Clone)]
+struct Session {
+    start: SystemTime,
+    max_fingers: usize,
+    mode: Mode,
+    two_finger: Option<TwoFinger>,
+    ended: Vec<Track>,
+}
+
+/// Detects gestures from the output of an
+/// [`MTStateTracker`](crate::multitouch::MTStateTracker).
+#[derive(Debug, Clone)]
+pub struct GestureRecognizer {
+    config: GestureConfig,
+    active: HashMap<i32, Track>,
+    session: Option<Session>,
+    last_tap: Option<(SystemTime, (i32, i32))>,
+}
+
+impl GestureRecognizer {
+    /// Create a recognizer with the given thresholds.
+    pub fn new(config: GestureConfig) -> Self {
+        Self {
+            config,
+            active: HashMap::new(),
+            session: None,
+            last_tap: None,
+        }
+    }
+
+    /// Returns the thresholds used by this recognizer.
+    pub fn config(&self) -> &GestureConfig {
+        &self.config
+    }
+
+    /// Process the updates of a single frame, returning the gestures detected in it.
+    ///
+    /// `time` should be the timestamp of the `SYN_REPORT` that ended the frame.
+    pub fn update(&mut self, updates: &[ContactUpdate], time: SystemTime) -> Vec<Gesture> {
+        let mut gestures = Vec::new();
+        if updates.is_empty() {
+            return gestures;
+        }
+
+        let session = self.session.get_or_insert_with(|| Session {
+            start: time,
+            max_fingers: 0,
+            mode: Mode::Undecided,
+            two_finger: None,
+            ended: Vec::new(),
+        });
+        for update in updates {
+            let c = update.contact();
+            let position = (c.x, c.y);
+            match update {
+                ContactUpdate::Began(_) => {
+                    let track = Track {
+                        start: position,
+                        last: position,
+                    };
+                    self.active.insert(c.tracking_id, track);
+                }
+                ContactUpdate::Moved(_) => {
+                    if let Some(track) = self.active.get_mut(&c.tracking_id) {
+                        track.last = position;
+                    }
+                }
+                ContactUpdate::Ended(_) => {
+                    if let Some(mut track) = self.active.remove(&c.tracking_id) {
+                        track.last = position;
+                        session.ended.push(track);
+                    }
+                }
+            }
+        }
+        session.max_fingers = session.max_fingers.max(self.active.len());
+
+        if session.max_fingers > 2 && session.mode != Mode::Undecided {
+            session.mode = Mode::Ignored;
+        }
+        if self.active.len() == 2 && session.max_fingers == 2 {
+            self.two_finger(&mut gestures);
+        } else {
+            session.two_finger = None;
+        }
+
+        if self.active.is_empty() {
+            if let Some(session) = self.session.take() {
+                self.finish(session, time, &mut gestures);
+            }
+        }
+        gestures
+    }
+
+    fn two_finger(&mut self, gestures: &mut Vec<Gesture>) {
+        let session = match &mut self.session {
+            Some(session) => session,
+            None => return,
+        };
+        let mut tracks = self.active.values();
+        let (a, b) = match (tracks.next(), tracks.next()) {
+            (Some(a), Some(b)) => (a.last, b.last),
+            _ => return,
+        };
+        let centroid = ((a.0 + b.0) / 2, (a.1 + b.1) / 2);
+        let distance = length((a.0 - b.0, a.1 - b.1));
+
+        let state = match &mut session.two_finger {
+            Some(state) => state,
+            None => {
+                session.two_finger = Some(TwoFinger {
+                    start_distance: distance,
+                    start_centroid: centroid,
+                    last_centroid: centroid,
+                });
+                return;
+            }
+        };
+
+        if session.mode == Mode::Undecided {
+            let moved = (
+                centroid.0 - state.start_centroid.0,
+                centroid.1 - state.start_centroid.1,
+            );
+            if (distance - state.start_distance).abs() >= self.config.pinch_min_distance as f64 {
+                session.mode = Mode::Pinch;
+            } else if length(moved) >= self.config.scroll_min_distance as f64 {
+                session.mode = Mode::Scroll;
+            }
+        }
+
+        match session.mode {
+            Mode::Scroll => {
+                let dx = centroid.0 - state.last_centroid.0;
+                let dy = centroid.1 - state.last_centroid.1;
+                if dx != 0 || dy != 0 {
+                    gestures.push(Gesture::Scroll { dx, dy });
+                }
+                state.last_centroid = centroid;
+            }
+            Mode::Pinch if state.start_distance > 0.0 => {
+                gestures.push(Gesture::Pinch {
+                    scale: distance / state.start_distance,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(&mut self, session: Session, time: SystemTime, gestures: &mut Vec<Gesture>) {
+        if session.mode != Mode::Undecided || session.ended.is_empty() {
+            return;
+        }
+        let duration = time.duration_since(session.start).unwrap_or_default();
+
+        if session.max_fingers == 1 && session.ended.len() == 1 {
+            let track = session.ended[0];
+            if duration <= self.config.tap_max_duration
+                && length(track.delta()) <= self.config.tap_max_distance as f64
+            {
+                let position = track.start;
+                let double = self.last_tap.map_or(false, |(at, last)| {
+                    time.duration_since(at).unwrap_or_default() <= self.config.double_tap_interval
+                        && length((position.0 - last.0, position.1 - last.1))
+                            <= self.config.tap_max_distance as f64
+                });
+                if double {
+                    self.last_tap = None;
+                    gestures.push(Gesture::DoubleTap { position });
+                } else {
+                    self.last_tap = Some((time, position));
+                    gestures.push(Gesture::Tap { position });
+                }
+                return;
+            }
+        }
+
+        if duration <= self.config.swipe_max_duration {
+            let n = session.ended.len() as i32;
+            let (sx, sy) = session.ended.iter().fold((0, 0), |(x, y), track| {
+                let (dx, dy) = track.delta();
+                (x + dx, y + dy)
+            });
+            let (dx, dy) = (sx / n, sy / n);
+            if length((dx, dy)) >= self.config.swipe_min_distance as f64 {
+                let direction = if dx.abs() >= dy.abs() {
+                    if dx > 0 {
+                        SwipeDirection::Right
+                    } else {
+                        SwipeDirection::Left
+                    }
+                } else if dy > 0 {
+                    SwipeDirection::Down
+                } else {
+                    SwipeDirection::Up
+                };
+                gestures.push(Gesture::Swipe {
+                    fingers: session.max_fingers,
+                    direction,
+                });
+            }
+        }
+    }
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        Self::new(GestureConfig::default())
+    }
+}
+
+fn length((x, y): (i32, i32)) -> f64 {
+    (x as f64).hypot(y as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multitouch::Contact;
+    use crate::time_at as at;
+
+    fn contact(id: i32, x: i32, y: i32) -> Contact {
+        Contact {
+            slot: id as usize,
+            tracking_id: id,
+            x,
+            y,
+            pressure: None,
+            touch_major: None,
+            touch_minor: None,
+        }
+    }
+
+    fn tap(g: &mut GestureRecognizer, start: u64, x: i32, y: i32) -> Vec<Gesture> {
+        g.update(&[ContactUpdate::Began(contact(0, x, y))], at(start));
+        g.update(&[ContactUpdate::Ended(contact(0, x, y))], at(start + 50))
+    }
+
+    #[test]
+    fn tap_and_double_tap() {
+        let mut g = GestureRecognizer::default();
+        assert_eq!(
+            tap(&mut g, 0, 100, 100),
+            [Gesture::Tap {
+                position: (100, 100)
+            }]
+        );
+        assert_eq!(
+            tap(&mut g, 200, 105, 100),
+            [Gesture::DoubleTap {
+                position: (105, 100)
+            }]
+        );
+        // too late for another double tap
+        assert_eq!(
+            tap(&mut g, 1000, 100, 100),
+            [Gesture::Tap {
+                position: (100, 100)
+            }]
+        );
+    }
+
+    #[test]
+    fn two_finger_scroll() {
+        let mut g = GestureRecognizer::default();
+        g.update(
+            &[
+                ContactUpdate::Began(contact(0, 0, 100)),
+                ContactUpdate::Began(contact(1, 100, 100)),
+            ],
+            at(0),
+        );
+        let out = g.update(
+            &[
+                ContactUpdate::Moved(contact(0, 0, 140)),
+                ContactUpdate::Moved(contact(1, 100, 140)),
+            ],
+            at(10),
+        );
+        assert_eq!(out, [Gesture::Scroll { dx: 0, dy: 40 }]);
+        let out = g.update(
+            &[
+                ContactUpdate::Moved(contact(0, 0, 150)),
+                ContactUpdate::Moved(contact(1, 100, 150)),
+            ],
+            at(20),
+        );
+        assert_eq!(out, [Gesture::Scroll { dx: 0, dy: 10 }]);
+        let out = g.update(
+            &[
+                ContactUpdate::Ended(contact(0, 0, 150)),
+                ContactUpdate::Ended(contact(1, 100, 150)),
+            ],
+            at(30),
+        );
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn pinch() {
+        let mut g = GestureRecognizer::default();
+        g.update(
+            &[
+                ContactUpdate::Began(contact(0, 100, 0)),
+                ContactUpdate::Began(contact(1, 200, 0)),
+            ],
+            at(0),
+        );
+        let out = g.update(
+            &[
+                ContactUpdate::Moved(contact(0, 50, 0)),
+                ContactUpdate::Moved(contact(1, 250, 0)),
+            ],
+            at(10),
+        );
+        assert_eq!(out, [Gesture::Pinch { scale: 2.0 }]);
+    }
+
+    #[test]
+    fn three_finger_swipe() {
+        let mut g = GestureRecognizer::default();
+        let began: Vec<_> = (0..3)
+            .map(|i| ContactUpdate::Began(contact(i, i * 50, 500)))
+            .collect();
+        g.update(&began, at(0));
+        let ended: Vec<_> = (0..3)
+            .map(|i| ContactUpdate::Ended(contact(i, i * 50, 100)))
+            .collect();
+        assert_eq!(
+            g.update(&ended, at(200)),
+            [Gesture::Swipe {
+                fingers: 3,
+                direction: SwipeDirection::Up
+            }]
+        );
+    }
+}
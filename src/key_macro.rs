@@ -0,0 +1,179 @@
+use crate::uinput::VirtualDevice;
+use crate::{Device, EventType, InputEvent};
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// A timed sequence of key events that can be replayed through a [`VirtualDevice`].
+///
+/// Each event is stored along with its offset from the first event, so replaying it reproduces
+/// the original timing. Key repeats are not stored; a virtual device with autorepeat enabled
+/// generates them itself.
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use evdev::{KeyCode, Macro};
+/// let mut device = evdev::Device::open("/dev/input/event0")?;
+/// // record until F12 is pressed
+/// let recorded = Macro::record(&mut device, |ev| ev.code() == KeyCode::KEY_F12.0)?;
+///
+/// let mut keys = evdev::AttributeSet::<KeyCode>::new();
+/// for (_, ev) in recorded.events() {
+///     keys.insert(KeyCode::new(ev.code()));
+/// }
+/// let mut virt = evdev::uinput::VirtualDevice::builder()?
+///     .name("macro player")
+///     .with_keys(&keys)?
+///     .build()?;
+/// recorded.play_scaled(&mut virt, 2.0)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Macro {
+    events: Vec<(Duration, InputEvent)>,
+    start: Option<SystemTime>,
+}
+
+impl Macro {
+    /// Create an empty macro.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record key presses and releases from a device until `stop` returns `true` for one of
+    /// them. The event `stop` returned `true` for is not recorded.
+    pub fn record(
+        device: &mut Device,
+        mut stop: impl FnMut(&InputEvent) -> bool,
+    ) -> io::Result<Self> {
+        let mut recorded = Self::new();
+        loop {
+            for ev in device.fetch_events()? {
+                if ev.event_type() != EventType::KEY || ev.value() == 2 {
+                    continue;
+                }
+                if stop(&ev) {
+                    return Ok(recorded);
+                }
+                recorded.push(ev);
+            }
+        }
+    }
+
+    /// Append an event, using its timestamp to determine its offset from the first event.
+    ///
+    /// Events that aren't key presses or releases are ignored. Events with a timestamp before
+    /// the previous event are placed at the same offset as the previous event.
+    pub fn push(&mut self, event: InputEvent) {
+        if event.event_type() != EventType::KEY || event.value() == 2 {
+            return;
+        }
+        let start = *self.start.get_or_insert_with(|| event.timestamp());
+        let offset = event.timestamp().duration_since(start).unwrap_or_default();
+        let offset = offset.max(self.duration());
+        self.events.push((offset, event));
+    }
+
+    /// Append an event at the given offset from the first event.
+    ///
+    /// Offsets before that of the previous event are raised to it.
+    pub fn push_at(&mut self, offset: Duration, event: InputEvent) {
+        let offset = offset.max(self.duration());
+        self.events.push((offset, event));
+    }
+
+    /// Returns the recorded events with their offsets from the first event.
+    pub fn events(&self) -> impl Iterator<Item = (Duration, InputEvent)> + '_ {
+        self.events.iter().copied()
+    }
+
+    /// Returns the offset of the last event.
+    pub fn duration(&self) -> Duration {
+        self.events
+            .last()
+            .map_or(Duration::ZERO, |&(offset, _)| offset)
+    }
+
+    /// Returns the number of events in the macro.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns `true` if the macro contains no events.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Replay the macro with its original timing, blocking until it is done.
+    pub fn play(&self, device: &mut VirtualDevice) -> io::Result<()> {
+        self.play_scaled(device, 1.0)
+    }
+
+    /// Replay the macro `speed` times as fast as it was recorded, blocking until it is done.
+    ///
+    /// A speed that is not positive and finite replays all events without delay.
+    pub fn play_scaled(&self, device: &mut VirtualDevice, speed: f64) -> io::Result<()> {
+        let start = Instant::now();
+        let timed = speed.is_finite() && speed > 0.0;
+        for &(offset, ev) in &self.events {
+            if timed {
+                let at = start + offset.div_f64(speed);
+                let now = Instant::now();
+                if at > now {
+                    thread::sleep(at - now);
+                }
+            }
+            device.emit(&[InputEvent::new(ev.event_type(), ev.code(), ev.value())])?;
+        }
+        Ok(())
+    }
+}
+
+impl Extend<InputEvent> for Macro {
+    fn extend<T: IntoIterator<Item = InputEvent>>(&mut self, iter: T) {
+        for ev in iter {
+            self.push(ev);
+        }
+    }
+}
+
+impl FromIterator<InputEvent> for Macro {
+    fn from_iter<T: IntoIterator<Item = InputEvent>>(iter: T) -> Self {
+        let mut recorded = Self::new();
+        recorded.extend(iter);
+        recorded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KeyCode, KeyEvent};
+
+    fn key_at(code: KeyCode, value: i32, ms: u64) -> InputEvent {
+        crate::event_at(ms, *KeyEvent::new(code, value))
+    }
+
+    #[test]
+    fn offsets_from_timestamps() {
+        let recorded: Macro = [
+            key_at(KeyCode::KEY_A, 1, 1000),
+            key_at(KeyCode::KEY_A, 2, 1100),
+            key_at(KeyCode::KEY_A, 0, 1150),
+            key_at(KeyCode::KEY_B, 1, 1050),
+        ]
+        .into_iter()
+        .collect();
+        let offsets: Vec<_> = recorded.events().map(|(offset, _)| offset).collect();
+        assert_eq!(
+            offsets,
+            [
+                Duration::ZERO,
+                Duration::from_millis(150),
+                Duration::from_millis(150)
+            ]
+        );
+        assert_eq!(recorded.duration(), Duration::from_millis(150));
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{Device, KeyCode};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::{fs, io};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The scancode to keycode table of a device, which can be saved to a file and applied again
+/// later.
+///
+/// Keyboards translate the scancodes of their keys to key codes through a table in the kernel,
+/// which can be changed with [`Device::update_scancode`]. Changes are lost when the device is
+/// unplugged or the machine reboots. A keymap captures the full table, or just the entries to
+/// change, so it can be applied again at startup without writing udev hwdb entries.
+///
+/// Keymaps are saved as TOML, with scancodes as hexadecimal keys and key codes by name:
+///
+/// ```toml
+/// [keymap]
+/// 0x0007003a = "KEY_ESC"
+/// 0x00070039 = "KEY_LEFTCTRL"
+/// ```
+///
+/// The number of hex digits gives the length of the scancode, and its bytes are written as a
+/// little-endian number, so that scancodes read like the values of `MSC_SCAN` events on most
+/// machines. With the `serde` feature, keymaps can be serialized to other formats such as JSON
+/// as well.
+///
+/// ```no_run
+/// use evdev::{Device, Keymap};
+///
+/// let device = Device::open("/dev/input/event0")?;
+/// Keymap::from_device(&device)?.save("keyboard.toml")?;
+/// // ... after editing the file, e.g. at startup:
+/// Keymap::load("keyboard.toml")?.apply(&device)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Keymap {
+    pub entries: Vec<KeymapEntry>,
+}
+
+/// An entry of a [`Keymap`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeymapEntry {
+    /// The raw scancode, as used by [`Device::update_scancode`].
+    pub scancode: Vec<u8>,
+    pub keycode: KeyCode,
+}
+
+impl Keymap {
+    /// Read the full table of a device.
+    ///
+    /// Devices without a table, such as most mice, result in an empty keymap.
+    pub fn from_device(device: &Device) -> io::Result<Self> {
+        let mut entries = Vec::new();
+        for index in 0..=u16::MAX {
+            match device.get_scancode_by_index(index) {
+                Ok((keycode, scancode)) => entries.push(KeymapEntry {
+                    scancode,
+                    keycode: KeyCode::new(keycode as u16),
+                }),
+                Err(e) if e.raw_os_error() == Some(libc::EINVAL) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Self { entries })
+    }
+
+    /// Update the table of the device with the entries of this keymap.
+    ///
+    /// Scancodes the keymap has no entry for keep their key code.
+    pub fn apply(&self, device: &Device) -> io::Result<()> {
+        for entry in &self.entries {
+            device.update_scancode(entry.keycode, &entry.scancode)?;
+        }
+        Ok(())
+    }
+
+    /// Format the keymap as TOML.
+    pub fn to_toml(&self) -> String {
+        let mut out = String::from("[keymap]\n");
+        for entry in &self.entries {
+            out.push_str("0x");
+            for byte in entry.scancode.iter().rev() {
+                let _ = write!(out, "{byte:02x}");
+            }
+            let _ = writeln!(out, " = \"{}\"", entry.keycode);
+        }
+        out
+    }
+
+    /// Parse a keymap in the format written by [`to_toml`](Self::to_toml).
+    ///
+    /// Only the subset of TOML used by that format is supported: comments, the `[keymap]` table
+    /// header, and entries with a bare or quoted hexadecimal key and a quoted key code.
+    /// Key codes can also be given by number. Fails with
+    /// [`InvalidData`](io::ErrorKind::InvalidData) on anything else.
+    pub fn from_toml(text: &str) -> io::Result<Self> {
+        let mut entries = Vec::new();
+        for (lineno, line) in text.lines().enumerate() {
+            let invalid = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid keymap entry on line {}", lineno + 1),
+                )
+            };
+            let line = match line.find('#') {
+                Some(i) => &line[..i],
+                None => line,
+            }
+            .trim();
+            if line.is_empty() || line == "[keymap]" {
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(invalid)?;
+            let key = unquote(key.trim()).unwrap_or(key.trim());
+            let value = value.trim();
+            let keycode = unquote(value).unwrap_or(value);
+            entries.push(KeymapEntry {
+                scancode: parse_scancode(key).ok_or_else(invalid)?,
+                keycode: keycode.parse().map_err(|_| invalid())?,
+            });
+        }
+        Ok(Self { entries })
+    }
+
+    /// Read and parse a keymap from a TOML file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Save the keymap to a TOML file.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_toml())
+    }
+}
+
+fn unquote(s: &str) -> Option<&str> {
+    s.strip_prefix('"')?.strip_suffix('"')
+}
+
+/// Parses a scancode written as a hexadecimal number, most significant byte first.
+fn parse_scancode(s: &str) -> Option<Vec<u8>> {
+    let digits = s.strip_prefix("0x")?;
+    if digits.is_empty() || digits.len() % 2 != 0 || digits.len() > 64 {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .rev()
+        .map(|i| u8::from_str_radix(digits.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_round_trip() {
+        let keymap = Keymap {
+            entries: vec![
+                KeymapEntry {
+                    scancode: 0x7003a_u32.to_le_bytes().to_vec(),
+                    keycode: KeyCode::KEY_ESC,
+                },
+                KeymapEntry {
+                    scancode: vec![0x1e],
+                    keycode: KeyCode::KEY_A,
+                },
+            ],
+        };
+        let text = keymap.to_toml();
+        assert_eq!(
+            text,
+            "[keymap]\n0x0007003a = \"KEY_ESC\"\n0x1e = \"KEY_A\"\n"
+        );
+        assert_eq!(Keymap::from_toml(&text).unwrap(), keymap);
+
+        let parsed = Keymap::from_toml("# mine\n\"0x1e\" = 30 # a\n").unwrap();
+        assert_eq!(parsed.entries[0].keycode, KeyCode::KEY_A);
+        assert!(Keymap::from_toml("0x1 = \"KEY_A\"").is_err());
+        assert!(Keymap::from_toml("0x1e = \"KEY_NOPE\"").is_err());
+    }
+}
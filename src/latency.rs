@@ -0,0 +1,159 @@
+use crate::{EventClock, EventSummary, EventTimestamp, InputEvent, SynchronizationCode};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Statistics over the latencies of recent frames, as reported by [`LatencyMetrics::stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyStats {
+    /// The number of frames the statistics are based on.
+    pub samples: usize,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    /// The 95th percentile: 95% of the frames were read at most this late.
+    pub p95: Duration,
+    /// The standard deviation, i.e. how much the latency varies from frame to frame.
+    pub jitter: Duration,
+}
+
+/// Measures how long frames take from the kernel timestamping them to the application reading
+/// them.
+///
+/// This helps to tell apart whether input feels laggy because of the device, e.g. because of a
+/// low polling rate, or because of the application, e.g. because it reads events too rarely. The
+/// latency of each frame is the time between the timestamp of its `SYN_REPORT` and the time it
+/// was processed, and statistics are kept over a rolling window of recent frames.
+///
+/// The timestamps of the events must come from the clock the collector was created with, see
+/// [`Device::set_event_clock`]. Using the monotonic clock avoids bogus results when the system
+/// time changes.
+///
+/// ```no_run
+/// use evdev::{Device, EventClock, LatencyMetrics};
+///
+/// let mut device = Device::open("/dev/input/event0")?;
+/// device.set_event_clock(EventClock::Monotonic)?;
+/// let mut metrics = LatencyMetrics::new(EventClock::Monotonic, 1000);
+/// loop {
+///     for ev in device.fetch_events()? {
+///         metrics.process(&ev);
+///     }
+///     if let Some(stats) = metrics.stats() {
+///         println!("mean {:?}, p95 {:?}, jitter {:?}", stats.mean, stats.p95, stats.jitter);
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// [`Device::set_event_clock`]: crate::Device::set_event_clock
+#[derive(Debug, Clone)]
+pub struct LatencyMetrics {
+    clock: EventClock,
+    window: usize,
+    latencies: VecDeque<Duration>,
+}
+
+impl LatencyMetrics {
+    /// Create a collector for events timestamped with `clock`, keeping statistics over the last
+    /// `window` frames.
+    pub fn new(clock: EventClock, window: usize) -> Self {
+        Self {
+            clock,
+            window: window.max(1),
+            latencies: VecDeque::new(),
+        }
+    }
+
+    /// Update the collector with an event that was just read.
+    ///
+    /// Returns the latency of the frame if `event` is a `SYN_REPORT`.
+    pub fn process(&mut self, event: &InputEvent) -> Option<Duration> {
+        self.record(event, EventTimestamp::now(self.clock))
+    }
+
+    /// Update the collector with an event that was read at `read_at`, e.g. when events are
+    /// processed in batches some time after reading them.
+    ///
+    /// Returns the latency of the frame if `event` is a `SYN_REPORT`.
+    pub fn record(&mut self, event: &InputEvent, read_at: EventTimestamp) -> Option<Duration> {
+        match event.destructure() {
+            EventSummary::Synchronization(_, SynchronizationCode::SYN_REPORT, _) => {
+                let latency = read_at.duration_since(EventTimestamp::of(event, self.clock));
+                if self.latencies.len() == self.window {
+                    self.latencies.pop_front();
+                }
+                self.latencies.push_back(latency);
+                Some(latency)
+            }
+            _ => None,
+        }
+    }
+
+    /// Forget the frames recorded so far.
+    pub fn reset(&mut self) {
+        self.latencies.clear();
+    }
+
+    /// Returns statistics over the recent frames, or `None` if none were recorded yet.
+    pub fn stats(&self) -> Option<LatencyStats> {
+        LatencyStats::from_samples(self.latencies.iter().copied())
+    }
+}
+
+impl LatencyStats {
+    /// Compute statistics over latencies, returning `None` if there are none.
+    pub(crate) fn from_samples(latencies: impl IntoIterator<Item = Duration>) -> Option<Self> {
+        let mut sorted: Vec<_> = latencies.into_iter().collect();
+        let samples = sorted.len();
+        if samples == 0 {
+            return None;
+        }
+        sorted.sort();
+        let secs: Vec<f64> = sorted.iter().map(Duration::as_secs_f64).collect();
+        let mean = secs.iter().sum::<f64>() / samples as f64;
+        let variance = secs.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / samples as f64;
+        // the smallest latency that at least 95% of the frames don't exceed
+        let p95 = (samples * 95 + 99) / 100;
+        Some(LatencyStats {
+            samples,
+            min: sorted[0],
+            max: sorted[samples - 1],
+            mean: Duration::from_secs_f64(mean),
+            p95: sorted[p95.max(1) - 1],
+            jitter: Duration::from_secs_f64(variance.sqrt()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventType, SynchronizationEvent};
+
+    #[test]
+    fn rolling_stats() {
+        let mut metrics = LatencyMetrics::new(EventClock::Monotonic, 4);
+        assert_eq!(metrics.stats(), None);
+        let report = *SynchronizationEvent::new(SynchronizationCode::SYN_REPORT, 0);
+        let read_at = |ms| EventTimestamp::new(EventClock::Monotonic, Duration::from_millis(ms));
+        for ms in [100, 2, 4, 6, 8] {
+            assert_eq!(
+                metrics.record(&report, read_at(ms)),
+                Some(Duration::from_millis(ms))
+            );
+        }
+        let key = InputEvent::new(EventType::KEY, 30, 1);
+        assert_eq!(metrics.record(&key, read_at(50)), None);
+
+        let stats = metrics.stats().unwrap();
+        assert_eq!(stats.samples, 4);
+        assert_eq!(
+            (stats.min, stats.max),
+            (Duration::from_millis(2), Duration::from_millis(8))
+        );
+        assert_eq!(stats.p95, Duration::from_millis(8));
+        assert!((stats.mean.as_secs_f64() - 0.005).abs() < 1e-9);
+        // the latencies are 3 and 1 ms away from the mean
+        assert!((stats.jitter.as_secs_f64() - 5f64.sqrt() / 1000.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{
+    AttributeSet, AttributeSetRef, Device, DeviceState, EventSummary, InputEvent, KeyCode, LedCode,
+    LedEvent, SynchronizationCode, SynchronizationEvent,
+};
+use std::io;
+
+/// The lock keys toggled by [`LedSync`] and the LEDs they belong to.
+const LOCKS: [(KeyCode, LedCode); 3] = [
+    (KeyCode::KEY_CAPSLOCK, LedCode::LED_CAPSL),
+    (KeyCode::KEY_NUMLOCK, LedCode::LED_NUML),
+    (KeyCode::KEY_SCROLLLOCK, LedCode::LED_SCROLLL),
+];
+
+/// Keeps the lock LEDs of a keyboard in sync with its lock keys, or with the LEDs of another
+/// device.
+///
+/// Once a keyboard is grabbed, the LEDs of its caps lock, num lock and scroll lock keys are no
+/// longer updated, since the display server that normally sets them no longer sees the key
+/// presses. `LedSync` toggles its record of the LEDs whenever a lock key is pressed and produces
+/// the events needed to update the keyboard.
+///
+/// `LED_*` events, e.g. from a virtual device that is read by the display server, set the
+/// corresponding LEDs directly. A tracker created with [`mirror`](Self::mirror) ignores the lock
+/// keys and only mirrors these.
+///
+/// ```no_run
+/// use evdev::{Device, LedSync};
+///
+/// let mut keyboard = Device::open("/dev/input/event0")?;
+/// let mut leds = LedSync::from_state(keyboard.cached_state());
+/// keyboard.grab()?;
+/// leds.apply(&mut keyboard)?;
+/// loop {
+///     let events: Vec<_> = keyboard.fetch_events()?.collect();
+///     for ev in events {
+///         leds.forward(&ev, &mut keyboard)?;
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LedSync {
+    leds: AttributeSet<LedCode>,
+    mirror: bool,
+}
+
+impl LedSync {
+    /// Create a tracker with all LEDs off that follows the lock keys.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a tracker that ignores the lock keys and only follows `LED_*` events, for mirroring
+    /// the LEDs of one device on another.
+    pub fn mirror() -> Self {
+        Self {
+            mirror: true,
+            ..Self::default()
+        }
+    }
+
+    /// Create a tracker that follows the lock keys, starting from the LEDs of a device state, e.g.
+    /// [`Device::cached_state`].
+    pub fn from_state(state: &DeviceState) -> Self {
+        let mut sync = Self::new();
+        if let Some(leds) = state.led_vals() {
+            for led in leds.iter() {
+                sync.leds.insert(led);
+            }
+        }
+        sync
+    }
+
+    /// Returns the LEDs that are currently on.
+    pub fn leds(&self) -> &AttributeSetRef<LedCode> {
+        &self.leds
+    }
+
+    /// Update the tracked LEDs with an event.
+    ///
+    /// Returns the events that bring a device up to date, ending with a `SYN_REPORT`, if `event`
+    /// changed one of the LEDs.
+    pub fn process(&mut self, event: &InputEvent) -> Option<[InputEvent; 2]> {
+        let (led, on) = match event.destructure() {
+            EventSummary::Key(_, key, 1) if !self.mirror => {
+                let (_, led) = LOCKS.iter().find(|(k, _)| *k == key)?;
+                (*led, !self.leds.contains(*led))
+            }
+            EventSummary::Led(_, led, value) => {
+                let on = value != 0;
+                if self.leds.contains(led) == on {
+                    return None;
+                }
+                (led, on)
+            }
+            _ => return None,
+        };
+        if on {
+            self.leds.insert(led);
+        } else {
+            self.leds.remove(led);
+        }
+        Some([
+            *LedEvent::with_state(led, on),
+            *SynchronizationEvent::new(SynchronizationCode::SYN_REPORT, 0),
+        ])
+    }
+
+    /// Update the tracked LEDs with an event, and send the change to `device` if there is one.
+    pub fn forward(&mut self, event: &InputEvent, device: &mut Device) -> io::Result<()> {
+        match self.process(event) {
+            Some(events) => device.send_events(&events),
+            None => Ok(()),
+        }
+    }
+
+    /// Send the state of every lock LED to `device`, e.g. after grabbing it.
+    pub fn apply(&self, device: &mut Device) -> io::Result<()> {
+        let mut events: Vec<InputEvent> = LOCKS
+            .iter()
+            .map(|&(_, led)| *LedEvent::with_state(led, self.leds.contains(led)))
+            .collect();
+        events.push(*SynchronizationEvent::new(
+            SynchronizationCode::SYN_REPORT,
+            0,
+        ));
+        device.send_events(&events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyEvent;
+
+    #[test]
+    fn toggle_and_mirror() {
+        let mut sync = LedSync::new();
+        let press = *KeyEvent::new(KeyCode::KEY_CAPSLOCK, 1);
+        let release = *KeyEvent::new(KeyCode::KEY_CAPSLOCK, 0);
+        let out = sync.process(&press).unwrap();
+        assert_eq!((out[0].code(), out[0].value()), (LedCode::LED_CAPSL.0, 1));
+        assert!(sync.process(&release).is_none());
+        assert_eq!(sync.process(&press).unwrap()[0].value(), 0);
+        assert!(sync.process(&KeyEvent::new(KeyCode::KEY_A, 1)).is_none());
+
+        let mut sync = LedSync::mirror();
+        assert!(sync.process(&press).is_none());
+        let led = *LedEvent::with_state(LedCode::LED_NUML, true);
+        assert!(sync.process(&led).is_some());
+        assert!(sync.process(&led).is_none());
+        assert!(sync.leds().contains(LedCode::LED_NUML));
+    }
+}
@@ -149,6 +149,19 @@
 //!
 //! For demonstrations of how to use this library in blocking, nonblocking, and async (tokio) modes,
 //! please reference the "examples" directory.
+//!
+//! # Threading
+//!
+//! Every device type, i.e. [`Device`], [`RawDevice`](raw_stream::RawDevice),
+//! [`VirtualDevice`](uinput::VirtualDevice) and the event streams of the `tokio` feature, is
+//! `Send` and `Sync`, as are [`FFEffect`] and [`DeviceControl`]. This is checked at compile time.
+//!
+//! A device can be moved to a dedicated thread for reading its events. Reading requires `&mut`
+//! access, as the read buffer and the [cached state](Device::cached_state) belong to the reader,
+//! so a shared device only allows the queries that take `&self`, which go to the kernel directly.
+//! To send events to a device or upload force feedback effects while another thread reads from
+//! it, use a [`DeviceControl`] from [`Device::control`] rather than wrapping the device in a
+//! mutex. Uploaded effects can be played and dropped from any thread.
 
 // should really be cfg(target_os = "linux") and maybe also android?
 #![cfg(unix)]
@@ -165,35 +178,112 @@
 #[macro_use]
 mod attribute_set;
 
+mod abs_quirks;
+mod access;
+mod aggregate;
+mod capabilities;
+pub mod capture;
+mod chord;
 mod compat;
 mod constants;
+mod control;
+mod cursor;
+mod device_group;
 mod device_state;
+pub mod evemu;
+mod event_source;
 pub mod event_variants;
 mod ff;
+mod ff_manager;
+mod filtered_grab;
+pub mod fmt;
+mod frames;
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+mod fuzz;
+pub mod gamepad;
+#[cfg(feature = "gestures")]
+pub mod gestures;
 mod inputid;
+mod key_macro;
+mod keymap;
+mod latency;
+mod led_sync;
+pub mod libinput_record;
+#[cfg(feature = "mock")]
+pub mod mock;
+mod modifiers;
+pub mod multitouch;
+mod normalize;
+mod pacing;
+#[cfg(feature = "portal")]
+pub mod portal;
+pub mod raw;
 pub mod raw_stream;
+#[cfg(feature = "remote")]
+pub mod remote;
+mod scan_pairing;
 mod scancodes;
+mod scroll;
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(feature = "proptest")]
+pub mod strategy;
 mod sync_stream;
 mod sys;
+pub mod sysfs;
+pub mod tablet;
+mod tagged_stream;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 #[cfg(test)]
 mod tests;
+mod timestamp;
+pub mod transform;
 pub mod uinput;
+#[cfg(feature = "xkb")]
+pub mod xkb;
 
-use crate::compat::{input_absinfo, input_event, uinput_abs_setup};
-use std::fmt::{self, Display};
+use crate::compat::{input_absinfo, input_event, timeval, uinput_abs_setup};
+use std::fmt::Display;
 use std::io;
 use std::os::fd::{AsFd, AsRawFd, OwnedFd};
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
 
+pub use abs_quirks::{AbsFix, AbsQuirk, AbsQuirks};
+pub use access::{
+    device_holders, enumerate_with_diagnostics, AccessProblem, DeviceAccess, DeviceHolder,
+};
+pub use aggregate::AggregateDevice;
 pub use attribute_set::{AttributeSet, AttributeSetRef, EvdevEnum};
+pub use capabilities::{CapabilitiesDiff, CapabilitiesReport, CodeChanges};
+pub use chord::{Chord, ChordDetector, ChordEvent};
 pub use constants::*;
+pub use control::DeviceControl;
+pub use cursor::{AbsoluteCursor, EdgeBehavior};
+pub use device_group::{enumerate_grouped, DeviceGroup};
 pub use device_state::DeviceState;
+pub use event_source::EventSource;
 pub use event_variants::*;
 pub use ff::*;
+pub use ff_manager::FFManager;
+pub use filtered_grab::FilteredGrab;
+pub use frames::{Frames, HardwareClock, TimestampedFrame, TimestampedFrames};
 pub use inputid::*;
+pub use key_macro::Macro;
+pub use keymap::{Keymap, KeymapEntry};
+pub use latency::{LatencyMetrics, LatencyStats};
+pub use led_sync::LedSync;
+pub use modifiers::{ModifierTracker, Modifiers};
+pub use normalize::AxisNormalizer;
+pub use pacing::EventPacer;
+pub use scan_pairing::{ScancodePairing, ScannedKey};
 pub use scancodes::*;
+pub use scroll::{ScrollAggregator, ScrollDelta, HI_RES_UNITS_PER_DETENT};
 pub use sync_stream::*;
+pub use tagged_stream::{DeviceHandle, TaggedEventStream};
+pub use timestamp::{EventClock, EventTimestamp};
 
 macro_rules! common_trait_impls {
     ($raw:ty, $wrapper:ty) => {
@@ -322,6 +412,37 @@ impl AbsInfo {
             resolution,
         })
     }
+
+    /// Converts a value of an axis measuring a position or size, such as `ABS_X` or
+    /// `ABS_MT_TOUCH_MAJOR`, into millimeters from the minimum of the axis.
+    ///
+    /// Returns `None` if the device doesn't report a resolution for the axis. See
+    /// [`AbsoluteAxisCode::unit`] for which axes this applies to.
+    pub fn to_mm(&self, value: i32) -> Option<f64> {
+        if self.0.resolution <= 0 {
+            return None;
+        }
+        Some((f64::from(value) - f64::from(self.0.minimum)) / f64::from(self.0.resolution))
+    }
+
+    /// Converts a value of a rotational axis, such as `ABS_RX` or `ABS_TILT_X`, into radians.
+    ///
+    /// Unlike [`to_mm`](Self::to_mm), the value isn't taken relative to the minimum, as 0 is the
+    /// neutral position of these axes, such as a pen held upright.
+    ///
+    /// Returns `None` if the device doesn't report a resolution for the axis.
+    pub fn to_radians(&self, value: i32) -> Option<f64> {
+        if self.0.resolution <= 0 {
+            return None;
+        }
+        Some(f64::from(value) / f64::from(self.0.resolution))
+    }
+
+    /// Returns the length of the range of the axis in millimeters, such as the width of a
+    /// touchpad for `ABS_X`, or `None` if the device doesn't report a resolution for the axis.
+    pub fn size_mm(&self) -> Option<f64> {
+        self.to_mm(self.0.maximum)
+    }
 }
 
 common_trait_impls!(input_absinfo, AbsInfo);
@@ -402,7 +523,7 @@ impl InputEvent {
     /// # Example
     /// ```
     /// use evdev::*;
-    /// let event =  InputEvent::new(1, KeyCode::KEY_A.0, 1);
+    /// let event =  InputEvent::new(EventType::KEY, KeyCode::KEY_A.0, 1);
     /// match event.destructure() {
     ///     EventSummary::Key(KeyEvent, KeyCode::KEY_A, 1) => (),
     ///     _=> panic!(),
@@ -413,13 +534,17 @@ impl InputEvent {
     }
 
     /// Create a new InputEvent. Only really useful for emitting events on virtual devices.
-    pub fn new(type_: u16, code: u16, value: i32) -> Self {
+    ///
+    /// This can create events of any type and code, including ones without a dedicated event
+    /// variant such as vendor-specific types. The event variants like [`KeyEvent`] provide more
+    /// convenient constructors for the known types.
+    pub fn new(type_: EventType, code: u16, value: i32) -> Self {
         let raw = input_event {
-            time: libc::timeval {
+            time: timeval {
                 tv_sec: 0,
                 tv_usec: 0,
             },
-            type_,
+            type_: type_.0,
             code,
             value,
         };
@@ -432,10 +557,10 @@ impl InputEvent {
     /// even though [`InputEvent::new`] creates an `input_event` with the time field as zero,
     /// the kernel will update `input_event.time` when it emits the events to any programs reading
     /// the event "file".
-    pub fn new_now(type_: u16, code: u16, value: i32) -> Self {
+    pub fn new_now(type_: EventType, code: u16, value: i32) -> Self {
         let raw = input_event {
             time: systime_to_timeval(&SystemTime::now()),
-            type_,
+            type_: type_.0,
             code,
             value,
         };
@@ -443,10 +568,57 @@ impl InputEvent {
     }
 }
 
-impl fmt::Debug for InputEvent {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+/// Events are ordered by their timestamp, then by type, code and value.
+///
+/// Sorting events from a single device with this order can move the events of a frame around,
+/// such as moving the `SYN_REPORT` ending a frame to its start. To interleave the events of
+/// several devices chronologically while keeping the order of events with the same timestamp,
+/// use [`sort_events`].
+impl Ord for InputEvent {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let key = |ev: &Self| {
+            let raw = ev.as_ref();
+            (
+                raw.time.tv_sec,
+                raw.time.tv_usec,
+                raw.type_,
+                raw.code,
+                raw.value,
+            )
+        };
+        key(self).cmp(&key(other))
+    }
+}
+
+impl PartialOrd for InputEvent {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Sort events chronologically by their timestamp, keeping events with the same timestamp in
+/// their original order.
+///
+/// This is useful for interleaving the events of several devices while keeping their frames
+/// intact.
+///
+/// ```
+/// use evdev::{sort_events, InputEvent};
+///
+/// let mut events: Vec<InputEvent> = vec![/* events collected from several devices */];
+/// sort_events(&mut events);
+/// ```
+pub fn sort_events(events: &mut [InputEvent]) {
+    events.sort_by_key(|ev| {
+        let time = ev.as_ref().time;
+        (time.tv_sec, time.tv_usec)
+    });
+}
+
+impl std::fmt::Debug for InputEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let summary = self.destructure();
-        let code: &dyn fmt::Debug = match &summary {
+        let code: &dyn std::fmt::Debug = match &summary {
             EventSummary::Synchronization(_, code, _) => code,
             EventSummary::Key(_, code, _) => code,
             EventSummary::RelativeAxis(_, code, _) => code,
@@ -481,6 +653,80 @@ pub fn enumerate() -> EnumerateDevices {
     }
 }
 
+/// Waits until at least one of `devices` has events to read, and returns the indices of the
+/// readable devices.
+///
+/// If `timeout` is `Some`, waits at most that long and returns an empty list if no device became
+/// readable. This is a simple alternative to [`TaggedEventStream`] or an async runtime for
+/// synchronous programs reading from a handful of devices.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use std::time::Duration;
+///
+/// let mut devices = vec![
+///     evdev::Device::open("/dev/input/event0")?,
+///     evdev::Device::open("/dev/input/event1")?,
+/// ];
+/// loop {
+///     for i in evdev::wait_any(&devices, Some(Duration::from_secs(1)))? {
+///         for event in devices[i].fetch_events()? {
+///             println!("device {i}: {event:?}");
+///         }
+///     }
+/// }
+/// # }
+/// ```
+pub fn wait_any(devices: &[Device], timeout: Option<Duration>) -> io::Result<Vec<usize>> {
+    let fds: Vec<_> = devices.iter().map(|dev| dev.as_fd()).collect();
+    poll_any(&fds, timeout)
+}
+
+/// Returns whether the target's kernel implements the evdev interface.
+///
+/// On other targets the crate still compiles, but [`enumerate`] finds no devices and opening or
+/// creating devices fails with [`Unsupported`](io::ErrorKind::Unsupported). This allows
+/// cross-platform applications to depend on evdev unconditionally and check for support at
+/// runtime.
+pub const fn is_supported() -> bool {
+    cfg!(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "dragonfly"
+    ))
+}
+
+fn check_supported() -> io::Result<()> {
+    if is_supported() {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "evdev is not supported on this platform",
+        ))
+    }
+}
+
+/// Waits until any of `fds` is readable, returning the indices of the readable ones.
+fn poll_any(
+    fds: &[std::os::fd::BorrowedFd<'_>],
+    timeout: Option<Duration>,
+) -> io::Result<Vec<usize>> {
+    use nix::poll::{PollFd, PollFlags, PollTimeout};
+    let mut fds: Vec<_> = fds
+        .iter()
+        .map(|&fd| PollFd::new(fd, PollFlags::POLLIN))
+        .collect();
+    nix::poll::poll(&mut fds, timeout.map_or(PollTimeout::NONE, poll_timeout))?;
+    let ready = fds.iter().enumerate().filter(|(_, fd)| {
+        fd.revents().map_or(false, |ev| {
+            ev.intersects(PollFlags::POLLIN | PollFlags::POLLERR | PollFlags::POLLHUP)
+        })
+    });
+    Ok(ready.map(|(i, _)| i).collect())
+}
+
 /// An iterator over currently connected evdev devices.
 pub struct EnumerateDevices {
     inner: raw_stream::EnumerateDevices,
@@ -495,24 +741,69 @@ impl Iterator for EnumerateDevices {
 }
 
 /// A safe Rust version of clock_gettime against CLOCK_REALTIME
-fn systime_to_timeval(time: &SystemTime) -> libc::timeval {
-    let (sign, dur) = match time.duration_since(SystemTime::UNIX_EPOCH) {
-        Ok(dur) => (1, dur),
-        Err(e) => (-1, e.duration()),
+///
+/// Times before the epoch are normalized like the kernel does, with `tv_usec` always positive.
+/// The fields are `long`s on 32-bit targets even when `time_t` is 64 bits wide, see `compat`.
+fn systime_to_timeval(time: &SystemTime) -> timeval {
+    let (sec, usec) = match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(dur) => (dur.as_secs() as i64, i64::from(dur.subsec_micros())),
+        Err(e) => {
+            let dur = e.duration();
+            // wraps only for `i64::MIN` seconds, which negates to itself
+            let sec = (dur.as_secs() as i64).wrapping_neg();
+            let usec = i64::from(dur.subsec_micros());
+            if usec == 0 {
+                (sec, 0)
+            } else {
+                (sec - 1, 1_000_000 - usec)
+            }
+        }
     };
 
-    libc::timeval {
-        tv_sec: dur.as_secs() as libc::time_t * sign,
-        tv_usec: dur.subsec_micros() as libc::suseconds_t,
+    timeval {
+        tv_sec: sec as _,
+        tv_usec: usec as _,
     }
 }
 
-fn timeval_to_systime(tv: &libc::timeval) -> SystemTime {
-    let dur = Duration::new(tv.tv_sec as u64, tv.tv_usec as u32 * 1000);
-    if tv.tv_sec >= 0 {
-        SystemTime::UNIX_EPOCH + dur
+/// Returns the time `ms` milliseconds after the epoch, for timestamps in tests.
+#[cfg(test)]
+pub(crate) fn time_at(ms: u64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_millis(ms)
+}
+
+/// Returns `event` with its timestamp set to `ms` milliseconds after the epoch.
+#[cfg(test)]
+pub(crate) fn event_at(ms: u64, event: InputEvent) -> InputEvent {
+    InputEvent(input_event {
+        time: systime_to_timeval(&time_at(ms)),
+        ..event.0
+    })
+}
+
+/// Returns the time `sec` seconds and `usec` microseconds after the epoch, or `None` if it
+/// can't be represented. Both can be negative, like the fields of a `timeval`.
+pub(crate) fn checked_systime(sec: i64, usec: i64) -> Option<SystemTime> {
+    let secs = sec.checked_add(usec.div_euclid(1_000_000))?;
+    let micros = Duration::from_micros(usec.rem_euclid(1_000_000) as u64);
+    let time = if secs >= 0 {
+        SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(secs as u64))
+    } else {
+        SystemTime::UNIX_EPOCH.checked_sub(Duration::from_secs(secs.unsigned_abs()))
+    };
+    time?.checked_add(micros)
+}
+
+fn timeval_to_systime(tv: &timeval) -> SystemTime {
+    // the fields are 32 bits wide on some platforms
+    #[allow(clippy::useless_conversion)]
+    let (sec, usec) = (i64::from(tv.tv_sec), i64::from(tv.tv_usec));
+    let micros = Duration::from_micros(usec.rem_euclid(1_000_000) as u64);
+    let secs = sec + usec.div_euclid(1_000_000);
+    if secs >= 0 {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs as u64) + micros
     } else {
-        SystemTime::UNIX_EPOCH - dur
+        SystemTime::UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs()) + micros
     }
 }
 
@@ -526,7 +817,7 @@ pub(crate) unsafe fn cast_to_bytes<T: ?Sized>(mem: &T) -> &[u8] {
 pub struct EnumParseError(());
 
 impl Display for EnumParseError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "failed to parse Key from string")
     }
 }
@@ -549,15 +840,136 @@ fn write_events(fd: std::os::fd::BorrowedFd<'_>, events: &[InputEvent]) -> nix::
     fd_write_all(fd, bytes)
 }
 
+/// Writes several batches of events with a single `writev`, failing if only part of them was
+/// written.
+fn write_events_vectored(
+    fd: std::os::fd::BorrowedFd<'_>,
+    batches: &[&[InputEvent]],
+) -> io::Result<()> {
+    let slices: Vec<_> = batches
+        .iter()
+        .map(|batch| std::io::IoSlice::new(unsafe { cast_to_bytes(*batch) }))
+        .collect();
+    let total: usize = slices.iter().map(|slice| slice.len()).sum();
+    let written = loop {
+        match nix::sys::uio::writev(fd, &slices) {
+            Err(nix::Error::EINTR) => {}
+            res => break res?,
+        }
+    };
+    if written < total {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("partial write: only {written} of {total} bytes were written"),
+        ));
+    }
+    Ok(())
+}
+
+/// Converts a timeout to whole milliseconds for poll, rounding up so that short timeouts don't
+/// turn into busy loops.
+fn poll_timeout(timeout: Duration) -> nix::poll::PollTimeout {
+    let millis = (timeout.as_nanos() + 999_999) / 1_000_000;
+    nix::poll::PollTimeout::try_from(millis).unwrap_or(nix::poll::PollTimeout::MAX)
+}
+
+/// Waits for at most `timeout` until `fd` is readable.
+fn poll_readable(fd: std::os::fd::BorrowedFd<'_>, timeout: Duration) -> io::Result<bool> {
+    use nix::poll::{PollFd, PollFlags};
+    let mut fds = [PollFd::new(fd, PollFlags::POLLIN)];
+    Ok(nix::poll::poll(&mut fds, poll_timeout(timeout))? > 0)
+}
+
+// The threading guarantees documented at the top of the crate. Adding a field that isn't `Send`
+// or `Sync`, such as an `Rc` or a `Cell`, to one of these types breaks the build here.
+const _: () = {
+    fn assert_send_sync<T: Send + Sync>() {}
+    #[allow(dead_code)]
+    fn assert_all() {
+        assert_send_sync::<Device>();
+        assert_send_sync::<raw_stream::RawDevice>();
+        assert_send_sync::<uinput::VirtualDevice>();
+        assert_send_sync::<DeviceControl>();
+        assert_send_sync::<FFEffect>();
+        assert_send_sync::<InputEvent>();
+        assert_send_sync::<DeviceState>();
+        #[cfg(feature = "tokio")]
+        {
+            assert_send_sync::<EventStream>();
+            assert_send_sync::<raw_stream::EventStream>();
+            assert_send_sync::<uinput::VirtualEventStream>();
+        }
+    }
+};
+
 /// Represents a force feedback effect that has been successfully uploaded to the device for
 /// playback.
 #[derive(Debug)]
 pub struct FFEffect {
-    fd: OwnedFd,
+    /// The file descriptor of the device, which must not be duplicated: closing a duplicate
+    /// erases all effects uploaded through the open file.
+    fd: Arc<OwnedFd>,
     id: u16,
+    /// The IDs of the effects uploaded through the device, shared with it so that the device
+    /// can erase the effects that are still around when it is dropped.
+    uploaded: Arc<Mutex<Vec<u16>>>,
 }
 
 impl FFEffect {
+    /// Uploads an effect through `fd`, registering it in `uploaded`.
+    fn upload(
+        fd: &Arc<OwnedFd>,
+        uploaded: &Arc<Mutex<Vec<u16>>>,
+        data: FFEffectData,
+    ) -> io::Result<FFEffect> {
+        let mut effect: sys::ff_effect = data.into();
+        effect.id = -1;
+
+        unsafe { sys::eviocsff(fd.as_raw_fd(), &effect)? };
+
+        let fd = fd.clone();
+        let id = effect.id as u16;
+        let uploaded = uploaded.clone();
+        uploaded.lock().unwrap_or_else(|e| e.into_inner()).push(id);
+
+        Ok(FFEffect { fd, id, uploaded })
+    }
+
+    /// Uploads and plays a rumble effect, erasing it from a timer thread once it's over.
+    fn rumble(
+        fd: &Arc<OwnedFd>,
+        uploaded: &Arc<Mutex<Vec<u16>>>,
+        strong: u16,
+        weak: u16,
+        duration: Duration,
+    ) -> io::Result<()> {
+        let length = u16::try_from(duration.as_millis()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "rumble duration longer than u16::MAX milliseconds",
+            )
+        })?;
+        let mut effect = FFEffect::upload(
+            fd,
+            uploaded,
+            FFEffectData {
+                direction: 0,
+                trigger: FFTrigger::default(),
+                replay: FFReplay { length, delay: 0 },
+                kind: FFEffectKind::Rumble {
+                    strong_magnitude: strong,
+                    weak_magnitude: weak,
+                },
+            },
+        )?;
+        effect.play(1)?;
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(length.into()));
+            drop(effect);
+        });
+        Ok(())
+    }
+
     /// Returns the effect ID.
     pub fn id(&self) -> u16 {
         self.id
@@ -593,7 +1005,12 @@ impl FFEffect {
 
 impl Drop for FFEffect {
     fn drop(&mut self) {
-        let _ = unsafe { sys::eviocrmff(self.fd.as_raw_fd(), self.id as _) };
+        let mut uploaded = self.uploaded.lock().unwrap_or_else(|e| e.into_inner());
+        // the device already erased the effect if it was dropped first
+        if let Some(pos) = uploaded.iter().position(|&id| id == self.id) {
+            uploaded.swap_remove(pos);
+            let _ = unsafe { sys::eviocrmff(self.fd.as_raw_fd(), self.id as _) };
+        }
     }
 }
 
@@ -0,0 +1,323 @@
+//! Import of recordings made with `libinput record`.
+//!
+//! `libinput record` writes a YAML file describing each recorded device along with its events.
+//! Many bug reports include such recordings. This module parses the device descriptions and
+//! kernel events of a recording into evemu [`Recording`]s, which can be replayed through a
+//! virtual device or written out in the evemu format. Events recorded from libinput itself
+//! (with `--with-libinput`) and other metadata are skipped.
+//!
+//! Only the subset of YAML written by `libinput record` is supported.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let recordings = evdev::libinput_record::load("recording.yml")?;
+//! for recording in recordings {
+//!     let mut device = recording.description.create_virtual_device()?;
+//!     recording.replay(&mut device)?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Recording`]: crate::evemu::Recording
+
+use crate::compat::input_event;
+use crate::evemu::{Description, Recording, DEFAULT_MAX_FF_EFFECTS};
+use crate::{
+    AbsInfo, AbsoluteAxisCode, AttributeSet, BusType, EventType, InputEvent, InputId, KeyCode,
+};
+use std::path::Path;
+use std::{fs, io};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Other,
+    Codes,
+    Absinfo,
+    Events,
+}
+
+/// Parse a `libinput record` YAML file, returning a recording for each device in it.
+///
+/// Fails with [`InvalidData`](io::ErrorKind::InvalidData) if a line can't be parsed.
+pub fn parse(text: &str) -> io::Result<Vec<Recording>> {
+    let mut recordings = Vec::new();
+    let mut current: Option<Recording> = None;
+    let mut section = Section::Other;
+    let mut section_indent = 0;
+
+    for (lineno, raw) in text.lines().enumerate() {
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid libinput recording on line {}", lineno + 1),
+            )
+        };
+        let line = strip_comment(raw);
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+
+        if trimmed.starts_with("- node:") {
+            recordings.extend(current.take());
+            current = Some(empty_recording());
+            section = Section::Other;
+            continue;
+        }
+        let recording = match current.as_mut() {
+            Some(recording) => recording,
+            None => continue,
+        };
+        let desc = &mut recording.description;
+
+        if section == Section::Events && trimmed.starts_with("- [") {
+            let values = parse_list(trimmed.trim_start_matches('-')).ok_or_else(invalid)?;
+            let (secs, usecs, ty, code, value) = match values[..] {
+                [secs, usecs, ty, code, value] => (secs, usecs, ty, code, value),
+                _ => return Err(invalid()),
+            };
+            let time = crate::checked_systime(secs, usecs).ok_or_else(invalid)?;
+            recording.events.push(InputEvent::from(input_event {
+                time: crate::systime_to_timeval(&time),
+                type_: u16::try_from(ty).map_err(|_| invalid())?,
+                code: u16::try_from(code).map_err(|_| invalid())?,
+                value: i32::try_from(value).map_err(|_| invalid())?,
+            }));
+            continue;
+        }
+
+        let (key, value) = match trimmed.split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => continue,
+        };
+        if indent <= section_indent && !key.starts_with('-') {
+            section = Section::Other;
+        }
+        match (section, key) {
+            (Section::Codes, _) if indent > section_indent => {
+                let ty: u16 = key.parse().map_err(|_| invalid())?;
+                let codes = parse_list(value).ok_or_else(invalid)?;
+                set_codes(desc, EventType(ty), &codes);
+            }
+            (Section::Absinfo, _) if indent > section_indent => {
+                let axis: u16 = key.parse().map_err(|_| invalid())?;
+                let info = match parse_list(value).ok_or_else(invalid)?[..] {
+                    [min, max, fuzz, flat, res] => AbsInfo::new(
+                        0,
+                        min as i32,
+                        max as i32,
+                        fuzz as i32,
+                        flat as i32,
+                        res as i32,
+                    ),
+                    _ => return Err(invalid()),
+                };
+                desc.absolute_axes.push((AbsoluteAxisCode(axis), info));
+            }
+            (_, "name") if desc.name.is_empty() => desc.name = unquote(value).to_owned(),
+            (_, "id") => match parse_list(value).ok_or_else(invalid)?[..] {
+                [bus, vendor, product, version] => {
+                    desc.input_id = InputId::new(
+                        BusType(bus as u16),
+                        vendor as u16,
+                        product as u16,
+                        version as u16,
+                    );
+                }
+                _ => return Err(invalid()),
+            },
+            // the evdev properties are a list of numbers, unlike the udev properties
+            (_, "properties") if !value.is_empty() => {
+                for prop in parse_list(value).ok_or_else(invalid)? {
+                    desc.properties.insert(crate::PropType(prop as u16));
+                }
+            }
+            (_, "codes") => {
+                section = Section::Codes;
+                section_indent = indent;
+            }
+            (_, "absinfo") => {
+                section = Section::Absinfo;
+                section_indent = indent;
+            }
+            (_, "events") => {
+                section = Section::Events;
+                section_indent = indent;
+            }
+            _ => {}
+        }
+    }
+    recordings.extend(current);
+    Ok(recordings)
+}
+
+/// Read and parse a `libinput record` YAML file.
+pub fn load(path: impl AsRef<Path>) -> io::Result<Vec<Recording>> {
+    parse(&fs::read_to_string(path)?)
+}
+
+fn empty_recording() -> Recording {
+    Recording {
+        description: Description {
+            name: String::new(),
+            input_id: InputId::new(BusType(0), 0, 0, 0),
+            properties: AttributeSet::new(),
+            event_types: AttributeSet::new(),
+            keys: AttributeSet::new(),
+            relative_axes: AttributeSet::new(),
+            absolute_axes: Vec::new(),
+            misc: AttributeSet::new(),
+            switches: AttributeSet::new(),
+            leds: AttributeSet::new(),
+            sounds: AttributeSet::new(),
+            ff: AttributeSet::new(),
+            max_ff_effects: DEFAULT_MAX_FF_EFFECTS,
+        },
+        events: Vec::new(),
+    }
+}
+
+fn set_codes(desc: &mut Description, ty: EventType, codes: &[i64]) {
+    if ty.0 as usize >= EventType::COUNT {
+        return;
+    }
+    desc.event_types.insert(ty);
+    for &code in codes {
+        let code = code as u16;
+        match ty {
+            EventType::KEY if (code as usize) < KeyCode::COUNT => desc.keys.insert(KeyCode(code)),
+            EventType::RELATIVE if (code as usize) < crate::RelativeAxisCode::COUNT => {
+                desc.relative_axes.insert(crate::RelativeAxisCode(code))
+            }
+            EventType::MISC if (code as usize) < crate::MiscCode::COUNT => {
+                desc.misc.insert(crate::MiscCode(code))
+            }
+            EventType::SWITCH if (code as usize) < crate::SwitchCode::COUNT => {
+                desc.switches.insert(crate::SwitchCode(code))
+            }
+            EventType::LED if (code as usize) < crate::LedCode::COUNT => {
+                desc.leds.insert(crate::LedCode(code))
+            }
+            EventType::SOUND if (code as usize) < crate::SoundCode::COUNT => {
+                desc.sounds.insert(crate::SoundCode(code))
+            }
+            EventType::FORCEFEEDBACK if (code as usize) < crate::FFEffectCode::COUNT => {
+                desc.ff.insert(crate::FFEffectCode(code))
+            }
+            // absolute axes are described by their absinfo
+            _ => {}
+        }
+    }
+}
+
+/// Removes a trailing `# comment` that is not inside a quoted string.
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+/// Parses a flow sequence of integers, like `[1, 2, 3]`.
+fn parse_list(value: &str) -> Option<Vec<i64>> {
+    let inner = value.trim().strip_prefix('[')?.strip_suffix(']')?;
+    inner
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PropType, RelativeAxisCode};
+    use std::time::{Duration, SystemTime};
+
+    const RECORDING: &str = r#"# libinput record
+version: 1
+ndevices: 1
+libinput:
+  version: "1.19.0"
+system:
+  kernel: "5.15.0"
+devices:
+- node: /dev/input/event5
+  evdev:
+    # Name: Test Mouse # with a comment
+    name: "Test Mouse #1"
+    id: [3, 1133, 50475, 273]
+    codes:
+      0: [0, 1, 2] # EV_SYN
+      1: [272, 273] # EV_KEY
+      2: [0, 1] # EV_REL
+      3: [0]
+    absinfo:
+      0: [0, 1919, 0, 0, 10]
+    properties: [0]
+  udev:
+    properties:
+    - ID_INPUT=1
+  events:
+  - evdev:
+    - [  0,      0,   2,   0,       1] # EV_REL / REL_X   1
+    - [  0,      0,   0,   0,       0] # ------------ SYN_REPORT (0) ---------- +0ms
+  - libinput:
+    - {time: 0.000000, type: POINTER_MOTION}
+  - evdev:
+    - [  0,  16000,   1, 272,       1] # EV_KEY / BTN_LEFT 1
+    - [  0,  16000,   0,   0,       0]
+"#;
+
+    #[test]
+    fn parse_recording() {
+        let recordings = parse(RECORDING).unwrap();
+        assert_eq!(recordings.len(), 1);
+        let recording = &recordings[0];
+        let desc = &recording.description;
+        assert_eq!(desc.name, "Test Mouse #1");
+        assert_eq!(desc.input_id.vendor(), 1133);
+        assert!(desc.keys.contains(KeyCode::BTN_LEFT));
+        assert!(desc.relative_axes.contains(RelativeAxisCode::REL_Y));
+        assert!(desc.event_types.contains(EventType::ABSOLUTE));
+        assert!(desc.properties.contains(PropType::POINTER));
+        assert_eq!(desc.absolute_axes.len(), 1);
+        assert_eq!(desc.absolute_axes[0].1.resolution(), 10);
+
+        assert_eq!(recording.events.len(), 4);
+        assert_eq!(recording.events[2].code(), KeyCode::BTN_LEFT.0);
+        assert_eq!(
+            recording.events[2].timestamp(),
+            SystemTime::UNIX_EPOCH + Duration::from_millis(16)
+        );
+    }
+
+    #[test]
+    fn out_of_range_event() {
+        let event = "[  0,  16000,   1, 272,       1]";
+        for bad in [
+            "[  0,  16000,   1, 65808,     1]",
+            "[  0,  16000,   1, 272, 4294967296]",
+            "[-9223372036854775808, -1, 1, 272, 1]",
+        ] {
+            let recording = RECORDING.replace(event, bad);
+            assert_ne!(recording, RECORDING);
+            assert!(parse(&recording).is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! An in-memory device for testing input handling without access to `/dev/input`.
+//!
+//! A [`MockDevice`] is fed with scripted events and implements [`EventSource`] like the real
+//! devices, so code written against the trait can be unit-tested on machines without any input
+//! devices.
+//!
+//! ```
+//! use evdev::mock::MockDevice;
+//! use evdev::{EventSource, KeyCode, KeyEvent};
+//!
+//! let mut device = MockDevice::new("Test Keyboard");
+//! device.capabilities_mut().keys.push(KeyCode::KEY_A);
+//! device.push_frame(&[*KeyEvent::new(KeyCode::KEY_A, 1)]);
+//!
+//! let events: Vec<_> = EventSource::fetch_events(&mut device).unwrap().collect();
+//! assert_eq!(events.len(), 2);
+//! ```
+
+use crate::{
+    BusType, CapabilitiesReport, EventSource, EventType, InputEvent, SynchronizationCode,
+    SynchronizationEvent,
+};
+use nix::fcntl::{fcntl, FcntlArg, FdFlag, OFlag};
+use std::collections::VecDeque;
+use std::io;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd};
+
+/// A device that returns scripted events, see the [module documentation](self).
+///
+/// The file descriptor of the device is readable while events are queued, so the device can be
+/// polled like a real one.
+#[derive(Debug)]
+pub struct MockDevice {
+    report: CapabilitiesReport,
+    queue: VecDeque<InputEvent>,
+    /// A pipe holding a byte while events are queued, to make the device pollable.
+    ready: (OwnedFd, OwnedFd),
+}
+
+impl MockDevice {
+    /// Create a device with the given name that supports nothing but synchronization events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file descriptor for polling the device can't be created.
+    pub fn new(name: &str) -> Self {
+        Self::with_capabilities(CapabilitiesReport {
+            name: Some(name.to_owned()),
+            physical_path: None,
+            unique_name: None,
+            bus_type: BusType::BUS_VIRTUAL,
+            vendor: 0,
+            product: 0,
+            version: 0,
+            driver_version: (1, 0, 1),
+            properties: Vec::new(),
+            event_types: vec![EventType::SYNCHRONIZATION],
+            keys: Vec::new(),
+            relative_axes: Vec::new(),
+            absolute_axes: Vec::new(),
+            misc: Vec::new(),
+            switches: Vec::new(),
+            leds: Vec::new(),
+            sounds: Vec::new(),
+            ff_effects: Vec::new(),
+            max_ff_effects: 0,
+        })
+    }
+
+    /// Create a device with the given capabilities, for example copied from a real device with
+    /// [`Device::capabilities_report`](crate::Device::capabilities_report).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file descriptor for polling the device can't be created.
+    pub fn with_capabilities(report: CapabilitiesReport) -> Self {
+        let ready = nix::unistd::pipe().expect("failed to create pipe");
+        for fd in [&ready.0, &ready.1] {
+            let _ = fcntl(fd.as_raw_fd(), FcntlArg::F_SETFL(OFlag::O_NONBLOCK));
+            let _ = fcntl(fd.as_raw_fd(), FcntlArg::F_SETFD(FdFlag::FD_CLOEXEC));
+        }
+        Self {
+            report,
+            queue: VecDeque::new(),
+            ready,
+        }
+    }
+
+    /// Returns the capabilities reported by the device.
+    pub fn capabilities(&self) -> &CapabilitiesReport {
+        &self.report
+    }
+
+    /// Returns the capabilities reported by the device for modification.
+    pub fn capabilities_mut(&mut self) -> &mut CapabilitiesReport {
+        &mut self.report
+    }
+
+    /// Queue an event to be returned by the next fetch.
+    pub fn push(&mut self, event: InputEvent) {
+        if self.queue.is_empty() {
+            let _ = nix::unistd::write(&self.ready.1, &[0]);
+        }
+        self.queue.push_back(event);
+    }
+
+    /// Queue events followed by a `SYN_REPORT`.
+    pub fn push_frame(&mut self, events: &[InputEvent]) {
+        for &ev in events {
+            self.push(ev);
+        }
+        self.push(*SynchronizationEvent::new(
+            SynchronizationCode::SYN_REPORT,
+            0,
+        ));
+    }
+
+    /// Returns the number of queued events.
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns all queued events.
+    ///
+    /// As the device can't block waiting for more events, this fails with
+    /// [`WouldBlock`](io::ErrorKind::WouldBlock) if no events are queued, like a real device in
+    /// non-blocking mode.
+    pub fn fetch_events(&mut self) -> io::Result<impl Iterator<Item = InputEvent> + '_> {
+        if self.queue.is_empty() {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        let _ = nix::unistd::read(self.ready.0.as_raw_fd(), &mut [0]);
+        Ok(self.queue.drain(..))
+    }
+}
+
+impl AsFd for MockDevice {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.ready.0.as_fd()
+    }
+}
+
+impl EventSource for MockDevice {
+    fn fetch_events(&mut self) -> io::Result<Box<dyn Iterator<Item = InputEvent> + '_>> {
+        Ok(Box::new(MockDevice::fetch_events(self)?))
+    }
+
+    fn capabilities_report(&self) -> io::Result<CapabilitiesReport> {
+        Ok(self.report.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KeyCode, KeyEvent};
+    use std::time::Duration;
+
+    #[test]
+    fn scripted_events() {
+        let mut device = MockDevice::new("Mock");
+        assert_eq!(
+            device.fetch_events().err().map(|e| e.kind()),
+            Some(io::ErrorKind::WouldBlock)
+        );
+        assert!(!crate::poll_readable(device.as_fd(), Duration::ZERO).unwrap());
+
+        device.push_frame(&[*KeyEvent::new(KeyCode::KEY_A, 1)]);
+        assert_eq!(device.pending(), 2);
+        assert!(crate::poll_readable(device.as_fd(), Duration::ZERO).unwrap());
+
+        let codes: Vec<_> = device.fetch_events().unwrap().map(|ev| ev.code()).collect();
+        assert_eq!(codes, [KeyCode::KEY_A.code(), 0]);
+        assert!(!crate::poll_readable(device.as_fd(), Duration::ZERO).unwrap());
+        assert_eq!(device.input_id().unwrap().bus_type(), BusType::BUS_VIRTUAL);
+    }
+}
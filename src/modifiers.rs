@@ -0,0 +1,288 @@
+use crate::{DeviceState, EventSummary, InputEvent, KeyCode, LedCode};
+use std::{fmt, ops};
+
+/// A set of active keyboard modifiers, distinguishing between the left and right keys.
+///
+/// The lock modifiers ([`CAPS_LOCK`](Self::CAPS_LOCK) and [`NUM_LOCK`](Self::NUM_LOCK)) are
+/// toggles rather than held keys. Modifiers are usually obtained from a [`ModifierTracker`].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Modifiers(u16);
+
+impl Modifiers {
+    pub const NONE: Self = Self(0);
+    pub const LEFT_SHIFT: Self = Self(1 << 0);
+    pub const RIGHT_SHIFT: Self = Self(1 << 1);
+    pub const LEFT_CTRL: Self = Self(1 << 2);
+    pub const RIGHT_CTRL: Self = Self(1 << 3);
+    pub const LEFT_ALT: Self = Self(1 << 4);
+    pub const RIGHT_ALT: Self = Self(1 << 5);
+    pub const LEFT_META: Self = Self(1 << 6);
+    pub const RIGHT_META: Self = Self(1 << 7);
+    pub const CAPS_LOCK: Self = Self(1 << 8);
+    pub const NUM_LOCK: Self = Self(1 << 9);
+
+    /// Either shift key.
+    pub const SHIFT: Self = Self(Self::LEFT_SHIFT.0 | Self::RIGHT_SHIFT.0);
+    /// Either control key.
+    pub const CTRL: Self = Self(Self::LEFT_CTRL.0 | Self::RIGHT_CTRL.0);
+    /// Either alt key.
+    pub const ALT: Self = Self(Self::LEFT_ALT.0 | Self::RIGHT_ALT.0);
+    /// Either meta ("super", "windows") key.
+    pub const META: Self = Self(Self::LEFT_META.0 | Self::RIGHT_META.0);
+
+    const NAMES: &'static [(Self, &'static str)] = &[
+        (Self::LEFT_SHIFT, "LEFT_SHIFT"),
+        (Self::RIGHT_SHIFT, "RIGHT_SHIFT"),
+        (Self::LEFT_CTRL, "LEFT_CTRL"),
+        (Self::RIGHT_CTRL, "RIGHT_CTRL"),
+        (Self::LEFT_ALT, "LEFT_ALT"),
+        (Self::RIGHT_ALT, "RIGHT_ALT"),
+        (Self::LEFT_META, "LEFT_META"),
+        (Self::RIGHT_META, "RIGHT_META"),
+        (Self::CAPS_LOCK, "CAPS_LOCK"),
+        (Self::NUM_LOCK, "NUM_LOCK"),
+    ];
+
+    /// Returns the modifier corresponding to a held modifier key, if `key` is one.
+    pub fn from_key(key: KeyCode) -> Option<Self> {
+        Some(match key {
+            KeyCode::KEY_LEFTSHIFT => Self::LEFT_SHIFT,
+            KeyCode::KEY_RIGHTSHIFT => Self::RIGHT_SHIFT,
+            KeyCode::KEY_LEFTCTRL => Self::LEFT_CTRL,
+            KeyCode::KEY_RIGHTCTRL => Self::RIGHT_CTRL,
+            KeyCode::KEY_LEFTALT => Self::LEFT_ALT,
+            KeyCode::KEY_RIGHTALT => Self::RIGHT_ALT,
+            KeyCode::KEY_LEFTMETA => Self::LEFT_META,
+            KeyCode::KEY_RIGHTMETA => Self::RIGHT_META,
+            _ => return None,
+        })
+    }
+
+    /// Returns `true` if no modifiers are active.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if all modifiers in `other` are active.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if any of the modifiers in `other` are active.
+    pub fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Returns `true` if either shift key is held.
+    pub fn shift(self) -> bool {
+        self.intersects(Self::SHIFT)
+    }
+
+    /// Returns `true` if either control key is held.
+    pub fn ctrl(self) -> bool {
+        self.intersects(Self::CTRL)
+    }
+
+    /// Returns `true` if either alt key is held.
+    pub fn alt(self) -> bool {
+        self.intersects(Self::ALT)
+    }
+
+    /// Returns `true` if either meta key is held.
+    pub fn meta(self) -> bool {
+        self.intersects(Self::META)
+    }
+
+    /// Returns `true` if caps lock is on.
+    pub fn caps_lock(self) -> bool {
+        self.contains(Self::CAPS_LOCK)
+    }
+
+    /// Returns `true` if num lock is on.
+    pub fn num_lock(self) -> bool {
+        self.contains(Self::NUM_LOCK)
+    }
+
+    /// Returns the raw bitmask.
+    pub fn bits(self) -> u16 {
+        self.0
+    }
+
+    fn set(&mut self, other: Self, on: bool) {
+        if on {
+            self.0 |= other.0;
+        } else {
+            self.0 &= !other.0;
+        }
+    }
+}
+
+impl ops::BitOr for Modifiers {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl ops::BitOrAssign for Modifiers {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl ops::BitAnd for Modifiers {
+    type Output = Self;
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl fmt::Debug for Modifiers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut set = f.debug_set();
+        for &(m, name) in Self::NAMES {
+            if self.contains(m) {
+                set.entry(&format_args!("{name}"));
+            }
+        }
+        set.finish()
+    }
+}
+
+/// Keeps track of the active [`Modifiers`] of a keyboard as its events are processed.
+///
+/// Held modifiers are tracked from key events. Caps lock and num lock are tracked from LED
+/// events if the device reports them, since the LEDs reflect the actual lock state. Until an LED
+/// event has been seen for a lock, it is toggled whenever its key is pressed instead.
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use evdev::{Device, KeyCode, ModifierTracker};
+/// let mut device = Device::open("/dev/input/event0")?;
+/// let mut tracker = ModifierTracker::from_state(device.cached_state());
+/// for (ev, mods) in tracker.track(device.fetch_events()?) {
+///     if ev.code() == KeyCode::KEY_C.code() && mods.ctrl() {
+///         println!("ctrl+c");
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ModifierTracker {
+    modifiers: Modifiers,
+    /// Locks whose state is known from LED events.
+    led_locks: Modifiers,
+}
+
+impl ModifierTracker {
+    /// Create a tracker with no active modifiers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a tracker initialized from a device state, e.g. [`Device::cached_state`].
+    ///
+    /// [`Device::cached_state`]: crate::Device::cached_state
+    pub fn from_state(state: &DeviceState) -> Self {
+        let mut tracker = Self::new();
+        if let Some(keys) = state.key_vals() {
+            for key in keys.iter() {
+                if let Some(m) = Modifiers::from_key(key) {
+                    tracker.modifiers |= m;
+                }
+            }
+        }
+        if let Some(leds) = state.led_vals() {
+            tracker.led_locks = Modifiers::CAPS_LOCK | Modifiers::NUM_LOCK;
+            tracker
+                .modifiers
+                .set(Modifiers::CAPS_LOCK, leds.contains(LedCode::LED_CAPSL));
+            tracker
+                .modifiers
+                .set(Modifiers::NUM_LOCK, leds.contains(LedCode::LED_NUML));
+        }
+        tracker
+    }
+
+    /// Returns the currently active modifiers.
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// Update the tracked state with an event, returning the modifiers active after it.
+    pub fn process(&mut self, event: &InputEvent) -> Modifiers {
+        match event.destructure() {
+            EventSummary::Key(_, key, value) => {
+                if let Some(m) = Modifiers::from_key(key) {
+                    self.modifiers.set(m, value != 0);
+                } else if value == 1 {
+                    let lock = match key {
+                        KeyCode::KEY_CAPSLOCK => Modifiers::CAPS_LOCK,
+                        KeyCode::KEY_NUMLOCK => Modifiers::NUM_LOCK,
+                        _ => Modifiers::NONE,
+                    };
+                    if !lock.is_empty() && !self.led_locks.contains(lock) {
+                        let on = !self.modifiers.contains(lock);
+                        self.modifiers.set(lock, on);
+                    }
+                }
+            }
+            EventSummary::Led(_, led, value) => {
+                let lock = match led {
+                    LedCode::LED_CAPSL => Modifiers::CAPS_LOCK,
+                    LedCode::LED_NUML => Modifiers::NUM_LOCK,
+                    _ => return self.modifiers,
+                };
+                self.led_locks |= lock;
+                self.modifiers.set(lock, value != 0);
+            }
+            _ => {}
+        }
+        self.modifiers
+    }
+
+    /// Wrap an iterator of events, pairing every event with the modifiers active after it.
+    pub fn track<'a, I>(
+        &'a mut self,
+        events: I,
+    ) -> impl Iterator<Item = (InputEvent, Modifiers)> + 'a
+    where
+        I: IntoIterator<Item = InputEvent>,
+        I::IntoIter: 'a,
+    {
+        events.into_iter().map(move |ev| (ev, self.process(&ev)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KeyEvent, LedEvent};
+
+    #[test]
+    fn tracks_held_and_locks() {
+        let mut tracker = ModifierTracker::new();
+        let events = [
+            *KeyEvent::new(KeyCode::KEY_RIGHTCTRL, 1),
+            *KeyEvent::new(KeyCode::KEY_CAPSLOCK, 1),
+            *KeyEvent::new(KeyCode::KEY_CAPSLOCK, 0),
+        ];
+        for ev in &events {
+            tracker.process(ev);
+        }
+        let mods = tracker.modifiers();
+        assert!(mods.ctrl() && mods.contains(Modifiers::RIGHT_CTRL));
+        assert!(!mods.contains(Modifiers::LEFT_CTRL));
+        assert!(mods.caps_lock());
+
+        // once the LED is known it takes precedence over toggling
+        tracker.process(&LedEvent::new(LedCode::LED_CAPSL, 0));
+        tracker.process(&KeyEvent::new(KeyCode::KEY_CAPSLOCK, 1));
+        assert!(!tracker.modifiers().caps_lock());
+        assert_eq!(
+            format!("{:?}", tracker.modifiers()),
+            "{RIGHT_CTRL}".to_string()
+        );
+    }
+}
@@ -0,0 +1,435 @@
+//! Tracking of multitouch contacts reported via the kernel's multitouch protocol B.
+//!
+//! Devices using protocol B (most touchpads and touchscreens) report each contact in a "slot".
+//! `ABS_MT_SLOT` selects the slot that subsequent `ABS_MT_*` events refer to, and
+//! `ABS_MT_TRACKING_ID` assigns a contact to the slot (or removes it with a value of `-1`).
+//! Changes are only complete once the frame is terminated by a `SYN_REPORT`.
+//!
+//! The [`MTStateTracker`] reassembles this into per-contact state and reports
+//! [`ContactUpdate`]s for every frame. Touchscreen applications that only care about touch
+//! points can instead use [`MTStateTracker::touch_events`], which yields [`TouchEvent`]s. See the
+//! [kernel documentation](https://www.kernel.org/doc/Documentation/input/multi-touch-protocol.txt)
+//! for details on the protocol.
+
+use crate::{AbsoluteAxisCode, Device, EventSummary, InputEvent, PropType, SynchronizationCode};
+use std::io;
+use std::os::fd::AsRawFd;
+
+/// The state of a single contact (e.g. a finger) on a multitouch device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Contact {
+    /// The slot this contact is reported in.
+    pub slot: usize,
+    /// The kernel-assigned tracking ID, unique for the duration of the contact.
+    pub tracking_id: i32,
+    /// `ABS_MT_POSITION_X`
+    pub x: i32,
+    /// `ABS_MT_POSITION_Y`
+    pub y: i32,
+    /// `ABS_MT_PRESSURE`, if reported by the device.
+    pub pressure: Option<i32>,
+    /// `ABS_MT_TOUCH_MAJOR`, if reported by the device.
+    pub touch_major: Option<i32>,
+    /// `ABS_MT_TOUCH_MINOR`, if reported by the device.
+    pub touch_minor: Option<i32>,
+}
+
+/// A change of a [`Contact`] within a frame, reported by [`MTStateTracker::process`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContactUpdate {
+    /// A new contact was placed on the device.
+    Began(Contact),
+    /// An existing contact changed its position, pressure, or size.
+    Moved(Contact),
+    /// A contact was lifted. The contact holds its last known state.
+    Ended(Contact),
+}
+
+impl ContactUpdate {
+    /// Returns the contact this update refers to.
+    pub fn contact(&self) -> &Contact {
+        match self {
+            ContactUpdate::Began(c) | ContactUpdate::Moved(c) | ContactUpdate::Ended(c) => c,
+        }
+    }
+}
+
+/// The phase of a touch point in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TouchPhase {
+    /// The touch point was placed on the device.
+    Began,
+    /// The touch point moved or changed its pressure.
+    Moved,
+    /// The touch point was lifted.
+    Ended,
+}
+
+/// A change of a single touch point, as yielded by [`TouchEvents`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TouchEvent {
+    /// The tracking ID of the touch point, stable from [`Began`](TouchPhase::Began) to
+    /// [`Ended`](TouchPhase::Ended).
+    pub id: i32,
+    /// Whether the touch point began, moved, or ended.
+    pub phase: TouchPhase,
+    /// The `(x, y)` position in device coordinates.
+    pub position: (i32, i32),
+    /// The pressure, if reported by the device.
+    pub pressure: Option<i32>,
+}
+
+impl From<ContactUpdate> for TouchEvent {
+    fn from(update: ContactUpdate) -> Self {
+        let (phase, c) = match update {
+            ContactUpdate::Began(c) => (TouchPhase::Began, c),
+            ContactUpdate::Moved(c) => (TouchPhase::Moved, c),
+            ContactUpdate::Ended(c) => (TouchPhase::Ended, c),
+        };
+        TouchEvent {
+            id: c.tracking_id,
+            phase,
+            position: (c.x, c.y),
+            pressure: c.pressure,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    contact: Contact,
+    /// The tracking ID as of the last `SYN_REPORT`.
+    reported_id: i32,
+    dirty: bool,
+}
+
+/// Maintains the state of every slot of a multitouch device.
+///
+/// Feed every event of the device to [`process`](Self::process). At the end of every frame it
+/// returns the contacts that began, moved, or ended in that frame.
+///
+/// After events were dropped by the kernel, the tracked state may be wrong; recreate the tracker
+/// with [`from_device`](Self::from_device) to recover.
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use evdev::multitouch::{ContactUpdate, MTStateTracker};
+/// use evdev::Device;
+/// let mut device = Device::open("/dev/input/event0")?;
+/// let mut tracker = MTStateTracker::from_device(&device)?;
+/// loop {
+///     for ev in device.fetch_events()? {
+///         for update in tracker.process(&ev) {
+///             if let ContactUpdate::Began(contact) = update {
+///                 println!("touch at {}x{}", contact.x, contact.y);
+///             }
+///         }
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MTStateTracker {
+    slots: Vec<Slot>,
+    current: usize,
+}
+
+impl MTStateTracker {
+    /// Create a tracker for a device with `num_slots` slots, with no active contacts.
+    pub fn new(num_slots: usize) -> Self {
+        let slot = |slot| Slot {
+            contact: Contact {
+                slot,
+                tracking_id: -1,
+                x: 0,
+                y: 0,
+                pressure: None,
+                touch_major: None,
+                touch_minor: None,
+            },
+            reported_id: -1,
+            dirty: false,
+        };
+        Self {
+            slots: (0..num_slots.max(1)).map(slot).collect(),
+            current: 0,
+        }
+    }
+
+    /// Create a tracker for a device, initialized with the contacts currently on it.
+    ///
+    /// The number of slots is derived from the range of the device's `ABS_MT_SLOT` axis.
+    pub fn from_device(device: &Device) -> io::Result<Self> {
+        let absinfo: Vec<_> = device.get_absinfo()?.collect();
+        let info = |axis| absinfo.iter().find(|(a, _)| *a == axis).map(|(_, i)| *i);
+        let num_slots =
+            info(AbsoluteAxisCode::ABS_MT_SLOT).map_or(1, |i| (i.maximum() + 1).max(1) as usize);
+
+        let mut tracker = Self::new(num_slots);
+        tracker.current = info(AbsoluteAxisCode::ABS_MT_SLOT)
+            .map_or(0, |i| i.value().max(0) as usize)
+            .min(num_slots - 1);
+
+        for &(axis, _) in &absinfo {
+            if !is_tracked(axis) {
+                continue;
+            }
+            let values = mt_slot_values(device, axis, num_slots)?;
+            for (slot, value) in tracker.slots.iter_mut().zip(values) {
+                slot.apply(axis, value);
+                slot.reported_id = slot.contact.tracking_id;
+                slot.dirty = false;
+            }
+        }
+        Ok(tracker)
+    }
+
+    /// Create a tracker for a touchscreen, initialized with the contacts currently on it.
+    ///
+    /// This is like [`from_device`](Self::from_device), but fails with
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if the device does not have the
+    /// `INPUT_PROP_DIRECT` property, i.e. if its coordinates don't map directly to a screen.
+    pub fn from_touchscreen(device: &Device) -> io::Result<Self> {
+        if !device.properties().contains(PropType::DIRECT) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "device is not a direct touch device",
+            ));
+        }
+        Self::from_device(device)
+    }
+
+    /// Returns the number of slots of the device.
+    pub fn num_slots(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the contacts that were active as of the last `SYN_REPORT`.
+    pub fn contacts(&self) -> impl Iterator<Item = &Contact> + '_ {
+        self.slots
+            .iter()
+            .filter(|s| s.reported_id >= 0)
+            .map(|s| &s.contact)
+    }
+
+    /// Update the tracked state with an event.
+    ///
+    /// Returns the changes of the frame if `event` is a `SYN_REPORT`, and nothing otherwise.
+    pub fn process(&mut self, event: &InputEvent) -> Vec<ContactUpdate> {
+        match event.destructure() {
+            EventSummary::AbsoluteAxis(_, AbsoluteAxisCode::ABS_MT_SLOT, value) => {
+                if let Ok(slot) = usize::try_from(value) {
+                    self.current = slot;
+                }
+            }
+            EventSummary::AbsoluteAxis(_, axis, value) if is_tracked(axis) => {
+                if let Some(slot) = self.slots.get_mut(self.current) {
+                    slot.apply(axis, value);
+                }
+            }
+            EventSummary::Synchronization(_, SynchronizationCode::SYN_REPORT, _) => {
+                return self.finish_frame();
+            }
+            _ => {}
+        }
+        Vec::new()
+    }
+
+    /// Adapt an iterator of events into an iterator of [`TouchEvent`]s.
+    ///
+    /// The tracker keeps its state between calls, so the same tracker can be used for every batch
+    /// of events fetched from a device.
+    ///
+    /// ```no_run
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use evdev::multitouch::MTStateTracker;
+    /// let mut device = evdev::Device::open("/dev/input/event0")?;
+    /// let mut tracker = MTStateTracker::from_touchscreen(&device)?;
+    /// loop {
+    ///     for touch in tracker.touch_events(device.fetch_events()?) {
+    ///         println!("{:?} {} at {:?}", touch.phase, touch.id, touch.position);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn touch_events<I>(&mut self, events: I) -> TouchEvents<'_, I::IntoIter>
+    where
+        I: IntoIterator<Item = InputEvent>,
+    {
+        TouchEvents {
+            tracker: self,
+            events: events.into_iter(),
+            pending: Vec::new().into_iter(),
+        }
+    }
+
+    fn finish_frame(&mut self) -> Vec<ContactUpdate> {
+        let mut updates = Vec::new();
+        for slot in &mut self.slots {
+            let old = slot.reported_id;
+            let new = slot.contact.tracking_id;
+            if old >= 0 && old != new {
+                updates.push(ContactUpdate::Ended(Contact {
+                    tracking_id: old,
+                    ..slot.contact
+                }));
+            }
+            if new >= 0 && old != new {
+                updates.push(ContactUpdate::Began(slot.contact));
+            } else if new >= 0 && slot.dirty {
+                updates.push(ContactUpdate::Moved(slot.contact));
+            }
+            slot.reported_id = new;
+            slot.dirty = false;
+        }
+        updates
+    }
+}
+
+/// An iterator of [`TouchEvent`]s, created by [`MTStateTracker::touch_events`].
+#[derive(Debug)]
+pub struct TouchEvents<'a, I> {
+    tracker: &'a mut MTStateTracker,
+    events: I,
+    pending: std::vec::IntoIter<ContactUpdate>,
+}
+
+impl<'a, I: Iterator<Item = InputEvent>> Iterator for TouchEvents<'a, I> {
+    type Item = TouchEvent;
+
+    fn next(&mut self) -> Option<TouchEvent> {
+        loop {
+            if let Some(update) = self.pending.next() {
+                return Some(update.into());
+            }
+            let ev = self.events.next()?;
+            self.pending = self.tracker.process(&ev).into_iter();
+        }
+    }
+}
+
+impl Slot {
+    fn apply(&mut self, axis: AbsoluteAxisCode, value: i32) {
+        let contact = &mut self.contact;
+        match axis {
+            AbsoluteAxisCode::ABS_MT_TRACKING_ID => contact.tracking_id = value.max(-1),
+            AbsoluteAxisCode::ABS_MT_POSITION_X => contact.x = value,
+            AbsoluteAxisCode::ABS_MT_POSITION_Y => contact.y = value,
+            AbsoluteAxisCode::ABS_MT_PRESSURE => contact.pressure = Some(value),
+            AbsoluteAxisCode::ABS_MT_TOUCH_MAJOR => contact.touch_major = Some(value),
+            AbsoluteAxisCode::ABS_MT_TOUCH_MINOR => contact.touch_minor = Some(value),
+            _ => return,
+        }
+        self.dirty = true;
+    }
+}
+
+fn is_tracked(axis: AbsoluteAxisCode) -> bool {
+    matches!(
+        axis,
+        AbsoluteAxisCode::ABS_MT_TRACKING_ID
+            | AbsoluteAxisCode::ABS_MT_POSITION_X
+            | AbsoluteAxisCode::ABS_MT_POSITION_Y
+            | AbsoluteAxisCode::ABS_MT_PRESSURE
+            | AbsoluteAxisCode::ABS_MT_TOUCH_MAJOR
+            | AbsoluteAxisCode::ABS_MT_TOUCH_MINOR
+    )
+}
+
+/// Query the values of `axis` for every slot using EVIOCGMTSLOTS.
+fn mt_slot_values(
+    device: &Device,
+    axis: AbsoluteAxisCode,
+    num_slots: usize,
+) -> io::Result<Vec<i32>> {
+    // the request is a u32 code followed by one i32 value per slot
+    let mut buf = vec![0i32; num_slots + 1];
+    buf[0] = axis.0 as i32;
+    // SAFETY: i32 has no padding and any bit pattern is valid
+    let bytes = unsafe {
+        std::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, std::mem::size_of_val(&buf[..]))
+    };
+    unsafe { crate::sys::eviocgmtslots(device.as_raw_fd(), bytes)? };
+    buf.remove(0);
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbsoluteAxisEvent, SynchronizationEvent};
+
+    fn abs(axis: AbsoluteAxisCode, value: i32) -> InputEvent {
+        *AbsoluteAxisEvent::new(axis, value)
+    }
+
+    fn report() -> InputEvent {
+        *SynchronizationEvent::new(SynchronizationCode::SYN_REPORT, 0)
+    }
+
+    fn run(tracker: &mut MTStateTracker, events: &[InputEvent]) -> Vec<ContactUpdate> {
+        events.iter().flat_map(|ev| tracker.process(ev)).collect()
+    }
+
+    #[test]
+    fn contact_lifecycle() {
+        let mut tracker = MTStateTracker::new(2);
+        let updates = run(
+            &mut tracker,
+            &[
+                abs(AbsoluteAxisCode::ABS_MT_SLOT, 1),
+                abs(AbsoluteAxisCode::ABS_MT_TRACKING_ID, 42),
+                abs(AbsoluteAxisCode::ABS_MT_POSITION_X, 10),
+                abs(AbsoluteAxisCode::ABS_MT_POSITION_Y, 20),
+                report(),
+            ],
+        );
+        assert_eq!(updates.len(), 1);
+        assert!(matches!(updates[0], ContactUpdate::Began(c) if c.slot == 1 && c.x == 10));
+        assert_eq!(tracker.contacts().count(), 1);
+
+        let updates = run(
+            &mut tracker,
+            &[abs(AbsoluteAxisCode::ABS_MT_POSITION_X, 11), report()],
+        );
+        assert!(matches!(updates[..], [ContactUpdate::Moved(c)] if c.x == 11 && c.y == 20));
+
+        let updates = run(
+            &mut tracker,
+            &[abs(AbsoluteAxisCode::ABS_MT_TRACKING_ID, -1), report()],
+        );
+        assert!(matches!(updates[..], [ContactUpdate::Ended(c)] if c.tracking_id == 42));
+        assert_eq!(tracker.contacts().count(), 0);
+    }
+
+    #[test]
+    fn touch_events() {
+        let mut tracker = MTStateTracker::new(2);
+        let events = [
+            abs(AbsoluteAxisCode::ABS_MT_TRACKING_ID, 7),
+            abs(AbsoluteAxisCode::ABS_MT_POSITION_X, 5),
+            abs(AbsoluteAxisCode::ABS_MT_PRESSURE, 30),
+            report(),
+            abs(AbsoluteAxisCode::ABS_MT_TRACKING_ID, -1),
+            report(),
+        ];
+        let touches: Vec<_> = tracker.touch_events(events).collect();
+        assert_eq!(
+            touches,
+            [
+                TouchEvent {
+                    id: 7,
+                    phase: TouchPhase::Began,
+                    position: (5, 0),
+                    pressure: Some(30),
+                },
+                TouchEvent {
+                    id: 7,
+                    phase: TouchPhase::Ended,
+                    position: (5, 0),
+                    pressure: Some(30),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{AbsInfo, AbsoluteAxisCode, AbsoluteAxisEvent, Device};
+use std::collections::HashMap;
+use std::io;
+
+/// Normalizes absolute axis events of a device, using the `AbsInfo` of each axis.
+///
+/// The axis information is read once when the normalizer is created, so normalizing events
+/// doesn't require any syscalls. See [`AbsoluteAxisEvent::normalized`] for how values are
+/// mapped.
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use evdev::{AxisNormalizer, EventSummary};
+/// let mut device = evdev::Device::open("/dev/input/event0")?;
+/// let normalizer = AxisNormalizer::new(&device)?;
+/// loop {
+///     for ev in device.fetch_events()? {
+///         if let EventSummary::AbsoluteAxis(ev, axis, _) = ev.destructure() {
+///             println!("{:?}: {:?}", axis, normalizer.normalize(&ev));
+///         }
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AxisNormalizer {
+    axes: HashMap<AbsoluteAxisCode, AbsInfo>,
+}
+
+impl AxisNormalizer {
+    /// Read the axis information of a device.
+    pub fn new(device: &Device) -> io::Result<Self> {
+        Ok(Self {
+            axes: device.get_absinfo()?.collect(),
+        })
+    }
+
+    /// Returns the `AbsInfo` of an axis, if the device supports it.
+    pub fn absinfo(&self, axis: AbsoluteAxisCode) -> Option<&AbsInfo> {
+        self.axes.get(&axis)
+    }
+
+    /// Normalize the value of an event, returning `None` if the device doesn't support its
+    /// axis.
+    pub fn normalize(&self, event: &AbsoluteAxisEvent) -> Option<f32> {
+        self.absinfo(event.code())
+            .map(|info| event.normalized(info))
+    }
+}
@@ -0,0 +1,183 @@
+use crate::uinput::VirtualDevice;
+use crate::{EventType, InputEvent, SynchronizationCode};
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Writes recorded events to a virtual device with the timing they were recorded with.
+///
+/// The kernel gives events written to a virtual device the time they were written at as their
+/// timestamp, so the timestamps of recorded events can't be passed on. Instead, the pacer waits
+/// before writing each frame until as much time has passed since the first frame as had passed
+/// between their timestamps, so that readers see the original timing.
+///
+/// If a frame is written more than the tolerance too late, e.g. because the process was stalled,
+/// the following frames are delayed by as much instead of being written in a burst to catch up.
+/// This keeps the time between frames as recorded, except around the stall.
+///
+/// The frames can also be written faster or slower than recorded, see
+/// [`set_speed`](Self::set_speed).
+///
+/// ```no_run
+/// use evdev::evemu::Recording;
+/// use evdev::EventPacer;
+/// use std::time::Duration;
+///
+/// let recording = Recording::load("mouse.evemu")?;
+/// let mut device = recording.description.create_virtual_device()?;
+/// let mut pacer = EventPacer::new(Duration::from_millis(2));
+/// pacer.emit(&mut device, &recording.events)?;
+/// println!("frames were up to {:?} late", pacer.max_lateness());
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventPacer {
+    tolerance: Duration,
+    /// The timestamp of the first frame and when it was written.
+    origin: Option<(SystemTime, Instant)>,
+    max_lateness: Duration,
+    speed: f64,
+}
+
+impl EventPacer {
+    /// Create a pacer that lets frames be up to `tolerance` late before delaying the following
+    /// frames.
+    pub fn new(tolerance: Duration) -> Self {
+        Self {
+            tolerance,
+            origin: None,
+            max_lateness: Duration::ZERO,
+            speed: 1.0,
+        }
+    }
+
+    /// Set the speed at which frames are written, e.g. `2.0` to write them twice as fast as
+    /// recorded. The default is `1.0`, the original speed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `speed` isn't a positive number.
+    pub fn set_speed(&mut self, speed: f64) {
+        assert!(speed > 0.0, "replay speed must be positive");
+        self.speed = speed;
+    }
+
+    /// Returns the longest a frame within the tolerance was written too late.
+    pub fn max_lateness(&self) -> Duration {
+        self.max_lateness
+    }
+
+    /// Start over, writing the next frame right away as if it was the first one.
+    pub fn reset(&mut self) {
+        self.origin = None;
+        self.max_lateness = Duration::ZERO;
+    }
+
+    /// Write `events` to `device` frame by frame, waiting before each frame until it is due.
+    ///
+    /// Frames end with a `SYN_REPORT` event, which is not added if it's missing. The events can
+    /// be split across several calls, as the timing is kept between them.
+    pub fn emit(&mut self, device: &mut VirtualDevice, events: &[InputEvent]) -> io::Result<()> {
+        let frames = events.split_inclusive(|ev| {
+            ev.event_type() == EventType::SYNCHRONIZATION
+                && ev.code() == SynchronizationCode::SYN_REPORT.0
+        });
+        for frame in frames {
+            let due = self.due(frame[0].timestamp(), Instant::now());
+            let now = Instant::now();
+            if due > now {
+                thread::sleep(due - now);
+            }
+            device.write_raw(frame)?;
+            self.written(due, Instant::now());
+        }
+        Ok(())
+    }
+
+    /// Returns when a frame with the timestamp `time` is due.
+    fn due(&mut self, time: SystemTime, now: Instant) -> Instant {
+        match self.origin {
+            Some((first, start)) if time >= first => {
+                let offset = time.duration_since(first).unwrap().as_secs_f64() / self.speed;
+                // a frame too far in the future to represent is as good as never due
+                let offset = if offset < u64::MAX as f64 {
+                    Duration::from_secs_f64(offset)
+                } else {
+                    Duration::MAX
+                };
+                saturating_add(start, offset)
+            }
+            // the first frame, or timestamps went backwards
+            _ => {
+                self.origin = Some((time, now));
+                now
+            }
+        }
+    }
+
+    /// Records that a frame due at `due` was written at `now`.
+    fn written(&mut self, due: Instant, now: Instant) {
+        let late = now.saturating_duration_since(due);
+        if late > self.tolerance {
+            if let Some((_, start)) = &mut self.origin {
+                *start += late;
+            }
+        } else {
+            self.max_lateness = self.max_lateness.max(late);
+        }
+    }
+}
+
+/// Returns `start + offset`, or the latest instant within that the platform can represent.
+fn saturating_add(start: Instant, mut offset: Duration) -> Instant {
+    loop {
+        if let Some(time) = start.checked_add(offset) {
+            return time;
+        }
+        offset /= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_after_stall() {
+        let mut pacer = EventPacer::new(Duration::from_millis(2));
+        let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+
+        assert_eq!(pacer.due(t0, start), start);
+        pacer.written(start, start + ms(1));
+        assert_eq!(pacer.due(t0 + ms(10), start), start + ms(10));
+
+        // written 5ms late: the following frames keep their distance to it
+        pacer.written(start + ms(10), start + ms(15));
+        assert_eq!(pacer.due(t0 + ms(20), start), start + ms(25));
+        assert_eq!(pacer.max_lateness(), ms(1));
+
+        // timestamps going backwards start over
+        let now = start + ms(30);
+        assert_eq!(pacer.due(t0 - ms(4), now), now);
+        assert_eq!(pacer.due(t0, now), now + ms(4));
+
+        pacer.set_speed(2.0);
+        assert_eq!(pacer.due(t0 + ms(6), now), now + ms(5));
+    }
+
+    #[test]
+    fn saturates_far_frames() {
+        let mut pacer = EventPacer::new(Duration::from_millis(2));
+        let t0 = SystemTime::UNIX_EPOCH;
+        let start = Instant::now();
+        pacer.set_speed(f64::MIN_POSITIVE);
+        assert_eq!(pacer.due(t0, start), start);
+        assert!(pacer.due(t0 + Duration::from_secs(1), start) > start);
+
+        pacer.set_speed(1.0);
+        let far = t0 + Duration::from_secs(i64::MAX as u64 / 2);
+        assert!(pacer.due(far, start) > start);
+    }
+}
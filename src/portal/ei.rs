@@ -0,0 +1,327 @@
+use crate::compat::{input_event, timeval};
+use crate::{AbsoluteAxisCode, EventType, InputEvent, RelativeAxisCode, SynchronizationCode};
+use libc::{c_char, c_int, c_uint, c_void};
+use std::ffi::CString;
+use std::io;
+use std::os::fd::{AsFd, BorrowedFd, IntoRawFd, OwnedFd};
+use std::ptr::NonNull;
+use std::time::Duration;
+
+#[repr(C)]
+struct Ei {
+    _private: [u8; 0],
+}
+#[repr(C)]
+struct EiEvent {
+    _private: [u8; 0],
+}
+#[repr(C)]
+struct EiSeat {
+    _private: [u8; 0],
+}
+
+const EI_EVENT_DISCONNECT: c_uint = 2;
+const EI_EVENT_SEAT_ADDED: c_uint = 3;
+const EI_EVENT_FRAME: c_uint = 100;
+const EI_EVENT_POINTER_MOTION: c_uint = 300;
+const EI_EVENT_POINTER_MOTION_ABSOLUTE: c_uint = 400;
+const EI_EVENT_BUTTON_BUTTON: c_uint = 500;
+const EI_EVENT_SCROLL_DISCRETE: c_uint = 603;
+const EI_EVENT_KEYBOARD_KEY: c_uint = 700;
+const EI_EVENT_TOUCH_DOWN: c_uint = 800;
+const EI_EVENT_TOUCH_UP: c_uint = 801;
+const EI_EVENT_TOUCH_MOTION: c_uint = 802;
+
+const EI_DEVICE_CAP_POINTER: c_uint = 1 << 0;
+const EI_DEVICE_CAP_POINTER_ABSOLUTE: c_uint = 1 << 1;
+const EI_DEVICE_CAP_KEYBOARD: c_uint = 1 << 2;
+const EI_DEVICE_CAP_TOUCH: c_uint = 1 << 3;
+const EI_DEVICE_CAP_SCROLL: c_uint = 1 << 4;
+const EI_DEVICE_CAP_BUTTON: c_uint = 1 << 5;
+
+#[link(name = "ei")]
+extern "C" {
+    fn ei_new_receiver(user_data: *mut c_void) -> *mut Ei;
+    fn ei_unref(ei: *mut Ei) -> *mut Ei;
+    fn ei_configure_name(ei: *mut Ei, name: *const c_char);
+    fn ei_setup_backend_fd(ei: *mut Ei, fd: c_int) -> c_int;
+    fn ei_get_fd(ei: *mut Ei) -> c_int;
+    fn ei_dispatch(ei: *mut Ei);
+    fn ei_get_event(ei: *mut Ei) -> *mut EiEvent;
+    fn ei_event_unref(event: *mut EiEvent) -> *mut EiEvent;
+    fn ei_event_get_type(event: *mut EiEvent) -> c_uint;
+    fn ei_event_get_time(event: *mut EiEvent) -> u64;
+    fn ei_event_get_seat(event: *mut EiEvent) -> *mut EiSeat;
+    fn ei_seat_bind_capabilities(seat: *mut EiSeat, ...);
+    fn ei_event_pointer_get_dx(event: *mut EiEvent) -> f64;
+    fn ei_event_pointer_get_dy(event: *mut EiEvent) -> f64;
+    fn ei_event_pointer_get_absolute_x(event: *mut EiEvent) -> f64;
+    fn ei_event_pointer_get_absolute_y(event: *mut EiEvent) -> f64;
+    fn ei_event_button_get_button(event: *mut EiEvent) -> u32;
+    fn ei_event_button_get_is_press(event: *mut EiEvent) -> bool;
+    fn ei_event_scroll_get_discrete_dx(event: *mut EiEvent) -> i32;
+    fn ei_event_scroll_get_discrete_dy(event: *mut EiEvent) -> i32;
+    fn ei_event_keyboard_get_key(event: *mut EiEvent) -> u32;
+    fn ei_event_keyboard_get_key_is_press(event: *mut EiEvent) -> bool;
+    fn ei_event_touch_get_id(event: *mut EiEvent) -> u32;
+    fn ei_event_touch_get_x(event: *mut EiEvent) -> f64;
+    fn ei_event_touch_get_y(event: *mut EiEvent) -> f64;
+}
+
+/// Receives the input captured through the InputCapture portal from the compositor, using
+/// libei, and translates it into evdev events.
+///
+/// Created with [`InputCapture::connect_to_eis`](super::InputCapture::connect_to_eis), or
+/// [`EiReceiver::new`] for a file descriptor obtained some other way. The events of all
+/// captured devices are merged into one stream:
+///
+/// - key and button presses become `EV_KEY` events, as libei uses evdev key codes,
+/// - relative pointer motion becomes `REL_X` and `REL_Y`, keeping the fractional remainders
+///   for the next motion, and absolute motion becomes `ABS_X` and `ABS_Y` in logical pixels,
+/// - discrete scrolling becomes `REL_WHEEL_HI_RES` and `REL_HWHEEL_HI_RES`, along with
+///   `REL_WHEEL` and `REL_HWHEEL` for every full detent, while smooth scrolling is dropped,
+/// - touches become type B multitouch events (`ABS_MT_SLOT`, `ABS_MT_TRACKING_ID` and
+///   `ABS_MT_POSITION_X`/`Y`),
+/// - and the end of each frame becomes a `SYN_REPORT`.
+///
+/// The timestamps of the events are taken from libei, which uses the monotonic clock (see
+/// [`EventClock::Monotonic`](crate::EventClock::Monotonic)).
+#[derive(Debug)]
+pub struct EiReceiver {
+    ei: NonNull<Ei>,
+    events: Vec<InputEvent>,
+    /// The fractional parts of relative motion not reported yet.
+    motion: (f64, f64),
+    /// The hi-res scroll distances not reported as full detents yet.
+    wheel: (i32, i32),
+    /// The touch IDs, indexed by slot.
+    slots: Vec<Option<u32>>,
+    disconnected: bool,
+}
+
+// libei contexts aren't shared between threads, and all access goes through `&mut self`.
+unsafe impl Send for EiReceiver {}
+
+impl EiReceiver {
+    /// Create a receiver for the connection to an EIS implementation on `fd`, binding all
+    /// capabilities of the seats it offers.
+    pub fn new(fd: OwnedFd) -> io::Result<Self> {
+        let ei =
+            NonNull::new(unsafe { ei_new_receiver(std::ptr::null_mut()) }).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::Other, "failed to create libei context")
+            })?;
+        let receiver = Self {
+            ei,
+            events: Vec::new(),
+            motion: (0.0, 0.0),
+            wheel: (0, 0),
+            slots: Vec::new(),
+            disconnected: false,
+        };
+        let name = CString::new("evdev-rs").unwrap();
+        unsafe { ei_configure_name(ei.as_ptr(), name.as_ptr()) };
+        // libei owns the file descriptor from now on
+        let res = unsafe { ei_setup_backend_fd(ei.as_ptr(), fd.into_raw_fd()) };
+        if res < 0 {
+            return Err(io::Error::from_raw_os_error(-res));
+        }
+        Ok(receiver)
+    }
+
+    /// Fetches the events that are available, blocking until there are some.
+    ///
+    /// Fails with [`ConnectionReset`](io::ErrorKind::ConnectionReset) once the compositor closed
+    /// the connection, e.g. because the input capture session ended.
+    pub fn fetch_events(&mut self) -> io::Result<impl Iterator<Item = InputEvent> + '_> {
+        loop {
+            unsafe { ei_dispatch(self.ei.as_ptr()) };
+            self.translate_events();
+            if !self.events.is_empty() {
+                break;
+            }
+            if self.disconnected {
+                return Err(io::Error::new(
+                    io::ErrorKind::ConnectionReset,
+                    "the EIS implementation disconnected",
+                ));
+            }
+            crate::poll_readable(self.as_fd(), Duration::MAX)?;
+        }
+        Ok(self.events.drain(..))
+    }
+
+    fn translate_events(&mut self) {
+        loop {
+            let event = unsafe { ei_get_event(self.ei.as_ptr()) };
+            if event.is_null() {
+                return;
+            }
+            unsafe {
+                self.translate(event);
+                ei_event_unref(event);
+            }
+        }
+    }
+
+    unsafe fn translate(&mut self, event: *mut EiEvent) {
+        let micros = ei_event_get_time(event);
+        let time = timeval {
+            tv_sec: (micros / 1_000_000) as _,
+            tv_usec: (micros % 1_000_000) as _,
+        };
+        let mut push = |ty: EventType, code: u16, value: i32| {
+            self.events.push(InputEvent::from(input_event {
+                time,
+                type_: ty.0,
+                code,
+                value,
+            }))
+        };
+        match ei_event_get_type(event) {
+            EI_EVENT_SEAT_ADDED => ei_seat_bind_capabilities(
+                ei_event_get_seat(event),
+                EI_DEVICE_CAP_POINTER,
+                EI_DEVICE_CAP_POINTER_ABSOLUTE,
+                EI_DEVICE_CAP_KEYBOARD,
+                EI_DEVICE_CAP_TOUCH,
+                EI_DEVICE_CAP_SCROLL,
+                EI_DEVICE_CAP_BUTTON,
+                0 as c_uint,
+            ),
+            EI_EVENT_DISCONNECT => self.disconnected = true,
+            EI_EVENT_FRAME => push(
+                EventType::SYNCHRONIZATION,
+                SynchronizationCode::SYN_REPORT.0,
+                0,
+            ),
+            EI_EVENT_POINTER_MOTION => {
+                let x = self.motion.0 + ei_event_pointer_get_dx(event);
+                let y = self.motion.1 + ei_event_pointer_get_dy(event);
+                let (dx, dy) = (x.trunc(), y.trunc());
+                self.motion = (x - dx, y - dy);
+                if dx != 0.0 {
+                    push(EventType::RELATIVE, RelativeAxisCode::REL_X.0, dx as i32);
+                }
+                if dy != 0.0 {
+                    push(EventType::RELATIVE, RelativeAxisCode::REL_Y.0, dy as i32);
+                }
+            }
+            EI_EVENT_POINTER_MOTION_ABSOLUTE => {
+                let x = ei_event_pointer_get_absolute_x(event).round() as i32;
+                let y = ei_event_pointer_get_absolute_y(event).round() as i32;
+                push(EventType::ABSOLUTE, AbsoluteAxisCode::ABS_X.0, x);
+                push(EventType::ABSOLUTE, AbsoluteAxisCode::ABS_Y.0, y);
+            }
+            EI_EVENT_BUTTON_BUTTON => {
+                let button = ei_event_button_get_button(event) as u16;
+                let pressed = ei_event_button_get_is_press(event);
+                push(EventType::KEY, button, pressed.into());
+            }
+            EI_EVENT_KEYBOARD_KEY => {
+                let key = ei_event_keyboard_get_key(event) as u16;
+                let pressed = ei_event_keyboard_get_key_is_press(event);
+                push(EventType::KEY, key, pressed.into());
+            }
+            EI_EVENT_SCROLL_DISCRETE => {
+                // libei scrolls down for positive values, like libinput, evdev scrolls up
+                let dx = ei_event_scroll_get_discrete_dx(event);
+                let dy = -ei_event_scroll_get_discrete_dy(event);
+                let axes = [
+                    (
+                        dy,
+                        RelativeAxisCode::REL_WHEEL,
+                        RelativeAxisCode::REL_WHEEL_HI_RES,
+                    ),
+                    (
+                        dx,
+                        RelativeAxisCode::REL_HWHEEL,
+                        RelativeAxisCode::REL_HWHEEL_HI_RES,
+                    ),
+                ];
+                for (i, (delta, axis, hi_res)) in axes.into_iter().enumerate() {
+                    if delta == 0 {
+                        continue;
+                    }
+                    push(EventType::RELATIVE, hi_res.0, delta);
+                    let total = if i == 0 {
+                        &mut self.wheel.0
+                    } else {
+                        &mut self.wheel.1
+                    };
+                    *total += delta;
+                    let detents = *total / crate::HI_RES_UNITS_PER_DETENT;
+                    if detents != 0 {
+                        *total -= detents * crate::HI_RES_UNITS_PER_DETENT;
+                        push(EventType::RELATIVE, axis.0, detents);
+                    }
+                }
+            }
+            EI_EVENT_TOUCH_DOWN | EI_EVENT_TOUCH_MOTION | EI_EVENT_TOUCH_UP => {
+                let ty = ei_event_get_type(event);
+                let id = ei_event_touch_get_id(event);
+                let slot = match self.slots.iter().position(|&s| s == Some(id)) {
+                    Some(slot) => slot,
+                    None if ty == EI_EVENT_TOUCH_DOWN => {
+                        match self.slots.iter().position(Option::is_none) {
+                            Some(slot) => slot,
+                            None => {
+                                self.slots.push(None);
+                                self.slots.len() - 1
+                            }
+                        }
+                    }
+                    None => return,
+                };
+                push(
+                    EventType::ABSOLUTE,
+                    AbsoluteAxisCode::ABS_MT_SLOT.0,
+                    slot as i32,
+                );
+                if ty == EI_EVENT_TOUCH_UP {
+                    self.slots[slot] = None;
+                    push(
+                        EventType::ABSOLUTE,
+                        AbsoluteAxisCode::ABS_MT_TRACKING_ID.0,
+                        -1,
+                    );
+                    return;
+                }
+                if ty == EI_EVENT_TOUCH_DOWN {
+                    self.slots[slot] = Some(id);
+                    let tracking_id = (id & i32::MAX as u32) as i32;
+                    push(
+                        EventType::ABSOLUTE,
+                        AbsoluteAxisCode::ABS_MT_TRACKING_ID.0,
+                        tracking_id,
+                    );
+                }
+                let x = ei_event_touch_get_x(event).round() as i32;
+                let y = ei_event_touch_get_y(event).round() as i32;
+                push(
+                    EventType::ABSOLUTE,
+                    AbsoluteAxisCode::ABS_MT_POSITION_X.0,
+                    x,
+                );
+                push(
+                    EventType::ABSOLUTE,
+                    AbsoluteAxisCode::ABS_MT_POSITION_Y.0,
+                    y,
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+impl AsFd for EiReceiver {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // the file descriptor stays open as long as the context
+        unsafe { BorrowedFd::borrow_raw(ei_get_fd(self.ei.as_ptr())) }
+    }
+}
+
+impl Drop for EiReceiver {
+    fn drop(&mut self) {
+        unsafe { ei_unref(self.ei.as_ptr()) };
+    }
+}
@@ -0,0 +1,472 @@
+//! Capturing input through the `org.freedesktop.portal.InputCapture` desktop portal.
+//!
+//! On Wayland, applications usually can't open `/dev/input` devices, and even when they can,
+//! the compositor doesn't expect them to steal its input. The InputCapture portal instead lets
+//! an unprivileged application ask the compositor for the input of the seat, e.g. once the
+//! pointer crosses a barrier at the edge of the screen, as done by software sharing a keyboard
+//! and mouse between computers. The compositor then sends the input through libei, which
+//! [`EiReceiver`] translates into evdev events, so the same code can handle them as events read
+//! from a [`Device`](crate::Device), e.g. through [`InputEvent::destructure`].
+//!
+//! This module requires the `portal` feature, which talks to the portal over the D-Bus session
+//! bus with [`zbus`], and linking against libei.
+//!
+//! ```no_run
+//! use evdev::portal::{Barrier, CaptureCapabilities, InputCapture};
+//! use evdev::EventSummary;
+//!
+//! let mut capture = InputCapture::create_session("", CaptureCapabilities::all())?;
+//! let zones = capture.zones()?;
+//! // a barrier along the top edge of the first screen
+//! let zone = zones[0];
+//! let top = Barrier::new(1, zone.x, zone.y, zone.x + zone.width as i32 - 1, zone.y);
+//! capture.set_pointer_barriers(&[top])?;
+//! let mut receiver = capture.connect_to_eis()?;
+//! capture.enable()?;
+//! loop {
+//!     for ev in receiver.fetch_events()? {
+//!         if let EventSummary::Key(_, key, value) = ev.destructure() {
+//!             println!("{key:?} {value}");
+//!         }
+//!     }
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! [`InputEvent::destructure`]: crate::InputEvent::destructure
+
+mod ei;
+
+pub use ei::EiReceiver;
+
+use std::collections::HashMap;
+use std::io;
+use std::ops::BitOr;
+use std::os::fd::OwnedFd;
+use zbus::blocking::{Connection, MessageIterator};
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, StructureBuilder, Value};
+use zbus::Message;
+
+const DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const PATH: &str = "/org/freedesktop/portal/desktop";
+const INTERFACE: &str = "org.freedesktop.portal.InputCapture";
+
+/// The options of a portal method, a D-Bus `a{sv}`.
+type Options<'a> = HashMap<&'a str, Value<'a>>;
+/// The results of a `Request`.
+type Results = HashMap<String, OwnedValue>;
+
+/// The kinds of input a session can capture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CaptureCapabilities(pub u32);
+
+impl CaptureCapabilities {
+    pub const KEYBOARD: Self = Self(1);
+    pub const POINTER: Self = Self(2);
+    pub const TOUCHSCREEN: Self = Self(4);
+
+    /// Returns all kinds of input.
+    pub fn all() -> Self {
+        Self::KEYBOARD | Self::POINTER | Self::TOUCHSCREEN
+    }
+
+    /// Returns `true` if all kinds of input in `other` are included.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for CaptureCapabilities {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+/// A region of the desktop, usually a screen, in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Zone {
+    pub width: u32,
+    pub height: u32,
+    pub x: i32,
+    pub y: i32,
+}
+
+/// A pointer barrier, a horizontal or vertical line along the edge of a [`Zone`] that starts
+/// the capture when the pointer moves across it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Barrier {
+    /// The ID of the barrier, which must not be zero. Reported in [`CaptureSignal::Activated`].
+    pub id: u32,
+    pub x1: i32,
+    pub y1: i32,
+    pub x2: i32,
+    pub y2: i32,
+}
+
+impl Barrier {
+    pub fn new(id: u32, x1: i32, y1: i32, x2: i32, y2: i32) -> Self {
+        Self { id, x1, y1, x2, y2 }
+    }
+}
+
+/// A change of the state of an input capture session, see [`InputCapture::wait_signal`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum CaptureSignal {
+    /// The capture started, and input is now sent to the [`EiReceiver`].
+    Activated {
+        /// Identifies this activation for [`InputCapture::release`].
+        activation_id: Option<u32>,
+        /// The position of the pointer when the capture started.
+        cursor_position: Option<(f64, f64)>,
+        /// The barrier that the pointer crossed, if any.
+        barrier_id: Option<u32>,
+    },
+    /// The capture stopped, e.g. because the compositor took back the input.
+    Deactivated { activation_id: Option<u32> },
+    /// The session was disabled and has to be [enabled](InputCapture::enable) again.
+    Disabled,
+    /// The zones changed, invalidating the pointer barriers, which have to be set again.
+    ZonesChanged,
+}
+
+/// An input capture session of the InputCapture portal.
+///
+/// Creating the session asks the user for permission. A session then goes through these
+/// steps, see the [module documentation](self) for an example:
+///
+/// 1. [`zones`](Self::zones) returns the regions of the desktop,
+/// 2. [`set_pointer_barriers`](Self::set_pointer_barriers) sets up barriers along their edges,
+/// 3. [`connect_to_eis`](Self::connect_to_eis) returns the [`EiReceiver`] for the input,
+/// 4. [`enable`](Self::enable) arms the barriers, and once the pointer crosses one, the capture
+///    is [activated](CaptureSignal::Activated) and input goes to the receiver,
+/// 5. [`release`](Self::release) gives the input back to the compositor.
+///
+/// The session is closed when this is dropped.
+pub struct InputCapture {
+    conn: Connection,
+    signals: MessageIterator,
+    session: OwnedObjectPath,
+    capabilities: CaptureCapabilities,
+    zone_set: Option<u32>,
+    tokens: u32,
+}
+
+impl InputCapture {
+    /// Create a session capturing the given kinds of input.
+    ///
+    /// `parent_window` identifies the window of the application for the permission dialog,
+    /// e.g. `wayland:<handle>`, or is empty. Fails with
+    /// [`PermissionDenied`](io::ErrorKind::PermissionDenied) if the user denies the request.
+    pub fn create_session(
+        parent_window: &str,
+        capabilities: CaptureCapabilities,
+    ) -> io::Result<Self> {
+        let conn = Connection::session().map_err(dbus_error)?;
+        let rule = format!("type='signal',interface='{INTERFACE}',path='{PATH}'");
+        let signals =
+            MessageIterator::for_match_rule(rule.as_str(), &conn, None).map_err(dbus_error)?;
+        let mut capture = Self {
+            conn,
+            signals,
+            session: OwnedObjectPath::default(),
+            capabilities,
+            zone_set: None,
+            tokens: 0,
+        };
+        let session_token = capture.token();
+        let options = Options::from([
+            ("session_handle_token", Value::from(session_token.as_str())),
+            ("capabilities", Value::from(capabilities.0)),
+        ]);
+        let results =
+            capture.request("CreateSession", vec![Value::from(parent_window)], options)?;
+        // the handle is a string, though some portals send an object path
+        let session = results
+            .get("session_handle")
+            .and_then(|handle| match &**handle {
+                Value::Str(handle) => ObjectPath::try_from(handle.as_str()).ok(),
+                Value::ObjectPath(handle) => Some(handle.to_owned()),
+                _ => None,
+            });
+        capture.session = session
+            .ok_or_else(|| invalid_reply("CreateSession"))?
+            .into();
+        if let Some(granted) = results.get("capabilities").and_then(as_u32) {
+            capture.capabilities = CaptureCapabilities(granted);
+        }
+        Ok(capture)
+    }
+
+    /// Returns the kinds of input the session captures, which may be fewer than requested.
+    pub fn capabilities(&self) -> CaptureCapabilities {
+        self.capabilities
+    }
+
+    /// Returns the zones of the desktop, which pointer barriers must lie on the edges of.
+    pub fn zones(&mut self) -> io::Result<Vec<Zone>> {
+        let results = self.request("GetZones", vec![self.session_path()], Options::new())?;
+        self.zone_set = results.get("zone_set").and_then(as_u32);
+        let zones = results
+            .get("zones")
+            .and_then(|zones| zones.try_clone().ok())
+            .and_then(|zones| Vec::<(u32, u32, i32, i32)>::try_from(zones).ok())
+            .ok_or_else(|| invalid_reply("GetZones"))?;
+        Ok(zones
+            .into_iter()
+            .map(|(width, height, x, y)| Zone {
+                width,
+                height,
+                x,
+                y,
+            })
+            .collect())
+    }
+
+    /// Set up pointer barriers, replacing any set up before. Returns the IDs of the barriers
+    /// the compositor rejected, e.g. because they don't lie on the edge of a zone.
+    ///
+    /// The zones must have been queried with [`zones`](Self::zones) first, and again after
+    /// they [changed](CaptureSignal::ZonesChanged).
+    pub fn set_pointer_barriers(&mut self, barriers: &[Barrier]) -> io::Result<Vec<u32>> {
+        let zone_set = match self.zone_set {
+            Some(zone_set) => zone_set,
+            None => {
+                self.zones()?;
+                self.zone_set.unwrap_or(0)
+            }
+        };
+        let barriers: Vec<Options<'_>> = barriers
+            .iter()
+            .map(|b| {
+                Options::from([
+                    ("barrier_id", Value::from(b.id)),
+                    ("position", Value::from((b.x1, b.y1, b.x2, b.y2))),
+                ])
+            })
+            .collect();
+        // the barriers and zone set come after the options
+        let results = self.request_with(
+            "SetPointerBarriers",
+            vec![self.session_path()],
+            Options::new(),
+            vec![Value::from(barriers), Value::from(zone_set)],
+        )?;
+        Ok(results
+            .get("failed_barriers")
+            .and_then(|ids| ids.try_clone().ok())
+            .and_then(|ids| Vec::<u32>::try_from(ids).ok())
+            .unwrap_or_default())
+    }
+
+    /// Connect to the compositor to receive the captured input.
+    pub fn connect_to_eis(&mut self) -> io::Result<EiReceiver> {
+        let reply = self.call("ConnectToEIS", self.with_session(Options::new()))?;
+        let fd: zbus::zvariant::OwnedFd = reply
+            .body()
+            .deserialize()
+            .map_err(|_| invalid_reply("ConnectToEIS"))?;
+        EiReceiver::new(OwnedFd::from(fd))
+    }
+
+    /// Arm the pointer barriers, allowing the capture to be activated.
+    pub fn enable(&mut self) -> io::Result<()> {
+        self.call("Enable", self.with_session(Options::new()))
+            .map(drop)
+    }
+
+    /// Disarm the pointer barriers, and stop the capture if it is active.
+    pub fn disable(&mut self) -> io::Result<()> {
+        self.call("Disable", self.with_session(Options::new()))
+            .map(drop)
+    }
+
+    /// Stop the capture, giving the input back to the compositor, and optionally move the
+    /// pointer to `cursor_position`, e.g. back to where it crossed the barrier.
+    pub fn release(
+        &mut self,
+        activation_id: Option<u32>,
+        cursor_position: Option<(f64, f64)>,
+    ) -> io::Result<()> {
+        let mut options = Options::new();
+        if let Some(id) = activation_id {
+            options.insert("activation_id", Value::from(id));
+        }
+        if let Some(position) = cursor_position {
+            options.insert("cursor_position", Value::from(position));
+        }
+        self.call("Release", self.with_session(options)).map(drop)
+    }
+
+    /// Waits for the next change of the state of the session.
+    pub fn wait_signal(&mut self) -> io::Result<CaptureSignal> {
+        loop {
+            let message = match self.signals.next() {
+                Some(message) => message.map_err(dbus_error)?,
+                None => return Err(io::ErrorKind::UnexpectedEof.into()),
+            };
+            let (session, options): (OwnedObjectPath, Results) = match message.body().deserialize()
+            {
+                Ok(body) => body,
+                Err(_) => continue,
+            };
+            if session != self.session {
+                continue;
+            }
+            let activation_id = options.get("activation_id").and_then(as_u32);
+            let header = message.header();
+            return Ok(match header.member().map(|member| member.as_str()) {
+                Some("Activated") => CaptureSignal::Activated {
+                    activation_id,
+                    cursor_position: options
+                        .get("cursor_position")
+                        .and_then(|pos| <(f64, f64)>::try_from(pos.try_clone().ok()?).ok()),
+                    barrier_id: options.get("barrier_id").and_then(as_u32),
+                },
+                Some("Deactivated") => CaptureSignal::Deactivated { activation_id },
+                Some("Disabled") => CaptureSignal::Disabled,
+                _ => {
+                    self.zone_set = None;
+                    CaptureSignal::ZonesChanged
+                }
+            });
+        }
+    }
+
+    fn session_path(&self) -> Value<'static> {
+        Value::from(self.session.clone())
+    }
+
+    /// Returns the arguments of a method taking the session and options.
+    fn with_session<'a>(&'a self, options: Options<'a>) -> Vec<Value<'a>> {
+        vec![self.session_path(), Value::from(options)]
+    }
+
+    fn token(&mut self) -> String {
+        self.tokens += 1;
+        format!("evdev{}_{}", std::process::id(), self.tokens)
+    }
+
+    fn call(&self, method: &str, args: Vec<Value<'_>>) -> io::Result<Message> {
+        let body = args
+            .into_iter()
+            .fold(StructureBuilder::new(), StructureBuilder::append_field)
+            .build()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        self.conn
+            .call_method(Some(DESTINATION), PATH, Some(INTERFACE), method, &body)
+            .map_err(dbus_error)
+    }
+
+    /// Calls a method that answers through a `Request` object, returning the results of its
+    /// `Response` signal.
+    fn request<'a>(
+        &mut self,
+        method: &str,
+        args: Vec<Value<'a>>,
+        options: Options<'a>,
+    ) -> io::Result<Results> {
+        self.request_with(method, args, options, vec![])
+    }
+
+    /// Like [`request`](Self::request), for methods with arguments after the options.
+    fn request_with<'a>(
+        &mut self,
+        method: &str,
+        mut args: Vec<Value<'a>>,
+        mut options: Options<'a>,
+        trailing: Vec<Value<'a>>,
+    ) -> io::Result<Results> {
+        let token = self.token();
+        // the path of the request is known in advance, so that no response can be missed
+        let sender = match self.conn.unique_name() {
+            Some(name) => name.as_str()[1..].replace('.', "_"),
+            None => String::new(),
+        };
+        let path = format!("{PATH}/request/{sender}/{token}");
+        let mut responses = self.responses(&path)?;
+        options.insert("handle_token", Value::from(token));
+        args.push(Value::from(options));
+        args.extend(trailing);
+        let reply = self.call(method, args)?;
+        // older portals return a different path
+        if let Ok(handle) = reply.body().deserialize::<OwnedObjectPath>() {
+            if handle.as_str() != path {
+                responses = self.responses(handle.as_str())?;
+            }
+        }
+        let response = match responses.next() {
+            Some(response) => response.map_err(dbus_error)?,
+            None => return Err(io::ErrorKind::UnexpectedEof.into()),
+        };
+        let (status, results): (u32, Results) = response
+            .body()
+            .deserialize()
+            .map_err(|_| invalid_reply(method))?;
+        match status {
+            0 => Ok(results),
+            1 => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{method} was cancelled"),
+            )),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("{method} failed"),
+            )),
+        }
+    }
+
+    /// Returns the `Response` signals of the request at `path`.
+    fn responses(&self, path: &str) -> io::Result<MessageIterator> {
+        let rule = format!(
+            "type='signal',interface='org.freedesktop.portal.Request',member='Response',path='{path}'"
+        );
+        MessageIterator::for_match_rule(rule.as_str(), &self.conn, None).map_err(dbus_error)
+    }
+}
+
+impl std::fmt::Debug for InputCapture {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InputCapture")
+            .field("session", &self.session.as_str())
+            .field("capabilities", &self.capabilities)
+            .field("zone_set", &self.zone_set)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for InputCapture {
+    fn drop(&mut self) {
+        // the session was never created
+        if self.session.as_str() == "/" {
+            return;
+        }
+        let message = Message::method_call(self.session.as_ref(), "Close")
+            .and_then(|builder| builder.destination(DESTINATION))
+            .and_then(|builder| builder.interface("org.freedesktop.portal.Session"))
+            .and_then(|builder| builder.build(&()));
+        if let Ok(message) = message {
+            let _ = self.conn.send(&message);
+        }
+    }
+}
+
+fn as_u32(value: &OwnedValue) -> Option<u32> {
+    value.downcast_ref().ok()
+}
+
+fn dbus_error(e: zbus::Error) -> io::Error {
+    match e {
+        zbus::Error::InputOutput(e) => io::Error::new(e.kind(), e),
+        e => io::Error::new(io::ErrorKind::Other, e),
+    }
+}
+
+fn invalid_reply(method: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unexpected reply to {method}"),
+    )
+}
@@ -0,0 +1,334 @@
+//! Low-level access to the evdev and uinput ioctls.
+//!
+//! These are thin, typed wrappers around the requests of `linux/input.h` and `linux/uinput.h`,
+//! operating on any file descriptor. They don't keep any state, so nothing stops them from
+//! contradicting what a [`Device`](crate::Device) or [`VirtualDevice`](crate::uinput::VirtualDevice)
+//! using the same file descriptor has cached. Prefer the high-level API where it covers your use
+//! case, and use these for what it doesn't cover yet.
+//!
+//! Buffers for bitmaps are filled like the kernel does: bit `n` is bit `n % 8` of byte `n / 8`.
+//! Functions taking a buffer return the number of bytes the kernel wrote.
+//!
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! use evdev::{raw, EventType};
+//! use std::os::fd::AsFd;
+//!
+//! let file = std::fs::File::open("/dev/input/event0")?;
+//! let mut keys = [0u8; 0x300 / 8];
+//! raw::event_bits(file.as_fd(), EventType::KEY, &mut keys)?;
+//! let count: u32 = keys.iter().map(|b| b.count_ones()).sum();
+//! println!("{:?} supports {count} keys", raw::name(file.as_fd())?);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::compat::{input_absinfo, input_id, uinput_setup, UINPUT_MAX_NAME_SIZE};
+use crate::sys;
+use crate::{
+    AbsInfo, AbsoluteAxisCode, AutoRepeat, EventType, FFEffectData, InputId, PropType,
+    UinputAbsSetup,
+};
+use nix::sys::ioctl::ioctl_param_type;
+use nix::{convert_ioctl_res, request_code_read};
+use std::io;
+use std::os::fd::{AsRawFd, BorrowedFd};
+
+/// Size of the buffer used for reading strings, the kernel truncates longer ones.
+const STRING_BUFFER_SIZE: usize = 256;
+
+fn read_string(
+    fd: BorrowedFd<'_>,
+    f: unsafe fn(libc::c_int, &mut [u8]) -> nix::Result<libc::c_int>,
+) -> io::Result<Vec<u8>> {
+    let mut buf = vec![0; STRING_BUFFER_SIZE];
+    let len = unsafe { f(fd.as_raw_fd(), &mut buf)? } as usize;
+    buf.truncate(len.min(STRING_BUFFER_SIZE));
+    // the returned length includes the trailing nul
+    while buf.last() == Some(&0) {
+        buf.pop();
+    }
+    Ok(buf)
+}
+
+/// `EVIOCGVERSION`: get the version of the evdev protocol implemented by the driver.
+pub fn version(fd: BorrowedFd<'_>) -> io::Result<i32> {
+    let mut version = 0;
+    unsafe { sys::eviocgversion(fd.as_raw_fd(), &mut version)? };
+    Ok(version)
+}
+
+/// `EVIOCGID`: get the bus type, vendor, product and version of the device.
+pub fn id(fd: BorrowedFd<'_>) -> io::Result<InputId> {
+    let mut id = input_id {
+        bustype: 0,
+        vendor: 0,
+        product: 0,
+        version: 0,
+    };
+    unsafe { sys::eviocgid(fd.as_raw_fd(), &mut id)? };
+    Ok(InputId::from(id))
+}
+
+/// `EVIOCGNAME`: get the name of the device, without the trailing nul.
+pub fn name(fd: BorrowedFd<'_>) -> io::Result<Vec<u8>> {
+    read_string(fd, sys::eviocgname)
+}
+
+/// `EVIOCGPHYS`: get the physical location of the device, without the trailing nul.
+pub fn phys(fd: BorrowedFd<'_>) -> io::Result<Vec<u8>> {
+    read_string(fd, sys::eviocgphys)
+}
+
+/// `EVIOCGUNIQ`: get the unique identifier of the device, without the trailing nul.
+pub fn uniq(fd: BorrowedFd<'_>) -> io::Result<Vec<u8>> {
+    read_string(fd, sys::eviocguniq)
+}
+
+/// `EVIOCGPROP`: get the bitmap of device properties.
+pub fn properties(fd: BorrowedFd<'_>, buf: &mut [u8]) -> io::Result<usize> {
+    Ok(unsafe { sys::eviocgprop(fd.as_raw_fd(), buf)? } as usize)
+}
+
+/// `EVIOCGBIT`: get the bitmap of codes of an event type supported by the device.
+///
+/// With [`EventType::SYNCHRONIZATION`] (type 0), this gets the bitmap of supported event types.
+pub fn event_bits(fd: BorrowedFd<'_>, event_type: EventType, buf: &mut [u8]) -> io::Result<usize> {
+    let res = unsafe {
+        convert_ioctl_res!(libc::ioctl(
+            fd.as_raw_fd(),
+            request_code_read!(b'E', 0x20 + u32::from(event_type.0), buf.len()),
+            buf.as_mut_ptr()
+        ))
+    };
+    Ok(res? as usize)
+}
+
+/// `EVIOCGKEY`: get the bitmap of keys that are currently pressed.
+pub fn key_state(fd: BorrowedFd<'_>, buf: &mut [u8]) -> io::Result<usize> {
+    Ok(unsafe { sys::eviocgkey(fd.as_raw_fd(), buf)? } as usize)
+}
+
+/// `EVIOCGLED`: get the bitmap of LEDs that are currently lit.
+pub fn led_state(fd: BorrowedFd<'_>, buf: &mut [u8]) -> io::Result<usize> {
+    Ok(unsafe { sys::eviocgled(fd.as_raw_fd(), buf)? } as usize)
+}
+
+/// `EVIOCGSND`: get the bitmap of sounds that are currently playing.
+pub fn sound_state(fd: BorrowedFd<'_>, buf: &mut [u8]) -> io::Result<usize> {
+    Ok(unsafe { sys::eviocgsnd(fd.as_raw_fd(), buf)? } as usize)
+}
+
+/// `EVIOCGSW`: get the bitmap of switches that are currently on.
+pub fn switch_state(fd: BorrowedFd<'_>, buf: &mut [u8]) -> io::Result<usize> {
+    Ok(unsafe { sys::eviocgsw(fd.as_raw_fd(), buf)? } as usize)
+}
+
+/// `EVIOCGABS`: get the current value and limits of an absolute axis.
+pub fn abs_info(fd: BorrowedFd<'_>, axis: AbsoluteAxisCode) -> io::Result<AbsInfo> {
+    if axis.0 >= AbsoluteAxisCode::COUNT as u16 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "absolute axis code out of range",
+        ));
+    }
+    let mut info = input_absinfo {
+        value: 0,
+        minimum: 0,
+        maximum: 0,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+    unsafe { sys::eviocgabs(fd.as_raw_fd(), axis.0.into(), &mut info)? };
+    Ok(AbsInfo(info))
+}
+
+/// `EVIOCGMTSLOTS`: get the values of a multitouch axis for as many slots as `values` holds.
+pub fn mt_slots(fd: BorrowedFd<'_>, axis: AbsoluteAxisCode, values: &mut [i32]) -> io::Result<()> {
+    // the request takes the axis code followed by the values, all as 32-bit integers
+    let mut buf = vec![0i32; values.len() + 1];
+    buf[0] = axis.0.into();
+    let bytes =
+        unsafe { std::slice::from_raw_parts_mut(buf.as_mut_ptr().cast::<u8>(), buf.len() * 4) };
+    unsafe { sys::eviocgmtslots(fd.as_raw_fd(), bytes)? };
+    values.copy_from_slice(&buf[1..]);
+    Ok(())
+}
+
+/// `EVIOCGREP`: get the auto-repeat settings of the device.
+pub fn auto_repeat(fd: BorrowedFd<'_>) -> io::Result<AutoRepeat> {
+    let mut rep = [0; 2];
+    unsafe { sys::eviocgrep(fd.as_raw_fd(), &mut rep)? };
+    Ok(AutoRepeat {
+        delay: rep[0],
+        period: rep[1],
+    })
+}
+
+/// `EVIOCSREP`: set the auto-repeat settings of the device.
+pub fn set_auto_repeat(fd: BorrowedFd<'_>, repeat: &AutoRepeat) -> io::Result<()> {
+    unsafe { sys::eviocsrep(fd.as_raw_fd(), &[repeat.delay, repeat.period])? };
+    Ok(())
+}
+
+/// `EVIOCGRAB`: grab the device for exclusive access, or release it.
+pub fn grab(fd: BorrowedFd<'_>, grab: bool) -> io::Result<()> {
+    unsafe { sys::eviocgrab(fd.as_raw_fd(), grab as ioctl_param_type)? };
+    Ok(())
+}
+
+/// `EVIOCREVOKE`: revoke access to the device through this file descriptor and all of its
+/// duplicates. Only reads and writes fail afterwards, closing the file descriptor is still
+/// required.
+pub fn revoke(fd: BorrowedFd<'_>) -> io::Result<()> {
+    unsafe { sys::eviocrevoke(fd.as_raw_fd(), 0)? };
+    Ok(())
+}
+
+/// `EVIOCSCLOCKID`: set the clock used for event timestamps, such as `libc::CLOCK_MONOTONIC`.
+pub fn set_clock_id(fd: BorrowedFd<'_>, clock: libc::clockid_t) -> io::Result<()> {
+    unsafe { sys::eviocsclockid(fd.as_raw_fd(), &clock)? };
+    Ok(())
+}
+
+/// `EVIOCGEFFECTS`: get the number of force feedback effects the device can hold at once.
+pub fn max_ff_effects(fd: BorrowedFd<'_>) -> io::Result<i32> {
+    let mut effects = 0;
+    unsafe { sys::eviocgeffects(fd.as_raw_fd(), &mut effects)? };
+    Ok(effects)
+}
+
+/// `EVIOCSFF`: upload a force feedback effect, returning its id.
+///
+/// With `id` set to `-1` a new effect is created, otherwise the effect with that id is updated.
+/// Unlike [`Device::upload_ff_effect`](crate::Device::upload_ff_effect), the effect isn't
+/// erased automatically, see [`erase_ff_effect`].
+pub fn upload_ff_effect(fd: BorrowedFd<'_>, id: i16, data: FFEffectData) -> io::Result<i16> {
+    let mut effect: sys::ff_effect = data.into();
+    effect.id = id;
+    unsafe { sys::eviocsff(fd.as_raw_fd(), &effect)? };
+    Ok(effect.id)
+}
+
+/// `EVIOCRMFF`: erase a force feedback effect.
+pub fn erase_ff_effect(fd: BorrowedFd<'_>, id: i16) -> io::Result<()> {
+    unsafe { sys::eviocrmff(fd.as_raw_fd(), id as ioctl_param_type)? };
+    Ok(())
+}
+
+/// `UI_GET_VERSION`: get the version of the uinput protocol.
+pub fn uinput_version(fd: BorrowedFd<'_>) -> io::Result<u32> {
+    let mut version = 0;
+    unsafe { sys::ui_get_version(fd.as_raw_fd(), &mut version)? };
+    Ok(version)
+}
+
+/// `UI_GET_SYSNAME`: get the name of the created device in `/sys/devices/virtual/input`, without
+/// the trailing nul.
+pub fn uinput_sysname(fd: BorrowedFd<'_>) -> io::Result<Vec<u8>> {
+    read_string(fd, sys::ui_get_sysname)
+}
+
+/// `UI_SET_EVBIT`: enable an event type on a device that wasn't created yet.
+pub fn uinput_enable_event_type(fd: BorrowedFd<'_>, event_type: EventType) -> io::Result<()> {
+    unsafe { sys::ui_set_evbit(fd.as_raw_fd(), event_type.0 as ioctl_param_type)? };
+    Ok(())
+}
+
+/// `UI_SET_*BIT`: enable a code of an event type on a device that wasn't created yet.
+///
+/// Fails with [`InvalidInput`](io::ErrorKind::InvalidInput) for event types without codes.
+pub fn uinput_enable_code(fd: BorrowedFd<'_>, event_type: EventType, code: u16) -> io::Result<()> {
+    let f = match event_type {
+        EventType::KEY => sys::ui_set_keybit,
+        EventType::RELATIVE => sys::ui_set_relbit,
+        EventType::ABSOLUTE => sys::ui_set_absbit,
+        EventType::MISC => sys::ui_set_mscbit,
+        EventType::LED => sys::ui_set_ledbit,
+        EventType::SOUND => sys::ui_set_sndbit,
+        EventType::FORCEFEEDBACK => sys::ui_set_ffbit,
+        EventType::SWITCH => sys::ui_set_swbit,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "event type has no codes to enable",
+            ))
+        }
+    };
+    unsafe { f(fd.as_raw_fd(), code as ioctl_param_type)? };
+    Ok(())
+}
+
+/// `UI_SET_PROPBIT`: set a property on a device that wasn't created yet.
+pub fn uinput_enable_property(fd: BorrowedFd<'_>, property: PropType) -> io::Result<()> {
+    unsafe { sys::ui_set_propbit(fd.as_raw_fd(), property.0 as ioctl_param_type)? };
+    Ok(())
+}
+
+/// `UI_DEV_SETUP`: set the name, identifiers and number of force feedback effects of a device
+/// that wasn't created yet.
+pub fn uinput_setup(
+    fd: BorrowedFd<'_>,
+    name: &[u8],
+    id: InputId,
+    ff_effects_max: u32,
+) -> io::Result<()> {
+    if name.len() >= UINPUT_MAX_NAME_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "device name too long",
+        ));
+    }
+    let mut setup = uinput_setup {
+        id: id.0,
+        name: [0; UINPUT_MAX_NAME_SIZE],
+        ff_effects_max,
+    };
+    for (dst, &src) in setup.name.iter_mut().zip(name) {
+        *dst = src as libc::c_char;
+    }
+    unsafe { sys::ui_dev_setup(fd.as_raw_fd(), &setup)? };
+    Ok(())
+}
+
+/// `UI_ABS_SETUP`: set up an absolute axis of a device that wasn't created yet.
+pub fn uinput_abs_setup(fd: BorrowedFd<'_>, setup: &UinputAbsSetup) -> io::Result<()> {
+    unsafe { sys::ui_abs_setup(fd.as_raw_fd(), &setup.0)? };
+    Ok(())
+}
+
+/// `UI_DEV_CREATE`: create the device after it was set up.
+pub fn uinput_create(fd: BorrowedFd<'_>) -> io::Result<()> {
+    unsafe { sys::ui_dev_create(fd.as_raw_fd())? };
+    Ok(())
+}
+
+/// `UI_DEV_DESTROY`: destroy the device, which is also done when the file descriptor is closed.
+pub fn uinput_destroy(fd: BorrowedFd<'_>) -> io::Result<()> {
+    unsafe { sys::ui_dev_destroy(fd.as_raw_fd())? };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::fd::AsFd;
+
+    #[test]
+    fn rejects_other_files() {
+        let file = std::fs::File::open("/dev/null").unwrap();
+        let err = version(file.as_fd()).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::ENOTTY));
+        let err = uinput_enable_code(file.as_fd(), EventType::REPEAT, 0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = uinput_setup(
+            file.as_fd(),
+            &[b'a'; 100],
+            InputId::new(crate::BusType::BUS_VIRTUAL, 0, 0, 0),
+            0,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
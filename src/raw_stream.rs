@@ -4,14 +4,17 @@ use std::fs::{File, OpenOptions};
 use std::mem::MaybeUninit;
 use std::os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use std::{io, mem};
 
 use crate::compat::{input_absinfo, input_event, input_id, input_keymap_entry};
 use crate::constants::*;
 use crate::ff::*;
 use crate::{
-    sys, AbsInfo, AttributeSet, AttributeSetRef, AutoRepeat, FFEffect, FFEffectCode, FFEvent,
-    InputEvent, InputId, KeyCode,
+    sys, AbsFix, AbsInfo, AbsQuirks, AttributeSet, AttributeSetRef, AutoRepeat, CapabilitiesReport,
+    DeviceControl, EventClock, FFEffect, FFEffectCode, FFEvent, Frames, InputEvent, InputId,
+    KeyCode,
 };
 
 fn ioctl_get_cstring(
@@ -63,7 +66,10 @@ const INPUT_KEYMAP_BY_INDEX: u8 = 1;
 /// the actual state of the device.
 #[derive(Debug)]
 pub struct RawDevice {
-    fd: OwnedFd,
+    /// Shared with the force feedback effects uploaded through the device, as closing any
+    /// duplicate of it erases all effects of the open file. Only `None` once the device was
+    /// converted into its file descriptor.
+    fd: Option<Arc<OwnedFd>>,
     ty: AttributeSet<EventType>,
     name: Option<String>,
     phys: Option<String>,
@@ -84,59 +90,42 @@ pub struct RawDevice {
     // ff_stat: Option<FFStatus>,
     supported_snd: Option<AttributeSet<SoundCode>>,
     pub(crate) event_buf: Vec<input_event>,
+    read_batch: usize,
+    /// The number of events at the start of `event_buf` that were handed out as a slice.
+    borrowed_events: usize,
     grabbed: bool,
+    uploaded_effects: Arc<Mutex<Vec<u16>>>,
+    cleanup_on_drop: bool,
+    clock: EventClock,
+    /// Corrections applied to the `AbsInfo` read from the kernel.
+    abs_fixes: Vec<(AbsoluteAxisCode, AbsFix)>,
 }
 
-impl RawDevice {
-    /// Opens a device, given its system path.
-    ///
-    /// Paths are typically something like `/dev/input/event0`.
-    #[inline(always)]
-    pub fn open(path: impl AsRef<Path>) -> io::Result<RawDevice> {
-        Self::_open(path.as_ref())
-    }
-
-    fn _open(path: &Path) -> io::Result<RawDevice> {
-        let mut options = OpenOptions::new();
-
-        // Try to load read/write, then fall back to read-only.
-        let fd: OwnedFd = options
-            .read(true)
-            .write(true)
-            .open(path)
-            .or_else(|_| options.write(false).open(path))?
-            .into();
-
-        Self::from_fd(fd)
-    }
+/// The supported events, properties and force feedback effects of a device, as queried from the
+/// kernel.
+struct Capabilities {
+    ty: AttributeSet<EventType>,
+    props: AttributeSet<PropType>,
+    supported_keys: Option<AttributeSet<KeyCode>>,
+    supported_relative: Option<AttributeSet<RelativeAxisCode>>,
+    supported_absolute: Option<AttributeSet<AbsoluteAxisCode>>,
+    supported_switch: Option<AttributeSet<SwitchCode>>,
+    supported_led: Option<AttributeSet<LedCode>>,
+    supported_misc: Option<AttributeSet<MiscCode>>,
+    supported_ff: Option<AttributeSet<FFEffectCode>>,
+    supported_snd: Option<AttributeSet<SoundCode>>,
+    auto_repeat: Option<AutoRepeat>,
+    max_ff_effects: usize,
+}
 
-    /// Opens a device, given an already opened file descriptor
-    pub fn from_fd(fd: OwnedFd) -> io::Result<RawDevice> {
+impl Capabilities {
+    fn query(fd: BorrowedFd<'_>) -> io::Result<Self> {
         let ty = {
             let mut ty = AttributeSet::<EventType>::new();
             unsafe { sys::eviocgbit_type(fd.as_raw_fd(), ty.as_mut_raw_slice())? };
             ty
         };
 
-        let name = ioctl_get_cstring(sys::eviocgname, fd.as_raw_fd()).map(bytes_into_string_lossy);
-        let phys = ioctl_get_cstring(sys::eviocgphys, fd.as_raw_fd()).map(bytes_into_string_lossy);
-        let uniq = ioctl_get_cstring(sys::eviocguniq, fd.as_raw_fd()).map(bytes_into_string_lossy);
-
-        let id = unsafe {
-            let mut id = MaybeUninit::uninit();
-            sys::eviocgid(fd.as_raw_fd(), id.as_mut_ptr())?;
-            id.assume_init()
-        };
-        let mut driver_version: i32 = 0;
-        unsafe {
-            sys::eviocgversion(fd.as_raw_fd(), &mut driver_version)?;
-        }
-        let driver_version = (
-            ((driver_version >> 16) & 0xff) as u8,
-            ((driver_version >> 8) & 0xff) as u8,
-            (driver_version & 0xff) as u8,
-        );
-
         let props = {
             let mut props = AttributeSet::<PropType>::new();
             unsafe { sys::eviocgprop(fd.as_raw_fd(), props.as_mut_raw_slice())? };
@@ -233,15 +222,9 @@ impl RawDevice {
             None
         };
 
-        Ok(RawDevice {
-            fd,
+        Ok(Capabilities {
             ty,
-            name,
-            phys,
-            uniq,
-            id,
             props,
-            driver_version,
             supported_keys,
             supported_relative,
             supported_absolute,
@@ -252,8 +235,124 @@ impl RawDevice {
             supported_snd,
             auto_repeat,
             max_ff_effects,
+        })
+    }
+}
+
+fn open_node(path: &Path) -> io::Result<OwnedFd> {
+    crate::check_supported()?;
+    let mut options = OpenOptions::new();
+
+    // Try to load read/write, then fall back to read-only.
+    Ok(options
+        .read(true)
+        .write(true)
+        .open(path)
+        .or_else(|_| options.write(false).open(path))?
+        .into())
+}
+
+fn lock_exclusive(fd: BorrowedFd<'_>) -> io::Result<()> {
+    if unsafe { libc::flock(fd.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        return Ok(());
+    }
+    let err = io::Error::last_os_error();
+    match err.raw_os_error() {
+        Some(libc::EWOULDBLOCK) => Err(io::Error::new(
+            io::ErrorKind::WouldBlock,
+            "device is locked by another process",
+        )),
+        _ => Err(err),
+    }
+}
+
+impl RawDevice {
+    /// Opens a device, given its system path.
+    ///
+    /// Paths are typically something like `/dev/input/event0`.
+    #[inline(always)]
+    pub fn open(path: impl AsRef<Path>) -> io::Result<RawDevice> {
+        Self::_open(path.as_ref())
+    }
+
+    fn _open(path: &Path) -> io::Result<RawDevice> {
+        Self::from_fd(open_node(path)?)
+    }
+
+    /// Opens a device like [`open`](Self::open), and takes an advisory exclusive lock on it.
+    ///
+    /// Fails with [`io::ErrorKind::WouldBlock`] if another process holds the lock. The lock is
+    /// released when the device is closed. Unlike [`grab`](Self::grab), it doesn't stop anyone
+    /// from reading the device, it merely lets cooperating processes, such as several instances
+    /// of the same daemon, avoid handling a device twice.
+    pub fn open_exclusive(path: impl AsRef<Path>) -> io::Result<RawDevice> {
+        let fd = open_node(path.as_ref())?;
+        lock_exclusive(fd.as_fd())?;
+        Self::from_fd(fd)
+    }
+
+    /// Opens a device, given an already opened file descriptor
+    ///
+    /// This allows using a device opened by a privileged helper process and received over a Unix
+    /// socket. Fails with [`InvalidInput`](io::ErrorKind::InvalidInput) if `fd` isn't an evdev
+    /// file descriptor.
+    pub fn from_fd(fd: OwnedFd) -> io::Result<RawDevice> {
+        crate::check_supported()?;
+        let mut driver_version: i32 = 0;
+        match unsafe { sys::eviocgversion(fd.as_raw_fd(), &mut driver_version) } {
+            Err(nix::Error::ENOTTY | nix::Error::EINVAL) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "not an evdev file descriptor",
+                ))
+            }
+            res => res?,
+        };
+
+        let name = ioctl_get_cstring(sys::eviocgname, fd.as_raw_fd()).map(bytes_into_string_lossy);
+        let phys = ioctl_get_cstring(sys::eviocgphys, fd.as_raw_fd()).map(bytes_into_string_lossy);
+        let uniq = ioctl_get_cstring(sys::eviocguniq, fd.as_raw_fd()).map(bytes_into_string_lossy);
+
+        let id = unsafe {
+            let mut id = MaybeUninit::uninit();
+            sys::eviocgid(fd.as_raw_fd(), id.as_mut_ptr())?;
+            id.assume_init()
+        };
+        let driver_version = (
+            ((driver_version >> 16) & 0xff) as u8,
+            ((driver_version >> 8) & 0xff) as u8,
+            (driver_version & 0xff) as u8,
+        );
+
+        let caps = Capabilities::query(fd.as_fd())?;
+
+        Ok(RawDevice {
+            fd: Some(Arc::new(fd)),
+            ty: caps.ty,
+            name,
+            phys,
+            uniq,
+            id,
+            props: caps.props,
+            driver_version,
+            supported_keys: caps.supported_keys,
+            supported_relative: caps.supported_relative,
+            supported_absolute: caps.supported_absolute,
+            supported_switch: caps.supported_switch,
+            supported_led: caps.supported_led,
+            supported_misc: caps.supported_misc,
+            supported_ff: caps.supported_ff,
+            supported_snd: caps.supported_snd,
+            auto_repeat: caps.auto_repeat,
+            max_ff_effects: caps.max_ff_effects,
             event_buf: Vec::new(),
+            read_batch: crate::EVENT_BATCH_SIZE,
+            borrowed_events: 0,
             grabbed: false,
+            uploaded_effects: Arc::default(),
+            cleanup_on_drop: true,
+            clock: EventClock::Realtime,
+            abs_fixes: Vec::new(),
         })
     }
 
@@ -422,15 +521,33 @@ impl RawDevice {
         self.supported_snd.as_deref()
     }
 
-    /// Read a maximum of `num` events into the internal buffer. If the underlying fd is not
-    /// O_NONBLOCK, this will block.
+    /// Returns the maximum number of events read from the kernel at once.
+    pub fn read_buffer_capacity(&self) -> usize {
+        self.read_batch
+    }
+
+    /// Set the maximum number of events read from the kernel at once. Defaults to 32.
+    ///
+    /// Devices producing many events, such as multitouch screens, can use a larger capacity to
+    /// read them in fewer system calls, while memory-constrained programs can reduce it. The
+    /// capacity is at least 1.
+    pub fn set_read_buffer_capacity(&mut self, capacity: usize) {
+        self.read_batch = capacity.max(1);
+        self.event_buf
+            .shrink_to(self.event_buf.len() + self.read_batch);
+    }
+
+    /// Read a maximum of [`read_buffer_capacity`](Self::read_buffer_capacity) events into the
+    /// internal buffer. If the underlying fd is not O_NONBLOCK, this will block.
     ///
     /// Returns the number of events that were read, or an error.
     pub(crate) fn fill_events(&mut self) -> io::Result<usize> {
         let fd = self.as_raw_fd();
-        self.event_buf.reserve(crate::EVENT_BATCH_SIZE);
+        let borrowed = mem::take(&mut self.borrowed_events).min(self.event_buf.len());
+        self.event_buf.drain(..borrowed);
+        self.event_buf.reserve(self.read_batch);
 
-        let spare_capacity = self.event_buf.spare_capacity_mut();
+        let spare_capacity = &mut self.event_buf.spare_capacity_mut()[..self.read_batch];
         let spare_capacity_size = std::mem::size_of_val(spare_capacity);
 
         // use libc::read instead of nix::unistd::read b/c we need to pass an uninitialized buf
@@ -454,6 +571,45 @@ impl RawDevice {
         Ok(self.event_buf.drain(..).map(InputEvent::from))
     }
 
+    /// Fetches events from the kernel ring buffer like [`fetch_events`](Self::fetch_events), but
+    /// returns them as a slice of the internal buffer instead of copying them out.
+    ///
+    /// The slice stays valid until the next fetch, which reuses the buffer. This avoids any
+    /// per-event work when processing events at a high rate.
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let mut device = evdev::raw_stream::RawDevice::open("/dev/input/event0")?;
+    /// loop {
+    ///     let events = device.fetch_events_slice()?;
+    ///     println!("read {} events", events.len());
+    /// }
+    /// # }
+    /// ```
+    pub fn fetch_events_slice(&mut self) -> io::Result<&[InputEvent]> {
+        self.fill_events()?;
+        self.borrowed_events = self.event_buf.len();
+        // SAFETY: InputEvent is a repr(transparent) wrapper around input_event
+        Ok(unsafe {
+            std::slice::from_raw_parts(
+                self.event_buf.as_ptr() as *const InputEvent,
+                self.event_buf.len(),
+            )
+        })
+    }
+
+    /// Fetches events from the kernel ring buffer grouped into frames, see [`Frames`].
+    ///
+    /// Events following the last `SYN_REPORT` are kept in the internal buffer until the rest of
+    /// their frame has been read, so frames are never split between calls.
+    pub fn fetch_frames(&mut self) -> io::Result<impl Iterator<Item = Vec<InputEvent>> + '_> {
+        self.fill_events()?;
+        let complete = crate::frames::complete_frames_len(&self.event_buf);
+        Ok(Frames::new(
+            self.event_buf.drain(..complete).map(InputEvent::from),
+        ))
+    }
+
     /// Retrieve the current keypress state directly via kernel syscall.
     #[inline]
     pub fn get_key_state(&self) -> io::Result<AttributeSet<KeyCode>> {
@@ -482,6 +638,45 @@ impl RawDevice {
             .map(move |axes| (axes, AbsInfo(raw_absinfo[axes.0 as usize]))))
     }
 
+    /// Apply the corrections of `quirks` that match this device to the `AbsInfo` read from it
+    /// from now on, replacing corrections applied before.
+    ///
+    /// Returns `true` if any corrections matched. See [`AbsQuirks`].
+    pub fn apply_abs_quirks(&mut self, quirks: &AbsQuirks) -> bool {
+        self.abs_fixes = quirks.lookup(&InputId::from(self.id));
+        !self.abs_fixes.is_empty()
+    }
+
+    /// Returns a report of everything the device supports, such as its identifiers, properties,
+    /// supported codes and axis ranges.
+    pub fn capabilities_report(&self) -> io::Result<CapabilitiesReport> {
+        CapabilitiesReport::new(self)
+    }
+
+    /// Query the supported events, properties, auto repeat settings and force feedback effects
+    /// from the kernel again, replacing those read when the device was opened.
+    ///
+    /// These usually never change, but some drivers update them at runtime, e.g. when a
+    /// different mode of the device is selected, as can virtual devices created in tests.
+    pub fn refresh_capabilities(&mut self) -> io::Result<()> {
+        // queried through the device's own file descriptor, as closing a duplicate of it would
+        // erase the force feedback effects
+        let caps = Capabilities::query(self.fd().as_fd())?;
+        self.ty = caps.ty;
+        self.props = caps.props;
+        self.supported_keys = caps.supported_keys;
+        self.supported_relative = caps.supported_relative;
+        self.supported_absolute = caps.supported_absolute;
+        self.supported_switch = caps.supported_switch;
+        self.supported_led = caps.supported_led;
+        self.supported_misc = caps.supported_misc;
+        self.supported_ff = caps.supported_ff;
+        self.supported_snd = caps.supported_snd;
+        self.auto_repeat = caps.auto_repeat;
+        self.max_ff_effects = caps.max_ff_effects;
+        Ok(())
+    }
+
     /// Retrieve the current switch state directly via kernel syscall.
     #[inline]
     pub fn get_switch_state(&self) -> io::Result<AttributeSet<SwitchCode>> {
@@ -526,6 +721,9 @@ impl RawDevice {
                 };
             }
         }
+        for (axis, fix) in &self.abs_fixes {
+            fix.apply(&mut abs_vals[axis.0 as usize]);
+        }
         Ok(())
     }
 
@@ -667,6 +865,89 @@ impl RawDevice {
         self.grabbed
     }
 
+    /// Set the clock used for the timestamps of the events read from now on.
+    pub fn set_event_clock(&mut self, clock: EventClock) -> io::Result<()> {
+        crate::raw::set_clock_id(self.fd().as_fd(), clock.clock_id())?;
+        self.clock = clock;
+        Ok(())
+    }
+
+    /// Returns the clock used for event timestamps, as set with
+    /// [`set_event_clock`](Self::set_event_clock). Devices start out using the wall clock.
+    pub fn event_clock(&self) -> EventClock {
+        self.clock
+    }
+
+    /// Create a new handle for the device, sharing its file descriptor.
+    ///
+    /// Both handles refer to the same open file: events read through one aren't seen by the
+    /// other, and a grab applies to both. The new handle starts out with an empty read buffer,
+    /// isn't considered grabbed, and only erases the force feedback effects uploaded through it
+    /// when dropped, so dropping it leaves the grab and the effects of this handle in place. The
+    /// file descriptor is closed once all handles are dropped.
+    ///
+    /// This is mostly useful for handing a device to another component that sends events to it,
+    /// e.g. to set LEDs or play effects, while this handle keeps reading.
+    pub fn try_clone(&self) -> io::Result<RawDevice> {
+        Ok(RawDevice {
+            fd: self.fd.clone(),
+            ty: self.ty.clone(),
+            name: self.name.clone(),
+            phys: self.phys.clone(),
+            uniq: self.uniq.clone(),
+            id: self.id,
+            props: self.props.clone(),
+            driver_version: self.driver_version,
+            supported_keys: self.supported_keys.clone(),
+            supported_relative: self.supported_relative.clone(),
+            supported_absolute: self.supported_absolute.clone(),
+            supported_switch: self.supported_switch.clone(),
+            supported_led: self.supported_led.clone(),
+            supported_misc: self.supported_misc.clone(),
+            supported_ff: self.supported_ff.clone(),
+            supported_snd: self.supported_snd.clone(),
+            auto_repeat: self.auto_repeat.clone(),
+            max_ff_effects: self.max_ff_effects,
+            event_buf: Vec::new(),
+            read_batch: self.read_batch,
+            borrowed_events: 0,
+            grabbed: false,
+            uploaded_effects: Arc::default(),
+            cleanup_on_drop: true,
+            clock: self.clock,
+            abs_fixes: self.abs_fixes.clone(),
+        })
+    }
+
+    /// Returns a handle for controlling the device from another thread while this one reads its
+    /// events.
+    ///
+    /// See [`DeviceControl`].
+    pub fn control(&self) -> DeviceControl {
+        DeviceControl::new(self.fd(), &self.uploaded_effects)
+    }
+
+    /// Give up the device without releasing its grab or erasing its force feedback effects,
+    /// returning a file descriptor for it.
+    ///
+    /// Normally, dropping a device releases its grab and erases the effects uploaded through it,
+    /// even while unwinding from a panic. Processes that hand the device on to another process
+    /// can use this to keep the grab in place for the recipient.
+    ///
+    /// Effects only last as long as every copy of the file descriptor stays open, though: the
+    /// kernel erases all effects uploaded through the open file whenever a copy of its
+    /// descriptor is closed, so they are gone once the sending process closes its copy after
+    /// handing it on.
+    pub fn leak(self) -> OwnedFd {
+        self.into()
+    }
+
+    fn fd(&self) -> &Arc<OwnedFd> {
+        self.fd
+            .as_ref()
+            .expect("the file descriptor is only taken when consuming the device")
+    }
+
     /// Send an event to the device.
     ///
     /// Events that are typically sent to devices are
@@ -674,28 +955,38 @@ impl RawDevice {
     /// [EventType::SOUND] (play a sound on the device)
     /// and [EventType::FORCEFEEDBACK] (play force feedback effects on the device, i.e. rumble).
     pub fn send_events(&mut self, events: &[InputEvent]) -> io::Result<()> {
-        crate::write_events(self.fd.as_fd(), events)?;
+        crate::write_events(self.fd().as_fd(), events)?;
         Ok(())
     }
 
     /// Uploads a force feedback effect to the device.
     pub fn upload_ff_effect(&mut self, data: FFEffectData) -> io::Result<FFEffect> {
-        let mut effect: sys::ff_effect = data.into();
-        effect.id = -1;
-
-        unsafe { sys::eviocsff(self.fd.as_raw_fd(), &effect)? };
-
-        let fd = self.fd.try_clone()?;
-        let id = effect.id as u16;
+        FFEffect::upload(self.fd(), &self.uploaded_effects, data)
+    }
 
-        Ok(FFEffect { fd, id })
+    /// Rumbles the device with the given magnitudes of its heavy and light motor for `duration`.
+    ///
+    /// This uploads a temporary `FF_RUMBLE` effect and plays it once. The device stops it by
+    /// itself, and the effect is erased from a background thread afterwards, so it doesn't take
+    /// up one of the device's effect slots for longer than necessary. Fails with
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if `duration` is longer than `u16::MAX`
+    /// milliseconds, the longest an effect can play.
+    ///
+    /// This covers most uses of force feedback on gamepads; use [`upload_ff_effect`] to control
+    /// effects in more detail. As every call uploads an effect and spawns a thread, effects that
+    /// are played often, e.g. on every hit in a game, are better uploaded once and played
+    /// repeatedly, see also [`FFManager`](crate::FFManager).
+    ///
+    /// [`upload_ff_effect`]: Self::upload_ff_effect
+    pub fn rumble(&mut self, strong: u16, weak: u16, duration: Duration) -> io::Result<()> {
+        FFEffect::rumble(self.fd(), &self.uploaded_effects, strong, weak, duration)
     }
 
     /// Sets the force feedback gain, i.e. how strong the force feedback effects should be for the
     /// device. A gain of 0 means no gain, whereas `u16::MAX` is the maximum gain.
     pub fn set_ff_gain(&mut self, value: u16) -> io::Result<()> {
         let events = [*FFEvent::new(FFEffectCode::FF_GAIN, value.into())];
-        crate::write_events(self.fd.as_fd(), &events)?;
+        crate::write_events(self.fd().as_fd(), &events)?;
 
         Ok(())
     }
@@ -703,21 +994,39 @@ impl RawDevice {
     /// Enables or disables autocenter for the force feedback device.
     pub fn set_ff_autocenter(&mut self, value: u16) -> io::Result<()> {
         let events = [*FFEvent::new(FFEffectCode::FF_AUTOCENTER, value.into())];
-        crate::write_events(self.fd.as_fd(), &events)?;
+        crate::write_events(self.fd().as_fd(), &events)?;
 
         Ok(())
     }
 }
 
+impl Drop for RawDevice {
+    fn drop(&mut self) {
+        if !self.cleanup_on_drop || self.fd.is_none() {
+            return;
+        }
+        if let Err(error) = self.ungrab() {
+            eprintln!("Failed to ungrab device: {error}");
+        }
+        let mut uploaded = self
+            .uploaded_effects
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        for id in uploaded.drain(..) {
+            let _ = unsafe { sys::eviocrmff(self.fd().as_raw_fd(), id as _) };
+        }
+    }
+}
+
 impl AsFd for RawDevice {
     fn as_fd(&self) -> BorrowedFd<'_> {
-        self.fd.as_fd()
+        self.fd().as_fd()
     }
 }
 
 impl AsRawFd for RawDevice {
     fn as_raw_fd(&self) -> RawFd {
-        self.fd.as_raw_fd()
+        self.fd().as_raw_fd()
     }
 }
 
@@ -729,13 +1038,39 @@ impl TryFrom<File> for RawDevice {
     }
 }
 
+impl TryFrom<OwnedFd> for RawDevice {
+    type Error = io::Error;
+
+    fn try_from(fd: OwnedFd) -> Result<Self, Self::Error> {
+        Self::from_fd(fd)
+    }
+}
+
+/// Gives up the device like [`RawDevice::leak`], keeping its grab and force feedback effects.
+///
+/// If force feedback effects uploaded through the device are still around, they keep using its
+/// file descriptor, and a duplicate of it is returned.
+impl From<RawDevice> for OwnedFd {
+    fn from(mut device: RawDevice) -> Self {
+        device.cleanup_on_drop = false;
+        let fd = device.fd.take().expect("the device wasn't consumed yet");
+        // effects uploaded through the device still use it
+        Arc::try_unwrap(fd).unwrap_or_else(|fd| {
+            fd.try_clone()
+                .expect("failed to duplicate the file descriptor of the device")
+        })
+    }
+}
+
 /// Crawls `/dev/input` for evdev devices.
 ///
 /// Will not bubble up any errors in opening devices or traversing the directory. Instead returns
 /// an empty iterator or omits the devices that could not be opened.
 pub fn enumerate() -> EnumerateDevices {
     EnumerateDevices {
-        readdir: std::fs::read_dir("/dev/input").ok(),
+        readdir: crate::is_supported()
+            .then(|| std::fs::read_dir("/dev/input").ok())
+            .flatten(),
     }
 }
 
@@ -846,3 +1181,21 @@ mod tokio_stream {
 }
 #[cfg(feature = "tokio")]
 pub use tokio_stream::EventStream;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exclusive_lock() {
+        let path = std::env::temp_dir().join(format!("evdev-lock-{}", std::process::id()));
+        let first = std::fs::File::create(&path).unwrap();
+        let second = std::fs::File::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        lock_exclusive(first.as_fd()).unwrap();
+        let err = lock_exclusive(second.as_fd()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+        drop(first);
+        lock_exclusive(second.as_fd()).unwrap();
+    }
+}
@@ -0,0 +1,165 @@
+//! Forwarding devices to other machines or processes.
+//!
+//! A [`Sender`] serves a device over any stream, such as a TCP connection or Unix socket, and a
+//! [`Receiver`] on the other end reconstructs it as a [`VirtualDevice`] and replays its events.
+//! The stream uses the [capture format](crate::capture): the description of the device followed
+//! by its events, so a forwarded session can also be saved to a file and read with
+//! [`capture::Reader`](crate::capture::Reader).
+//!
+//! On the machine with the device:
+//!
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! let listener = std::net::TcpListener::bind("0.0.0.0:5000")?;
+//! let (stream, _) = listener.accept()?;
+//! let mut device = evdev::Device::open("/dev/input/event0")?;
+//! evdev::remote::serve(&mut device, stream)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! On the machine that should receive the input:
+//!
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! let stream = std::net::TcpStream::connect("192.168.1.10:5000")?;
+//! evdev::remote::receive(stream)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::capture;
+use crate::evemu::Description;
+use crate::uinput::VirtualDevice;
+use crate::{Device, InputEvent};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+/// Sends the description and events of a device over a stream.
+#[derive(Debug)]
+pub struct Sender<W: Write> {
+    writer: capture::Writer<BufWriter<W>>,
+}
+
+impl<W: Write> Sender<W> {
+    /// Create a sender, sending the description of the device right away.
+    pub fn new(stream: W, description: &Description) -> io::Result<Self> {
+        let mut writer = capture::Writer::new(BufWriter::new(stream), description)?;
+        writer.flush()?;
+        Ok(Self { writer })
+    }
+
+    /// Send a batch of events, flushing the stream afterwards.
+    pub fn send(&mut self, events: impl IntoIterator<Item = InputEvent>) -> io::Result<()> {
+        for ev in events {
+            self.writer.write_event(&ev)?;
+        }
+        self.writer.flush()
+    }
+
+    /// Send the events of `device` as they arrive, until reading from the device or writing to the
+    /// stream fails.
+    pub fn forward(&mut self, device: &mut Device) -> io::Result<()> {
+        loop {
+            let events = device.fetch_events()?;
+            self.send(events)?;
+        }
+    }
+}
+
+/// Serves `device` over `stream`, until reading from the device or writing to the stream fails.
+pub fn serve(device: &mut Device, stream: impl Write) -> io::Result<()> {
+    let description = Description::from_device(device)?;
+    Sender::new(stream, &description)?.forward(device)
+}
+
+/// Receives the description and events of a device from a stream.
+#[derive(Debug)]
+pub struct Receiver<R: Read> {
+    reader: capture::Reader<BufReader<R>>,
+}
+
+impl<R: Read> Receiver<R> {
+    /// Create a receiver, reading the description of the device right away.
+    pub fn new(stream: R) -> io::Result<Self> {
+        Ok(Self {
+            reader: capture::Reader::new(BufReader::new(stream))?,
+        })
+    }
+
+    /// Returns the description of the remote device.
+    pub fn description(&self) -> &Description {
+        self.reader.description()
+    }
+
+    /// Create a virtual device matching the remote device.
+    pub fn create_virtual_device(&self) -> io::Result<VirtualDevice> {
+        self.description().create_virtual_device()
+    }
+
+    /// Receive the next event, returning `None` once the sender closed the stream.
+    pub fn next_event(&mut self) -> io::Result<Option<InputEvent>> {
+        self.reader.read_event()
+    }
+
+    /// Emit the received events through `device` until the sender closes the stream.
+    pub fn forward(&mut self, device: &mut VirtualDevice) -> io::Result<()> {
+        while let Some(ev) = self.next_event()? {
+            device.write_raw(&[InputEvent::new(ev.event_type(), ev.code(), ev.value())])?;
+        }
+        Ok(())
+    }
+}
+
+/// Recreates the device served on the other end of `stream` and emits its events until the sender
+/// closes the stream.
+pub fn receive(stream: impl Read) -> io::Result<()> {
+    let mut receiver = Receiver::new(stream)?;
+    let mut device = receiver.create_virtual_device()?;
+    receiver.forward(&mut device)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AttributeSet, BusType, EventType, InputId, KeyCode, KeyEvent, SynchronizationEvent,
+    };
+
+    #[test]
+    fn send_and_receive() {
+        let description = Description {
+            name: "Remote Keyboard".to_owned(),
+            input_id: InputId::new(BusType::BUS_VIRTUAL, 1, 2, 3),
+            properties: AttributeSet::new(),
+            event_types: [EventType::KEY].into_iter().collect(),
+            keys: [KeyCode::KEY_A].into_iter().collect(),
+            relative_axes: AttributeSet::new(),
+            absolute_axes: Vec::new(),
+            misc: AttributeSet::new(),
+            switches: AttributeSet::new(),
+            leds: AttributeSet::new(),
+            sounds: AttributeSet::new(),
+            ff: AttributeSet::new(),
+            max_ff_effects: 0,
+        };
+        let mut stream = Vec::new();
+        let mut sender = Sender::new(&mut stream, &description).unwrap();
+        let events = [
+            *KeyEvent::new(KeyCode::KEY_A, 1),
+            *SynchronizationEvent::new(crate::SynchronizationCode::SYN_REPORT, 0),
+        ];
+        sender.send(events).unwrap();
+        drop(sender);
+
+        let mut receiver = Receiver::new(&stream[..]).unwrap();
+        assert_eq!(receiver.description().name, "Remote Keyboard");
+        for expected in events {
+            let ev = receiver.next_event().unwrap().unwrap();
+            assert_eq!(
+                (ev.event_type(), ev.code(), ev.value()),
+                (expected.event_type(), expected.code(), expected.value())
+            );
+        }
+        assert!(receiver.next_event().unwrap().is_none());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{EventSummary, InputEvent, KeyCode, MiscCode, SynchronizationCode};
+use std::time::SystemTime;
+
+/// A key event together with the hardware scancode reported for it, as produced by a
+/// [`ScancodePairing`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScannedKey {
+    /// The timestamp of the key event.
+    pub timestamp: SystemTime,
+    pub key: KeyCode,
+    /// `0` for a release, `1` for a press and `2` for an autorepeat.
+    pub value: i32,
+    /// The value of the `MSC_SCAN` event preceding the key event in its frame, if there was one.
+    pub scancode: Option<u32>,
+}
+
+/// Pairs key events with the `MSC_SCAN` event that the kernel emits right before them.
+///
+/// Many keyboards report the hardware scancode of a key in an `MSC_SCAN` event in the same frame
+/// as the key event. This is the scancode that hwdb entries and [`Device::update_scancode`]
+/// remap, so tools that let users remap keys interactively need to know which scancode produced
+/// which key. Autorepeat events are generated by the kernel and carry no scancode.
+///
+/// ```
+/// use evdev::{KeyCode, KeyEvent, MiscCode, MiscEvent, ScancodePairing};
+///
+/// let events = [
+///     *MiscEvent::new(MiscCode::MSC_SCAN, 0x70004),
+///     *KeyEvent::new(KeyCode::KEY_A, 1),
+/// ];
+/// let mut pairing = ScancodePairing::new();
+/// let keys: Vec<_> = pairing.scanned_keys(events).collect();
+/// assert_eq!((keys[0].key, keys[0].scancode), (KeyCode::KEY_A, Some(0x70004)));
+/// ```
+///
+/// [`Device::update_scancode`]: crate::Device::update_scancode
+#[derive(Debug, Clone, Default)]
+pub struct ScancodePairing {
+    pending: Option<u32>,
+}
+
+impl ScancodePairing {
+    /// Create a new pairing adapter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the adapter with an event.
+    ///
+    /// Returns the key event paired with its scancode if `event` is a key event.
+    pub fn process(&mut self, event: &InputEvent) -> Option<ScannedKey> {
+        match event.destructure() {
+            EventSummary::Misc(_, MiscCode::MSC_SCAN, value) => {
+                self.pending = Some(value as u32);
+            }
+            EventSummary::Key(_, key, value) => {
+                return Some(ScannedKey {
+                    timestamp: event.timestamp(),
+                    key,
+                    value,
+                    scancode: self.pending.take(),
+                });
+            }
+            EventSummary::Synchronization(_, SynchronizationCode::SYN_REPORT, _) => {
+                self.pending = None;
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Apply the adapter to an iterator of events, yielding every key event paired with its
+    /// scancode.
+    pub fn scanned_keys<'a, I>(&'a mut self, events: I) -> impl Iterator<Item = ScannedKey> + 'a
+    where
+        I: IntoIterator<Item = InputEvent>,
+        I::IntoIter: 'a,
+    {
+        events.into_iter().filter_map(move |ev| self.process(&ev))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KeyEvent, MiscEvent, SynchronizationEvent};
+
+    #[test]
+    fn pairs_within_frame() {
+        let events = [
+            *MiscEvent::new(MiscCode::MSC_SCAN, 0x1e),
+            *KeyEvent::new(KeyCode::KEY_A, 1),
+            *MiscEvent::new(MiscCode::MSC_SCAN, 0x30),
+            *KeyEvent::new(KeyCode::KEY_B, 1),
+            *SynchronizationEvent::new(SynchronizationCode::SYN_REPORT, 0),
+            *MiscEvent::new(MiscCode::MSC_SCAN, 0x2e),
+            *SynchronizationEvent::new(SynchronizationCode::SYN_REPORT, 0),
+            *KeyEvent::new(KeyCode::KEY_A, 2),
+        ];
+        let mut pairing = ScancodePairing::new();
+        let keys: Vec<_> = pairing
+            .scanned_keys(events)
+            .map(|k| (k.key, k.scancode))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                (KeyCode::KEY_A, Some(0x1e)),
+                (KeyCode::KEY_B, Some(0x30)),
+                (KeyCode::KEY_A, None)
+            ]
+        );
+    }
+}
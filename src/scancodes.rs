@@ -21,6 +21,45 @@ impl KeyCode {
     }
 
     pub(crate) const COUNT: usize = KEY_CNT;
+
+    /// Returns `true` if the code is a button (`BTN_*`) rather than a key, following the
+    /// ranges of the kernel headers.
+    pub const fn is_button(self) -> bool {
+        matches!(self.0, 0x100..=0x15f | 0x220..=0x227 | 0x2c0..=0x2e7)
+    }
+
+    /// Returns `true` if the code is a key (`KEY_*`), such as the keys of keyboards, remote
+    /// controls and multimedia keys.
+    pub const fn is_keyboard_key(self) -> bool {
+        matches!(self.0, 0x001..=0x0ff | 0x160..=0x2ff) && !self.is_button()
+    }
+
+    /// Returns `true` if the code is a mouse button, between `BTN_MOUSE` and `BTN_JOYSTICK`.
+    pub const fn is_mouse_button(self) -> bool {
+        matches!(self.0, 0x110..=0x11f)
+    }
+
+    /// Returns `true` if the code is a joystick button, between `BTN_JOYSTICK` and
+    /// `BTN_GAMEPAD`.
+    pub const fn is_joystick_button(self) -> bool {
+        matches!(self.0, 0x120..=0x12f)
+    }
+
+    /// Returns `true` if the code is a gamepad button: the buttons between `BTN_GAMEPAD` and
+    /// `BTN_DIGI`, the d-pad and grip buttons, and the extra `BTN_TRIGGER_HAPPY*` buttons.
+    pub const fn is_gamepad_button(self) -> bool {
+        matches!(self.0, 0x130..=0x13f | 0x220..=0x227 | 0x2c0..=0x2e7)
+    }
+
+    /// Returns `true` if the code is a tool or button of a digitizer such as a tablet or
+    /// touchpad, between `BTN_DIGI` and `BTN_WHEEL`.
+    pub const fn is_digitizer_button(self) -> bool {
+        matches!(self.0, 0x140..=0x14f)
+    }
+
+    // aliases, which are left out of `ALL`
+    pub const KEY_ROTATE_DISPLAY: Self = Self::KEY_DIRECTION;
+    pub const KEY_FULL_SCREEN: Self = Self::KEY_ZOOM;
 }
 
 evdev_enum!(
@@ -179,7 +218,6 @@ evdev_enum!(
     KEY_MSDOS = 151,
     KEY_COFFEE = 152, /* AL Terminal Lock/Screensaver */
     KEY_DIRECTION = 153,
-    KEY_ROTATE_DISPLAY = 153,
     KEY_CYCLEWINDOWS = 154,
     KEY_MAIL = 155,
     KEY_BOOKMARKS = 156, /* AC Bookmarks */
@@ -354,7 +392,6 @@ evdev_enum!(
     KEY_SUBTITLE = 0x172,
     KEY_ANGLE = 0x173,
     KEY_ZOOM = 0x174,
-    KEY_FULL_SCREEN = 0x174,
     KEY_MODE = 0x175,
     KEY_KEYBOARD = 0x176,
     KEY_SCREEN = 0x177,
@@ -420,13 +457,15 @@ evdev_enum!(
     KEY_EURO = 0x1b3,
     KEY_FRAMEBACK = 0x1b4, /* Consumer - transport controls */
     KEY_FRAMEFORWARD = 0x1b5,
-    KEY_CONTEXT_MENU = 0x1b6,   /* GenDesc - system context menu */
-    KEY_MEDIA_REPEAT = 0x1b7,   /* Consumer - transport control */
-    KEY_10CHANNELSUP = 0x1b8,   /* 10 channels up (10+) */
-    KEY_10CHANNELSDOWN = 0x1b9, /* 10 channels down (10-) */
-    KEY_IMAGES = 0x1ba,         /* AL Image Browser */
+    KEY_CONTEXT_MENU = 0x1b6,        /* GenDesc - system context menu */
+    KEY_MEDIA_REPEAT = 0x1b7,        /* Consumer - transport control */
+    KEY_10CHANNELSUP = 0x1b8,        /* 10 channels up (10+) */
+    KEY_10CHANNELSDOWN = 0x1b9,      /* 10 channels down (10-) */
+    KEY_IMAGES = 0x1ba,              /* AL Image Browser */
+    KEY_NOTIFICATION_CENTER = 0x1bc, /* Show/hide the notification center */
     KEY_PICKUP_PHONE = 0x1bd,
     KEY_HANGUP_PHONE = 0x1be,
+    KEY_LINK_PHONE = 0x1bf, /* AL Phone Syncing */
     KEY_DEL_EOL = 0x1c0,
     KEY_DEL_EOS = 0x1c1,
     KEY_INS_LINE = 0x1c2,
@@ -452,6 +491,7 @@ evdev_enum!(
     KEY_FN_F = 0x1e2,
     KEY_FN_S = 0x1e3,
     KEY_FN_B = 0x1e4,
+    KEY_FN_RIGHT_SHIFT = 0x1e5,
     KEY_BRL_DOT1 = 0x1f1,
     KEY_BRL_DOT2 = 0x1f2,
     KEY_BRL_DOT3 = 0x1f3,
@@ -497,16 +537,29 @@ evdev_enum!(
     BTN_DPAD_DOWN = 0x221,
     BTN_DPAD_LEFT = 0x222,
     BTN_DPAD_RIGHT = 0x223,
-    KEY_ALS_TOGGLE = 0x230,   /* Ambient light sensor */
-    KEY_BUTTONCONFIG = 0x240, /* AL Button Configuration */
-    KEY_TASKMANAGER = 0x241,  /* AL Task/Project Manager */
-    KEY_JOURNAL = 0x242,      /* AL Log/Journal/Timecard */
-    KEY_CONTROLPANEL = 0x243, /* AL Control Panel */
-    KEY_APPSELECT = 0x244,    /* AL Select Task/Application */
-    KEY_SCREENSAVER = 0x245,  /* AL Screen Saver */
-    KEY_VOICECOMMAND = 0x246, /* Listening Voice Command */
+    BTN_GRIPL = 0x224,
+    BTN_GRIPR = 0x225,
+    BTN_GRIPL2 = 0x226,
+    BTN_GRIPR2 = 0x227,
+    KEY_ALS_TOGGLE = 0x230,          /* Ambient light sensor */
+    KEY_ROTATE_LOCK_TOGGLE = 0x231,  /* Display rotation lock */
+    KEY_REFRESH_RATE_TOGGLE = 0x232, /* Display refresh rate toggle */
+    KEY_BUTTONCONFIG = 0x240,        /* AL Button Configuration */
+    KEY_TASKMANAGER = 0x241,         /* AL Task/Project Manager */
+    KEY_JOURNAL = 0x242,             /* AL Log/Journal/Timecard */
+    KEY_CONTROLPANEL = 0x243,        /* AL Control Panel */
+    KEY_APPSELECT = 0x244,           /* AL Select Task/Application */
+    KEY_SCREENSAVER = 0x245,         /* AL Screen Saver */
+    KEY_VOICECOMMAND = 0x246,        /* Listening Voice Command */
     KEY_ASSISTANT = 0x247,
     KEY_KBD_LAYOUT_NEXT = 0x248,
+    KEY_EMOJI_PICKER = 0x249,
+    KEY_DICTATE = 0x24a,
+    KEY_CAMERA_ACCESS_ENABLE = 0x24b,
+    KEY_CAMERA_ACCESS_DISABLE = 0x24c,
+    KEY_CAMERA_ACCESS_TOGGLE = 0x24d,
+    KEY_ACCESSIBILITY = 0x24e, /* Toggles the accessibility menu */
+    KEY_DO_NOT_DISTURB = 0x24f,
     KEY_BRIGHTNESS_MIN = 0x250, /* Set Brightness to Minimum */
     KEY_BRIGHTNESS_MAX = 0x251, /* Set Brightness to Maximum */
     KEY_KBDINPUTASSIST_PREV = 0x260,
@@ -536,6 +589,62 @@ evdev_enum!(
     KEY_ONSCREEN_KEYBOARD = 0x278,
     KEY_PRIVACY_SCREEN_TOGGLE = 0x279,
     KEY_SELECTIVE_SCREENSHOT = 0x27a,
+    KEY_NEXT_ELEMENT = 0x27b,
+    KEY_PREVIOUS_ELEMENT = 0x27c,
+    KEY_AUTOPILOT_ENGAGE_TOGGLE = 0x27d,
+    KEY_MARK_WAYPOINT = 0x27e,
+    KEY_SOS = 0x27f,
+    KEY_NAV_CHART = 0x280,
+    KEY_FISHING_CHART = 0x281,
+    KEY_SINGLE_RANGE_RADAR = 0x282,
+    KEY_DUAL_RANGE_RADAR = 0x283,
+    KEY_RADAR_OVERLAY = 0x284,
+    KEY_TRADITIONAL_SONAR = 0x285,
+    KEY_CLEARVU_SONAR = 0x286,
+    KEY_SIDEVU_SONAR = 0x287,
+    KEY_NAV_INFO = 0x288,
+    KEY_BRIGHTNESS_MENU = 0x289,
+    KEY_MACRO1 = 0x290,
+    KEY_MACRO2 = 0x291,
+    KEY_MACRO3 = 0x292,
+    KEY_MACRO4 = 0x293,
+    KEY_MACRO5 = 0x294,
+    KEY_MACRO6 = 0x295,
+    KEY_MACRO7 = 0x296,
+    KEY_MACRO8 = 0x297,
+    KEY_MACRO9 = 0x298,
+    KEY_MACRO10 = 0x299,
+    KEY_MACRO11 = 0x29a,
+    KEY_MACRO12 = 0x29b,
+    KEY_MACRO13 = 0x29c,
+    KEY_MACRO14 = 0x29d,
+    KEY_MACRO15 = 0x29e,
+    KEY_MACRO16 = 0x29f,
+    KEY_MACRO17 = 0x2a0,
+    KEY_MACRO18 = 0x2a1,
+    KEY_MACRO19 = 0x2a2,
+    KEY_MACRO20 = 0x2a3,
+    KEY_MACRO21 = 0x2a4,
+    KEY_MACRO22 = 0x2a5,
+    KEY_MACRO23 = 0x2a6,
+    KEY_MACRO24 = 0x2a7,
+    KEY_MACRO25 = 0x2a8,
+    KEY_MACRO26 = 0x2a9,
+    KEY_MACRO27 = 0x2aa,
+    KEY_MACRO28 = 0x2ab,
+    KEY_MACRO29 = 0x2ac,
+    KEY_MACRO30 = 0x2ad,
+    KEY_MACRO_RECORD_START = 0x2b0,
+    KEY_MACRO_RECORD_STOP = 0x2b1,
+    KEY_MACRO_PRESET_CYCLE = 0x2b2,
+    KEY_MACRO_PRESET1 = 0x2b3,
+    KEY_MACRO_PRESET2 = 0x2b4,
+    KEY_MACRO_PRESET3 = 0x2b5,
+    KEY_KBD_LCD_MENU1 = 0x2b8, /* Select LCD menu items on the keyboard */
+    KEY_KBD_LCD_MENU2 = 0x2b9,
+    KEY_KBD_LCD_MENU3 = 0x2ba,
+    KEY_KBD_LCD_MENU4 = 0x2bb,
+    KEY_KBD_LCD_MENU5 = 0x2bc,
     BTN_TRIGGER_HAPPY1 = 0x2c0,
     BTN_TRIGGER_HAPPY2 = 0x2c1,
     BTN_TRIGGER_HAPPY3 = 0x2c2,
@@ -578,10 +687,67 @@ evdev_enum!(
     BTN_TRIGGER_HAPPY40 = 0x2e7,
 );
 
+evdev_enum_display!(KeyCode);
+
 #[test]
 fn from_str() {
     use std::str::FromStr;
 
     assert_eq!(KeyCode::from_str("KEY_A"), Ok(KeyCode::KEY_A));
     assert!(KeyCode::from_str("KEY_FOOBAR").is_err());
+    assert_eq!(KeyCode::from_str("BTN_A"), Ok(KeyCode::BTN_SOUTH));
+    assert_eq!(KeyCode::from_str("0x130"), Ok(KeyCode::BTN_SOUTH));
+    assert_eq!(KeyCode::from_str("304"), Ok(KeyCode::BTN_SOUTH));
+    assert!(KeyCode::from_str("65536").is_err());
+    assert!(crate::SwitchCode::from_str("BTN_A").is_err());
+}
+
+#[test]
+fn all() {
+    let all: std::collections::HashSet<_> = KeyCode::iter().collect();
+    assert_eq!(all.len(), KeyCode::ALL.len());
+    assert!(all.contains(&KeyCode::KEY_A));
+    assert!(all.contains(&KeyCode::KEY_FULL_SCREEN));
+    assert_eq!(
+        crate::RelativeAxisCode::ALL[0],
+        crate::RelativeAxisCode::REL_X
+    );
+}
+
+#[test]
+fn classification() {
+    assert!(KeyCode::KEY_A.is_keyboard_key() && !KeyCode::KEY_A.is_button());
+    assert!(KeyCode::KEY_MACRO1.is_keyboard_key());
+    assert!(!KeyCode::KEY_RESERVED.is_keyboard_key());
+    assert!(KeyCode::BTN_LEFT.is_mouse_button() && KeyCode::BTN_LEFT.is_button());
+    assert!(!KeyCode::BTN_LEFT.is_keyboard_key());
+    assert!(KeyCode::BTN_TRIGGER.is_joystick_button());
+    assert!(KeyCode::BTN_SOUTH.is_gamepad_button() && !KeyCode::BTN_SOUTH.is_mouse_button());
+    assert!(KeyCode::BTN_DPAD_UP.is_gamepad_button());
+    assert!(KeyCode::BTN_TRIGGER_HAPPY40.is_gamepad_button());
+    assert!(KeyCode::BTN_TOOL_PEN.is_digitizer_button());
+}
+
+#[test]
+fn display() {
+    assert_eq!(KeyCode::BTN_SOUTH.to_string(), "BTN_SOUTH");
+    assert_eq!(KeyCode(0x2ff).to_string(), "KEY_UNKNOWN(0x2ff)");
+    assert_eq!(format!("{:?}", KeyCode(0x2ff)), "KEY_UNKNOWN(0x2ff)");
+    assert_eq!("KEY_UNKNOWN(0x2ff)".parse(), Ok(KeyCode(0x2ff)));
+    assert_eq!(format!("{:>8}", KeyCode::KEY_A), "   KEY_A");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn deserialize_name_or_number() {
+    use serde::de::value::{Error, StrDeserializer, U64Deserializer};
+    use serde::Deserialize;
+
+    let name = StrDeserializer::<Error>::new("key_a");
+    assert_eq!(KeyCode::deserialize(name), Ok(KeyCode::KEY_A));
+    let number = U64Deserializer::<Error>::new(30);
+    assert_eq!(KeyCode::deserialize(number), Ok(KeyCode::KEY_A));
+    let numeric_str = StrDeserializer::<Error>::new("30");
+    assert_eq!(KeyCode::deserialize(numeric_str), Ok(KeyCode::KEY_A));
+    assert!(KeyCode::deserialize(U64Deserializer::<Error>::new(0x10000)).is_err());
 }
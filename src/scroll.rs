@@ -0,0 +1,180 @@
+use crate::{EventSummary, InputEvent, RelativeAxisCode, SynchronizationCode};
+use std::time::SystemTime;
+
+/// The number of high-resolution scroll units per wheel detent.
+pub const HI_RES_UNITS_PER_DETENT: i32 = 120;
+
+/// The scroll motion of a single frame, as reported by a [`ScrollAggregator`].
+///
+/// Both axes are in high-resolution units, where [`HI_RES_UNITS_PER_DETENT`] (120) units
+/// correspond to one notch of a traditional scroll wheel. As with `REL_WHEEL`, positive vertical
+/// values scroll up, and positive horizontal values scroll right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScrollDelta {
+    /// The timestamp of the `SYN_REPORT` ending the frame.
+    pub timestamp: SystemTime,
+    pub vertical: i32,
+    pub horizontal: i32,
+}
+
+impl ScrollDelta {
+    /// Returns the vertical motion in detents.
+    pub fn vertical_detents(&self) -> f64 {
+        self.vertical as f64 / HI_RES_UNITS_PER_DETENT as f64
+    }
+
+    /// Returns the horizontal motion in detents.
+    pub fn horizontal_detents(&self) -> f64 {
+        self.horizontal as f64 / HI_RES_UNITS_PER_DETENT as f64
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Axis {
+    /// Whether the device was seen emitting high-resolution events for this axis.
+    hi_res: bool,
+    lo: i32,
+    hi: i32,
+}
+
+impl Axis {
+    fn take(&mut self) -> i32 {
+        let delta = if self.hi_res {
+            self.hi
+        } else {
+            self.lo.saturating_mul(HI_RES_UNITS_PER_DETENT)
+        };
+        self.lo = 0;
+        self.hi = 0;
+        delta
+    }
+}
+
+/// Merges the low- and high-resolution scroll axes of a device into a single scroll delta.
+///
+/// Devices with high-resolution scrolling emit both `REL_WHEEL_HI_RES` and `REL_WHEEL` (and
+/// likewise for `REL_HWHEEL`), while older devices only emit the latter. Consumers that handle
+/// both axes end up scrolling twice. The aggregator uses the high-resolution axis once the device
+/// is seen emitting it and falls back to the low-resolution axis otherwise, reporting the result
+/// for each frame.
+#[derive(Debug, Clone, Default)]
+pub struct ScrollAggregator {
+    vertical: Axis,
+    horizontal: Axis,
+}
+
+impl ScrollAggregator {
+    /// Create a new aggregator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the aggregator with an event.
+    ///
+    /// Returns the scroll motion of the frame if `event` is a `SYN_REPORT` ending a frame that
+    /// contained any scrolling.
+    pub fn process(&mut self, event: &InputEvent) -> Option<ScrollDelta> {
+        match event.destructure() {
+            EventSummary::RelativeAxis(_, RelativeAxisCode::REL_WHEEL, value) => {
+                self.vertical.lo = self.vertical.lo.saturating_add(value);
+            }
+            EventSummary::RelativeAxis(_, RelativeAxisCode::REL_HWHEEL, value) => {
+                self.horizontal.lo = self.horizontal.lo.saturating_add(value);
+            }
+            EventSummary::RelativeAxis(_, RelativeAxisCode::REL_WHEEL_HI_RES, value) => {
+                self.vertical.hi_res = true;
+                self.vertical.hi = self.vertical.hi.saturating_add(value);
+            }
+            EventSummary::RelativeAxis(_, RelativeAxisCode::REL_HWHEEL_HI_RES, value) => {
+                self.horizontal.hi_res = true;
+                self.horizontal.hi = self.horizontal.hi.saturating_add(value);
+            }
+            EventSummary::Synchronization(_, SynchronizationCode::SYN_REPORT, _) => {
+                let vertical = self.vertical.take();
+                let horizontal = self.horizontal.take();
+                if vertical != 0 || horizontal != 0 {
+                    return Some(ScrollDelta {
+                        timestamp: event.timestamp(),
+                        vertical,
+                        horizontal,
+                    });
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Apply the aggregator to an iterator of events, yielding the scroll motion of every frame.
+    pub fn scroll_events<'a, I>(&'a mut self, events: I) -> impl Iterator<Item = ScrollDelta> + 'a
+    where
+        I: IntoIterator<Item = InputEvent>,
+        I::IntoIter: 'a,
+    {
+        events.into_iter().filter_map(move |ev| self.process(&ev))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RelativeAxisEvent, SynchronizationEvent};
+
+    fn rel(axis: RelativeAxisCode, value: i32) -> InputEvent {
+        *RelativeAxisEvent::new(axis, value)
+    }
+
+    fn report() -> InputEvent {
+        *SynchronizationEvent::new(SynchronizationCode::SYN_REPORT, 0)
+    }
+
+    #[test]
+    fn low_res_only() {
+        let mut scroll = ScrollAggregator::new();
+        let events = [
+            rel(RelativeAxisCode::REL_WHEEL, -1),
+            rel(RelativeAxisCode::REL_HWHEEL, 2),
+            report(),
+        ];
+        let deltas: Vec<_> = scroll.scroll_events(events).collect();
+        assert_eq!(deltas.len(), 1);
+        assert_eq!(deltas[0].vertical, -120);
+        assert_eq!(deltas[0].horizontal, 240);
+        assert_eq!(deltas[0].vertical_detents(), -1.0);
+    }
+
+    #[test]
+    fn hi_res_is_not_double_counted() {
+        let mut scroll = ScrollAggregator::new();
+        let events = [
+            rel(RelativeAxisCode::REL_WHEEL_HI_RES, 60),
+            report(),
+            rel(RelativeAxisCode::REL_WHEEL_HI_RES, 60),
+            rel(RelativeAxisCode::REL_WHEEL, 1),
+            report(),
+            rel(RelativeAxisCode::REL_X, 5),
+            report(),
+        ];
+        let deltas: Vec<_> = scroll
+            .scroll_events(events)
+            .map(|d| (d.vertical, d.horizontal))
+            .collect();
+        assert_eq!(deltas, [(60, 0), (60, 0)]);
+    }
+
+    #[test]
+    fn saturates_large_values() {
+        let mut scroll = ScrollAggregator::new();
+        let events = [
+            rel(RelativeAxisCode::REL_WHEEL, i32::MAX),
+            rel(RelativeAxisCode::REL_WHEEL, 1),
+            rel(RelativeAxisCode::REL_HWHEEL, i32::MIN / 2),
+            report(),
+        ];
+        let deltas: Vec<_> = scroll
+            .scroll_events(events)
+            .map(|d| (d.vertical, d.horizontal))
+            .collect();
+        assert_eq!(deltas, [(i32::MAX, i32::MIN)]);
+    }
+}
@@ -0,0 +1,330 @@
+//! Serialization of events, axis information and device state.
+//!
+//! With a human-readable serializer such as JSON, events are maps of their timestamp, type,
+//! code and value, with the type and code given by name when they have one:
+//!
+//! ```json
+//! {"sec": 12, "usec": 500, "type": "EV_KEY", "code": "KEY_A", "value": 1}
+//! ```
+//!
+//! Other serializers get a compact tuple of numbers, `(sec, usec, type, code, value)`.
+
+use crate::compat::input_event;
+use crate::constants::*;
+use crate::event_variants::*;
+use crate::scancodes::KeyCode;
+use crate::{AbsInfo, DeviceState, EventSummary, InputEvent};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::time::SystemTime;
+
+/// A type or code, by name if it has one.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Name {
+    Number(u16),
+    Name(String),
+}
+
+#[derive(Serialize, Deserialize)]
+struct NamedEvent {
+    sec: i64,
+    usec: i64,
+    #[serde(rename = "type")]
+    type_: Name,
+    code: Name,
+    value: i32,
+}
+
+type CompactEvent = (i64, i64, u16, u16, i32);
+
+fn type_name(ty: EventType) -> Name {
+    match ty {
+        EventType::SYNCHRONIZATION
+        | EventType::KEY
+        | EventType::RELATIVE
+        | EventType::ABSOLUTE
+        | EventType::MISC
+        | EventType::SWITCH
+        | EventType::LED
+        | EventType::SOUND
+        | EventType::REPEAT
+        | EventType::FORCEFEEDBACK
+        | EventType::POWER
+        | EventType::FORCEFEEDBACKSTATUS => Name::Name(crate::evemu::type_name(ty)),
+        _ => Name::Number(ty.0),
+    }
+}
+
+fn code_name(event: &InputEvent) -> Name {
+    macro_rules! name {
+        ($code:expr) => {
+            match format!("{:?}", $code) {
+                // codes without a name are shown like `KEY_UNKNOWN(0x2e7)`
+                name if name.ends_with(')') => Name::Number(event.code()),
+                name => Name::Name(name),
+            }
+        };
+    }
+    match event.destructure() {
+        EventSummary::Synchronization(_, code, _) => name!(code),
+        EventSummary::Key(_, code, _) => name!(code),
+        EventSummary::RelativeAxis(_, code, _) => name!(code),
+        EventSummary::AbsoluteAxis(_, code, _) => name!(code),
+        EventSummary::Misc(_, code, _) => name!(code),
+        EventSummary::Switch(_, code, _) => name!(code),
+        EventSummary::Led(_, code, _) => name!(code),
+        EventSummary::Sound(_, code, _) => name!(code),
+        EventSummary::Repeat(_, code, _) => name!(code),
+        _ => Name::Number(event.code()),
+    }
+}
+
+fn parse_type(name: &str) -> Option<EventType> {
+    [
+        EventType::SYNCHRONIZATION,
+        EventType::KEY,
+        EventType::RELATIVE,
+        EventType::ABSOLUTE,
+        EventType::MISC,
+        EventType::SWITCH,
+        EventType::LED,
+        EventType::SOUND,
+        EventType::REPEAT,
+        EventType::FORCEFEEDBACK,
+        EventType::POWER,
+        EventType::FORCEFEEDBACKSTATUS,
+    ]
+    .into_iter()
+    .find(|&ty| crate::evemu::type_name(ty) == name)
+}
+
+fn parse_code(ty: EventType, name: &str) -> Option<u16> {
+    match ty {
+        EventType::SYNCHRONIZATION => name.parse::<SynchronizationCode>().ok().map(|c| c.0),
+        EventType::KEY => name.parse::<KeyCode>().ok().map(|c| c.0),
+        EventType::RELATIVE => name.parse::<RelativeAxisCode>().ok().map(|c| c.0),
+        EventType::ABSOLUTE => name.parse::<AbsoluteAxisCode>().ok().map(|c| c.0),
+        EventType::MISC => name.parse::<MiscCode>().ok().map(|c| c.0),
+        EventType::SWITCH => name.parse::<SwitchCode>().ok().map(|c| c.0),
+        EventType::LED => name.parse::<LedCode>().ok().map(|c| c.0),
+        EventType::SOUND => name.parse::<SoundCode>().ok().map(|c| c.0),
+        EventType::REPEAT => name.parse::<RepeatCode>().ok().map(|c| c.0),
+        _ => None,
+    }
+}
+
+fn make_event(sec: i64, usec: i64, type_: u16, code: u16, value: i32) -> InputEvent {
+    InputEvent::from(input_event {
+        time: crate::compat::timeval {
+            tv_sec: sec as _,
+            tv_usec: usec as _,
+        },
+        type_,
+        code,
+        value,
+    })
+}
+
+impl Serialize for InputEvent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let raw = self.as_ref();
+        // the fields are 32 bits wide on some platforms
+        #[allow(clippy::useless_conversion)]
+        let (sec, usec) = (i64::from(raw.time.tv_sec), i64::from(raw.time.tv_usec));
+        if serializer.is_human_readable() {
+            NamedEvent {
+                sec,
+                usec,
+                type_: type_name(self.event_type()),
+                code: code_name(self),
+                value: raw.value,
+            }
+            .serialize(serializer)
+        } else {
+            (sec, usec, raw.type_, raw.code, raw.value).serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for InputEvent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            let (sec, usec, type_, code, value) = CompactEvent::deserialize(deserializer)?;
+            return Ok(make_event(sec, usec, type_, code, value));
+        }
+        let named = NamedEvent::deserialize(deserializer)?;
+        let ty = match named.type_ {
+            Name::Number(n) => EventType(n),
+            Name::Name(name) => parse_type(&name)
+                .ok_or_else(|| D::Error::custom(format!("unknown event type {}", name)))?,
+        };
+        let code = match named.code {
+            Name::Number(n) => n,
+            Name::Name(name) => parse_code(ty, &name)
+                .ok_or_else(|| D::Error::custom(format!("unknown event code {}", name)))?,
+        };
+        Ok(make_event(named.sec, named.usec, ty.0, code, named.value))
+    }
+}
+
+macro_rules! event_variant_serde {
+    ($($name:ident => $summary:ident,)*) => {
+        $(
+            impl Serialize for $name {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    (**self).serialize(serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $name {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    match InputEvent::deserialize(deserializer)?.destructure() {
+                        EventSummary::$summary(event, ..) => Ok(event),
+                        _ => Err(D::Error::custom(concat!("expected a ", stringify!($name)))),
+                    }
+                }
+            }
+        )*
+
+        impl Serialize for EventSummary {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    $(EventSummary::$summary(event, ..) => event.serialize(serializer),)*
+                }
+            }
+        }
+    };
+}
+
+event_variant_serde! {
+    SynchronizationEvent => Synchronization,
+    KeyEvent => Key,
+    RelativeAxisEvent => RelativeAxis,
+    AbsoluteAxisEvent => AbsoluteAxis,
+    MiscEvent => Misc,
+    SwitchEvent => Switch,
+    LedEvent => Led,
+    SoundEvent => Sound,
+    RepeatEvent => Repeat,
+    FFEvent => ForceFeedback,
+    PowerEvent => Power,
+    FFStatusEvent => ForceFeedbackStatus,
+    UInputEvent => UInput,
+    OtherEvent => Other,
+}
+
+impl<'de> Deserialize<'de> for EventSummary {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        InputEvent::deserialize(deserializer).map(InputEvent::destructure)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct NamedAbsInfo {
+    value: i32,
+    minimum: i32,
+    maximum: i32,
+    fuzz: i32,
+    flat: i32,
+    resolution: i32,
+}
+
+impl Serialize for AbsInfo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            NamedAbsInfo {
+                value: self.value(),
+                minimum: self.minimum(),
+                maximum: self.maximum(),
+                fuzz: self.fuzz(),
+                flat: self.flat(),
+                resolution: self.resolution(),
+            }
+            .serialize(serializer)
+        } else {
+            (
+                self.value(),
+                self.minimum(),
+                self.maximum(),
+                self.fuzz(),
+                self.flat(),
+                self.resolution(),
+            )
+                .serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for AbsInfo {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            let (value, min, max, fuzz, flat, res) = Deserialize::deserialize(deserializer)?;
+            return Ok(AbsInfo::new(value, min, max, fuzz, flat, res));
+        }
+        let info = NamedAbsInfo::deserialize(deserializer)?;
+        Ok(AbsInfo::new(
+            info.value,
+            info.minimum,
+            info.maximum,
+            info.fuzz,
+            info.flat,
+            info.resolution,
+        ))
+    }
+}
+
+/// The serialized form of a [`DeviceState`]. Axes whose information is all zeroes are left out.
+#[derive(Serialize, Deserialize)]
+struct SerializedState {
+    timestamp: SystemTime,
+    keys: Option<Vec<KeyCode>>,
+    absolute_axes: Option<Vec<(AbsoluteAxisCode, AbsInfo)>>,
+    switches: Option<Vec<SwitchCode>>,
+    leds: Option<Vec<LedCode>>,
+}
+
+impl Serialize for DeviceState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializedState {
+            timestamp: self.timestamp,
+            keys: self.key_vals.as_ref().map(|set| set.iter().collect()),
+            absolute_axes: self.abs_vals.as_ref().map(|vals| {
+                vals.iter()
+                    .enumerate()
+                    .map(|(i, &info)| (AbsoluteAxisCode(i as u16), AbsInfo(info)))
+                    .filter(|(_, info)| *info != AbsInfo::new(0, 0, 0, 0, 0, 0))
+                    .collect()
+            }),
+            switches: self.switch_vals.as_ref().map(|set| set.iter().collect()),
+            leds: self.led_vals.as_ref().map(|set| set.iter().collect()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DeviceState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let state = SerializedState::deserialize(deserializer)?;
+        let abs_vals = match state.absolute_axes {
+            Some(axes) => {
+                let mut vals = Box::new(crate::raw_stream::ABS_VALS_INIT);
+                for (axis, info) in axes {
+                    let slot = vals
+                        .get_mut(axis.0 as usize)
+                        .ok_or_else(|| D::Error::custom("absolute axis out of range"))?;
+                    *slot = info.0;
+                }
+                Some(vals)
+            }
+            None => None,
+        };
+        Ok(DeviceState {
+            timestamp: state.timestamp,
+            key_vals: state.keys.map(|keys| keys.into_iter().collect()),
+            abs_vals,
+            switch_vals: state.switches.map(|sw| sw.into_iter().collect()),
+            led_vals: state.leds.map(|leds| leds.into_iter().collect()),
+        })
+    }
+}
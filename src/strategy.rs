@@ -0,0 +1,68 @@
+//! [Proptest](https://docs.rs/proptest) strategies generating events.
+//!
+//! The events look like the ones devices send: their codes are mostly named codes of their type,
+//! and their values are in the range their type uses, e.g. 0 to 2 for keys. Strategies for codes
+//! of a specific type can be made from the list of named codes, e.g.
+//! `proptest::sample::select(KeyCode::ALL)`.
+//!
+//! ```
+//! use evdev::strategy;
+//! use evdev::EventType;
+//! use proptest::prelude::*;
+//! use proptest::test_runner::TestRunner;
+//!
+//! let mut runner = TestRunner::default();
+//! runner
+//!     .run(&strategy::frame(), |frame| {
+//!         prop_assert_eq!(frame.last().unwrap().event_type(), EventType::SYNCHRONIZATION);
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! ```
+
+use crate::fuzz::{event, named_codes, value_range, SECONDS};
+use crate::{EventSummary, EventType, InputEvent, SynchronizationCode};
+use proptest::prelude::*;
+
+/// Returns a strategy generating the named event types.
+pub fn event_type() -> impl Strategy<Value = EventType> {
+    proptest::sample::select(EventType::ALL)
+}
+
+/// Returns a strategy generating events of any type.
+pub fn input_event() -> impl Strategy<Value = InputEvent> {
+    (event_type(), SECONDS, 0..1_000_000u32).prop_flat_map(|(ty, sec, usec)| {
+        let (count, code_at) = named_codes(ty);
+        let code = if count > 0 {
+            (0..count).prop_map(code_at).boxed()
+        } else {
+            any::<u16>().boxed()
+        };
+        (code, value_range(ty)).prop_map(move |(code, value)| event(ty, code, value, sec, usec))
+    })
+}
+
+/// Returns a strategy generating events of any type, destructured.
+pub fn event_summary() -> impl Strategy<Value = EventSummary> {
+    input_event().prop_map(|ev| ev.destructure())
+}
+
+/// Returns a strategy generating frames: one to eight events other than synchronization
+/// events, followed by a `SYN_REPORT`, all with the same timestamp.
+pub fn frame() -> impl Strategy<Value = Vec<InputEvent>> {
+    let events = input_event().prop_filter("synchronization event", |ev| {
+        ev.event_type() != EventType::SYNCHRONIZATION
+    });
+    let report = SynchronizationCode::SYN_REPORT.0;
+    (
+        proptest::collection::vec(events, 1..=8),
+        SECONDS,
+        0..1_000_000u32,
+    )
+        .prop_map(move |(events, sec, usec)| {
+            let retime = |ev: &InputEvent| event(ev.event_type(), ev.code(), ev.value(), sec, usec);
+            let mut frame: Vec<_> = events.iter().map(retime).collect();
+            frame.push(event(EventType::SYNCHRONIZATION, report, 0, sec, usec));
+            frame
+        })
+}
@@ -4,15 +4,15 @@ use crate::device_state::DeviceState;
 use crate::ff::*;
 use crate::raw_stream::RawDevice;
 use crate::{
-    AbsInfo, AttributeSet, AttributeSetRef, AutoRepeat, EventSummary, FFEffect, InputEvent,
-    InputId, KeyCode,
+    AbsInfo, AbsQuirks, AttributeSet, AttributeSetRef, AutoRepeat, CapabilitiesReport,
+    EventSummary, FFEffect, Frames, InputEvent, InputId, KeyCode,
 };
 
 use nix::fcntl;
 use std::fs::File;
 use std::os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd};
 use std::path::Path;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 use std::{fmt, io};
 
 /// A physical or virtual device supported by evdev.
@@ -27,12 +27,15 @@ use std::{fmt, io};
 /// If `fetch_events()` isn't called often enough and the kernel drops events from its internal
 /// buffer, synthetic events will be injected into the iterator returned by `fetch_events()` and
 /// [`Device::cached_state()`] will be kept up to date when `fetch_events()` is called.
+/// Synthetic events can be told apart from real ones with [`FetchEventsSynced::is_synthetic`].
+/// This recovery can be disabled with [`Device::set_sync_on_drop`].
 #[derive(Debug)]
 pub struct Device {
     raw: RawDevice,
     prev_state: DeviceState,
     state: DeviceState,
     block_dropped: bool,
+    sync_on_drop: bool,
 }
 
 impl Device {
@@ -44,7 +47,16 @@ impl Device {
         Self::_open(path.as_ref())
     }
 
+    /// Opens a device like [`open`](Self::open), and takes an advisory exclusive lock on it.
+    ///
+    /// See [`RawDevice::open_exclusive`].
+    pub fn open_exclusive(path: impl AsRef<Path>) -> io::Result<Device> {
+        RawDevice::open_exclusive(path).map(Self::from_raw_device)
+    }
+
     /// Opens a device, given an already opened file descriptor.
+    ///
+    /// See [`RawDevice::from_fd`].
     #[inline(always)]
     pub fn from_fd(fd: OwnedFd) -> io::Result<Device> {
         RawDevice::from_fd(fd).map(Self::from_raw_device)
@@ -65,6 +77,7 @@ impl Device {
             prev_state,
             state,
             block_dropped: false,
+            sync_on_drop: true,
         }
     }
 
@@ -295,6 +308,41 @@ impl Device {
         self.raw.get_absinfo()
     }
 
+    /// Returns a report of everything the device supports, such as its identifiers, properties,
+    /// supported codes and axis ranges.
+    ///
+    /// With the `serde` feature enabled, the report can be serialized to get a machine-readable
+    /// description of the device.
+    pub fn capabilities_report(&self) -> io::Result<CapabilitiesReport> {
+        self.raw.capabilities_report()
+    }
+
+    /// Query the capabilities of the device from the kernel again, see
+    /// [`RawDevice::refresh_capabilities`].
+    ///
+    /// The [cached state](Self::cached_state), including the current `AbsInfo` of the axes, is
+    /// queried again as well, to match the new capabilities.
+    pub fn refresh_capabilities(&mut self) -> io::Result<()> {
+        self.raw.refresh_capabilities()?;
+        self.state = DeviceState::new(&self.raw);
+        self.sync_state(SystemTime::now())?;
+        self.prev_state.clone_from(&self.state);
+        Ok(())
+    }
+
+    /// Apply the corrections of `quirks` that match this device, see [`RawDevice::apply_abs_quirks`].
+    ///
+    /// The `AbsInfo` of the [cached state](Self::cached_state) is queried again with the
+    /// corrections applied. Returns `true` if any corrections matched.
+    pub fn apply_abs_quirks(&mut self, quirks: &AbsQuirks) -> io::Result<bool> {
+        let matched = self.raw.apply_abs_quirks(quirks);
+        if let Some(ref mut abs_vals) = self.state.abs_vals {
+            self.raw.update_abs_state(abs_vals)?;
+            self.prev_state.abs_vals.clone_from(&self.state.abs_vals);
+        }
+        Ok(matched)
+    }
+
     /// Retrieve the current switch state directly via kernel syscall.
     pub fn get_switch_state(&self) -> io::Result<AttributeSet<SwitchCode>> {
         self.raw.get_switch_state()
@@ -306,32 +354,36 @@ impl Device {
     }
 
     fn sync_state(&mut self, now: SystemTime) -> io::Result<()> {
-        if let Some(ref mut key_vals) = self.state.key_vals {
-            self.raw.update_key_state(key_vals)?;
-        }
-        if let Some(ref mut abs_vals) = self.state.abs_vals {
-            self.raw.update_abs_state(abs_vals)?;
-        }
-        if let Some(ref mut switch_vals) = self.state.switch_vals {
-            self.raw.update_switch_state(switch_vals)?;
-        }
-        if let Some(ref mut led_vals) = self.state.led_vals {
-            self.raw.update_led_state(led_vals)?;
-        }
-        self.state.timestamp = now;
-        Ok(())
+        query_state(&self.raw, &mut self.state, now)
+    }
+
+    /// Query the current key, absolute axis, switch, and LED state directly from the kernel.
+    ///
+    /// Unlike [`cached_state`](Self::cached_state) this always reflects the state of the device
+    /// at the time of the call, regardless of how far the event stream has been read. Together
+    /// with [`DeviceState::diff`] this can be used to restore a previously saved state.
+    pub fn snapshot_state(&self) -> io::Result<DeviceState> {
+        let mut state = DeviceState::new(&self.raw);
+        query_state(&self.raw, &mut state, SystemTime::now())?;
+        Ok(state)
+    }
+
+    /// Re-query the kernel state after events were dropped, returning the state for emitting the
+    /// compensatory events.
+    fn resync(&mut self) -> io::Result<SyncState> {
+        self.block_dropped = false;
+        self.prev_state.clone_from(&self.state);
+        let now = SystemTime::now();
+        self.sync_state(now)?;
+        Ok(SyncState::KeyTypes {
+            time: crate::systime_to_timeval(&now),
+            start: KeyCode::new(0),
+        })
     }
 
     fn fetch_events_inner(&mut self) -> io::Result<Option<SyncState>> {
-        let block_dropped = std::mem::take(&mut self.block_dropped);
-        let sync = if block_dropped {
-            self.prev_state.clone_from(&self.state);
-            let now = SystemTime::now();
-            self.sync_state(now)?;
-            Some(SyncState::KeyTypes {
-                time: crate::systime_to_timeval(&now),
-                start: KeyCode::new(0),
-            })
+        let sync = if self.block_dropped {
+            Some(self.resync()?)
         } else {
             None
         };
@@ -341,27 +393,109 @@ impl Device {
         Ok(sync)
     }
 
+    /// Whether the device recovers from dropped events by re-querying the kernel state.
+    ///
+    /// See [`set_sync_on_drop`](Self::set_sync_on_drop).
+    pub fn sync_on_drop(&self) -> bool {
+        self.sync_on_drop
+    }
+
+    /// Enable or disable the recovery from dropped events. It is enabled by default.
+    ///
+    /// When enabled, any frame affected by a `SYN_DROPPED` is discarded and the device state is
+    /// re-queried from the kernel. Synthetic events describing the changes are then emitted in
+    /// place of the lost ones, followed by a `SYN_REPORT`.
+    ///
+    /// When disabled, events are returned exactly as they were read from the kernel, including
+    /// `SYN_DROPPED` and incomplete frames, and the [cached state](Self::cached_state) is updated
+    /// from them as well as possible. This is mostly useful for tools that need to observe the
+    /// raw event stream while still using a [`Device`].
+    pub fn set_sync_on_drop(&mut self, enabled: bool) {
+        self.sync_on_drop = enabled;
+        if !enabled {
+            self.block_dropped = false;
+        }
+    }
+
     /// Fetches and returns events from the kernel ring buffer, doing synchronization on SYN_DROPPED.
     ///
     /// By default this will block until events are available. Typically, users will want to call
     /// this in a tight loop within a thread.
     /// Will insert "fake" events.
     pub fn fetch_events(&mut self) -> io::Result<FetchEventsSynced<'_>> {
+        self.fetch_events_synced(false)
+    }
+
+    /// Fetches events, leaving events after the last `SYN_REPORT` in the buffer if
+    /// `whole_frames` is set.
+    fn fetch_events_synced(&mut self, whole_frames: bool) -> io::Result<FetchEventsSynced<'_>> {
         let sync = self.fetch_events_inner()?;
+        // without recovery, every event in the buffer is passed through as-is
+        let passthrough = !self.sync_on_drop;
+        let (range, consumed_to) = if passthrough {
+            let len = if whole_frames {
+                crate::frames::complete_frames_len(&self.raw.event_buf)
+            } else {
+                self.raw.event_buf.len()
+            };
+            (0..len, len)
+        } else {
+            (0..0, 0)
+        };
 
         Ok(FetchEventsSynced {
             dev: self,
-            range: 0..0,
-            consumed_to: 0,
+            range,
+            consumed_to,
             sync,
+            passthrough,
+            synthetic: false,
         })
     }
 
+    /// Fetches events like [`fetch_events`](Self::fetch_events), but waits at most `timeout` for
+    /// events to become available.
+    ///
+    /// Returns `Ok(None)` if no events arrived in time. This allows blocking callers to wake up
+    /// periodically without switching the device to non-blocking mode. Timeouts are rounded up
+    /// to whole milliseconds.
+    pub fn fetch_events_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> io::Result<Option<FetchEventsSynced<'_>>> {
+        if !crate::poll_readable(self.as_fd(), timeout)? {
+            return Ok(None);
+        }
+        self.fetch_events().map(Some)
+    }
+
+    /// Fetches events like [`fetch_events`](Self::fetch_events), but grouped into frames.
+    ///
+    /// Each frame contains the events between two `SYN_REPORT`s, without the report itself.
+    /// Frames affected by a `SYN_DROPPED` are replaced by synthetic events as usual. Events
+    /// following the last `SYN_REPORT` are kept in the internal buffer until the rest of their
+    /// frame has been read, so frames are never split between calls.
+    pub fn fetch_frames(&mut self) -> io::Result<Frames<FetchEventsSynced<'_>>> {
+        self.fetch_events_synced(true).map(Frames::new)
+    }
+
     #[cfg(feature = "tokio")]
     pub fn into_event_stream(self) -> io::Result<EventStream> {
         EventStream::new(self)
     }
 
+    /// Returns the maximum number of events read from the kernel at once.
+    pub fn read_buffer_capacity(&self) -> usize {
+        self.raw.read_buffer_capacity()
+    }
+
+    /// Set the maximum number of events read from the kernel at once. Defaults to 32.
+    ///
+    /// See [`RawDevice::set_read_buffer_capacity`].
+    pub fn set_read_buffer_capacity(&mut self, capacity: usize) {
+        self.raw.set_read_buffer_capacity(capacity)
+    }
+
     /// Set `O_NONBLOCK` on this device handle.
     pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
         let mut flags =
@@ -389,6 +523,135 @@ impl Device {
         self.raw.is_grabbed()
     }
 
+    /// Returns whether the device may wake the system from suspend, or `None` if it can't.
+    ///
+    /// This reads the `power/wakeup` attribute in sysfs of the device, or rather of the physical
+    /// device it belongs to, such as a USB keyboard.
+    pub fn wakeup(&self) -> io::Result<Option<bool>> {
+        match self.wakeup_attribute()? {
+            Some(attr) => crate::sysfs::parse_wakeup(&std::fs::read_to_string(attr)?),
+            None => Ok(None),
+        }
+    }
+
+    /// Allow or forbid the device to wake the system from suspend, which usually requires root.
+    ///
+    /// Fails with [`io::ErrorKind::Unsupported`] if the device can't wake the system. See
+    /// [`wakeup`](Self::wakeup).
+    pub fn set_wakeup(&self, enabled: bool) -> io::Result<()> {
+        let attr = self.wakeup_attribute()?.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Unsupported, "device can't wake the system")
+        })?;
+        std::fs::write(attr, if enabled { "enabled" } else { "disabled" })
+    }
+
+    fn wakeup_attribute(&self) -> io::Result<Option<std::path::PathBuf>> {
+        Ok(crate::sysfs::wakeup_attribute(&self.sysfs_dir()?))
+    }
+
+    /// Returns the battery of the device, for wireless devices whose driver reports it, such as
+    /// many Bluetooth gamepads and Logitech devices.
+    ///
+    /// The battery is found among the `power_supply` devices in sysfs that belong to the device
+    /// or to the physical device it is part of. Returns `None` if there is no such battery or it
+    /// can't be read.
+    pub fn battery(&self) -> Option<crate::sysfs::BatteryInfo> {
+        crate::sysfs::battery(&self.sysfs_dir().ok()?)
+    }
+
+    /// Returns the hidraw nodes, such as `/dev/hidraw2`, of the HID device this device belongs
+    /// to, e.g. to send vendor-specific feature reports to it.
+    ///
+    /// The nodes of the HID device itself come first, followed by those of the other interfaces
+    /// of the same USB device. Returns an empty list for devices that aren't HID devices.
+    pub fn hidraw_paths(&self) -> io::Result<Vec<std::path::PathBuf>> {
+        Ok(crate::sysfs::hidraw_nodes(&self.sysfs_dir()?))
+    }
+
+    /// Returns the LED class devices of the device in sysfs, such as
+    /// `/sys/class/leds/input3::capslock`, along with the LEDs they control.
+    ///
+    /// These are created by the kernel for every LED of an input device, and can be used to
+    /// control LEDs of drivers that ignore `EV_LED` events, see
+    /// [`set_led_brightness`](Self::set_led_brightness).
+    pub fn sysfs_leds(&self) -> io::Result<Vec<(LedCode, std::path::PathBuf)>> {
+        let dir = self.sysfs_dir()?;
+        let input = dir.parent().unwrap_or(&dir);
+        Ok(crate::sysfs::led_class_dirs(input))
+    }
+
+    /// Set the brightness of an LED through its LED class device in sysfs, which usually
+    /// requires root. Any value other than `0` turns on LEDs that can only be on or off.
+    ///
+    /// Fails with [`io::ErrorKind::NotFound`] if the device has no LED class device for `led`.
+    /// See [`sysfs_leds`](Self::sysfs_leds).
+    pub fn set_led_brightness(&self, led: LedCode, brightness: u32) -> io::Result<()> {
+        let (_, dir) = self
+            .sysfs_leds()?
+            .into_iter()
+            .find(|(l, _)| *l == led)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no LED class device"))?;
+        std::fs::write(dir.join("brightness"), brightness.to_string())
+    }
+
+    /// Returns the sysfs directory of the event node, such as
+    /// `/sys/devices/platform/i8042/serio0/input/input3/event3`.
+    fn sysfs_dir(&self) -> io::Result<std::path::PathBuf> {
+        let stat = nix::sys::stat::fstat(self.as_raw_fd())?;
+        crate::sysfs::char_device_dir(stat.st_rdev)
+    }
+
+    /// Set the clock used for the timestamps of the events read from now on, see
+    /// [`EventTimestamp`](crate::EventTimestamp) for converting between clocks.
+    pub fn set_event_clock(&mut self, clock: crate::EventClock) -> io::Result<()> {
+        self.raw.set_event_clock(clock)
+    }
+
+    /// Returns the clock used for event timestamps, as set with
+    /// [`set_event_clock`](Self::set_event_clock). Devices start out using the wall clock.
+    pub fn event_clock(&self) -> crate::EventClock {
+        self.raw.event_clock()
+    }
+
+    /// Create a new handle for the device, sharing its file descriptor, with a copy of its
+    /// cached state.
+    ///
+    /// See [`RawDevice::try_clone`].
+    pub fn try_clone(&self) -> io::Result<Device> {
+        Ok(Device {
+            raw: self.raw.try_clone()?,
+            prev_state: self.prev_state.clone(),
+            state: self.state.clone(),
+            block_dropped: false,
+            sync_on_drop: self.sync_on_drop,
+        })
+    }
+
+    /// Returns a handle for sending events to the device and managing its force feedback
+    /// effects from other threads while this one reads its events.
+    ///
+    /// See [`DeviceControl`](crate::DeviceControl).
+    pub fn control(&self) -> crate::DeviceControl {
+        self.raw.control()
+    }
+
+    /// Give up the device without releasing its grab or erasing its force feedback effects,
+    /// returning a file descriptor for it.
+    ///
+    /// See [`RawDevice::leak`].
+    pub fn leak(self) -> OwnedFd {
+        self.raw.leak()
+    }
+
+    /// Finds the processes other than this one that have the device open, e.g. to report who
+    /// holds it when [`grab`](Self::grab) fails with `EBUSY`. See [`device_holders`].
+    ///
+    /// [`device_holders`]: crate::device_holders
+    pub fn holders(&self) -> io::Result<Vec<crate::DeviceHolder>> {
+        let stat = nix::sys::stat::fstat(self.as_raw_fd())?;
+        Ok(crate::access::holders_of(stat.st_rdev))
+    }
+
     /// Send an event to the device.
     ///
     /// Events that are typically sent to devices are
@@ -404,6 +667,19 @@ impl Device {
         self.raw.upload_ff_effect(data)
     }
 
+    /// Rumbles the device for `duration`, see [`RawDevice::rumble`].
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// let mut gamepad = evdev::Device::open("/dev/input/event0")?;
+    /// gamepad.rumble(0xc000, 0x4000, Duration::from_millis(200))?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn rumble(&mut self, strong: u16, weak: u16, duration: Duration) -> io::Result<()> {
+        self.raw.rumble(strong, weak, duration)
+    }
+
     /// Sets the force feedback gain, i.e. how strong the force feedback effects should be for the
     /// device. A gain of 0 means no gain, whereas `u16::MAX` is the maximum gain.
     pub fn set_ff_gain(&mut self, value: u16) -> io::Result<()> {
@@ -416,14 +692,6 @@ impl Device {
     }
 }
 
-impl Drop for Device {
-    fn drop(&mut self) {
-        if let Err(error) = self.ungrab() {
-            eprintln!("Failed to ungrab device: {error}");
-        }
-    }
-}
-
 impl AsFd for Device {
     fn as_fd(&self) -> BorrowedFd<'_> {
         self.raw.as_fd()
@@ -436,6 +704,21 @@ impl AsRawFd for Device {
     }
 }
 
+impl TryFrom<OwnedFd> for Device {
+    type Error = io::Error;
+
+    fn try_from(fd: OwnedFd) -> Result<Self, Self::Error> {
+        Self::from_fd(fd)
+    }
+}
+
+/// Gives up the device like [`Device::leak`], keeping its grab and force feedback effects.
+impl From<Device> for OwnedFd {
+    fn from(device: Device) -> Self {
+        device.raw.into()
+    }
+}
+
 impl TryFrom<File> for Device {
     type Error = io::Error;
 
@@ -444,6 +727,23 @@ impl TryFrom<File> for Device {
     }
 }
 
+fn query_state(raw: &RawDevice, state: &mut DeviceState, now: SystemTime) -> io::Result<()> {
+    if let Some(ref mut key_vals) = state.key_vals {
+        raw.update_key_state(key_vals)?;
+    }
+    if let Some(ref mut abs_vals) = state.abs_vals {
+        raw.update_abs_state(abs_vals)?;
+    }
+    if let Some(ref mut switch_vals) = state.switch_vals {
+        raw.update_switch_state(switch_vals)?;
+    }
+    if let Some(ref mut led_vals) = state.led_vals {
+        raw.update_led_state(led_vals)?;
+    }
+    state.timestamp = now;
+    Ok(())
+}
+
 /// An iterator over events of a [`Device`], produced by [`Device::fetch_events`].
 pub struct FetchEventsSynced<'a> {
     dev: &'a mut Device,
@@ -455,23 +755,38 @@ pub struct FetchEventsSynced<'a> {
     /// Our current synchronization state, i.e. whether we're currently diffing key_vals,
     /// abs_vals, switch_vals, led_vals, or none of them.
     sync: Option<SyncState>,
+    /// Whether events are passed through without dropped-event recovery.
+    passthrough: bool,
+    /// Whether the last event we returned was synthesized during recovery.
+    synthetic: bool,
+}
+
+impl FetchEventsSynced<'_> {
+    /// Returns `true` if the event most recently returned by this iterator is synthetic.
+    ///
+    /// Synthetic events are generated after the kernel dropped events, in order to bring the
+    /// consumer back in sync with the current state of the device. They carry the time of the
+    /// resynchronization as their timestamp.
+    pub fn is_synthetic(&self) -> bool {
+        self.synthetic
+    }
 }
 
 enum SyncState {
     KeyTypes {
-        time: libc::timeval,
+        time: crate::compat::timeval,
         start: KeyCode,
     },
     Absolutes {
-        time: libc::timeval,
+        time: crate::compat::timeval,
         start: AbsoluteAxisCode,
     },
     Switches {
-        time: libc::timeval,
+        time: crate::compat::timeval,
         start: SwitchCode,
     },
     Leds {
-        time: libc::timeval,
+        time: crate::compat::timeval,
         start: LedCode,
     },
 }
@@ -588,26 +903,39 @@ fn compensate_events(state: &mut Option<SyncState>, dev: &mut Device) -> Option<
 impl Iterator for FetchEventsSynced<'_> {
     type Item = InputEvent;
     fn next(&mut self) -> Option<InputEvent> {
-        // first: check if we need to emit compensatory events due to a SYN_DROPPED we found in the
-        // last batch of blocks
-        if let Some(ev) = compensate_events(&mut self.sync, self.dev) {
-            self.dev.prev_state.process_event(ev);
+        if self.passthrough {
+            self.synthetic = false;
+            let ev = InputEvent::from(self.dev.raw.event_buf[self.range.next()?]);
+            self.dev.state.process_event(ev);
             return Some(ev);
         }
-        let state = &mut self.dev.state;
-        let (res, consumed_to) = sync_events(&mut self.range, &self.dev.raw.event_buf, |ev| {
-            state.process_event(ev)
-        });
-        if let Some(end) = consumed_to {
-            self.consumed_to = end
-        }
-        match res {
-            Ok(ev) => Some(InputEvent::from(ev)),
-            Err(requires_sync) => {
-                if requires_sync {
-                    self.dev.block_dropped = true;
-                }
-                None
+        loop {
+            // first: check if we need to emit compensatory events due to a SYN_DROPPED we found
+            // in an earlier block
+            if let Some(ev) = compensate_events(&mut self.sync, self.dev) {
+                self.dev.prev_state.process_event(ev);
+                self.synthetic = true;
+                return Some(ev);
+            }
+            self.synthetic = false;
+            let state = &mut self.dev.state;
+            let (res, consumed_to) = sync_events(&mut self.range, &self.dev.raw.event_buf, |ev| {
+                state.process_event(ev)
+            });
+            if let Some(end) = consumed_to {
+                self.consumed_to = end
+            }
+            match res {
+                Ok(ev) => return Some(InputEvent::from(ev)),
+                Err(true) => match self.dev.resync() {
+                    Ok(sync) => self.sync = Some(sync),
+                    // try again on the next call to fetch_events
+                    Err(_) => {
+                        self.dev.block_dropped = true;
+                        return None;
+                    }
+                },
+                Err(false) => return None,
             }
         }
     }
@@ -783,6 +1111,7 @@ impl fmt::Display for Device {
 mod tokio_stream {
     use super::*;
 
+    use crate::{HardwareClock, TimestampedFrame};
     use std::future::poll_fn;
     use std::task::{ready, Context, Poll};
     use tokio::io::unix::AsyncFd;
@@ -798,6 +1127,8 @@ mod tokio_stream {
         event_range: std::ops::Range<usize>,
         consumed_to: usize,
         sync: Option<SyncState>,
+        synthetic: bool,
+        clock: HardwareClock,
     }
     impl Unpin for EventStream {}
 
@@ -810,9 +1141,18 @@ mod tokio_stream {
                 event_range: 0..0,
                 consumed_to: 0,
                 sync: None,
+                synthetic: false,
+                clock: HardwareClock::new(),
             })
         }
 
+        /// Returns `true` if the event most recently returned by this stream is synthetic.
+        ///
+        /// See [`FetchEventsSynced::is_synthetic`].
+        pub fn is_synthetic(&self) -> bool {
+            self.synthetic
+        }
+
         /// Returns a reference to the underlying device
         pub fn device(&self) -> &Device {
             self.device.get_ref()
@@ -829,27 +1169,71 @@ mod tokio_stream {
             poll_fn(|cx| self.poll_event(cx)).await
         }
 
+        /// Wait for the next complete frame of events, i.e. all events up to the next
+        /// `SYN_REPORT`, which is not included.
+        ///
+        /// This is not cancel safe: if the returned future is dropped before it completes, the
+        /// events of the partially read frame are lost.
+        pub async fn next_frame(&mut self) -> io::Result<Vec<InputEvent>> {
+            let mut frame = Vec::new();
+            loop {
+                let ev = self.next_event().await?;
+                if ev.event_type() == EventType::SYNCHRONIZATION
+                    && ev.code() == SynchronizationCode::SYN_REPORT.0
+                {
+                    return Ok(frame);
+                }
+                frame.push(ev);
+            }
+        }
+
+        /// Wait for the next complete frame of events, along with its hardware timestamp
+        /// (`MSC_TIMESTAMP`), if the device reports one.
+        ///
+        /// Wraparounds of the hardware counter are tracked across calls; see [`HardwareClock`].
+        /// Like [`next_frame`](Self::next_frame), this is not cancel safe.
+        pub async fn next_timestamped_frame(&mut self) -> io::Result<TimestampedFrame> {
+            let events = self.next_frame().await?;
+            let hardware_time = self.clock.update_from_frame(&events);
+            Ok(TimestampedFrame {
+                events,
+                hardware_time,
+            })
+        }
+
         /// A lower-level function for directly polling this stream.
         pub fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<InputEvent>> {
             'outer: loop {
                 let dev = self.device.get_mut();
                 if let Some(ev) = compensate_events(&mut self.sync, dev) {
+                    dev.prev_state.process_event(ev);
+                    self.synthetic = true;
                     return Poll::Ready(Ok(ev));
                 }
-                let state = &mut dev.state;
-                let (res, consumed_to) =
-                    sync_events(&mut self.event_range, &dev.raw.event_buf, |ev| {
-                        state.process_event(ev)
-                    });
-                if let Some(end) = consumed_to {
-                    self.consumed_to = end
-                }
-                match res {
-                    Ok(ev) => return Poll::Ready(Ok(InputEvent::from(ev))),
-                    Err(requires_sync) => {
-                        if requires_sync {
-                            dev.block_dropped = true;
+                self.synthetic = false;
+                if !dev.sync_on_drop {
+                    if let Some(idx) = self.event_range.next() {
+                        let ev = InputEvent::from(dev.raw.event_buf[idx]);
+                        dev.state.process_event(ev);
+                        return Poll::Ready(Ok(ev));
+                    }
+                    self.consumed_to = self.event_range.end;
+                } else {
+                    let state = &mut dev.state;
+                    let (res, consumed_to) =
+                        sync_events(&mut self.event_range, &dev.raw.event_buf, |ev| {
+                            state.process_event(ev)
+                        });
+                    if let Some(end) = consumed_to {
+                        self.consumed_to = end
+                    }
+                    match res {
+                        Ok(ev) => return Poll::Ready(Ok(InputEvent::from(ev))),
+                        Err(true) => {
+                            self.sync = Some(dev.resync()?);
+                            continue 'outer;
                         }
+                        Err(false) => {}
                     }
                 }
                 dev.raw.event_buf.drain(..self.consumed_to);
@@ -862,7 +1246,12 @@ mod tokio_stream {
                     match res {
                         Ok(res) => {
                             self.sync = res?;
-                            self.event_range = 0..0;
+                            let dev = self.device.get_ref();
+                            self.event_range = if dev.sync_on_drop {
+                                0..0
+                            } else {
+                                0..dev.raw.event_buf.len()
+                            };
                             continue 'outer;
                         }
                         Err(_would_block) => continue,
@@ -909,7 +1298,7 @@ mod tests {
     }
 
     #[allow(non_upper_case_globals)]
-    const time: libc::timeval = libc::timeval {
+    const time: crate::compat::timeval = crate::compat::timeval {
         tv_sec: 0,
         tv_usec: 0,
     };
@@ -956,4 +1345,11 @@ mod tests {
         assert_eq!(next(), (Err(false), None));
         assert_eq!(next(), (Err(false), None));
     }
+
+    #[test]
+    fn test_from_fd_rejects_other_files() {
+        let file = File::open("/dev/null").unwrap();
+        let err = Device::from_fd(file.into()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
 }
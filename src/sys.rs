@@ -50,12 +50,10 @@ pub struct uinput_ff_erase {
 
 ioctl_read!(eviocgeffects, b'E', 0x84, ::libc::c_int);
 ioctl_read!(eviocgid, b'E', 0x02, /*struct*/ input_id);
-ioctl_read!(eviocgkeycode, b'E', 0x04, [::libc::c_uint; 2]);
 ioctl_read!(eviocgrep, b'E', 0x03, [::libc::c_uint; 2]);
 ioctl_read!(eviocgversion, b'E', 0x01, ::libc::c_int);
 ioctl_write_int!(eviocrmff, b'E', 0x81);
 
-ioctl_read!(eviocgkeycode_v2, b'E', 0x04, input_keymap_entry);
 ioctl_write_ptr!(eviocskeycode, b'E', 0x04, [::libc::c_uint; 2]);
 ioctl_write_ptr!(eviocskeycode_v2, b'E', 0x04, input_keymap_entry);
 ioctl_write_ptr!(eviocsrep, b'E', 0x03, [::libc::c_uint; 2]);
@@ -64,21 +62,35 @@ ioctl_read_buf!(eviocgname, b'E', 0x06, u8);
 ioctl_read_buf!(eviocgphys, b'E', 0x07, u8);
 ioctl_read_buf!(eviocguniq, b'E', 0x08, u8);
 ioctl_read_buf!(eviocgprop, b'E', 0x09, u8);
-ioctl_read_buf!(eviocgmtslots, b'E', 0x0a, u8);
 ioctl_read_buf!(eviocgkey, b'E', 0x18, u8);
 ioctl_read_buf!(eviocgled, b'E', 0x19, u8);
 ioctl_read_buf!(eviocgsnd, b'E', 0x1a, u8);
 ioctl_read_buf!(eviocgsw, b'E', 0x1b, u8);
 
+// The BSDs only copy the argument into the kernel for requests that are marked as writing, so the
+// requests that take a scancode or an axis code in their buffer are read-write there.
+cfg_if::cfg_if! {
+    if #[cfg(any(target_os = "freebsd", target_os = "dragonfly"))] {
+        ioctl_readwrite!(eviocgkeycode, b'E', 0x04, [::libc::c_uint; 2]);
+        ioctl_readwrite!(eviocgkeycode_v2, b'E', 0x04, input_keymap_entry);
+        nix::ioctl_readwrite_buf!(eviocgmtslots, b'E', 0x0a, u8);
+    } else {
+        ioctl_read!(eviocgkeycode, b'E', 0x04, [::libc::c_uint; 2]);
+        ioctl_read!(eviocgkeycode_v2, b'E', 0x04, input_keymap_entry);
+        ioctl_read_buf!(eviocgmtslots, b'E', 0x0a, u8);
+    }
+}
+
 ioctl_write_ptr!(eviocsff, b'E', 0x80, ff_effect);
 ioctl_write_int!(eviocgrab, b'E', 0x90);
 ioctl_write_int!(eviocrevoke, b'E', 0x91);
-ioctl_write_int!(eviocsclockid, b'E', 0xa0);
+ioctl_write_ptr!(eviocsclockid, b'E', 0xa0, libc::c_int);
 
 const UINPUT_IOCTL_BASE: u8 = b'U';
 ioctl_write_ptr!(ui_dev_setup, UINPUT_IOCTL_BASE, 3, uinput_setup);
 ioctl_write_ptr!(ui_abs_setup, UINPUT_IOCTL_BASE, 4, uinput_abs_setup);
 ioctl_none!(ui_dev_create, UINPUT_IOCTL_BASE, 1);
+ioctl_none!(ui_dev_destroy, UINPUT_IOCTL_BASE, 2);
 
 ioctl_write_int!(ui_set_evbit, UINPUT_IOCTL_BASE, 100);
 ioctl_write_int!(ui_set_keybit, UINPUT_IOCTL_BASE, 101);
@@ -93,6 +105,7 @@ ioctl_write_int!(ui_set_swbit, UINPUT_IOCTL_BASE, 109);
 ioctl_write_int!(ui_set_propbit, UINPUT_IOCTL_BASE, 110);
 
 ioctl_read_buf!(ui_get_sysname, UINPUT_IOCTL_BASE, 300, u8);
+ioctl_read!(ui_get_version, UINPUT_IOCTL_BASE, 45, ::libc::c_uint);
 
 ioctl_readwrite!(ui_begin_ff_upload, UINPUT_IOCTL_BASE, 200, uinput_ff_upload);
 ioctl_write_ptr!(ui_end_ff_upload, UINPUT_IOCTL_BASE, 201, uinput_ff_upload);
@@ -0,0 +1,439 @@
+//! Discovering input devices and their capabilities through sysfs.
+//!
+//! The kernel describes every input device in `/sys/class/input`, including the bitmaps of the
+//! codes it supports. Reading them requires no permission to open the event nodes, which makes
+//! this useful for unprivileged programs and for quickly scanning many devices.
+//!
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! for device in evdev::sysfs::scan()? {
+//!     if device.keys.contains(evdev::KeyCode::KEY_ENTER) {
+//!         println!("{:?} looks like a keyboard", device.name);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::evemu::from_bitmap;
+use crate::{
+    AbsoluteAxisCode, AttributeSet, BusType, EventType, FFEffectCode, InputId, KeyCode, LedCode,
+    MiscCode, PropType, RelativeAxisCode, SoundCode, SwitchCode,
+};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const SYSFS_INPUT: &str = "/sys/class/input";
+
+/// An input device as described by sysfs.
+#[derive(Debug, Clone)]
+pub struct SysfsDevice {
+    /// The sysfs directory of the device, such as `/sys/class/input/input5`.
+    pub sysfs_path: PathBuf,
+    /// The event node of the device, such as `/dev/input/event3`, if it has one.
+    pub event_node: Option<PathBuf>,
+    pub name: Option<String>,
+    pub physical_path: Option<String>,
+    pub unique_name: Option<String>,
+    pub input_id: InputId,
+    pub properties: AttributeSet<PropType>,
+    pub event_types: AttributeSet<EventType>,
+    pub keys: AttributeSet<KeyCode>,
+    pub relative_axes: AttributeSet<RelativeAxisCode>,
+    pub absolute_axes: AttributeSet<AbsoluteAxisCode>,
+    pub misc: AttributeSet<MiscCode>,
+    pub switches: AttributeSet<SwitchCode>,
+    pub leds: AttributeSet<LedCode>,
+    pub sounds: AttributeSet<SoundCode>,
+    pub ff_effects: AttributeSet<FFEffectCode>,
+}
+
+impl SysfsDevice {
+    /// Read the description of the device in the given sysfs directory, such as
+    /// `/sys/class/input/input5`.
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let caps = path.join("capabilities");
+        let id = path.join("id");
+        let input_id = InputId::new(
+            BusType(read_hex(&id.join("bustype"))?),
+            read_hex(&id.join("vendor"))?,
+            read_hex(&id.join("product"))?,
+            read_hex(&id.join("version"))?,
+        );
+        Ok(Self {
+            sysfs_path: path.to_owned(),
+            event_node: event_node(path),
+            name: read_string(&path.join("name")),
+            physical_path: read_string(&path.join("phys")),
+            unique_name: read_string(&path.join("uniq")),
+            input_id,
+            properties: read_bitmap(&path.join("properties"))?,
+            event_types: read_bitmap(&caps.join("ev"))?,
+            keys: read_bitmap(&caps.join("key"))?,
+            relative_axes: read_bitmap(&caps.join("rel"))?,
+            absolute_axes: read_bitmap(&caps.join("abs"))?,
+            misc: read_bitmap(&caps.join("msc"))?,
+            switches: read_bitmap(&caps.join("sw"))?,
+            leds: read_bitmap(&caps.join("led"))?,
+            sounds: read_bitmap(&caps.join("snd"))?,
+            ff_effects: read_bitmap(&caps.join("ff"))?,
+        })
+    }
+}
+
+/// Lists the input devices in `/sys/class/input` along with their capabilities.
+///
+/// Devices that disappear or can't be read while scanning are skipped.
+pub fn scan() -> io::Result<Vec<SysfsDevice>> {
+    let mut devices = Vec::new();
+    for entry in fs::read_dir(SYSFS_INPUT)? {
+        let entry = entry?;
+        if !entry.file_name().to_string_lossy().starts_with("input") {
+            continue;
+        }
+        if let Ok(device) = SysfsDevice::read(entry.path()) {
+            devices.push(device);
+        }
+    }
+    devices.sort_by(|a, b| a.sysfs_path.cmp(&b.sysfs_path));
+    Ok(devices)
+}
+
+/// Returns the sysfs directory of the character device with the device number `rdev`, such as
+/// `/sys/devices/platform/i8042/serio0/input/input3/event3` for an event node.
+pub(crate) fn char_device_dir(rdev: u64) -> io::Result<PathBuf> {
+    let (major, minor) = (libc::major(rdev as _), libc::minor(rdev as _));
+    fs::canonicalize(format!("/sys/dev/char/{major}:{minor}"))
+}
+
+/// Finds the `power/wakeup` attribute controlling whether the device in `dir` can wake the system.
+///
+/// The attribute usually belongs to the physical device (e.g. the USB device) rather than to the
+/// input device, so this looks at `dir` and each of its ancestors in turn.
+pub(crate) fn wakeup_attribute(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .take_while(|dir| dir.parent().is_some())
+        .map(|dir| dir.join("power/wakeup"))
+        .find(|attr| attr.is_file())
+}
+
+/// Parses the value of a `power/wakeup` attribute, which is empty for devices that can't wake the
+/// system.
+pub(crate) fn parse_wakeup(s: &str) -> io::Result<Option<bool>> {
+    match s.trim() {
+        "enabled" => Ok(Some(true)),
+        "disabled" => Ok(Some(false)),
+        "" => Ok(None),
+        other => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unexpected wakeup state {other:?}"),
+        )),
+    }
+}
+
+/// Whether a battery is being charged, as reported by [`Device::battery`].
+///
+/// [`Device::battery`]: crate::Device::battery
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BatteryStatus {
+    Charging,
+    Discharging,
+    /// Connected to power but not charging, e.g. because it is almost full.
+    NotCharging,
+    Full,
+    Unknown,
+}
+
+impl BatteryStatus {
+    fn parse(s: &str) -> Self {
+        match s {
+            "Charging" => Self::Charging,
+            "Discharging" => Self::Discharging,
+            "Not charging" => Self::NotCharging,
+            "Full" => Self::Full,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// The battery of a device, as reported by [`Device::battery`].
+///
+/// [`Device::battery`]: crate::Device::battery
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatteryInfo {
+    /// The sysfs directory of the battery, such as
+    /// `/sys/class/power_supply/hid-00:11:22:33:44:55-battery`.
+    pub sysfs_path: PathBuf,
+    /// The charge in percent, if the driver reports it.
+    pub capacity: Option<u8>,
+    pub status: BatteryStatus,
+}
+
+impl BatteryInfo {
+    /// Read the battery in the given `power_supply` directory.
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let status = fs::read_to_string(path.join("status"))?;
+        Ok(Self {
+            sysfs_path: path.to_owned(),
+            capacity: read_string(&path.join("capacity")).and_then(|s| s.parse().ok()),
+            status: BatteryStatus::parse(status.trim()),
+        })
+    }
+}
+
+/// Finds the battery of the device in `dir`, looking for a `power_supply` directory in it and in
+/// each of its ancestors in turn.
+pub(crate) fn battery(dir: &Path) -> Option<BatteryInfo> {
+    dir.ancestors()
+        .take_while(|dir| dir.parent().is_some())
+        .find_map(|dir| {
+            let mut supplies: Vec<_> = fs::read_dir(dir.join("power_supply"))
+                .ok()?
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .collect();
+            supplies.sort();
+            supplies
+                .into_iter()
+                .find_map(|supply| BatteryInfo::read(supply).ok())
+        })
+}
+
+/// Finds the hidraw nodes of the HID device the device in `dir` belongs to.
+///
+/// The nodes of the HID device itself come first. If it is one interface of a USB device, the
+/// nodes of its other interfaces follow, since vendor-specific reports are often sent through a
+/// separate interface.
+pub(crate) fn hidraw_nodes(dir: &Path) -> Vec<PathBuf> {
+    let hid = match dir
+        .ancestors()
+        .find(|dir| dir.join("hidraw").is_dir() && dir.parent().is_some())
+    {
+        Some(hid) => hid,
+        None => return Vec::new(),
+    };
+    let mut nodes = hidraw_of(hid);
+    let interface = hid
+        .parent()
+        .filter(|dir| dir.join("bInterfaceNumber").is_file());
+    if let Some(usb) = interface.and_then(Path::parent) {
+        let mut siblings: Vec<_> = fs::read_dir(usb)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|dir| dir.join("bInterfaceNumber").is_file())
+            .flat_map(|dir| fs::read_dir(dir).into_iter().flatten())
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|dir| dir != hid)
+            .flat_map(|dir| hidraw_of(&dir))
+            .collect();
+        siblings.sort();
+        nodes.extend(siblings);
+    }
+    nodes
+}
+
+fn hidraw_of(hid: &Path) -> Vec<PathBuf> {
+    let mut nodes: Vec<_> = fs::read_dir(hid.join("hidraw"))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name();
+            name.to_string_lossy()
+                .starts_with("hidraw")
+                .then(|| Path::new("/dev").join(name))
+        })
+        .collect();
+    nodes.sort();
+    nodes
+}
+
+/// The names the kernel's input-leds driver gives the LED class devices of each LED code, see
+/// `drivers/input/input-leds.c`.
+const LED_NAMES: [&str; 11] = [
+    "numlock",
+    "capslock",
+    "scrolllock",
+    "compose",
+    "kana",
+    "sleep",
+    "suspend",
+    "mute",
+    "misc",
+    "mail",
+    "charging",
+];
+
+/// Finds the LED class devices, such as `input3::capslock`, of the input device in `dir`.
+pub(crate) fn led_class_dirs(dir: &Path) -> Vec<(LedCode, PathBuf)> {
+    let input = match dir.file_name() {
+        Some(input) => input.to_string_lossy().into_owned(),
+        None => return Vec::new(),
+    };
+    let mut leds: Vec<_> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name();
+            let (prefix, led) = name.to_str()?.split_once("::")?;
+            let code = LED_NAMES.iter().position(|&n| n == led)?;
+            (prefix == input).then(|| (LedCode(code as u16), entry.path()))
+        })
+        .collect();
+    leds.sort_by_key(|(led, _)| led.0);
+    leds
+}
+
+fn event_node(path: &Path) -> Option<PathBuf> {
+    fs::read_dir(path).ok()?.find_map(|entry| {
+        let name = entry.ok()?.file_name();
+        name.to_string_lossy()
+            .starts_with("event")
+            .then(|| Path::new("/dev/input").join(name))
+    })
+}
+
+fn read_string(path: &Path) -> Option<String> {
+    let s = fs::read_to_string(path).ok()?;
+    let s = s.trim_end_matches('\n');
+    (!s.is_empty()).then(|| s.to_owned())
+}
+
+fn read_hex(path: &Path) -> io::Result<u16> {
+    let s = fs::read_to_string(path)?;
+    u16::from_str_radix(s.trim(), 16).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn read_bitmap<T: crate::attribute_set::ArrayedEvdevEnum>(
+    path: &Path,
+) -> io::Result<AttributeSet<T>> {
+    match fs::read_to_string(path) {
+        Ok(s) => parse_bitmap(&s).map(|bytes| from_bitmap(&bytes)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(AttributeSet::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Parses a sysfs bitmap, which consists of hexadecimal words of the size of a `long`, most
+/// significant word first, into little-endian bytes.
+fn parse_bitmap(s: &str) -> io::Result<Vec<u8>> {
+    const WORD: usize = std::mem::size_of::<libc::c_ulong>();
+    let mut bytes = Vec::new();
+    for word in s.split_whitespace().rev() {
+        let word = u64::from_str_radix(word, 16)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        bytes.extend_from_slice(&word.to_le_bytes()[..WORD]);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bitmap() {
+        let bytes = parse_bitmap("3 0\n").unwrap();
+        let keys: AttributeSet<KeyCode> = from_bitmap(&bytes);
+        let expected = std::mem::size_of::<libc::c_ulong>() * 8;
+        assert_eq!(
+            keys.iter().map(|k| k.code() as usize).collect::<Vec<_>>(),
+            [expected, expected + 1]
+        );
+
+        let types: AttributeSet<EventType> = from_bitmap(&parse_bitmap("7").unwrap());
+        assert_eq!(
+            types.iter().collect::<Vec<_>>(),
+            [
+                EventType::SYNCHRONIZATION,
+                EventType::KEY,
+                EventType::RELATIVE
+            ]
+        );
+        assert!(parse_bitmap("xyz").is_err());
+    }
+
+    #[test]
+    fn wakeup() {
+        let root = std::env::temp_dir().join(format!("evdev-wakeup-{}", std::process::id()));
+        let event = root.join("usb1/1-2/1-2:1.0/input/input7/event7");
+        fs::create_dir_all(&event).unwrap();
+        assert_eq!(wakeup_attribute(&event), None);
+        fs::create_dir_all(root.join("usb1/1-2/power")).unwrap();
+        fs::write(root.join("usb1/1-2/power/wakeup"), "disabled\n").unwrap();
+        let attr = wakeup_attribute(&event);
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(attr, Some(root.join("usb1/1-2/power/wakeup")));
+
+        assert_eq!(parse_wakeup("enabled\n").unwrap(), Some(true));
+        assert_eq!(parse_wakeup("\n").unwrap(), None);
+        assert!(parse_wakeup("sometimes").is_err());
+    }
+
+    #[test]
+    fn battery_of_hid_device() {
+        let root = std::env::temp_dir().join(format!("evdev-battery-{}", std::process::id()));
+        let hid = root.join("0005:054C:09CC.0001");
+        let event = hid.join("input/input9/event9");
+        let supply = hid.join("power_supply/ps-controller-battery-00:11:22:33:44:55");
+        fs::create_dir_all(&event).unwrap();
+        fs::create_dir_all(&supply).unwrap();
+        fs::write(supply.join("capacity"), "85\n").unwrap();
+        fs::write(supply.join("status"), "Not charging\n").unwrap();
+        let info = battery(&event);
+        fs::remove_dir_all(&root).unwrap();
+        let info = info.unwrap();
+        assert_eq!(info.sysfs_path, supply);
+        assert_eq!(info.capacity, Some(85));
+        assert_eq!(info.status, BatteryStatus::NotCharging);
+    }
+
+    #[test]
+    fn hidraw_of_usb_interfaces() {
+        let root = std::env::temp_dir().join(format!("evdev-hidraw-{}", std::process::id()));
+        let usb = root.join("usb1/1-2");
+        let keyboard = usb.join("1-2:1.0/0003:046D:C31C.0001");
+        let vendor = usb.join("1-2:1.1/0003:046D:C31C.0002");
+        let event = keyboard.join("input/input3/event3");
+        fs::create_dir_all(&event).unwrap();
+        fs::create_dir_all(keyboard.join("hidraw/hidraw1")).unwrap();
+        fs::create_dir_all(vendor.join("hidraw/hidraw2")).unwrap();
+        for interface in ["1-2:1.0", "1-2:1.1"] {
+            fs::write(usb.join(interface).join("bInterfaceNumber"), "00\n").unwrap();
+        }
+        let nodes = hidraw_nodes(&event);
+        let without_hid = hidraw_nodes(&root.join("usb1"));
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            nodes,
+            [Path::new("/dev/hidraw1"), Path::new("/dev/hidraw2")]
+        );
+        assert!(without_hid.is_empty());
+    }
+
+    #[test]
+    fn led_classes() {
+        let root = std::env::temp_dir().join(format!("evdev-leds-{}", std::process::id()));
+        let input = root.join("input4");
+        for dir in [
+            "input4::scrolllock",
+            "input4::capslock",
+            "input4::unknown",
+            "event4",
+        ] {
+            fs::create_dir_all(input.join(dir)).unwrap();
+        }
+        let leds = led_class_dirs(&input);
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            leds,
+            [
+                (LedCode::LED_CAPSL, input.join("input4::capslock")),
+                (LedCode::LED_SCROLLL, input.join("input4::scrolllock"))
+            ]
+        );
+    }
+}
@@ -0,0 +1,375 @@
+//! Interpretation of graphics tablet (stylus) events.
+//!
+//! Tablets report which tool is in proximity of the surface with `BTN_TOOL_*` keys, whether the
+//! tip touches the surface with `BTN_TOUCH`, and the state of the tool with axes like
+//! `ABS_PRESSURE`, `ABS_DISTANCE` and `ABS_TILT_X`/`ABS_TILT_Y`. Tablets that can tell tools
+//! apart report the serial number of the tool with `MSC_SERIAL`.
+//!
+//! A [`TabletTracker`] assembles these into a [`PenState`] and reports [`PenEvent`]s for every
+//! frame.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use evdev::tablet::{PenEventKind, TabletTracker};
+//! let mut device = evdev::Device::open("/dev/input/event0")?;
+//! let mut tablet = TabletTracker::new();
+//! loop {
+//!     for ev in device.fetch_events()? {
+//!         for pen in tablet.process(&ev) {
+//!             if pen.kind == PenEventKind::Motion && pen.state.touching {
+//!                 println!("draw at {}x{}", pen.state.x, pen.state.y);
+//!             }
+//!         }
+//!     }
+//! }
+//! # }
+//! ```
+
+use crate::{AbsoluteAxisCode, EventSummary, InputEvent, KeyCode, MiscCode, SynchronizationCode};
+use std::time::SystemTime;
+
+/// The kind of tool in proximity of a tablet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Tool {
+    Pen,
+    /// The eraser end of a pen.
+    Rubber,
+    Brush,
+    Pencil,
+    Airbrush,
+    Mouse,
+    Lens,
+}
+
+impl Tool {
+    /// Returns the tool reported by a `BTN_TOOL_*` key.
+    ///
+    /// Finger tools (`BTN_TOOL_FINGER`, `BTN_TOOL_DOUBLETAP`, ...) are not tablet tools and
+    /// return `None`.
+    pub fn from_key(key: KeyCode) -> Option<Self> {
+        Some(match key {
+            KeyCode::BTN_TOOL_PEN => Tool::Pen,
+            KeyCode::BTN_TOOL_RUBBER => Tool::Rubber,
+            KeyCode::BTN_TOOL_BRUSH => Tool::Brush,
+            KeyCode::BTN_TOOL_PENCIL => Tool::Pencil,
+            KeyCode::BTN_TOOL_AIRBRUSH => Tool::Airbrush,
+            KeyCode::BTN_TOOL_MOUSE => Tool::Mouse,
+            KeyCode::BTN_TOOL_LENS => Tool::Lens,
+            _ => return None,
+        })
+    }
+}
+
+/// A button on the barrel of a pen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PenButton {
+    /// `BTN_STYLUS`
+    Primary,
+    /// `BTN_STYLUS2`
+    Secondary,
+}
+
+/// The state of the tool in proximity of a tablet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PenState {
+    pub tool: Tool,
+    /// The serial number of the tool, if reported by the tablet.
+    pub serial: Option<i32>,
+    pub x: i32,
+    pub y: i32,
+    pub pressure: Option<i32>,
+    pub distance: Option<i32>,
+    pub tilt_x: Option<i32>,
+    pub tilt_y: Option<i32>,
+    /// Whether the tip of the tool touches the surface.
+    pub touching: bool,
+    pub primary_button: bool,
+    pub secondary_button: bool,
+}
+
+/// What happened to the tool in a [`PenEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PenEventKind {
+    /// The tool came into proximity of the tablet.
+    ProximityIn,
+    /// The tip of the tool touched the surface.
+    TipDown,
+    /// The tool moved, or its pressure, distance, or tilt changed.
+    Motion,
+    /// A button was pressed (`true`) or released (`false`).
+    Button(PenButton, bool),
+    /// The tip of the tool was lifted from the surface.
+    TipUp,
+    /// The tool left the proximity of the tablet.
+    ProximityOut,
+}
+
+/// A change of the tool of a tablet, reported by [`TabletTracker::process`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PenEvent {
+    pub kind: PenEventKind,
+    /// The state of the tool after the change. For
+    /// [`ProximityOut`](PenEventKind::ProximityOut), the last state of the tool.
+    pub state: PenState,
+    /// The timestamp of the `SYN_REPORT` ending the frame.
+    pub timestamp: SystemTime,
+}
+
+/// Tracks the state of a tablet tool.
+///
+/// Feed every event of the device to [`process`](Self::process). At the end of every frame it
+/// returns the changes of the tool, ordered as proximity in, tip down, motion, buttons, tip up,
+/// proximity out.
+#[derive(Debug, Clone)]
+pub struct TabletTracker {
+    /// The state as it is being updated by the current frame.
+    pending: PenState,
+    pending_tool: Option<Tool>,
+    /// The state as of the last `SYN_REPORT`, if a tool was in proximity.
+    reported: Option<PenState>,
+    moved: bool,
+}
+
+impl TabletTracker {
+    /// Create a tracker with no tool in proximity.
+    pub fn new() -> Self {
+        Self {
+            pending: PenState {
+                tool: Tool::Pen,
+                serial: None,
+                x: 0,
+                y: 0,
+                pressure: None,
+                distance: None,
+                tilt_x: None,
+                tilt_y: None,
+                touching: false,
+                primary_button: false,
+                secondary_button: false,
+            },
+            pending_tool: None,
+            reported: None,
+            moved: false,
+        }
+    }
+
+    /// Returns the state of the tool in proximity as of the last `SYN_REPORT`.
+    pub fn state(&self) -> Option<&PenState> {
+        self.reported.as_ref()
+    }
+
+    /// Update the tracked state with an event.
+    ///
+    /// Returns the changes of the frame if `event` is a `SYN_REPORT`, and nothing otherwise.
+    pub fn process(&mut self, event: &InputEvent) -> Vec<PenEvent> {
+        let state = &mut self.pending;
+        match event.destructure() {
+            EventSummary::Key(_, key, value) => {
+                let down = value != 0;
+                match key {
+                    KeyCode::BTN_TOUCH => state.touching = down,
+                    KeyCode::BTN_STYLUS => state.primary_button = down,
+                    KeyCode::BTN_STYLUS2 => state.secondary_button = down,
+                    _ => {
+                        if let Some(tool) = Tool::from_key(key) {
+                            if down {
+                                self.pending_tool = Some(tool);
+                            } else if self.pending_tool == Some(tool) {
+                                self.pending_tool = None;
+                            }
+                        }
+                    }
+                }
+            }
+            EventSummary::AbsoluteAxis(_, axis, value) => {
+                match axis {
+                    AbsoluteAxisCode::ABS_X => state.x = value,
+                    AbsoluteAxisCode::ABS_Y => state.y = value,
+                    AbsoluteAxisCode::ABS_PRESSURE => state.pressure = Some(value),
+                    AbsoluteAxisCode::ABS_DISTANCE => state.distance = Some(value),
+                    AbsoluteAxisCode::ABS_TILT_X => state.tilt_x = Some(value),
+                    AbsoluteAxisCode::ABS_TILT_Y => state.tilt_y = Some(value),
+                    _ => return Vec::new(),
+                }
+                self.moved = true;
+            }
+            EventSummary::Misc(_, MiscCode::MSC_SERIAL, value) => state.serial = Some(value),
+            EventSummary::Synchronization(_, SynchronizationCode::SYN_REPORT, _) => {
+                return self.finish_frame(event.timestamp());
+            }
+            _ => {}
+        }
+        Vec::new()
+    }
+
+    fn finish_frame(&mut self, timestamp: SystemTime) -> Vec<PenEvent> {
+        let mut out = Vec::new();
+        let mut push = |kind, state| {
+            out.push(PenEvent {
+                kind,
+                state,
+                timestamp,
+            })
+        };
+        let moved = std::mem::take(&mut self.moved);
+        let old = self.reported;
+        let new = self.pending_tool.map(|tool| PenState {
+            tool,
+            ..self.pending
+        });
+
+        // a different tool replaces the old one
+        let old = match (old, new) {
+            (Some(o), Some(n)) if o.tool != n.tool || o.serial != n.serial => {
+                push(PenEventKind::ProximityOut, o);
+                None
+            }
+            _ => old,
+        };
+
+        match (old, new) {
+            (None, Some(n)) => {
+                push(PenEventKind::ProximityIn, n);
+                if n.touching {
+                    push(PenEventKind::TipDown, n);
+                }
+                for (button, pressed) in buttons(&n) {
+                    if pressed {
+                        push(PenEventKind::Button(button, true), n);
+                    }
+                }
+            }
+            (Some(o), Some(n)) => {
+                if n.touching && !o.touching {
+                    push(PenEventKind::TipDown, n);
+                }
+                if moved {
+                    push(PenEventKind::Motion, n);
+                }
+                for ((button, was), (_, is)) in buttons(&o).into_iter().zip(buttons(&n)) {
+                    if was != is {
+                        push(PenEventKind::Button(button, is), n);
+                    }
+                }
+                if o.touching && !n.touching {
+                    push(PenEventKind::TipUp, n);
+                }
+            }
+            (Some(o), None) => {
+                let last = PenState {
+                    tool: o.tool,
+                    ..self.pending
+                };
+                for (button, pressed) in buttons(&o) {
+                    if pressed {
+                        push(PenEventKind::Button(button, false), last);
+                    }
+                }
+                if o.touching {
+                    push(PenEventKind::TipUp, last);
+                }
+                push(PenEventKind::ProximityOut, last);
+            }
+            (None, None) => {}
+        }
+        self.reported = new;
+        out
+    }
+}
+
+impl Default for TabletTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn buttons(state: &PenState) -> [(PenButton, bool); 2] {
+    [
+        (PenButton::Primary, state.primary_button),
+        (PenButton::Secondary, state.secondary_button),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbsoluteAxisEvent, KeyEvent, SynchronizationEvent};
+
+    fn run(tablet: &mut TabletTracker, events: &[InputEvent]) -> Vec<PenEventKind> {
+        let report = *SynchronizationEvent::new(SynchronizationCode::SYN_REPORT, 0);
+        events
+            .iter()
+            .chain([&report])
+            .flat_map(|ev| tablet.process(ev))
+            .map(|pen| pen.kind)
+            .collect()
+    }
+
+    #[test]
+    fn proximity_and_tip() {
+        let mut tablet = TabletTracker::new();
+        let kinds = run(
+            &mut tablet,
+            &[
+                *KeyEvent::new(KeyCode::BTN_TOOL_PEN, 1),
+                *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_X, 100),
+                *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_DISTANCE, 10),
+            ],
+        );
+        assert_eq!(kinds, [PenEventKind::ProximityIn]);
+        assert_eq!(tablet.state().unwrap().x, 100);
+
+        let kinds = run(
+            &mut tablet,
+            &[
+                *KeyEvent::new(KeyCode::BTN_TOUCH, 1),
+                *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_PRESSURE, 500),
+                *KeyEvent::new(KeyCode::BTN_STYLUS, 1),
+            ],
+        );
+        assert_eq!(
+            kinds,
+            [
+                PenEventKind::TipDown,
+                PenEventKind::Motion,
+                PenEventKind::Button(PenButton::Primary, true)
+            ]
+        );
+
+        let kinds = run(
+            &mut tablet,
+            &[
+                *KeyEvent::new(KeyCode::BTN_TOUCH, 0),
+                *KeyEvent::new(KeyCode::BTN_STYLUS, 0),
+                *KeyEvent::new(KeyCode::BTN_TOOL_PEN, 0),
+            ],
+        );
+        assert_eq!(
+            kinds,
+            [
+                PenEventKind::Button(PenButton::Primary, false),
+                PenEventKind::TipUp,
+                PenEventKind::ProximityOut
+            ]
+        );
+        assert!(tablet.state().is_none());
+    }
+
+    #[test]
+    fn tool_switch() {
+        let mut tablet = TabletTracker::new();
+        run(&mut tablet, &[*KeyEvent::new(KeyCode::BTN_TOOL_PEN, 1)]);
+        let kinds = run(
+            &mut tablet,
+            &[
+                *KeyEvent::new(KeyCode::BTN_TOOL_PEN, 0),
+                *KeyEvent::new(KeyCode::BTN_TOOL_RUBBER, 1),
+            ],
+        );
+        assert_eq!(
+            kinds,
+            [PenEventKind::ProximityOut, PenEventKind::ProximityIn]
+        );
+        assert_eq!(tablet.state().unwrap().tool, Tool::Rubber);
+    }
+}
@@ -0,0 +1,353 @@
+use crate::{Device, EventSummary, InputEvent, InputId};
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::os::fd::AsFd;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Identifies the device that produced an event in a [`TaggedEventStream`].
+///
+/// Handles are cheap to clone, and compare equal if they refer to the same device of the same
+/// stream.
+#[derive(Debug, Clone)]
+pub struct DeviceHandle(Arc<HandleInner>);
+
+#[derive(Debug)]
+struct HandleInner {
+    id: u64,
+    name: Option<String>,
+    path: Option<PathBuf>,
+    input_id: InputId,
+}
+
+impl DeviceHandle {
+    /// Returns the name of the device.
+    pub fn name(&self) -> Option<&str> {
+        self.0.name.as_deref()
+    }
+
+    /// Returns the path the device was opened from, if it was opened through
+    /// [`TaggedEventStream::open`].
+    pub fn path(&self) -> Option<&Path> {
+        self.0.path.as_deref()
+    }
+
+    /// Returns the bustype, vendor, product, and version identifiers of the device.
+    pub fn input_id(&self) -> &InputId {
+        &self.0.input_id
+    }
+}
+
+impl PartialEq for DeviceHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for DeviceHandle {}
+
+impl Hash for DeviceHandle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.id.hash(state)
+    }
+}
+
+/// Reads events from several devices at once, tagging each event with the device it came from.
+///
+/// Devices are waited on with poll, and switched to non-blocking mode when added. Events of
+/// each device are processed like with [`Device::fetch_events`], so dropped events are
+/// recovered from as usual. Devices that are unplugged are removed from the stream.
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use evdev::TaggedEventStream;
+/// let mut stream = TaggedEventStream::new()?;
+/// stream.open("/dev/input/event0")?;
+/// stream.open("/dev/input/event1")?;
+/// for res in stream {
+///     let (device, event) = res?;
+///     println!("{}: {:?}", device.name().unwrap_or("unknown"), event);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TaggedEventStream {
+    devices: HashMap<u64, (DeviceHandle, Device)>,
+    next_id: u64,
+    pending: VecDeque<(DeviceHandle, EventSummary)>,
+    reorder: Option<ReorderBuffer>,
+}
+
+impl TaggedEventStream {
+    /// Create a stream without any devices.
+    pub fn new() -> io::Result<Self> {
+        Ok(Self {
+            devices: HashMap::new(),
+            next_id: 0,
+            pending: VecDeque::new(),
+            reorder: None,
+        })
+    }
+
+    /// Hold events back for up to `delay` to return the events of all devices in the order of
+    /// their timestamps, or return them right away if `delay` is `None`, which is the default.
+    ///
+    /// An event is returned once it has been held for `delay`, after all held events with an
+    /// earlier timestamp. Events of different devices that arrive further apart than `delay`
+    /// can still be out of order. Events with the same timestamp keep the order they were read
+    /// in, so frames stay intact.
+    ///
+    /// Disabling the buffer returns the events it holds first.
+    pub fn set_reorder_delay(&mut self, delay: Option<Duration>) {
+        match (&mut self.reorder, delay) {
+            (Some(reorder), Some(delay)) => reorder.delay = delay,
+            (reorder, Some(delay)) => *reorder = Some(ReorderBuffer::new(delay)),
+            (reorder, None) => {
+                if let Some(mut reorder) = reorder.take() {
+                    self.pending.extend(reorder.drain());
+                }
+            }
+        }
+    }
+
+    /// Returns the delay of the reorder buffer, if it is enabled.
+    pub fn reorder_delay(&self) -> Option<Duration> {
+        self.reorder.as_ref().map(|reorder| reorder.delay)
+    }
+
+    /// Open a device and add it to the stream.
+    pub fn open(&mut self, path: impl AsRef<Path>) -> io::Result<DeviceHandle> {
+        let path = path.as_ref();
+        let device = Device::open(path)?;
+        self.insert(device, Some(path.to_owned()))
+    }
+
+    /// Add an opened device to the stream.
+    pub fn add(&mut self, device: Device) -> io::Result<DeviceHandle> {
+        self.insert(device, None)
+    }
+
+    fn insert(&mut self, device: Device, path: Option<PathBuf>) -> io::Result<DeviceHandle> {
+        let id = self.next_id;
+        device.set_nonblocking(true)?;
+        self.next_id += 1;
+        let handle = DeviceHandle(Arc::new(HandleInner {
+            id,
+            name: device.name().map(str::to_owned),
+            path,
+            input_id: device.input_id(),
+        }));
+        self.devices.insert(id, (handle.clone(), device));
+        Ok(handle)
+    }
+
+    /// Remove a device from the stream, returning it. Events of the device that weren't returned
+    /// yet are discarded.
+    pub fn remove(&mut self, handle: &DeviceHandle) -> Option<Device> {
+        let (_, device) = self.devices.remove(&handle.0.id)?;
+        self.pending.retain(|(h, _)| h != handle);
+        if let Some(reorder) = &mut self.reorder {
+            reorder.entries.retain(|entry| entry.handle != *handle);
+        }
+        Some(device)
+    }
+
+    /// Returns the handles of the devices in the stream.
+    pub fn devices(&self) -> impl Iterator<Item = &DeviceHandle> + '_ {
+        self.devices.values().map(|(handle, _)| handle)
+    }
+
+    /// Returns the device a handle refers to, if it is still part of the stream.
+    pub fn device(&self, handle: &DeviceHandle) -> Option<&Device> {
+        self.devices.get(&handle.0.id).map(|(_, device)| device)
+    }
+
+    /// Wait for the next event of any device, blocking until one is available.
+    ///
+    /// Fails with [`InvalidInput`](io::ErrorKind::InvalidInput) if the stream has no devices.
+    pub fn next_event(&mut self) -> io::Result<(DeviceHandle, EventSummary)> {
+        loop {
+            if let Some(next) = self.pending.pop_front() {
+                return Ok(next);
+            }
+            let mut timeout = None;
+            if let Some(reorder) = &mut self.reorder {
+                let now = Instant::now();
+                if let Some(next) = reorder.pop(now) {
+                    return Ok(next);
+                }
+                timeout = reorder.next_deadline().map(|deadline| deadline - now);
+            }
+            if self.devices.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "no devices to read events from",
+                ));
+            }
+            let ids: Vec<u64> = self.devices.keys().copied().collect();
+            let fds: Vec<_> = ids.iter().map(|id| self.devices[id].1.as_fd()).collect();
+            let ready = match crate::poll_any(&fds, timeout) {
+                Ok(ready) => ready,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            for i in ready {
+                self.read_device(ids[i])?;
+            }
+        }
+    }
+
+    fn read_device(&mut self, id: u64) -> io::Result<()> {
+        let (handle, device) = match self.devices.get_mut(&id) {
+            Some(entry) => entry,
+            None => return Ok(()),
+        };
+        let err = match device.fetch_events() {
+            Ok(events) => {
+                match &mut self.reorder {
+                    Some(reorder) => {
+                        let now = Instant::now();
+                        for ev in events {
+                            reorder.push(handle.clone(), ev, now);
+                        }
+                    }
+                    None => {
+                        let events = events.map(|ev| (handle.clone(), ev.destructure()));
+                        self.pending.extend(events);
+                    }
+                }
+                return Ok(());
+            }
+            Err(e) => e,
+        };
+        if err.kind() == io::ErrorKind::WouldBlock {
+            Ok(())
+        } else if err.raw_os_error() == Some(libc::ENODEV) {
+            let handle = handle.clone();
+            self.remove(&handle);
+            Ok(())
+        } else {
+            Err(err)
+        }
+    }
+}
+
+/// Events held back by a [`TaggedEventStream`], sorted by their timestamps.
+#[derive(Debug)]
+struct ReorderBuffer {
+    delay: Duration,
+    entries: VecDeque<Held>,
+}
+
+#[derive(Debug)]
+struct Held {
+    handle: DeviceHandle,
+    event: InputEvent,
+    read_at: Instant,
+}
+
+impl ReorderBuffer {
+    fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            entries: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, handle: DeviceHandle, event: InputEvent, now: Instant) {
+        // after all events with the same timestamp, to keep frames intact
+        let time = event.timestamp();
+        let index = self
+            .entries
+            .iter()
+            .rposition(|held| held.event.timestamp() <= time)
+            .map_or(0, |i| i + 1);
+        self.entries.insert(
+            index,
+            Held {
+                handle,
+                event,
+                read_at: now,
+            },
+        );
+    }
+
+    /// Returns the time at which the next event is due.
+    fn next_deadline(&self) -> Option<Instant> {
+        self.entries
+            .iter()
+            .map(|held| held.read_at + self.delay)
+            .min()
+    }
+
+    /// Returns the earliest event if any event was held for long enough.
+    fn pop(&mut self, now: Instant) -> Option<(DeviceHandle, EventSummary)> {
+        if self.next_deadline()? > now {
+            return None;
+        }
+        let held = self.entries.pop_front()?;
+        Some((held.handle, held.event.destructure()))
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = (DeviceHandle, EventSummary)> + '_ {
+        self.entries
+            .drain(..)
+            .map(|held| (held.handle, held.event.destructure()))
+    }
+}
+
+impl Iterator for TaggedEventStream {
+    type Item = io::Result<(DeviceHandle, EventSummary)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_event())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BusType, KeyCode, KeyEvent};
+
+    fn handle(id: u64) -> DeviceHandle {
+        DeviceHandle(Arc::new(HandleInner {
+            id,
+            name: None,
+            path: None,
+            input_id: InputId::new(BusType::BUS_USB, 0, 0, 0),
+        }))
+    }
+
+    fn key_at(ms: u64, key: KeyCode) -> InputEvent {
+        crate::event_at(ms, *KeyEvent::new(key, 1))
+    }
+
+    #[test]
+    fn reorder_by_timestamp() {
+        let (a, b) = (handle(0), handle(1));
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let mut buffer = ReorderBuffer::new(Duration::from_millis(4));
+        buffer.push(a.clone(), key_at(3, KeyCode::KEY_A), start);
+        buffer.push(a, key_at(3, KeyCode::KEY_B), start);
+        buffer.push(b.clone(), key_at(1, KeyCode::KEY_C), ms(2));
+        buffer.push(b, key_at(3, KeyCode::KEY_D), ms(2));
+        assert!(buffer.pop(ms(3)).is_none());
+
+        let mut keys = |now| {
+            let mut keys = Vec::new();
+            while let Some((_, EventSummary::Key(_, key, _))) = buffer.pop(now) {
+                keys.push(key);
+            }
+            keys
+        };
+        assert_eq!(
+            keys(ms(4)),
+            [KeyCode::KEY_C, KeyCode::KEY_A, KeyCode::KEY_B]
+        );
+        assert_eq!(keys(ms(6)), [KeyCode::KEY_D]);
+    }
+}
@@ -0,0 +1,330 @@
+//! Helpers for integration tests with virtual devices.
+//!
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! use evdev::test_util::DevicePair;
+//! use evdev::uinput::VirtualDevice;
+//! use evdev::{AttributeSet, KeyCode, KeyEvent};
+//!
+//! let keys: AttributeSet<KeyCode> = [KeyCode::KEY_A].into_iter().collect();
+//! let mut pair = DevicePair::new(VirtualDevice::builder()?.name("test").with_keys(&keys)?)?;
+//! pair.virtual_device().emit(&[*KeyEvent::new(KeyCode::KEY_A, 1)])?;
+//! let events: Vec<_> = pair.device().fetch_events()?.collect();
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::uinput::{VirtualDevice, VirtualDeviceBuilder};
+use crate::{
+    AttributeSet, CapabilitiesReport, Device, EventSource, EventType, InputEvent, InputId, KeyCode,
+    KeyEvent, LatencyStats, SynchronizationCode, SynchronizationEvent,
+};
+use std::collections::VecDeque;
+use std::io;
+use std::os::fd::{AsFd, BorrowedFd};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A virtual device along with its event node opened as a [`Device`].
+///
+/// After a virtual device is created, udev needs some time to create its event node and to apply
+/// its permissions. Creating the pair waits for that, so tests don't need to guess paths or sleep.
+#[derive(Debug)]
+pub struct DevicePair {
+    virtual_device: VirtualDevice,
+    device: Device,
+    path: PathBuf,
+}
+
+impl DevicePair {
+    /// Build the virtual device and open its event node, waiting up to 5 seconds for it to become
+    /// accessible.
+    pub fn new(builder: VirtualDeviceBuilder<'_>) -> io::Result<Self> {
+        Self::with_timeout(builder, DEFAULT_TIMEOUT)
+    }
+
+    /// Build the virtual device and open its event node, waiting up to `timeout` for it to become
+    /// accessible.
+    pub fn with_timeout(builder: VirtualDeviceBuilder<'_>, timeout: Duration) -> io::Result<Self> {
+        let mut virtual_device = builder.build()?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            match open_node(&mut virtual_device) {
+                Ok((path, device)) => {
+                    return Ok(Self {
+                        virtual_device,
+                        device,
+                        path,
+                    })
+                }
+                Err(e) if is_transient(&e) && Instant::now() < deadline => {
+                    thread::sleep(RETRY_INTERVAL)
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Returns the virtual device, for emitting events.
+    pub fn virtual_device(&mut self) -> &mut VirtualDevice {
+        &mut self.virtual_device
+    }
+
+    /// Returns the opened event node of the virtual device, for reading events.
+    pub fn device(&mut self) -> &mut Device {
+        &mut self.device
+    }
+
+    /// Returns the path of the event node.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the virtual device and the opened event node.
+    pub fn into_inner(self) -> (VirtualDevice, Device) {
+        (self.virtual_device, self.device)
+    }
+
+    /// Measure how long events take from being emitted by the virtual device to being read from
+    /// the event node, over `iterations` frames.
+    ///
+    /// Each frame presses or releases the first key the device supports, and is read back before
+    /// the next one is emitted. The event node is read like it is set up, so the effect of e.g.
+    /// [`Device::set_read_buffer_capacity`] can be measured. Fails with
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) if the device supports no keys.
+    pub fn measure_latency(&mut self, iterations: usize) -> io::Result<LatencyStats> {
+        let key = first_key(&self.device)?;
+        let mut latencies = Vec::with_capacity(iterations);
+        for i in 0..iterations {
+            let start = Instant::now();
+            self.virtual_device
+                .emit(&[*KeyEvent::new(key, (i % 2 == 0).into())])?;
+            while !self.device.fetch_events()?.any(|ev| is_report(&ev)) {}
+            latencies.push(start.elapsed());
+        }
+        no_samples(LatencyStats::from_samples(latencies))
+    }
+}
+
+fn open_node(virtual_device: &mut VirtualDevice) -> io::Result<(PathBuf, Device)> {
+    let path = virtual_device
+        .enumerate_dev_nodes_blocking()?
+        .next()
+        .unwrap_or_else(|| Err(io::ErrorKind::NotFound.into()))?;
+    let device = Device::open(&path)?;
+    Ok((path, device))
+}
+
+/// Errors that occur while udev is still setting up the event node.
+fn is_transient(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied
+    )
+}
+
+/// Measure the end-to-end latency of reading events from a device, by emitting `iterations`
+/// frames through a virtual keyboard and reading them back, see [`DevicePair::measure_latency`].
+///
+/// This is useful for comparing setups and for catching regressions in the way events are read.
+/// Results include the time udev and other readers of the device take, so they vary between
+/// runs and machines.
+///
+/// ```no_run
+/// let stats = evdev::test_util::bench_latency(1000)?;
+/// println!("mean {:?}, p95 {:?}, max {:?}", stats.mean, stats.p95, stats.max);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn bench_latency(iterations: usize) -> io::Result<LatencyStats> {
+    DevicePair::new(bench_device()?)?.measure_latency(iterations)
+}
+
+/// Like [`bench_latency`], but reads the events through an [`EventStream`].
+///
+/// [`EventStream`]: crate::EventStream
+#[cfg(feature = "tokio")]
+pub async fn bench_latency_stream(iterations: usize) -> io::Result<LatencyStats> {
+    let (mut virtual_device, device) = DevicePair::new(bench_device()?)?.into_inner();
+    let mut stream = device.into_event_stream()?;
+    let mut latencies = Vec::with_capacity(iterations);
+    for i in 0..iterations {
+        let start = Instant::now();
+        virtual_device.emit(&[*KeyEvent::new(KeyCode::KEY_A, (i % 2 == 0).into())])?;
+        while !is_report(&stream.next_event().await?) {}
+        latencies.push(start.elapsed());
+    }
+    no_samples(LatencyStats::from_samples(latencies))
+}
+
+fn bench_device() -> io::Result<VirtualDeviceBuilder<'static>> {
+    let keys: AttributeSet<KeyCode> = [KeyCode::KEY_A].into_iter().collect();
+    VirtualDevice::builder()?
+        .name("evdev latency benchmark")
+        .with_keys(&keys)
+}
+
+fn first_key(device: &Device) -> io::Result<KeyCode> {
+    device
+        .supported_keys()
+        .and_then(|keys| keys.iter().next())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "device supports no keys"))
+}
+
+fn is_report(event: &InputEvent) -> bool {
+    event.event_type() == EventType::SYNCHRONIZATION
+        && event.code() == SynchronizationCode::SYN_REPORT.0
+}
+
+fn no_samples(stats: Option<LatencyStats>) -> io::Result<LatencyStats> {
+    stats.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no iterations to measure"))
+}
+
+/// Wraps an [`EventSource`] and makes it report dropped events on demand, to test how
+/// applications recover from them.
+///
+/// When the kernel's buffer for a reader overflows, the reader gets a `SYN_DROPPED` event, and
+/// should discard the events up to the next `SYN_REPORT` and read the state of the device again.
+/// As this rarely happens on demand, the injector lets tests cause it in two ways:
+///
+/// - [`drop_events`](Self::drop_events) discards events read from the device and puts a
+///   `SYN_DROPPED` in their place, so the application misses them like after a real overflow.
+/// - [`inject_drop`](Self::inject_drop) adds a `SYN_DROPPED` followed by a frame of events that
+///   don't match the state of the device. Applications that don't discard them end up with the
+///   wrong state.
+///
+/// Works with a [`Device`] as well as a [`MockDevice`](crate::mock::MockDevice). Note that
+/// [`Device::fetch_events`] already recovers from dropped events on its own, before the injector
+/// sees its events.
+///
+/// ```no_run
+/// use evdev::test_util::DropInjector;
+/// use evdev::{Device, EventSource, KeyCode, KeyEvent};
+///
+/// let mut device = DropInjector::new(Device::open("/dev/input/event0")?);
+/// device.inject_drop(&[*KeyEvent::new(KeyCode::KEY_A, 1)]);
+/// for ev in device.fetch_events()? {
+///     println!("{ev:?}");
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct DropInjector<S> {
+    inner: S,
+    injected: VecDeque<InputEvent>,
+    to_drop: usize,
+}
+
+impl<S: EventSource> DropInjector<S> {
+    /// Wrap `inner`, passing its events through unchanged until a drop is requested.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            injected: VecDeque::new(),
+            to_drop: 0,
+        }
+    }
+
+    /// Returns the wrapped event source.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Returns the wrapped event source.
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Returns the wrapped event source, discarding injected events that weren't fetched yet.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Discard the next `count` events read from the event source, returning a `SYN_DROPPED`
+    /// in their place.
+    pub fn drop_events(&mut self, count: usize) {
+        self.to_drop += count;
+    }
+
+    /// Return a `SYN_DROPPED` on the next fetch, followed by `stale` and a `SYN_REPORT`.
+    ///
+    /// The injected events are returned without reading from the event source, and before the
+    /// events it returns. Its file descriptor doesn't become readable for them.
+    pub fn inject_drop(&mut self, stale: &[InputEvent]) {
+        self.injected
+            .push_back(syn(SynchronizationCode::SYN_DROPPED));
+        self.injected.extend(stale);
+        self.injected
+            .push_back(syn(SynchronizationCode::SYN_REPORT));
+    }
+}
+
+fn syn(code: SynchronizationCode) -> InputEvent {
+    *SynchronizationEvent::new(code, 0)
+}
+
+impl<S: AsFd> AsFd for DropInjector<S> {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.inner.as_fd()
+    }
+}
+
+impl<S: EventSource> EventSource for DropInjector<S> {
+    fn fetch_events(&mut self) -> io::Result<Box<dyn Iterator<Item = InputEvent> + '_>> {
+        if !self.injected.is_empty() {
+            return Ok(Box::new(self.injected.drain(..)));
+        }
+        let mut events = Vec::new();
+        let mut dropped = false;
+        for ev in self.inner.fetch_events()? {
+            if self.to_drop == 0 {
+                events.push(ev);
+                continue;
+            }
+            if !dropped {
+                events.push(syn(SynchronizationCode::SYN_DROPPED));
+                dropped = true;
+            }
+            self.to_drop -= 1;
+        }
+        Ok(Box::new(events.into_iter()))
+    }
+
+    fn capabilities_report(&self) -> io::Result<CapabilitiesReport> {
+        self.inner.capabilities_report()
+    }
+
+    fn input_id(&self) -> io::Result<InputId> {
+        self.inner.input_id()
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::mock::MockDevice;
+
+    #[test]
+    fn drops_and_injects() {
+        let key = |value| *KeyEvent::new(KeyCode::KEY_A, value);
+        let mut device = DropInjector::new(MockDevice::new("Mock"));
+        let fetch = |device: &mut DropInjector<MockDevice>| -> Vec<(u16, i32)> {
+            let events = EventSource::fetch_events(device).unwrap();
+            events.map(|ev| (ev.code(), ev.value())).collect()
+        };
+        let (a, report, dropped) = (KeyCode::KEY_A.0, 0, SynchronizationCode::SYN_DROPPED.0);
+
+        device.inject_drop(&[key(1)]);
+        device.inner_mut().push_frame(&[key(0)]);
+        assert_eq!(fetch(&mut device), [(dropped, 0), (a, 1), (report, 0)]);
+        assert_eq!(fetch(&mut device), [(a, 0), (report, 0)]);
+
+        device.drop_events(2);
+        device.inner_mut().push_frame(&[key(1)]);
+        device.inner_mut().push_frame(&[key(0)]);
+        assert_eq!(fetch(&mut device), [(dropped, 0), (a, 0), (report, 0)]);
+    }
+}
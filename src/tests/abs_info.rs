@@ -0,0 +1,20 @@
+use crate::{AbsInfo, AbsoluteAxisCode, AxisUnit};
+
+#[test]
+pub fn test_unit_conversion() {
+    // a touchpad 100 mm wide, with 40 units per mm
+    let x = AbsInfo::new(0, -2000, 2000, 0, 0, 40);
+    assert_eq!(x.size_mm(), Some(100.0));
+    assert_eq!(x.to_mm(-2000), Some(0.0));
+    assert_eq!(x.to_mm(0), Some(50.0));
+    assert_eq!(AbsoluteAxisCode::ABS_X.unit(), Some(AxisUnit::Millimeter));
+
+    // a pen tilted by 1 radian, with 57 units per radian
+    let tilt = AbsInfo::new(0, -64, 63, 0, 0, 57);
+    assert_eq!(tilt.to_radians(57), Some(1.0));
+    assert_eq!(AbsoluteAxisCode::ABS_TILT_X.unit(), Some(AxisUnit::Radian));
+
+    let pressure = AbsInfo::new(0, 0, 1023, 0, 0, 0);
+    assert_eq!(pressure.to_mm(512), None);
+    assert_eq!(AbsoluteAxisCode::ABS_PRESSURE.unit(), None);
+}
@@ -30,8 +30,70 @@ pub fn test_first_element_in_slice_iter() -> std::io::Result<()> {
     keys.insert(KeyCode(0));
     keys.insert(KeyCode(1));
 
-    assert_eq!(KeyCode(0), keys.slice_iter(KeyCode(0)).nth(0).unwrap());
-    assert_eq!(KeyCode(1), keys.slice_iter(KeyCode(1)).nth(0).unwrap());
+    assert_eq!(KeyCode(0), keys.slice_iter(KeyCode(0)).next().unwrap());
+    assert_eq!(KeyCode(1), keys.slice_iter(KeyCode(1)).next().unwrap());
 
     Ok(())
 }
+
+#[test]
+pub fn test_set_algebra() {
+    let a: AttributeSet<KeyCode> = [KeyCode::KEY_A, KeyCode::KEY_S].into_iter().collect();
+    let b: AttributeSet<KeyCode> = [KeyCode::KEY_S, KeyCode::KEY_D].into_iter().collect();
+
+    let collect = |set: AttributeSet<KeyCode>| set.iter().collect::<Vec<_>>();
+    assert_eq!(
+        collect(&a | &b),
+        [KeyCode::KEY_A, KeyCode::KEY_S, KeyCode::KEY_D]
+    );
+    assert_eq!(collect(&a & &b), [KeyCode::KEY_S]);
+    assert_eq!(collect(&a - &b), [KeyCode::KEY_A]);
+    assert_eq!(collect(&a ^ &b), [KeyCode::KEY_A, KeyCode::KEY_D]);
+
+    let mut c = a.intersection(&b);
+    assert!(c.is_subset(&a) && c.is_subset(&b));
+    assert!(a.is_superset(&c));
+    assert!(!a.is_disjoint(&b));
+    c |= &a;
+    assert_eq!(collect(c), [KeyCode::KEY_A, KeyCode::KEY_S]);
+    assert!((&a - &b).is_disjoint(&b));
+}
+
+#[test]
+pub fn test_extend_and_owned_iteration() {
+    let mut keys: AttributeSet<KeyCode> = AttributeSet::new();
+    assert!(keys.is_empty());
+    keys.extend([KeyCode::KEY_D, KeyCode::KEY_A]);
+    keys.extend(&[KeyCode::KEY_S]);
+    assert_eq!(keys.len(), 3);
+
+    let filtered: AttributeSet<KeyCode> = keys.iter().filter(|&k| k != KeyCode::KEY_S).collect();
+    let mut owned = filtered.into_iter();
+    assert_eq!(owned.len(), 2);
+    assert_eq!(owned.next(), Some(KeyCode::KEY_A));
+    assert_eq!(owned.next(), Some(KeyCode::KEY_D));
+    assert_eq!(owned.next(), None);
+}
+
+#[test]
+pub fn test_iteration_across_words() {
+    let codes = [0, 63, 64, 127, 200, 0x2ff];
+    let keys: AttributeSet<KeyCode> = codes.iter().map(|&c| KeyCode::new(c)).collect();
+    assert_eq!(keys.len(), codes.len());
+
+    let forward: Vec<_> = keys.iter().map(|k| k.code()).collect();
+    assert_eq!(forward, codes);
+    let backward: Vec<_> = keys.iter().rev().map(|k| k.code()).collect();
+    assert_eq!(backward, [0x2ff, 200, 127, 64, 63, 0]);
+
+    let mut tail = keys.slice_iter(KeyCode::new(64));
+    assert_eq!(tail.len(), 4);
+    assert_eq!(tail.next_back(), Some(KeyCode::new(0x2ff)));
+    assert_eq!(tail.next(), Some(KeyCode::new(64)));
+    assert_eq!(tail.len(), 2);
+
+    let mut owned = keys.into_iter();
+    assert_eq!(owned.next_back(), Some(KeyCode::new(0x2ff)));
+    assert_eq!(owned.next(), Some(KeyCode::new(0)));
+    assert_eq!(owned.len(), 4);
+}
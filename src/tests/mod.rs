@@ -1 +1,4 @@
+mod abs_info;
 mod attribute_set;
+mod ordering;
+mod time;
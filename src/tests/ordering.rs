@@ -0,0 +1,22 @@
+use crate::{event_at, sort_events, EventType, InputEvent, KeyCode, SynchronizationCode};
+
+fn at(ms: u64, type_: EventType, code: u16) -> InputEvent {
+    event_at(ms, InputEvent::new(type_, code, 1))
+}
+
+#[test]
+pub fn test_ordering() {
+    let key = at(5, EventType::KEY, KeyCode::KEY_A.0);
+    let syn = at(
+        5,
+        EventType::SYNCHRONIZATION,
+        SynchronizationCode::SYN_REPORT.0,
+    );
+    let earlier = at(4, EventType::KEY, KeyCode::KEY_B.0);
+    assert!(earlier < key);
+    assert!(syn < key);
+
+    let mut events = vec![key, syn, earlier];
+    sort_events(&mut events);
+    assert_eq!(events, [earlier, key, syn]);
+}
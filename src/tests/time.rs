@@ -0,0 +1,96 @@
+use crate::compat::{input_event, time64, timeval};
+use crate::{systime_to_timeval, timeval_to_systime, InputEvent};
+use libc::c_long;
+use std::mem::{align_of, size_of};
+use std::time::{Duration, SystemTime};
+
+/// The kernel's `struct input_event` is two of its `long`s followed by two `__u16`s and an
+/// `__s32`, regardless of the width of `time_t`.
+macro_rules! assert_kernel_layout {
+    ($ty:ty, $long:ty) => {{
+        let ev: $ty = unsafe { std::mem::zeroed() };
+        let base = &ev as *const _ as usize;
+        assert_eq!(size_of::<$ty>(), 2 * size_of::<$long>() + 8);
+        assert_eq!(align_of::<$ty>(), align_of::<$long>());
+        assert_eq!(
+            &ev.time.tv_usec as *const _ as usize - base,
+            size_of::<$long>()
+        );
+        assert_eq!(
+            &ev.type_ as *const _ as usize - base,
+            2 * size_of::<$long>()
+        );
+        assert_eq!(
+            &ev.code as *const _ as usize - base,
+            2 * size_of::<$long>() + 2
+        );
+        assert_eq!(
+            &ev.value as *const _ as usize - base,
+            2 * size_of::<$long>() + 4
+        );
+    }};
+}
+
+/// The kernel's `long`, which is 64 bits wide on x32 even though C's is 32.
+#[cfg(target_arch = "x86_64")]
+type KernelLong = i64;
+#[cfg(not(target_arch = "x86_64"))]
+type KernelLong = c_long;
+
+#[test]
+pub fn test_input_event_layout() {
+    assert_kernel_layout!(input_event, KernelLong);
+    // only used where the kernel's `long` is C's
+    assert_kernel_layout!(time64::input_event, c_long);
+    assert_eq!(size_of::<InputEvent>(), size_of::<input_event>());
+}
+
+#[test]
+pub fn test_timeval_conversion() {
+    let cases = [
+        (Duration::ZERO, false, (0, 0)),
+        (
+            Duration::new(1_700_000_000, 123_456_000),
+            false,
+            (1_700_000_000, 123_456),
+        ),
+        (Duration::from_micros(1_500_000), true, (-2, 500_000)),
+        (Duration::from_secs(3), true, (-3, 0)),
+    ];
+    for (dur, before_epoch, (sec, usec)) in cases {
+        let time = if before_epoch {
+            SystemTime::UNIX_EPOCH - dur
+        } else {
+            SystemTime::UNIX_EPOCH + dur
+        };
+        let tv = systime_to_timeval(&time);
+        assert_eq!((tv.tv_sec, tv.tv_usec), (sec as _, usec as _));
+        assert_eq!(timeval_to_systime(&tv), time);
+    }
+
+    // sub-microsecond precision is truncated
+    let time = SystemTime::UNIX_EPOCH + Duration::new(5, 999);
+    assert_eq!(
+        timeval_to_systime(&systime_to_timeval(&time)),
+        SystemTime::UNIX_EPOCH + Duration::from_secs(5)
+    );
+}
+
+#[test]
+pub fn test_event_timestamp() {
+    let raw = input_event {
+        time: timeval {
+            tv_sec: 12,
+            tv_usec: 345_678,
+        },
+        type_: 1,
+        code: 30,
+        value: 1,
+    };
+    let ev = InputEvent::from(raw);
+    assert_eq!(
+        ev.timestamp(),
+        SystemTime::UNIX_EPOCH + Duration::from_micros(12_345_678)
+    );
+    assert_eq!((ev.code(), ev.value()), (30, 1));
+}
@@ -0,0 +1,161 @@
+use crate::InputEvent;
+use std::time::{Duration, SystemTime};
+
+/// The clock a device uses for the timestamps of its events, see [`Device::set_event_clock`].
+///
+/// By default, event timestamps use the wall clock, which jumps when the system time is changed.
+/// Applications measuring time between events usually want the monotonic clock instead, or the
+/// boot time clock, which also advances while the system is suspended.
+///
+/// [`Device::set_event_clock`]: crate::Device::set_event_clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EventClock {
+    /// `CLOCK_REALTIME`, the wall clock.
+    #[default]
+    Realtime,
+    /// `CLOCK_MONOTONIC`, which doesn't advance while the system is suspended.
+    Monotonic,
+    /// `CLOCK_BOOTTIME`, the time since boot including suspend.
+    Boottime,
+}
+
+impl EventClock {
+    /// Returns the ID of the clock for `clock_gettime` and `EVIOCSCLOCKID`.
+    pub fn clock_id(self) -> libc::clockid_t {
+        match self {
+            Self::Realtime => libc::CLOCK_REALTIME,
+            Self::Monotonic => libc::CLOCK_MONOTONIC,
+            Self::Boottime => libc::CLOCK_BOOTTIME,
+        }
+    }
+
+    /// Returns the clock with the given ID, if it is one that devices support.
+    pub fn from_clock_id(id: libc::clockid_t) -> Option<Self> {
+        match id {
+            libc::CLOCK_REALTIME => Some(Self::Realtime),
+            libc::CLOCK_MONOTONIC => Some(Self::Monotonic),
+            libc::CLOCK_BOOTTIME => Some(Self::Boottime),
+            _ => None,
+        }
+    }
+
+    /// Returns the current time of the clock, as the time since its zero point.
+    pub fn now(self) -> Duration {
+        let mut ts = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // can't fail for the clocks above
+        unsafe { libc::clock_gettime(self.clock_id(), &mut ts) };
+        Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+    }
+}
+
+/// The timestamp of an event along with the clock it was taken with.
+///
+/// [`InputEvent::timestamp`] interprets every timestamp as wall clock time, which is wrong for
+/// devices set to another clock. This keeps track of the clock, so timestamps can be converted
+/// between clocks and compared reliably.
+///
+/// Conversions between clocks use the current offset between them, so they are only exact as
+/// long as neither clock has jumped since the event, e.g. because the system time was changed
+/// or the system was suspended.
+///
+/// ```
+/// use evdev::{EventClock, EventTimestamp, EventType, InputEvent};
+/// use std::time::Duration;
+///
+/// // an event from a device using the wall clock
+/// let event = InputEvent::new_now(EventType::KEY, 30, 1);
+/// let stamp = EventTimestamp::of(&event, EventClock::Realtime);
+/// let now = EventTimestamp::now(EventClock::Monotonic);
+/// assert!(now.duration_since(stamp) < Duration::from_secs(1));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EventTimestamp {
+    clock: EventClock,
+    time: Duration,
+}
+
+impl EventTimestamp {
+    /// Create a timestamp of `time` since the zero point of `clock`.
+    pub fn new(clock: EventClock, time: Duration) -> Self {
+        Self { clock, time }
+    }
+
+    /// Returns the timestamp of `event`, from a device using `clock`.
+    pub fn of(event: &InputEvent, clock: EventClock) -> Self {
+        let time = event
+            .timestamp()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO);
+        Self::new(clock, time)
+    }
+
+    /// Returns the current time of `clock`.
+    pub fn now(clock: EventClock) -> Self {
+        Self::new(clock, clock.now())
+    }
+
+    /// Returns the clock of the timestamp.
+    pub fn clock(&self) -> EventClock {
+        self.clock
+    }
+
+    /// Returns the time since the zero point of the clock.
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Convert the timestamp to another clock.
+    pub fn to_clock(self, clock: EventClock) -> Self {
+        if clock == self.clock {
+            return self;
+        }
+        let (from_now, to_now) = (self.clock.now(), clock.now());
+        let time = if to_now >= from_now {
+            self.time + (to_now - from_now)
+        } else {
+            self.time.saturating_sub(from_now - to_now)
+        };
+        Self::new(clock, time)
+    }
+
+    /// Convert the timestamp to wall clock time.
+    pub fn to_system_time(self) -> SystemTime {
+        SystemTime::UNIX_EPOCH + self.to_clock(EventClock::Realtime).time
+    }
+
+    /// Returns the time elapsed from `earlier` to this timestamp, or zero if `earlier` is later.
+    ///
+    /// `earlier` is converted to the clock of this timestamp first if needed.
+    pub fn duration_since(&self, earlier: EventTimestamp) -> Duration {
+        self.time.saturating_sub(earlier.to_clock(self.clock).time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn across_clocks() {
+        let mono = EventTimestamp::now(EventClock::Monotonic);
+        let boot = EventTimestamp::now(EventClock::Boottime);
+        // the boot time clock is never behind the monotonic clock
+        assert!(boot.time() >= mono.time());
+        let later =
+            EventTimestamp::new(EventClock::Monotonic, mono.time() + Duration::from_secs(2));
+        let elapsed = later.duration_since(boot);
+        assert!(elapsed > Duration::from_millis(1900) && elapsed <= Duration::from_secs(2));
+        assert_eq!(boot.duration_since(later), Duration::ZERO);
+
+        let wall = EventTimestamp::now(EventClock::Realtime).to_system_time();
+        let since = SystemTime::now().duration_since(wall).unwrap();
+        assert!(since < Duration::from_secs(1));
+        assert_eq!(
+            EventClock::from_clock_id(EventClock::Boottime.clock_id()),
+            Some(EventClock::Boottime)
+        );
+    }
+}
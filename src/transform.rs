@@ -0,0 +1,262 @@
+//! Composable transformations over streams of [`InputEvent`]s.
+//!
+//! An [`EventTransform`] takes events one at a time and writes zero or more events to an output
+//! buffer. This makes it possible to drop events (filters), rewrite them (remaps), or expand a
+//! single event into several (macros). Transforms can be chained with a [`Pipeline`], and applied
+//! to any iterator of events (such as the one returned by [`Device::fetch_events`]) via
+//! [`EventTransform::transform_events`].
+//!
+//! ```
+//! use evdev::transform::{self, EventTransform, Pipeline};
+//! use evdev::{EventType, InputEvent, KeyCode};
+//!
+//! // drop every event that isn't a key event
+//! let keys_only = transform::from_fn(|ev: InputEvent, out: &mut Vec<InputEvent>| {
+//!     if ev.event_type() == EventType::KEY {
+//!         out.push(ev);
+//!     }
+//! });
+//! let pipeline = Pipeline::new().with(keys_only);
+//!
+//! let events = [
+//!     InputEvent::new(EventType::KEY, KeyCode::KEY_A.0, 1),
+//!     InputEvent::new(EventType::RELATIVE, 0, 5),
+//! ];
+//! let out: Vec<_> = pipeline.transform_events(events).collect();
+//! assert_eq!(out.len(), 1);
+//! ```
+//!
+//! [`Device::fetch_events`]: crate::Device::fetch_events
+
+use crate::InputEvent;
+use std::fmt;
+
+mod axis;
+mod calibration;
+mod coalesce;
+mod debounce;
+mod filter;
+mod pressure;
+mod quirks;
+mod remap;
+mod touchpad;
+mod typematic;
+
+pub use axis::{AxisAdjustment, AxisTransform};
+pub use calibration::Calibration;
+pub use coalesce::Coalesce;
+pub use debounce::Debounce;
+pub use filter::AxisFilter;
+pub use pressure::PressureCurve;
+pub use quirks::ScancodeQuirks;
+pub use remap::KeyRemap;
+pub use touchpad::{PointerConfig, TouchpadPointer};
+pub use typematic::Typematic;
+
+/// A stateful transformation from input events to output events.
+///
+/// Implementations receive each event in order and push whatever they want to emit in its
+/// place onto `output`. Pushing nothing drops the event. `output` may already contain events
+/// from earlier calls, so implementations must only ever append to it.
+///
+/// Closures can be turned into a transform with [`from_fn`].
+pub trait EventTransform {
+    /// Process a single event, appending the resulting events to `output`.
+    fn transform(&mut self, event: InputEvent, output: &mut Vec<InputEvent>);
+
+    /// Apply this transform lazily to an iterator of events.
+    fn transform_events<I>(self, events: I) -> TransformEvents<I::IntoIter, Self>
+    where
+        I: IntoIterator<Item = InputEvent>,
+        Self: Sized,
+    {
+        TransformEvents {
+            events: events.into_iter(),
+            transform: self,
+            buf: Vec::new(),
+            index: 0,
+        }
+    }
+}
+
+impl<T: EventTransform + ?Sized> EventTransform for &mut T {
+    fn transform(&mut self, event: InputEvent, output: &mut Vec<InputEvent>) {
+        (**self).transform(event, output)
+    }
+}
+
+impl<T: EventTransform + ?Sized> EventTransform for Box<T> {
+    fn transform(&mut self, event: InputEvent, output: &mut Vec<InputEvent>) {
+        (**self).transform(event, output)
+    }
+}
+
+/// Create an [`EventTransform`] from a closure.
+pub fn from_fn<F: FnMut(InputEvent, &mut Vec<InputEvent>)>(f: F) -> FromFn<F> {
+    FromFn(f)
+}
+
+/// An [`EventTransform`] backed by a closure, created by [`from_fn`].
+pub struct FromFn<F>(F);
+
+impl<F: FnMut(InputEvent, &mut Vec<InputEvent>)> EventTransform for FromFn<F> {
+    fn transform(&mut self, event: InputEvent, output: &mut Vec<InputEvent>) {
+        (self.0)(event, output)
+    }
+}
+
+impl<F> fmt::Debug for FromFn<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FromFn").finish_non_exhaustive()
+    }
+}
+
+/// A chain of [`EventTransform`]s applied one after another.
+///
+/// Every event produced by a stage is fed into the next stage, and whatever the last stage
+/// produces is the output of the pipeline. An empty pipeline passes events through unchanged.
+#[derive(Default)]
+pub struct Pipeline {
+    stages: Vec<Box<dyn EventTransform + Send>>,
+    scratch: [Vec<InputEvent>; 2],
+}
+
+impl Pipeline {
+    /// Create an empty pipeline.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a stage to the end of the pipeline.
+    pub fn with<T: EventTransform + Send + 'static>(mut self, stage: T) -> Self {
+        self.push(stage);
+        self
+    }
+
+    /// Append a stage to the end of the pipeline.
+    pub fn push<T: EventTransform + Send + 'static>(&mut self, stage: T) {
+        self.stages.push(Box::new(stage));
+    }
+
+    /// Returns the number of stages in this pipeline.
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// Returns `true` if this pipeline has no stages.
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+}
+
+impl EventTransform for Pipeline {
+    fn transform(&mut self, event: InputEvent, output: &mut Vec<InputEvent>) {
+        let (last, init) = match self.stages.split_last_mut() {
+            Some(stages) => stages,
+            None => return output.push(event),
+        };
+
+        let [mut pending, mut next] = std::mem::take(&mut self.scratch);
+        pending.clear();
+        pending.push(event);
+        for stage in init {
+            for ev in pending.drain(..) {
+                stage.transform(ev, &mut next);
+            }
+            std::mem::swap(&mut pending, &mut next);
+        }
+        for ev in pending.drain(..) {
+            last.transform(ev, output);
+        }
+        self.scratch = [pending, next];
+    }
+}
+
+impl fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("stages", &self.stages.len())
+            .finish()
+    }
+}
+
+/// An iterator adapter applying an [`EventTransform`] to a stream of events.
+///
+/// Created by [`EventTransform::transform_events`].
+#[derive(Debug)]
+pub struct TransformEvents<I, T> {
+    events: I,
+    transform: T,
+    buf: Vec<InputEvent>,
+    index: usize,
+}
+
+impl<I, T> TransformEvents<I, T> {
+    /// Returns a reference to the underlying transform.
+    pub fn transform(&self) -> &T {
+        &self.transform
+    }
+
+    /// Returns a mutable reference to the underlying transform.
+    pub fn transform_mut(&mut self) -> &mut T {
+        &mut self.transform
+    }
+
+    /// Consumes the adapter, returning the underlying transform.
+    pub fn into_transform(self) -> T {
+        self.transform
+    }
+}
+
+impl<I: Iterator<Item = InputEvent>, T: EventTransform> Iterator for TransformEvents<I, T> {
+    type Item = InputEvent;
+
+    fn next(&mut self) -> Option<InputEvent> {
+        loop {
+            if let Some(&ev) = self.buf.get(self.index) {
+                self.index += 1;
+                return Some(ev);
+            }
+            self.buf.clear();
+            self.index = 0;
+            let ev = self.events.next()?;
+            self.transform.transform(ev, &mut self.buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventType, KeyCode};
+
+    fn key(code: KeyCode, value: i32) -> InputEvent {
+        InputEvent::new(EventType::KEY, code.0, value)
+    }
+
+    #[test]
+    fn empty_pipeline_passes_through() {
+        let events = [key(KeyCode::KEY_A, 1), key(KeyCode::KEY_A, 0)];
+        let out: Vec<_> = Pipeline::new().transform_events(events).collect();
+        assert_eq!(out, events);
+    }
+
+    #[test]
+    fn stages_are_chained_in_order() {
+        let double = from_fn(|ev: InputEvent, out: &mut Vec<InputEvent>| {
+            out.push(ev);
+            out.push(ev);
+        });
+        let drop_releases = from_fn(|ev: InputEvent, out: &mut Vec<InputEvent>| {
+            if ev.value() != 0 {
+                out.push(ev);
+            }
+        });
+        let mut pipeline = Pipeline::new().with(double).with(drop_releases);
+        assert_eq!(pipeline.len(), 2);
+
+        let events = [key(KeyCode::KEY_A, 1), key(KeyCode::KEY_A, 0)];
+        let out: Vec<_> = (&mut pipeline).transform_events(events).collect();
+        assert_eq!(out, [key(KeyCode::KEY_A, 1), key(KeyCode::KEY_A, 1)]);
+    }
+}
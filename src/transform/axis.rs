@@ -0,0 +1,235 @@
+use crate::compat::input_event;
+use crate::transform::EventTransform;
+use crate::{AbsoluteAxisCode, EventSummary, InputEvent, RelativeAxisCode};
+use std::collections::HashMap;
+
+/// How the values of a single axis are adjusted by an [`AxisTransform`].
+///
+/// The operations are applied in a fixed order: the value is first inverted (negated), then
+/// multiplied by the scale factor, then the offset is added, and finally the result is clamped.
+///
+/// ```
+/// use evdev::transform::AxisAdjustment;
+///
+/// // flip an absolute axis with a range of 0..=1023
+/// let flip = AxisAdjustment::mirror(0, 1023);
+/// assert_eq!(flip.apply(0), 1023);
+/// assert_eq!(flip.apply(1000), 23);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisAdjustment {
+    invert: bool,
+    scale: f64,
+    /// Wider than the values so that [`mirror`](Self::mirror) can hold the sum of the bounds.
+    offset: i64,
+    clamp: Option<(i32, i32)>,
+}
+
+impl Default for AxisAdjustment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AxisAdjustment {
+    /// An adjustment that leaves the value unchanged.
+    pub const fn new() -> Self {
+        Self {
+            invert: false,
+            scale: 1.0,
+            offset: 0,
+            clamp: None,
+        }
+    }
+
+    /// An adjustment that mirrors values within the `minimum..=maximum` range, as is usually
+    /// wanted when inverting an absolute axis.
+    pub const fn mirror(minimum: i32, maximum: i32) -> Self {
+        let mut adjustment = Self::new().inverted();
+        adjustment.offset = minimum as i64 + maximum as i64;
+        adjustment
+    }
+
+    /// Negate the value, e.g. to reverse the scroll direction of a wheel.
+    pub const fn inverted(mut self) -> Self {
+        self.invert = !self.invert;
+        self
+    }
+
+    /// Multiply the value by `factor`.
+    pub const fn scale(mut self, factor: f64) -> Self {
+        self.scale = factor;
+        self
+    }
+
+    /// Add `offset` to the value after inversion and scaling.
+    pub const fn offset(mut self, offset: i32) -> Self {
+        self.offset = offset as i64;
+        self
+    }
+
+    /// Restrict the final value to `minimum..=maximum`.
+    pub const fn clamp(mut self, minimum: i32, maximum: i32) -> Self {
+        self.clamp = Some((minimum, maximum));
+        self
+    }
+
+    /// Apply the adjustment to a single value.
+    pub fn apply(&self, value: i32) -> i32 {
+        self.finish(self.scaled(value).round())
+    }
+
+    fn scaled(&self, value: i32) -> f64 {
+        let value = value as f64;
+        let value = if self.invert { -value } else { value };
+        value * self.scale
+    }
+
+    fn finish(&self, value: f64) -> i32 {
+        let value = (value as i64).saturating_add(self.offset);
+        let value = value.clamp(i32::MIN.into(), i32::MAX.into()) as i32;
+        match self.clamp {
+            Some((min, max)) => value.clamp(min, max),
+            None => value,
+        }
+    }
+}
+
+/// An [`EventTransform`] that inverts, scales, offsets, and clamps relative and absolute axes.
+///
+/// Every axis is configured separately; axes without an [`AxisAdjustment`] and all other event
+/// types pass through unchanged. When a relative axis is scaled down, the fractional part of each
+/// value is carried over to the next event so that slow movements aren't lost. Relative events
+/// that end up with a value of zero are dropped.
+///
+/// ```
+/// use evdev::transform::{AxisAdjustment, AxisTransform, EventTransform};
+/// use evdev::{InputEvent, RelativeAxisCode, RelativeAxisEvent};
+///
+/// // natural scrolling
+/// let transform = AxisTransform::new()
+///     .with_relative(RelativeAxisCode::REL_WHEEL, AxisAdjustment::new().inverted());
+/// let events = [*RelativeAxisEvent::new(RelativeAxisCode::REL_WHEEL, 1)];
+/// let out: Vec<InputEvent> = transform.transform_events(events).collect();
+/// assert_eq!(out[0].value(), -1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AxisTransform {
+    relative: HashMap<RelativeAxisCode, AxisAdjustment>,
+    absolute: HashMap<AbsoluteAxisCode, AxisAdjustment>,
+    remainders: HashMap<RelativeAxisCode, f64>,
+}
+
+impl AxisTransform {
+    /// Create a transform that doesn't adjust any axes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the adjustment for a relative axis.
+    pub fn with_relative(mut self, axis: RelativeAxisCode, adjustment: AxisAdjustment) -> Self {
+        self.set_relative(axis, adjustment);
+        self
+    }
+
+    /// Set the adjustment for an absolute axis.
+    pub fn with_absolute(mut self, axis: AbsoluteAxisCode, adjustment: AxisAdjustment) -> Self {
+        self.set_absolute(axis, adjustment);
+        self
+    }
+
+    /// Set the adjustment for a relative axis.
+    pub fn set_relative(&mut self, axis: RelativeAxisCode, adjustment: AxisAdjustment) {
+        self.relative.insert(axis, adjustment);
+        self.remainders.remove(&axis);
+    }
+
+    /// Set the adjustment for an absolute axis.
+    pub fn set_absolute(&mut self, axis: AbsoluteAxisCode, adjustment: AxisAdjustment) {
+        self.absolute.insert(axis, adjustment);
+    }
+
+    /// Stop adjusting a relative axis.
+    pub fn remove_relative(&mut self, axis: RelativeAxisCode) {
+        self.relative.remove(&axis);
+        self.remainders.remove(&axis);
+    }
+
+    /// Stop adjusting an absolute axis.
+    pub fn remove_absolute(&mut self, axis: AbsoluteAxisCode) {
+        self.absolute.remove(&axis);
+    }
+}
+
+impl EventTransform for AxisTransform {
+    fn transform(&mut self, event: InputEvent, output: &mut Vec<InputEvent>) {
+        let value = match event.destructure() {
+            EventSummary::RelativeAxis(_, axis, value) => match self.relative.get(&axis) {
+                Some(adjustment) => {
+                    let remainder = self.remainders.entry(axis).or_insert(0.0);
+                    let scaled = adjustment.scaled(value) + *remainder;
+                    let whole = scaled.trunc();
+                    *remainder = scaled - whole;
+                    let value = adjustment.finish(whole);
+                    if value == 0 {
+                        return;
+                    }
+                    value
+                }
+                None => value,
+            },
+            EventSummary::AbsoluteAxis(_, axis, value) => match self.absolute.get(&axis) {
+                Some(adjustment) => adjustment.apply(value),
+                None => value,
+            },
+            _ => event.value(),
+        };
+        output.push(InputEvent::from(input_event {
+            value,
+            ..*event.as_ref()
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbsoluteAxisEvent, RelativeAxisEvent};
+
+    #[test]
+    fn adjustment_order() {
+        let adj = AxisAdjustment::new()
+            .inverted()
+            .scale(2.0)
+            .offset(10)
+            .clamp(0, 15);
+        assert_eq!(adj.apply(1), 8);
+        assert_eq!(adj.apply(-5), 15);
+        assert_eq!(adj.apply(10), 0);
+        assert_eq!(AxisAdjustment::new().inverted().apply(i32::MIN), i32::MAX);
+    }
+
+    #[test]
+    fn relative_remainders_accumulate() {
+        let mut transform = AxisTransform::new()
+            .with_relative(RelativeAxisCode::REL_X, AxisAdjustment::new().scale(0.5));
+        let events = [*RelativeAxisEvent::new(RelativeAxisCode::REL_X, 1); 4];
+        let out: Vec<_> = (&mut transform).transform_events(events).collect();
+        assert_eq!(out.len(), 2);
+        assert!(out.iter().all(|ev| ev.value() == 1));
+    }
+
+    #[test]
+    fn absolute_axes_are_mirrored() {
+        let transform = AxisTransform::new()
+            .with_absolute(AbsoluteAxisCode::ABS_X, AxisAdjustment::mirror(-100, 100));
+        let events = [*AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_X, 30)];
+        let out: Vec<_> = transform.transform_events(events).collect();
+        assert_eq!(out[0].value(), -30);
+
+        let flip = AxisAdjustment::mirror(i32::MAX - 10, i32::MAX);
+        assert_eq!(flip.apply(i32::MAX), i32::MAX - 10);
+        assert_eq!(flip.apply(i32::MAX - 3), i32::MAX - 7);
+        assert_eq!(AxisAdjustment::mirror(i32::MIN, 0).apply(i32::MIN), 0);
+    }
+}
@@ -0,0 +1,229 @@
+use crate::compat::input_event;
+use crate::transform::EventTransform;
+use crate::{AbsInfo, AbsoluteAxisCode, EventSummary, InputEvent, SynchronizationCode};
+use std::collections::HashMap;
+
+/// An [`EventTransform`] that applies a calibration matrix to the position of touches, for
+/// touchscreens that are rotated or don't line up with the display.
+///
+/// The matrix has the same meaning as libinput's `LIBINPUT_CALIBRATION_MATRIX`: its six values
+/// `[a, b, c, d, e, f]` map a position normalized to `0.0..=1.0` to
+///
+/// ```text
+/// x' = a * x + b * y + c
+/// y' = d * x + e * y + f
+/// ```
+///
+/// It applies to `ABS_X` and `ABS_Y` as well as to `ABS_MT_POSITION_X` and `ABS_MT_POSITION_Y`
+/// of every slot. As each coordinate may depend on both input coordinates, positions are held
+/// back until the end of the frame, or until the slot changes, and then emitted in pairs.
+/// Results outside the range of an axis aren't clamped.
+///
+/// ```
+/// use evdev::transform::{Calibration, EventTransform};
+/// use evdev::{AbsInfo, AbsoluteAxisCode, AbsoluteAxisEvent, SynchronizationCode, SynchronizationEvent};
+///
+/// let x = AbsInfo::new(0, 0, 1000, 0, 0, 0);
+/// let y = AbsInfo::new(0, 0, 1000, 0, 0, 0);
+/// let calibration = Calibration::new(Calibration::ROTATE_90, &x, &y);
+/// let events = [
+///     *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_X, 200),
+///     *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_Y, 100),
+///     *SynchronizationEvent::new(SynchronizationCode::SYN_REPORT, 0),
+/// ];
+/// let out: Vec<_> = calibration.transform_events(events).collect();
+/// assert_eq!((out[0].value(), out[1].value()), (900, 200));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Calibration {
+    matrix: [f64; 6],
+    x: (i32, i32),
+    y: (i32, i32),
+    mt_x: (i32, i32),
+    mt_y: (i32, i32),
+    single: Position,
+    slots: HashMap<i32, Position>,
+    slot: i32,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Position {
+    x: i32,
+    y: i32,
+    dirty: bool,
+}
+
+impl Calibration {
+    /// The matrix leaving positions unchanged.
+    pub const IDENTITY: [f64; 6] = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+    /// The matrix rotating positions by 90 degrees clockwise.
+    pub const ROTATE_90: [f64; 6] = [0.0, -1.0, 1.0, 1.0, 0.0, 0.0];
+    /// The matrix rotating positions by 180 degrees.
+    pub const ROTATE_180: [f64; 6] = [-1.0, 0.0, 1.0, 0.0, -1.0, 1.0];
+    /// The matrix rotating positions by 270 degrees clockwise.
+    pub const ROTATE_270: [f64; 6] = [0.0, 1.0, 0.0, -1.0, 0.0, 1.0];
+
+    /// Create a transform applying `matrix` to a device whose `ABS_X` and `ABS_Y` axes, and its
+    /// multitouch position axes, have the ranges of `x` and `y`.
+    ///
+    /// The current values of `x` and `y` are taken as the initial position, for frames that
+    /// change only one of the axes.
+    pub fn new(matrix: [f64; 6], x: &AbsInfo, y: &AbsInfo) -> Self {
+        Self {
+            matrix,
+            x: (x.minimum(), x.maximum()),
+            y: (y.minimum(), y.maximum()),
+            mt_x: (x.minimum(), x.maximum()),
+            mt_y: (y.minimum(), y.maximum()),
+            single: Position {
+                x: x.value(),
+                y: y.value(),
+                dirty: false,
+            },
+            slots: HashMap::new(),
+            slot: 0,
+        }
+    }
+
+    /// Use different ranges for `ABS_MT_POSITION_X` and `ABS_MT_POSITION_Y`, for devices where
+    /// they differ from those of `ABS_X` and `ABS_Y`.
+    pub fn with_mt_ranges(mut self, x: &AbsInfo, y: &AbsInfo) -> Self {
+        self.mt_x = (x.minimum(), x.maximum());
+        self.mt_y = (y.minimum(), y.maximum());
+        self
+    }
+
+    /// Returns the matrix applied by this transform.
+    pub fn matrix(&self) -> [f64; 6] {
+        self.matrix
+    }
+
+    fn apply(&self, x: i32, y: i32, x_range: (i32, i32), y_range: (i32, i32)) -> (i32, i32) {
+        let normalize = |v: i32, (min, max): (i32, i32)| {
+            if max > min {
+                (f64::from(v) - f64::from(min)) / (f64::from(max) - f64::from(min))
+            } else {
+                0.0
+            }
+        };
+        let denormalize = |v: f64, (min, max): (i32, i32)| {
+            (f64::from(min) + v * (f64::from(max) - f64::from(min))).round() as i32
+        };
+        let (nx, ny) = (normalize(x, x_range), normalize(y, y_range));
+        let [a, b, c, d, e, f] = self.matrix;
+        (
+            denormalize(a * nx + b * ny + c, x_range),
+            denormalize(d * nx + e * ny + f, y_range),
+        )
+    }
+
+    fn flush_single(&mut self, template: &input_event, output: &mut Vec<InputEvent>) {
+        if !self.single.dirty {
+            return;
+        }
+        self.single.dirty = false;
+        let (x, y) = self.apply(self.single.x, self.single.y, self.x, self.y);
+        output.push(with_code(template, AbsoluteAxisCode::ABS_X, x));
+        output.push(with_code(template, AbsoluteAxisCode::ABS_Y, y));
+    }
+
+    fn flush_slot(&mut self, template: &input_event, output: &mut Vec<InputEvent>) {
+        let pos = match self.slots.get_mut(&self.slot) {
+            Some(pos) if pos.dirty => pos,
+            _ => return,
+        };
+        pos.dirty = false;
+        let (x, y) = (pos.x, pos.y);
+        let (x, y) = self.apply(x, y, self.mt_x, self.mt_y);
+        output.push(with_code(template, AbsoluteAxisCode::ABS_MT_POSITION_X, x));
+        output.push(with_code(template, AbsoluteAxisCode::ABS_MT_POSITION_Y, y));
+    }
+}
+
+fn with_code(template: &input_event, axis: AbsoluteAxisCode, value: i32) -> InputEvent {
+    InputEvent::from(input_event {
+        type_: crate::EventType::ABSOLUTE.0,
+        code: axis.0,
+        value,
+        ..*template
+    })
+}
+
+impl EventTransform for Calibration {
+    fn transform(&mut self, event: InputEvent, output: &mut Vec<InputEvent>) {
+        let raw = event.as_ref();
+        match event.destructure() {
+            EventSummary::AbsoluteAxis(_, AbsoluteAxisCode::ABS_X, value) => {
+                self.single.x = value;
+                self.single.dirty = true;
+            }
+            EventSummary::AbsoluteAxis(_, AbsoluteAxisCode::ABS_Y, value) => {
+                self.single.y = value;
+                self.single.dirty = true;
+            }
+            EventSummary::AbsoluteAxis(_, AbsoluteAxisCode::ABS_MT_POSITION_X, value) => {
+                let pos = self.slots.entry(self.slot).or_default();
+                pos.x = value;
+                pos.dirty = true;
+            }
+            EventSummary::AbsoluteAxis(_, AbsoluteAxisCode::ABS_MT_POSITION_Y, value) => {
+                let pos = self.slots.entry(self.slot).or_default();
+                pos.y = value;
+                pos.dirty = true;
+            }
+            EventSummary::AbsoluteAxis(_, AbsoluteAxisCode::ABS_MT_SLOT, value) => {
+                self.flush_slot(raw, output);
+                self.slot = value;
+                output.push(event);
+            }
+            EventSummary::Synchronization(_, SynchronizationCode::SYN_REPORT, _) => {
+                self.flush_slot(raw, output);
+                self.flush_single(raw, output);
+                output.push(event);
+            }
+            _ => output.push(event),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbsoluteAxisEvent, SynchronizationEvent};
+
+    #[test]
+    fn multitouch_slots() {
+        let range = AbsInfo::new(0, 0, 100, 0, 0, 0);
+        let calibration = Calibration::new(Calibration::ROTATE_180, &range, &range);
+        let events = [
+            *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_MT_SLOT, 0),
+            *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_MT_POSITION_X, 10),
+            *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_MT_POSITION_Y, 20),
+            *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_MT_SLOT, 1),
+            *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_MT_POSITION_X, 30),
+            *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_MT_POSITION_Y, 40),
+            *SynchronizationEvent::new(SynchronizationCode::SYN_REPORT, 0),
+        ];
+        let out: Vec<_> = calibration
+            .transform_events(events)
+            .map(|ev| (ev.code(), ev.value()))
+            .collect();
+        let (slot, x, y) = (
+            AbsoluteAxisCode::ABS_MT_SLOT.0,
+            AbsoluteAxisCode::ABS_MT_POSITION_X.0,
+            AbsoluteAxisCode::ABS_MT_POSITION_Y.0,
+        );
+        assert_eq!(
+            out,
+            [
+                (slot, 0),
+                (x, 90),
+                (y, 80),
+                (slot, 1),
+                (x, 70),
+                (y, 60),
+                (SynchronizationCode::SYN_REPORT.0, 0)
+            ]
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use crate::compat::input_event;
+use crate::transform::EventTransform;
+use crate::{
+    AttributeSet, AttributeSetRef, EventSummary, EventType, InputEvent, RelativeAxisCode,
+    SynchronizationCode,
+};
+use std::time::{Duration, SystemTime};
+
+/// An [`EventTransform`] that merges relative motion into fewer, larger events.
+///
+/// Relative events on the coalesced axes are summed up instead of being forwarded. By default
+/// the sums are emitted at the end of every frame, which merges repeated events on the same axis
+/// within a frame. With [`Coalesce::window`], frames that contain nothing but coalesced motion
+/// are merged as well until the window has elapsed, which limits the rate of a high frequency
+/// mouse. Any other event (e.g. a button press) flushes the motion held back from earlier frames
+/// in a frame of its own before it is forwarded, so ordering relative to other events is
+/// preserved.
+///
+/// The coalesced axes default to `REL_X`, `REL_Y`, and the (high resolution) wheels.
+///
+/// ```
+/// use evdev::transform::Coalesce;
+/// use std::time::Duration;
+///
+/// // deliver mouse motion at most every 8ms (~125Hz)
+/// let coalesce = Coalesce::new().window(Duration::from_millis(8));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Coalesce {
+    axes: AttributeSet<RelativeAxisCode>,
+    window: Duration,
+    pending: Vec<(RelativeAxisCode, i32)>,
+    pending_time: Option<crate::compat::timeval>,
+    window_start: Option<SystemTime>,
+    /// Whether the pending motion includes frames that were held back.
+    held: bool,
+    frame_has_other: bool,
+}
+
+impl Default for Coalesce {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Coalesce {
+    /// Create a transform coalescing the default axes within each frame.
+    pub fn new() -> Self {
+        let axes = [
+            RelativeAxisCode::REL_X,
+            RelativeAxisCode::REL_Y,
+            RelativeAxisCode::REL_WHEEL,
+            RelativeAxisCode::REL_HWHEEL,
+            RelativeAxisCode::REL_WHEEL_HI_RES,
+            RelativeAxisCode::REL_HWHEEL_HI_RES,
+        ]
+        .into_iter()
+        .collect();
+        Self {
+            axes,
+            window: Duration::ZERO,
+            pending: Vec::new(),
+            pending_time: None,
+            window_start: None,
+            held: false,
+            frame_has_other: false,
+        }
+    }
+
+    /// Set which relative axes are coalesced.
+    pub fn axes(mut self, axes: &AttributeSetRef<RelativeAxisCode>) -> Self {
+        self.axes = axes.iter().collect();
+        self
+    }
+
+    /// Merge frames containing only coalesced motion until `window` has elapsed since the first
+    /// merged event.
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Emit any accumulated motion, followed by a `SYN_REPORT`.
+    pub fn flush(&mut self, output: &mut Vec<InputEvent>) {
+        if let Some(time) = self.pending_time {
+            self.emit_pending(output);
+            output.push(InputEvent::from(input_event {
+                time,
+                type_: EventType::SYNCHRONIZATION.0,
+                code: SynchronizationCode::SYN_REPORT.0,
+                value: 0,
+            }));
+        }
+    }
+
+    fn emit_pending(&mut self, output: &mut Vec<InputEvent>) {
+        if let Some(time) = self.pending_time.take() {
+            output.extend(
+                self.pending
+                    .drain(..)
+                    .filter(|&(_, v)| v != 0)
+                    .map(|(axis, value)| {
+                        InputEvent::from(input_event {
+                            time,
+                            type_: EventType::RELATIVE.0,
+                            code: axis.0,
+                            value,
+                        })
+                    }),
+            );
+        }
+        self.window_start = None;
+        self.held = false;
+    }
+}
+
+impl EventTransform for Coalesce {
+    fn transform(&mut self, event: InputEvent, output: &mut Vec<InputEvent>) {
+        match event.destructure() {
+            EventSummary::RelativeAxis(_, axis, value) if self.axes.contains(axis) => {
+                match self.pending.iter_mut().find(|(a, _)| *a == axis) {
+                    Some((_, sum)) => *sum = sum.saturating_add(value),
+                    None => self.pending.push((axis, value)),
+                }
+                self.pending_time = Some(event.as_ref().time);
+                self.window_start.get_or_insert(event.timestamp());
+            }
+            EventSummary::Synchronization(_, SynchronizationCode::SYN_REPORT, _) => {
+                let window_elapsed = self.window_start.map_or(true, |start| {
+                    event.timestamp().duration_since(start).unwrap_or_default() >= self.window
+                });
+                if self.frame_has_other || window_elapsed {
+                    self.emit_pending(output);
+                    output.push(event);
+                } else {
+                    self.held = self.pending_time.is_some();
+                }
+                self.frame_has_other = false;
+            }
+            _ => {
+                if !self.frame_has_other {
+                    if self.held {
+                        self.flush(output);
+                    } else {
+                        // motion earlier in this frame stays in it
+                        self.emit_pending(output);
+                    }
+                }
+                self.frame_has_other = true;
+                output.push(event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_at as at;
+    use crate::{KeyCode, KeyEvent, RelativeAxisEvent, SynchronizationEvent};
+
+    fn rel_x(ms: u64, value: i32) -> InputEvent {
+        at(ms, *RelativeAxisEvent::new(RelativeAxisCode::REL_X, value))
+    }
+
+    fn report(ms: u64) -> InputEvent {
+        at(
+            ms,
+            *SynchronizationEvent::new(SynchronizationCode::SYN_REPORT, 0),
+        )
+    }
+
+    #[test]
+    fn merges_within_frame() {
+        let events = [rel_x(0, 1), rel_x(0, 2), report(0)];
+        let out: Vec<_> = Coalesce::new().transform_events(events).collect();
+        assert_eq!(out, [rel_x(0, 3), report(0)]);
+    }
+
+    #[test]
+    fn merges_frames_within_window() {
+        let events = [
+            rel_x(0, 1),
+            report(0),
+            rel_x(1, 1),
+            report(1),
+            rel_x(2, 1),
+            report(2),
+            *KeyEvent::new(KeyCode::BTN_LEFT, 1),
+            report(3),
+        ];
+        let coalesce = Coalesce::new().window(Duration::from_millis(2));
+        let out: Vec<_> = coalesce
+            .transform_events(events)
+            .map(|ev| (ev.event_type(), ev.value()))
+            .collect();
+        assert_eq!(
+            out,
+            [
+                (EventType::RELATIVE, 3),
+                (EventType::SYNCHRONIZATION, 0),
+                (EventType::KEY, 1),
+                (EventType::SYNCHRONIZATION, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn flushes_held_motion_before_other_events() {
+        let events = [
+            rel_x(0, 1),
+            report(0),
+            rel_x(1, 1),
+            report(1),
+            at(2, *KeyEvent::new(KeyCode::BTN_LEFT, 1)),
+            rel_x(2, 1),
+            report(2),
+        ];
+        let coalesce = Coalesce::new().window(Duration::from_millis(5));
+        let out: Vec<_> = coalesce.transform_events(events).collect();
+        assert_eq!(
+            out,
+            [
+                rel_x(1, 2),
+                report(1),
+                at(2, *KeyEvent::new(KeyCode::BTN_LEFT, 1)),
+                rel_x(2, 1),
+                report(2),
+            ]
+        );
+    }
+}
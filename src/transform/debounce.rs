@@ -0,0 +1,214 @@
+use crate::compat::input_event;
+use crate::transform::EventTransform;
+use crate::{systime_to_timeval, EventType, InputEvent, KeyCode, SwitchCode, SynchronizationCode};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+/// An [`EventTransform`] that suppresses contact chatter on keys and switches.
+///
+/// The first change of a key or switch is forwarded immediately, after which any further changes
+/// of that code are held off for the configured duration. If the code ends up in a different
+/// state than the one last forwarded once the hold-off expires, a corrective event is emitted, so
+/// a genuine quick tap is never lost entirely. Time is measured using the event timestamps.
+///
+/// Corrections are emitted when the next event (of any code) arrives after the hold-off has
+/// expired. If the event source may go quiet, call [`Debounce::flush_until`] periodically to
+/// make sure corrections aren't delayed indefinitely.
+///
+/// Key repeats are only forwarded while the key is reported as pressed.
+///
+/// ```
+/// use evdev::transform::Debounce;
+/// use evdev::SwitchCode;
+/// use std::time::Duration;
+///
+/// let debounce = Debounce::new(Duration::from_millis(10))
+///     .with_switch(SwitchCode::SW_LID, Duration::from_millis(500));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Debounce {
+    default: Duration,
+    keys: HashMap<KeyCode, Duration>,
+    switches: HashMap<SwitchCode, Duration>,
+    states: HashMap<(EventType, u16), CodeState>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CodeState {
+    reported: bool,
+    actual: bool,
+    changed_at: SystemTime,
+    hold_off: Duration,
+}
+
+impl Debounce {
+    /// Create a debouncer applying `hold_off` to every key and switch.
+    ///
+    /// A hold-off of [`Duration::ZERO`] disables debouncing for codes that aren't configured
+    /// individually.
+    pub fn new(hold_off: Duration) -> Self {
+        Self {
+            default: hold_off,
+            keys: HashMap::new(),
+            switches: HashMap::new(),
+            states: HashMap::new(),
+        }
+    }
+
+    /// Use a different hold-off for a specific key.
+    pub fn with_key(mut self, key: KeyCode, hold_off: Duration) -> Self {
+        self.set_key(key, hold_off);
+        self
+    }
+
+    /// Use a different hold-off for a specific switch.
+    pub fn with_switch(mut self, switch: SwitchCode, hold_off: Duration) -> Self {
+        self.set_switch(switch, hold_off);
+        self
+    }
+
+    /// Use a different hold-off for a specific key.
+    pub fn set_key(&mut self, key: KeyCode, hold_off: Duration) {
+        self.keys.insert(key, hold_off);
+    }
+
+    /// Use a different hold-off for a specific switch.
+    pub fn set_switch(&mut self, switch: SwitchCode, hold_off: Duration) {
+        self.switches.insert(switch, hold_off);
+    }
+
+    /// Emit corrective events for every code whose hold-off has expired by `now`.
+    ///
+    /// Any events emitted are followed by a `SYN_REPORT`.
+    pub fn flush_until(&mut self, now: SystemTime, output: &mut Vec<InputEvent>) {
+        if self.expire(now, output) {
+            output.push(InputEvent::from(input_event {
+                time: systime_to_timeval(&now),
+                type_: EventType::SYNCHRONIZATION.0,
+                code: SynchronizationCode::SYN_REPORT.0,
+                value: 0,
+            }));
+        }
+    }
+
+    fn hold_off(&self, ty: EventType, code: u16) -> Option<Duration> {
+        let hold_off = match ty {
+            EventType::KEY => self.keys.get(&KeyCode(code)),
+            EventType::SWITCH => self.switches.get(&SwitchCode(code)),
+            _ => return None,
+        };
+        Some(*hold_off.unwrap_or(&self.default))
+    }
+
+    fn expire(&mut self, now: SystemTime, output: &mut Vec<InputEvent>) -> bool {
+        let mut expired: Vec<_> = self
+            .states
+            .iter_mut()
+            .filter(|(_, st)| {
+                st.reported != st.actual && elapsed(st.changed_at, now) >= st.hold_off
+            })
+            .map(|(&(ty, code), st)| {
+                st.reported = st.actual;
+                st.changed_at += st.hold_off;
+                (st.changed_at, ty, code, st.actual)
+            })
+            .collect();
+        expired.sort_by_key(|&(time, ty, code, _)| (time, ty.0, code));
+        for &(time, ty, code, value) in &expired {
+            output.push(InputEvent::from(input_event {
+                time: systime_to_timeval(&time),
+                type_: ty.0,
+                code,
+                value: value as i32,
+            }));
+        }
+        !expired.is_empty()
+    }
+}
+
+fn elapsed(since: SystemTime, now: SystemTime) -> Duration {
+    now.duration_since(since).unwrap_or(Duration::ZERO)
+}
+
+impl EventTransform for Debounce {
+    fn transform(&mut self, event: InputEvent, output: &mut Vec<InputEvent>) {
+        let now = event.timestamp();
+        self.expire(now, output);
+
+        let ty = event.event_type();
+        let hold_off = match self.hold_off(ty, event.code()) {
+            Some(hold_off) if !hold_off.is_zero() => hold_off,
+            _ => return output.push(event),
+        };
+
+        let value = event.value();
+        let st = self.states.entry((ty, event.code())).or_insert(CodeState {
+            reported: value == 0,
+            actual: value == 0,
+            changed_at: SystemTime::UNIX_EPOCH,
+            hold_off,
+        });
+        st.hold_off = hold_off;
+
+        if ty == EventType::KEY && value == 2 {
+            if st.reported {
+                output.push(event);
+            }
+            return;
+        }
+
+        st.actual = value != 0;
+        if st.actual != st.reported && elapsed(st.changed_at, now) >= hold_off {
+            st.reported = st.actual;
+            st.changed_at = now;
+            output.push(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_at(ms: u64, value: i32) -> InputEvent {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1000) + Duration::from_millis(ms);
+        InputEvent::from(input_event {
+            time: systime_to_timeval(&time),
+            type_: EventType::KEY.0,
+            code: KeyCode::KEY_A.0,
+            value,
+        })
+    }
+
+    fn run(debounce: &mut Debounce, events: &[InputEvent]) -> Vec<(u16, i32)> {
+        debounce
+            .transform_events(events.iter().copied())
+            .map(|ev| (ev.code(), ev.value()))
+            .collect()
+    }
+
+    #[test]
+    fn chatter_is_suppressed() {
+        let mut debounce = Debounce::new(Duration::from_millis(10));
+        let out = run(
+            &mut debounce,
+            &[key_at(0, 1), key_at(2, 0), key_at(3, 1), key_at(50, 0)],
+        );
+        let a = KeyCode::KEY_A.0;
+        assert_eq!(out, [(a, 1), (a, 0)]);
+    }
+
+    #[test]
+    fn quick_tap_is_corrected() {
+        let mut debounce = Debounce::new(Duration::from_millis(10));
+        let a = KeyCode::KEY_A.0;
+        let out = run(&mut debounce, &[key_at(0, 1), key_at(5, 0)]);
+        assert_eq!(out, [(a, 1)]);
+
+        let mut flushed = Vec::new();
+        debounce.flush_until(key_at(20, 0).timestamp(), &mut flushed);
+        assert_eq!(flushed.len(), 2);
+        assert_eq!((flushed[0].code(), flushed[0].value()), (a, 0));
+        assert_eq!(flushed[1].event_type(), EventType::SYNCHRONIZATION);
+    }
+}
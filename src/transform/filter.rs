@@ -0,0 +1,203 @@
+use crate::compat::input_event;
+use crate::transform::EventTransform;
+use crate::{AbsInfo, AbsoluteAxisCode, Device, EventSummary, InputEvent};
+use std::collections::HashMap;
+use std::io;
+
+/// An [`EventTransform`] that filters absolute axes with their fuzz and flat values, like the
+/// kernel is supposed to.
+///
+/// The kernel smooths out noise on axes with a fuzz value: changes within half the fuzz of the
+/// previous value are dropped, and changes within twice the fuzz are averaged with it. Some
+/// drivers, including many virtual devices, report a fuzz but bypass this filter. The filter is
+/// applied per slot for multitouch axes.
+///
+/// The flat value is the size of the dead zone in the center of an axis, which the kernel
+/// reports but leaves to applications. Values within the dead zone are reported as the center
+/// of the axis.
+///
+/// Events that the filter turns into a repetition of the previous value are dropped. Axes
+/// without parameters and all other event types pass through unchanged.
+///
+/// ```
+/// use evdev::transform::{AxisFilter, EventTransform};
+/// use evdev::{AbsInfo, AbsoluteAxisCode, AbsoluteAxisEvent, InputEvent};
+///
+/// let filter = AxisFilter::new()
+///     .with_axis(AbsoluteAxisCode::ABS_X, &AbsInfo::new(0, -512, 511, 8, 32, 0));
+/// let events = [
+///     *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_X, 20),
+///     *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_X, 100),
+/// ];
+/// let values: Vec<_> = filter.transform_events(events).map(|ev| ev.value()).collect();
+/// assert_eq!(values, [100]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AxisFilter {
+    axes: HashMap<AbsoluteAxisCode, Params>,
+    /// The last reported value of each axis, and slot for multitouch axes.
+    values: HashMap<(AbsoluteAxisCode, i32), i32>,
+    slot: i32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Params {
+    fuzz: i32,
+    flat: i32,
+    center: i32,
+}
+
+impl AxisFilter {
+    /// Create a filter without any axes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a filter for all axes of a device, using their fuzz and flat values.
+    ///
+    /// Axes with neither are left out.
+    pub fn from_device(device: &Device) -> io::Result<Self> {
+        let mut filter = Self::new();
+        for (axis, info) in device.get_absinfo()? {
+            if info.fuzz() != 0 || info.flat() != 0 {
+                filter.set_axis(axis, &info);
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Filter an axis with the fuzz, flat and range of `info`.
+    pub fn with_axis(mut self, axis: AbsoluteAxisCode, info: &AbsInfo) -> Self {
+        self.set_axis(axis, info);
+        self
+    }
+
+    /// Filter an axis with the fuzz, flat and range of `info`.
+    ///
+    /// The value of `info` is taken as the last reported value of the axis.
+    pub fn set_axis(&mut self, axis: AbsoluteAxisCode, info: &AbsInfo) {
+        let center = ((info.minimum() as i64 + info.maximum() as i64) / 2) as i32;
+        self.axes.insert(
+            axis,
+            Params {
+                fuzz: info.fuzz(),
+                flat: info.flat(),
+                center,
+            },
+        );
+        self.values.retain(|&(a, _), _| a != axis);
+        if !is_mt(axis) {
+            self.values.insert((axis, 0), info.value());
+        }
+    }
+
+    /// Stop filtering an axis.
+    pub fn remove_axis(&mut self, axis: AbsoluteAxisCode) {
+        self.axes.remove(&axis);
+        self.values.retain(|&(a, _), _| a != axis);
+    }
+}
+
+fn is_mt(axis: AbsoluteAxisCode) -> bool {
+    axis.0 >= AbsoluteAxisCode::ABS_MT_TOUCH_MAJOR.0
+}
+
+/// The kernel's fuzz filter, from `input_defuzz_abs_event`.
+fn defuzz(value: i32, old: i32, fuzz: i32) -> i32 {
+    if fuzz == 0 {
+        value
+    } else if value > old - fuzz / 2 && value < old + fuzz / 2 {
+        old
+    } else if value > old - fuzz && value < old + fuzz {
+        (old * 3 + value) / 4
+    } else if value > old - fuzz * 2 && value < old + fuzz * 2 {
+        (old + value) / 2
+    } else {
+        value
+    }
+}
+
+impl EventTransform for AxisFilter {
+    fn transform(&mut self, event: InputEvent, output: &mut Vec<InputEvent>) {
+        let (axis, value) = match event.destructure() {
+            EventSummary::AbsoluteAxis(_, AbsoluteAxisCode::ABS_MT_SLOT, slot) => {
+                self.slot = slot;
+                output.push(event);
+                return;
+            }
+            EventSummary::AbsoluteAxis(_, axis, value) => (axis, value),
+            _ => {
+                output.push(event);
+                return;
+            }
+        };
+        let params = match self.axes.get(&axis) {
+            Some(params) => *params,
+            None => {
+                output.push(event);
+                return;
+            }
+        };
+        let key = (axis, if is_mt(axis) { self.slot } else { 0 });
+        let mut value = match self.values.get(&key) {
+            Some(&old) => defuzz(value, old, params.fuzz),
+            None => value,
+        };
+        if (value - params.center).abs() <= params.flat {
+            value = params.center;
+        }
+        if self.values.insert(key, value) == Some(value) {
+            return;
+        }
+        output.push(InputEvent::from(input_event {
+            value,
+            ..*event.as_ref()
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AbsoluteAxisEvent;
+
+    #[test]
+    fn fuzz_per_slot() {
+        let info = AbsInfo::new(0, 0, 1000, 8, 0, 0);
+        let mut filter = AxisFilter::new()
+            .with_axis(AbsoluteAxisCode::ABS_X, &info)
+            .with_axis(AbsoluteAxisCode::ABS_MT_POSITION_X, &info);
+        let mut values = |events: &[(AbsoluteAxisCode, i32)]| -> Vec<i32> {
+            let events = events
+                .iter()
+                .map(|&(axis, value)| *AbsoluteAxisEvent::new(axis, value));
+            (&mut filter)
+                .transform_events(events)
+                .map(|ev| ev.value())
+                .collect()
+        };
+
+        // within half the fuzz, a quarter, half, and beyond twice the fuzz
+        let x = AbsoluteAxisCode::ABS_X;
+        assert_eq!(
+            values(&[(x, 503), (x, 505), (x, 509), (x, 518), (x, 540)]),
+            [503, 504, 511, 540]
+        );
+
+        let (slot, mt_x) = (
+            AbsoluteAxisCode::ABS_MT_SLOT,
+            AbsoluteAxisCode::ABS_MT_POSITION_X,
+        );
+        assert_eq!(
+            values(&[
+                (slot, 0),
+                (mt_x, 100),
+                (slot, 1),
+                (mt_x, 102),
+                (slot, 0),
+                (mt_x, 102)
+            ]),
+            [0, 100, 1, 102, 0]
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use crate::compat::input_event;
+use crate::transform::EventTransform;
+use crate::{AbsInfo, AbsoluteAxisCode, EventSummary, InputEvent};
+
+/// An [`EventTransform`] that maps the pressure of a pen or touch through a curve, to adjust how
+/// hard a drawing tablet has to be pressed.
+///
+/// The curve is applied to `ABS_PRESSURE` and `ABS_MT_PRESSURE`. Pressure is normalized to
+/// `0.0..=1.0` using the range of the axis, mapped through the curve, and scaled back to the
+/// range, so the minimum and maximum pressure are left unchanged. Values outside the range are
+/// clamped to it.
+///
+/// ```
+/// use evdev::transform::{EventTransform, PressureCurve};
+/// use evdev::{AbsInfo, AbsoluteAxisCode, AbsoluteAxisEvent};
+///
+/// // softer: less pressure is needed to reach the same value
+/// let range = AbsInfo::new(0, 0, 1000, 0, 0, 0);
+/// let curve = PressureCurve::gamma(0.5, &range);
+/// let events = [*AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_PRESSURE, 250)];
+/// let out: Vec<_> = curve.transform_events(events).collect();
+/// assert_eq!(out[0].value(), 500);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PressureCurve {
+    shape: Shape,
+    range: (i32, i32),
+    mt_range: (i32, i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Shape {
+    Linear,
+    Gamma(f64),
+    Bezier((f64, f64), (f64, f64)),
+}
+
+impl PressureCurve {
+    /// A curve that leaves pressure as the device reports it, for an axis with the range of
+    /// `range`.
+    pub fn linear(range: &AbsInfo) -> Self {
+        Self::with_shape(Shape::Linear, range)
+    }
+
+    /// A curve raising the normalized pressure to the power of `gamma`. Values below `1.0` make
+    /// the pen feel softer, values above `1.0` make it feel firmer.
+    pub fn gamma(gamma: f64, range: &AbsInfo) -> Self {
+        Self::with_shape(Shape::Gamma(gamma), range)
+    }
+
+    /// A cubic Bézier curve from `(0, 0)` to `(1, 1)` with the control points `p1` and `p2`, like
+    /// the pressure curves of most drawing applications and CSS's `cubic-bezier()`.
+    ///
+    /// The x coordinates of the control points are clamped to `0.0..=1.0` so that the curve
+    /// maps every pressure to exactly one value.
+    pub fn bezier(p1: (f64, f64), p2: (f64, f64), range: &AbsInfo) -> Self {
+        let p1 = (p1.0.clamp(0.0, 1.0), p1.1);
+        let p2 = (p2.0.clamp(0.0, 1.0), p2.1);
+        Self::with_shape(Shape::Bezier(p1, p2), range)
+    }
+
+    fn with_shape(shape: Shape, range: &AbsInfo) -> Self {
+        let range = (range.minimum(), range.maximum());
+        Self {
+            shape,
+            range,
+            mt_range: range,
+        }
+    }
+
+    /// Use a different range for `ABS_MT_PRESSURE`, for devices where it differs from that of
+    /// `ABS_PRESSURE`.
+    pub fn with_mt_range(mut self, range: &AbsInfo) -> Self {
+        self.mt_range = (range.minimum(), range.maximum());
+        self
+    }
+
+    /// Map a normalized pressure in `0.0..=1.0` through the curve.
+    pub fn map(&self, pressure: f64) -> f64 {
+        let p = pressure.clamp(0.0, 1.0);
+        let mapped = match self.shape {
+            Shape::Linear => p,
+            Shape::Gamma(gamma) => p.powf(gamma),
+            Shape::Bezier((x1, y1), (x2, y2)) => {
+                let bezier = |t: f64, a: f64, b: f64| {
+                    let u = 1.0 - t;
+                    3.0 * u * u * t * a + 3.0 * u * t * t * b + t * t * t
+                };
+                // x(t) is monotonic with the control points clamped, so bisect for the t at
+                // which it reaches p
+                let (mut lo, mut hi) = (0.0, 1.0);
+                for _ in 0..32 {
+                    let mid = (lo + hi) / 2.0;
+                    if bezier(mid, x1, x2) < p {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                bezier((lo + hi) / 2.0, y1, y2)
+            }
+        };
+        mapped.clamp(0.0, 1.0)
+    }
+
+    fn apply(&self, value: i32, (min, max): (i32, i32)) -> i32 {
+        if max <= min {
+            return value;
+        }
+        let span = f64::from(max) - f64::from(min);
+        let normalized = (f64::from(value) - f64::from(min)) / span;
+        (f64::from(min) + self.map(normalized) * span).round() as i32
+    }
+}
+
+impl EventTransform for PressureCurve {
+    fn transform(&mut self, event: InputEvent, output: &mut Vec<InputEvent>) {
+        let value = match event.destructure() {
+            EventSummary::AbsoluteAxis(_, AbsoluteAxisCode::ABS_PRESSURE, value) => {
+                self.apply(value, self.range)
+            }
+            EventSummary::AbsoluteAxis(_, AbsoluteAxisCode::ABS_MT_PRESSURE, value) => {
+                self.apply(value, self.mt_range)
+            }
+            _ => return output.push(event),
+        };
+        output.push(InputEvent::from(input_event {
+            value,
+            ..*event.as_ref()
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curves() {
+        let range = AbsInfo::new(0, 0, 100, 0, 0, 0);
+        let linear = PressureCurve::linear(&range);
+        assert_eq!(linear.apply(37, linear.range), 37);
+        assert_eq!(linear.apply(150, linear.range), 100);
+
+        let firm = PressureCurve::gamma(2.0, &range);
+        assert_eq!(firm.apply(50, firm.range), 25);
+
+        // with control points on the diagonal the curve is a straight line
+        let straight = PressureCurve::bezier((0.25, 0.25), (0.75, 0.75), &range);
+        assert!((straight.map(0.3) - 0.3).abs() < 1e-6);
+        let soft = PressureCurve::bezier((0.0, 0.5), (0.5, 1.0), &range);
+        assert!(soft.map(0.3) > 0.3);
+        assert!(soft.map(0.0) < 1e-6 && soft.map(1.0) > 1.0 - 1e-6);
+    }
+}
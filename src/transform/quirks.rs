@@ -0,0 +1,225 @@
+use crate::compat::input_event;
+use crate::transform::EventTransform;
+use crate::{Device, EventType, InputEvent, InputId, KeyCode, MiscCode, SynchronizationCode};
+use std::collections::HashMap;
+
+/// An entry of the embedded quirk table.
+struct Quirk {
+    vendor: u16,
+    product: Option<u16>,
+    name: Option<&'static str>,
+    scancodes: &'static [(u32, KeyCode)],
+    keys: &'static [(KeyCode, KeyCode)],
+}
+
+impl Quirk {
+    fn matches(&self, id: &InputId, name: &str) -> bool {
+        id.vendor() == self.vendor
+            && self.product.map_or(true, |p| p == id.product())
+            && self.name.map_or(true, |n| n == name)
+    }
+}
+
+/// The hotkeys of `thinkpad_acpi`, as numbered by the driver. Which of these get a proper key
+/// code depends on the kernel version and the model; older kernels report most of them as
+/// `KEY_UNKNOWN` or as `KEY_F20` and up.
+const THINKPAD_HOTKEYS: &[(u32, KeyCode)] = &[
+    (0x01, KeyCode::KEY_BATTERY),
+    (0x03, KeyCode::KEY_SLEEP),
+    (0x04, KeyCode::KEY_WLAN),
+    (0x06, KeyCode::KEY_SWITCHVIDEOMODE),
+    (0x0b, KeyCode::KEY_SUSPEND),
+    (0x11, KeyCode::KEY_KBDILLUMTOGGLE),
+    (0x13, KeyCode::KEY_ZOOM),
+    (0x17, KeyCode::KEY_PROG1),
+    (0x1a, KeyCode::KEY_MICMUTE),
+];
+
+/// The vendor-specific consumer usages (page `0xff10`, reported as `0xc1xxx`) of Logitech
+/// keyboards. The kernel only maps them for the handful of receivers it has quirks for, and
+/// reports them as `KEY_UNKNOWN` everywhere else.
+const LOGITECH_CONSUMER: &[(u32, KeyCode)] = &[
+    (0xc1001, KeyCode::KEY_MESSENGER),
+    (0xc1003, KeyCode::KEY_SOUND),
+    (0xc1004, KeyCode::KEY_VIDEO),
+    (0xc1005, KeyCode::KEY_AUDIO),
+    (0xc100a, KeyCode::KEY_DOCUMENTS),
+    (0xc1011, KeyCode::KEY_PREVIOUSSONG),
+    (0xc1012, KeyCode::KEY_NEXTSONG),
+    (0xc1013, KeyCode::KEY_CAMERA),
+    (0xc1019, KeyCode::KEY_PROG1),
+    (0xc101a, KeyCode::KEY_PROG2),
+    (0xc101b, KeyCode::KEY_PROG3),
+    (0xc101c, KeyCode::KEY_CYCLEWINDOWS),
+    (0xc101f, KeyCode::KEY_ZOOMIN),
+    (0xc1020, KeyCode::KEY_ZOOMOUT),
+    (0xc1021, KeyCode::KEY_ZOOMRESET),
+    (0xc1023, KeyCode::KEY_CLOSE),
+    (0xc1041, KeyCode::KEY_BATTERY),
+    (0xc1042, KeyCode::KEY_WORDPROCESSOR),
+    (0xc1043, KeyCode::KEY_SPREADSHEET),
+];
+
+const QUIRKS: &[Quirk] = &[
+    Quirk {
+        vendor: 0x17aa,
+        product: Some(0x5054),
+        name: Some("ThinkPad Extra Buttons"),
+        scancodes: THINKPAD_HOTKEYS,
+        // the microphone mute key was reported as F20 before KEY_MICMUTE existed, and some
+        // setups still remap it that way
+        keys: &[(KeyCode::KEY_F20, KeyCode::KEY_MICMUTE)],
+    },
+    Quirk {
+        vendor: 0x046d,
+        product: None,
+        name: None,
+        scancodes: LOGITECH_CONSUMER,
+        keys: &[],
+    },
+];
+
+/// An [`EventTransform`] that corrects the key codes of hardware reporting vendor-specific
+/// scancodes or the wrong keys.
+///
+/// Key events are preceded by an `MSC_SCAN` event in the same frame on most keyboards. When the
+/// scancode has an entry, the code of the key event that follows it is replaced. Keys can also
+/// be replaced directly, for devices that report a wrong but consistent key code. Everything
+/// else, including the `MSC_SCAN` events themselves, passes through unchanged.
+///
+/// [`ScancodeQuirks::for_device`] looks the device up in a small table embedded in the crate,
+/// covering common hardware like the Fn keys of ThinkPads and the extra keys of Logitech
+/// keyboards. Entries can be added on top of it, or a table can be built from scratch with
+/// [`ScancodeQuirks::new`].
+///
+/// Note that the replacement keys are not added to the capabilities of the device, so consumers
+/// that check [`Device::supported_keys`] won't know about them. When forwarding the events
+/// through a virtual device, enable the replacement keys on it as well.
+///
+/// ```no_run
+/// use evdev::transform::{EventTransform, ScancodeQuirks};
+/// use evdev::Device;
+///
+/// let mut device = Device::open("/dev/input/event0")?;
+/// let mut quirks = ScancodeQuirks::for_device(&device).unwrap_or_default();
+/// loop {
+///     let events: Vec<_> = device.fetch_events()?.collect();
+///     for ev in (&mut quirks).transform_events(events) {
+///         println!("{ev:?}");
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ScancodeQuirks {
+    scancodes: HashMap<u32, KeyCode>,
+    keys: HashMap<KeyCode, KeyCode>,
+    pending: Option<KeyCode>,
+}
+
+impl ScancodeQuirks {
+    /// Create a transform without any entries.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Look up the embedded quirks for a device.
+    ///
+    /// Returns `None` if the table has no entries for it.
+    pub fn for_device(device: &Device) -> Option<Self> {
+        Self::for_id(&device.input_id(), device.name().unwrap_or(""))
+    }
+
+    /// Look up the embedded quirks for a device with the given ID and name.
+    ///
+    /// Returns `None` if the table has no entries for it.
+    pub fn for_id(id: &InputId, name: &str) -> Option<Self> {
+        let mut quirks = Self::new();
+        let mut found = false;
+        for quirk in QUIRKS.iter().filter(|q| q.matches(id, name)) {
+            found = true;
+            quirks.scancodes.extend(quirk.scancodes.iter().copied());
+            quirks.keys.extend(quirk.keys.iter().copied());
+        }
+        found.then_some(quirks)
+    }
+
+    /// Report `key` for the key event following the scancode `scancode`.
+    pub fn insert_scancode(&mut self, scancode: u32, key: KeyCode) {
+        self.scancodes.insert(scancode, key);
+    }
+
+    /// Report `to` whenever the device reports `from`.
+    ///
+    /// Scancode entries take precedence over key entries.
+    pub fn insert_key(&mut self, from: KeyCode, to: KeyCode) {
+        self.keys.insert(from, to);
+    }
+
+    /// Returns the key the scancode `scancode` is mapped to, if it has an entry.
+    pub fn scancode(&self, scancode: u32) -> Option<KeyCode> {
+        self.scancodes.get(&scancode).copied()
+    }
+}
+
+impl EventTransform for ScancodeQuirks {
+    fn transform(&mut self, event: InputEvent, output: &mut Vec<InputEvent>) {
+        match event.event_type() {
+            EventType::MISC if event.code() == MiscCode::MSC_SCAN.0 => {
+                self.pending = self.scancode(event.value() as u32);
+            }
+            EventType::KEY => {
+                let from = KeyCode(event.code());
+                let to = self
+                    .pending
+                    .take()
+                    .or_else(|| self.keys.get(&from).copied());
+                if let Some(to) = to {
+                    output.push(InputEvent::from(input_event {
+                        code: to.0,
+                        ..*event.as_ref()
+                    }));
+                    return;
+                }
+            }
+            EventType::SYNCHRONIZATION if event.code() == SynchronizationCode::SYN_REPORT.0 => {
+                self.pending = None;
+            }
+            _ => {}
+        }
+        output.push(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BusType, KeyEvent, MiscEvent, SynchronizationEvent};
+
+    #[test]
+    fn thinkpad_hotkeys() {
+        let id = InputId::new(BusType::BUS_HOST, 0x17aa, 0x5054, 0x4101);
+        assert!(ScancodeQuirks::for_id(&id, "AT Translated Set 2 keyboard").is_none());
+        let quirks = ScancodeQuirks::for_id(&id, "ThinkPad Extra Buttons").unwrap();
+        let events = [
+            *MiscEvent::new(MiscCode::MSC_SCAN, 0x1a),
+            *KeyEvent::new(KeyCode::KEY_UNKNOWN, 1),
+            *SynchronizationEvent::new(SynchronizationCode::SYN_REPORT, 0),
+            *KeyEvent::new(KeyCode::KEY_F20, 0),
+            *KeyEvent::new(KeyCode::KEY_VOLUMEUP, 1),
+        ];
+        let keys: Vec<_> = quirks
+            .transform_events(events)
+            .filter(|ev| ev.event_type() == EventType::KEY)
+            .map(|ev| KeyCode(ev.code()))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                KeyCode::KEY_MICMUTE,
+                KeyCode::KEY_MICMUTE,
+                KeyCode::KEY_VOLUMEUP
+            ]
+        );
+    }
+}
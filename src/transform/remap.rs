@@ -0,0 +1,189 @@
+use crate::compat::input_event;
+use crate::transform::EventTransform;
+use crate::{EventType, InputEvent, KeyCode};
+use std::collections::HashMap;
+
+/// An [`EventTransform`] that replaces key codes according to a mapping.
+///
+/// Each key can be mapped to another key, to a sequence of keys (e.g. `KEY_LEFTCTRL` +
+/// `KEY_C`), or to nothing at all, which disables it. Keys without an entry pass through
+/// unchanged, as do all non-key events.
+///
+/// The remap remembers which output keys were pressed for every held input key, so when the
+/// mapping is changed while a key is down, releasing it will still release exactly the keys that
+/// were originally pressed.
+///
+/// ```
+/// use evdev::transform::{EventTransform, KeyRemap};
+/// use evdev::{InputEvent, KeyCode, KeyEvent};
+///
+/// let remap: KeyRemap = [(KeyCode::KEY_CAPSLOCK, KeyCode::KEY_ESC)].into_iter().collect();
+/// let events = [*KeyEvent::new(KeyCode::KEY_CAPSLOCK, 1)];
+/// let out: Vec<InputEvent> = remap.transform_events(events).collect();
+/// assert_eq!(out[0].code(), KeyCode::KEY_ESC.code());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct KeyRemap {
+    map: HashMap<KeyCode, Vec<KeyCode>>,
+    held: HashMap<KeyCode, Vec<KeyCode>>,
+}
+
+impl KeyRemap {
+    /// Create a remap with no entries.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map `from` to `to`, replacing any previous mapping of `from`.
+    pub fn insert(&mut self, from: KeyCode, to: KeyCode) {
+        self.map.insert(from, vec![to]);
+    }
+
+    /// Map `from` to a sequence of keys.
+    ///
+    /// When `from` is pressed, the keys are pressed in order; when it is released, they are
+    /// released in reverse order. Key repeats are forwarded to the last key of the sequence. An
+    /// empty sequence disables `from` entirely.
+    pub fn insert_sequence(&mut self, from: KeyCode, to: &[KeyCode]) {
+        self.map.insert(from, to.to_vec());
+    }
+
+    /// Remove the mapping for `from`, so that it passes through unchanged again.
+    pub fn remove(&mut self, from: KeyCode) {
+        self.map.remove(&from);
+    }
+
+    /// Remove all mappings. Keys that are currently held will still be released correctly.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Returns the keys `from` is currently mapped to, if it has a mapping.
+    pub fn get(&self, from: KeyCode) -> Option<&[KeyCode]> {
+        self.map.get(&from).map(Vec::as_slice)
+    }
+
+    fn lookup(&self, from: KeyCode) -> Vec<KeyCode> {
+        self.map.get(&from).cloned().unwrap_or_else(|| vec![from])
+    }
+}
+
+impl FromIterator<(KeyCode, KeyCode)> for KeyRemap {
+    fn from_iter<I: IntoIterator<Item = (KeyCode, KeyCode)>>(iter: I) -> Self {
+        let mut remap = Self::new();
+        iter.into_iter()
+            .for_each(|(from, to)| remap.insert(from, to));
+        remap
+    }
+}
+
+impl From<HashMap<KeyCode, KeyCode>> for KeyRemap {
+    fn from(map: HashMap<KeyCode, KeyCode>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl From<HashMap<KeyCode, Vec<KeyCode>>> for KeyRemap {
+    fn from(map: HashMap<KeyCode, Vec<KeyCode>>) -> Self {
+        Self {
+            map,
+            held: HashMap::new(),
+        }
+    }
+}
+
+impl EventTransform for KeyRemap {
+    fn transform(&mut self, event: InputEvent, output: &mut Vec<InputEvent>) {
+        if event.event_type() != EventType::KEY {
+            output.push(event);
+            return;
+        }
+
+        let from = KeyCode(event.code());
+        let emit = |output: &mut Vec<InputEvent>, key: KeyCode, value: i32| {
+            output.push(InputEvent::from(input_event {
+                code: key.0,
+                value,
+                ..*event.as_ref()
+            }));
+        };
+
+        match event.value() {
+            0 => {
+                let keys = self.held.remove(&from).unwrap_or_else(|| self.lookup(from));
+                for &key in keys.iter().rev() {
+                    emit(output, key, 0);
+                }
+            }
+            1 => {
+                let keys = self.lookup(from);
+                for &key in &keys {
+                    emit(output, key, 1);
+                }
+                self.held.insert(from, keys);
+            }
+            value => {
+                let last = match self.held.get(&from) {
+                    Some(keys) => keys.last().copied(),
+                    None => self.lookup(from).last().copied(),
+                };
+                if let Some(key) = last {
+                    emit(output, key, value);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, value: i32) -> InputEvent {
+        InputEvent::new(EventType::KEY, code.0, value)
+    }
+
+    fn run(remap: &mut KeyRemap, events: &[InputEvent]) -> Vec<InputEvent> {
+        remap.transform_events(events.iter().copied()).collect()
+    }
+
+    #[test]
+    fn remaps_sequences() {
+        let mut remap = KeyRemap::new();
+        remap.insert_sequence(KeyCode::KEY_F1, &[KeyCode::KEY_LEFTCTRL, KeyCode::KEY_C]);
+
+        let out = run(
+            &mut remap,
+            &[
+                key(KeyCode::KEY_F1, 1),
+                key(KeyCode::KEY_F1, 2),
+                key(KeyCode::KEY_F1, 0),
+            ],
+        );
+        assert_eq!(
+            out,
+            [
+                key(KeyCode::KEY_LEFTCTRL, 1),
+                key(KeyCode::KEY_C, 1),
+                key(KeyCode::KEY_C, 2),
+                key(KeyCode::KEY_C, 0),
+                key(KeyCode::KEY_LEFTCTRL, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn held_keys_survive_map_changes() {
+        let mut remap: KeyRemap = [(KeyCode::KEY_A, KeyCode::KEY_B)].into_iter().collect();
+
+        let out = run(&mut remap, &[key(KeyCode::KEY_A, 1)]);
+        assert_eq!(out, [key(KeyCode::KEY_B, 1)]);
+
+        remap.insert(KeyCode::KEY_A, KeyCode::KEY_C);
+        let out = run(
+            &mut remap,
+            &[key(KeyCode::KEY_A, 0), key(KeyCode::KEY_A, 1)],
+        );
+        assert_eq!(out, [key(KeyCode::KEY_B, 0), key(KeyCode::KEY_C, 1)]);
+    }
+}
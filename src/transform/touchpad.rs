@@ -0,0 +1,348 @@
+use crate::compat::input_event;
+use crate::transform::EventTransform;
+use crate::{
+    AbsInfo, AbsoluteAxisCode, Device, EventSummary, EventType, InputEvent, KeyCode,
+    RelativeAxisCode, SynchronizationCode,
+};
+use std::io;
+use std::time::{Duration, SystemTime};
+
+/// The width assumed for touchpads that don't report a resolution.
+const FALLBACK_WIDTH_MM: f64 = 100.0;
+
+/// Settings of a [`TouchpadPointer`].
+///
+/// Distances are in millimeters on the touchpad, and speeds in millimeters per millisecond.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointerConfig {
+    /// Pointer units per millimeter of finger movement, before acceleration.
+    pub speed: f64,
+    /// How much faster the pointer gets with the speed of the finger: the base speed is
+    /// multiplied by `1 + acceleration * finger_speed`.
+    pub acceleration: f64,
+    /// The largest factor acceleration multiplies the base speed with.
+    pub max_acceleration: f64,
+    /// Touches starting this close to the edge of the touchpad are ignored, to reject palms
+    /// and accidental touches while typing.
+    pub edge_margin: f64,
+    /// Whether a short touch without movement clicks the left button.
+    pub tap_to_click: bool,
+    /// The longest a touch may last to count as a tap.
+    pub tap_max_duration: Duration,
+    /// The farthest a finger may move during a tap.
+    pub tap_max_distance: f64,
+}
+
+impl Default for PointerConfig {
+    fn default() -> Self {
+        Self {
+            speed: 4.0,
+            acceleration: 4.0,
+            max_acceleration: 3.0,
+            edge_margin: 0.0,
+            tap_to_click: true,
+            tap_max_duration: Duration::from_millis(180),
+            tap_max_distance: 2.0,
+        }
+    }
+}
+
+/// An [`EventTransform`] turning the events of a touchpad into those of a mouse, for using a
+/// touchpad as a pointer without libinput.
+///
+/// The position of the first finger, as reported by `ABS_X` and `ABS_Y`, is turned into
+/// `REL_X` and `REL_Y` motion with acceleration. Lifting the finger and putting it down
+/// elsewhere doesn't move the pointer. The other absolute axes and the `BTN_TOUCH` and
+/// `BTN_TOOL_*` keys are dropped, while the buttons of clickpads pass through, so the output
+/// can be emitted by a virtual mouse supporting `REL_X`, `REL_Y` and `BTN_LEFT`.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// use evdev::transform::{EventTransform, PointerConfig, TouchpadPointer};
+/// let mut touchpad = evdev::Device::open("/dev/input/event0")?;
+/// let mut pointer = TouchpadPointer::from_device(&touchpad, PointerConfig::default())?;
+/// touchpad.grab()?;
+/// loop {
+///     for ev in (&mut pointer).transform_events(touchpad.fetch_events()?) {
+///         println!("{ev:?}");
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TouchpadPointer {
+    config: PointerConfig,
+    x: AbsInfo,
+    y: AbsInfo,
+    units_per_mm: (f64, f64),
+    position: (i32, i32),
+    last: Option<(i32, i32)>,
+    last_time: Option<SystemTime>,
+    remainder: (f64, f64),
+    touch: Touch,
+    frame_has_output: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Touch {
+    down: bool,
+    started: bool,
+    ended: bool,
+    ignored: bool,
+    start: Option<SystemTime>,
+    distance: f64,
+}
+
+impl TouchpadPointer {
+    /// Create a transform for a touchpad whose `ABS_X` and `ABS_Y` axes are described by `x`
+    /// and `y`.
+    pub fn new(x: AbsInfo, y: AbsInfo, config: PointerConfig) -> Self {
+        let units_per_mm = |info: &AbsInfo| match info.resolution() {
+            res if res > 0 => f64::from(res),
+            _ => {
+                (f64::from(info.maximum()) - f64::from(info.minimum())).max(1.0) / FALLBACK_WIDTH_MM
+            }
+        };
+        Self {
+            config,
+            units_per_mm: (units_per_mm(&x), units_per_mm(&y)),
+            position: (x.value(), y.value()),
+            x,
+            y,
+            last: None,
+            last_time: None,
+            remainder: (0.0, 0.0),
+            touch: Touch::default(),
+            frame_has_output: false,
+        }
+    }
+
+    /// Create a transform for a touchpad, reading the ranges of its axes.
+    pub fn from_device(device: &Device, config: PointerConfig) -> io::Result<Self> {
+        let mut x = None;
+        let mut y = None;
+        for (axis, info) in device.get_absinfo()? {
+            match axis {
+                AbsoluteAxisCode::ABS_X => x = Some(info),
+                AbsoluteAxisCode::ABS_Y => y = Some(info),
+                _ => {}
+            }
+        }
+        match (x, y) {
+            (Some(x), Some(y)) => Ok(Self::new(x, y, config)),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the device has no ABS_X and ABS_Y axes",
+            )),
+        }
+    }
+
+    /// Returns the settings of the transform.
+    pub fn config(&self) -> &PointerConfig {
+        &self.config
+    }
+
+    /// Returns the settings of the transform for modification.
+    pub fn config_mut(&mut self) -> &mut PointerConfig {
+        &mut self.config
+    }
+
+    fn in_edge(&self, (x, y): (i32, i32)) -> bool {
+        let margin = self.config.edge_margin;
+        let near = |v: i32, info: &AbsInfo, per_mm: f64| {
+            f64::from(v - info.minimum()) / per_mm < margin
+                || f64::from(info.maximum() - v) / per_mm < margin
+        };
+        margin > 0.0
+            && (near(x, &self.x, self.units_per_mm.0) || near(y, &self.y, self.units_per_mm.1))
+    }
+
+    fn motion(&mut self, time: SystemTime, template: &input_event, output: &mut Vec<InputEvent>) {
+        let last = match self.last {
+            Some(last) => last,
+            None => return,
+        };
+        let dx = f64::from(self.position.0 - last.0) / self.units_per_mm.0;
+        let dy = f64::from(self.position.1 - last.1) / self.units_per_mm.1;
+        let distance = dx.hypot(dy);
+        self.touch.distance += distance;
+
+        let elapsed = self
+            .last_time
+            .and_then(|last| time.duration_since(last).ok())
+            .unwrap_or_default();
+        let millis = (elapsed.as_secs_f64() * 1000.0).max(1.0);
+        let factor = (1.0 + self.config.acceleration * distance / millis)
+            .min(self.config.max_acceleration.max(1.0));
+        let gain = self.config.speed * factor;
+
+        let x = dx * gain + self.remainder.0;
+        let y = dy * gain + self.remainder.1;
+        self.remainder = (x.fract(), y.fract());
+        for (axis, value) in [(RelativeAxisCode::REL_X, x), (RelativeAxisCode::REL_Y, y)] {
+            let value = value.trunc() as i32;
+            if value != 0 {
+                output.push(event(template, EventType::RELATIVE, axis.0, value));
+                self.frame_has_output = true;
+            }
+        }
+    }
+
+    fn end_touch(
+        &mut self,
+        time: SystemTime,
+        template: &input_event,
+        output: &mut Vec<InputEvent>,
+    ) {
+        let touch = self.touch;
+        let duration = touch
+            .start
+            .and_then(|start| time.duration_since(start).ok())
+            .unwrap_or_default();
+        if self.config.tap_to_click
+            && !touch.ignored
+            && duration <= self.config.tap_max_duration
+            && touch.distance <= self.config.tap_max_distance
+        {
+            let (key, syn) = (EventType::KEY, EventType::SYNCHRONIZATION);
+            output.push(event(template, key, KeyCode::BTN_LEFT.0, 1));
+            output.push(event(template, syn, SynchronizationCode::SYN_REPORT.0, 0));
+            output.push(event(template, key, KeyCode::BTN_LEFT.0, 0));
+            self.frame_has_output = true;
+        }
+        self.last = None;
+        self.remainder = (0.0, 0.0);
+        self.touch = Touch::default();
+    }
+}
+
+fn event(template: &input_event, type_: EventType, code: u16, value: i32) -> InputEvent {
+    InputEvent::from(input_event {
+        type_: type_.0,
+        code,
+        value,
+        ..*template
+    })
+}
+
+impl EventTransform for TouchpadPointer {
+    fn transform(&mut self, ev: InputEvent, output: &mut Vec<InputEvent>) {
+        match ev.destructure() {
+            EventSummary::AbsoluteAxis(_, AbsoluteAxisCode::ABS_X, value) => {
+                self.position.0 = value
+            }
+            EventSummary::AbsoluteAxis(_, AbsoluteAxisCode::ABS_Y, value) => {
+                self.position.1 = value
+            }
+            EventSummary::AbsoluteAxis(..) => {}
+            EventSummary::Key(_, KeyCode::BTN_TOUCH, value) => {
+                if value != 0 && !self.touch.down {
+                    self.touch.down = true;
+                    self.touch.started = true;
+                } else if value == 0 && self.touch.down {
+                    self.touch.ended = true;
+                }
+            }
+            EventSummary::Key(_, key, _) if key.is_digitizer_button() => {}
+            EventSummary::Synchronization(_, SynchronizationCode::SYN_REPORT, _) => {
+                let time = ev.timestamp();
+                let template = ev.as_ref();
+                if self.touch.started {
+                    self.touch.started = false;
+                    self.touch.ignored = self.in_edge(self.position);
+                    self.touch.start = Some(time);
+                } else if self.touch.down && !self.touch.ignored {
+                    self.motion(time, template, output);
+                }
+                self.last = Some(self.position);
+                if self.touch.ended {
+                    self.end_touch(time, template, output);
+                }
+                self.last_time = Some(time);
+                if std::mem::take(&mut self.frame_has_output) {
+                    output.push(ev);
+                }
+            }
+            _ => {
+                self.frame_has_output = true;
+                output.push(ev);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbsoluteAxisEvent, KeyEvent, SynchronizationEvent};
+
+    fn frame(events: &[InputEvent], ms: u64) -> Vec<InputEvent> {
+        let mut frame = events.to_vec();
+        frame.push(*SynchronizationEvent::new(
+            SynchronizationCode::SYN_REPORT,
+            0,
+        ));
+        frame
+            .into_iter()
+            .map(|ev| crate::event_at(ms, ev))
+            .collect()
+    }
+
+    fn pointer(config: PointerConfig) -> TouchpadPointer {
+        // 100 x 50 mm with 10 units per mm
+        let x = AbsInfo::new(0, 0, 1000, 0, 0, 10);
+        let y = AbsInfo::new(0, 0, 500, 0, 0, 10);
+        TouchpadPointer::new(x, y, config)
+    }
+
+    #[test]
+    fn motion_without_jumps() {
+        let config = PointerConfig {
+            speed: 1.0,
+            acceleration: 0.0,
+            tap_to_click: false,
+            ..PointerConfig::default()
+        };
+        let mut pointer = pointer(config);
+        let x = |v| *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_X, v);
+        let touch = |v| *KeyEvent::new(KeyCode::BTN_TOUCH, v);
+        let mut events = frame(&[touch(1), x(100)], 0);
+        events.extend(frame(&[x(150)], 10));
+        events.extend(frame(&[touch(0)], 20));
+        events.extend(frame(&[touch(1), x(900)], 30));
+        let out: Vec<_> = (&mut pointer)
+            .transform_events(events)
+            .map(|ev| (ev.event_type(), ev.code(), ev.value()))
+            .collect();
+        assert_eq!(
+            out,
+            [
+                (EventType::RELATIVE, RelativeAxisCode::REL_X.0, 5),
+                (EventType::SYNCHRONIZATION, 0, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn tap_and_edge() {
+        let config = PointerConfig {
+            edge_margin: 5.0,
+            ..PointerConfig::default()
+        };
+        let mut pointer = pointer(config);
+        let x = |v| *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_X, v);
+        let y = *AbsoluteAxisEvent::new(AbsoluteAxisCode::ABS_Y, 250);
+        let touch = |v| *KeyEvent::new(KeyCode::BTN_TOUCH, v);
+        let mut events = frame(&[touch(1), x(500), y], 0);
+        events.extend(frame(&[touch(0)], 50));
+        // a tap on the edge is ignored
+        events.extend(frame(&[touch(1), x(10)], 500));
+        events.extend(frame(&[touch(0)], 550));
+        let out: Vec<_> = (&mut pointer)
+            .transform_events(events)
+            .filter(|ev| ev.event_type() == EventType::KEY)
+            .map(|ev| ev.value())
+            .collect();
+        assert_eq!(out, [1, 0]);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::compat::input_event;
+use crate::transform::EventTransform;
+use crate::{
+    systime_to_timeval, AutoRepeat, EventSummary, EventType, InputEvent, KeyCode,
+    SynchronizationCode,
+};
+use std::time::{Duration, SystemTime};
+
+/// An [`EventTransform`] that generates key repeats in userspace.
+///
+/// While a device is grabbed, the repeats the kernel generates are only delivered to the
+/// grabbing process, and they can't be forwarded through a virtual device as they are: the
+/// kernel would generate its own repeats for the virtual device on top of them. `Typematic`
+/// replaces the kernel's repeats with its own, following the same rules: only the key pressed
+/// last repeats, starting after `delay` and then every `period`, until it is released. Buttons
+/// (`BTN_*`) don't repeat.
+///
+/// Each repeat is followed by a `SYN_REPORT`. As with [`Debounce`](super::Debounce), time is
+/// measured using the event timestamps and repeats are emitted when the next event arrives. Since
+/// a held key usually produces no further events, call [`Typematic::flush_until`] whenever
+/// [`Typematic::next_deadline`] has passed, e.g. by waiting for events with
+/// [`Device::fetch_events_timeout`].
+///
+/// ```
+/// use evdev::transform::{EventTransform, Typematic};
+/// use evdev::{KeyCode, KeyEvent};
+/// use std::time::Duration;
+///
+/// let mut typematic = Typematic::new(Duration::from_millis(250), Duration::from_millis(33));
+/// let mut out = Vec::new();
+/// typematic.transform(*KeyEvent::new(KeyCode::KEY_A, 1), &mut out);
+/// let deadline = typematic.next_deadline().unwrap();
+/// typematic.flush_until(deadline, &mut out);
+/// assert_eq!(out[1].value(), 2);
+/// ```
+///
+/// [`Device::fetch_events_timeout`]: crate::Device::fetch_events_timeout
+#[derive(Debug, Clone)]
+pub struct Typematic {
+    delay: Duration,
+    period: Duration,
+    held: Option<(KeyCode, SystemTime)>,
+}
+
+impl Typematic {
+    /// Create a transform repeating keys held for `delay` every `period`. A `period` of zero
+    /// disables repeating, leaving only the removal of the kernel's repeats.
+    pub fn new(delay: Duration, period: Duration) -> Self {
+        Self {
+            delay,
+            period,
+            held: None,
+        }
+    }
+
+    /// Create a transform using the repeat settings of a device, see [`Device::get_auto_repeat`].
+    ///
+    /// [`Device::get_auto_repeat`]: crate::Device::get_auto_repeat
+    pub fn from_auto_repeat(repeat: &AutoRepeat) -> Self {
+        Self::new(
+            Duration::from_millis(repeat.delay.into()),
+            Duration::from_millis(repeat.period.into()),
+        )
+    }
+
+    /// Returns when the next repeat is due, if a key is held.
+    pub fn next_deadline(&self) -> Option<SystemTime> {
+        self.held.map(|(_, at)| at)
+    }
+
+    /// Emit every repeat that is due by `now`, each followed by a `SYN_REPORT`.
+    pub fn flush_until(&mut self, now: SystemTime, output: &mut Vec<InputEvent>) {
+        while let Some((key, at)) = self.held {
+            if at > now {
+                break;
+            }
+            let time = systime_to_timeval(&at);
+            output.push(InputEvent::from(input_event {
+                time,
+                type_: EventType::KEY.0,
+                code: key.0,
+                value: 2,
+            }));
+            output.push(InputEvent::from(input_event {
+                time,
+                type_: EventType::SYNCHRONIZATION.0,
+                code: SynchronizationCode::SYN_REPORT.0,
+                value: 0,
+            }));
+            self.held = Some((key, at + self.period));
+        }
+    }
+}
+
+impl EventTransform for Typematic {
+    fn transform(&mut self, event: InputEvent, output: &mut Vec<InputEvent>) {
+        self.flush_until(event.timestamp(), output);
+        match event.destructure() {
+            EventSummary::Key(_, _, 2) => return,
+            EventSummary::Key(_, key, 1) if !key.is_button() && !self.period.is_zero() => {
+                self.held = Some((key, event.timestamp() + self.delay));
+            }
+            EventSummary::Key(_, key, 0) if self.held.map(|(k, _)| k) == Some(key) => {
+                self.held = None;
+            }
+            _ => {}
+        }
+        output.push(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_at(ms: u64, key: KeyCode, value: i32) -> InputEvent {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1000) + Duration::from_millis(ms);
+        InputEvent::from(input_event {
+            time: systime_to_timeval(&time),
+            type_: EventType::KEY.0,
+            code: key.0,
+            value,
+        })
+    }
+
+    #[test]
+    fn repeats_last_key() {
+        let (a, b) = (KeyCode::KEY_A, KeyCode::KEY_B);
+        let typematic = Typematic::new(Duration::from_millis(100), Duration::from_millis(20));
+        let events = [
+            key_at(0, a, 1),
+            key_at(50, b, 1),
+            key_at(150, a, 2),
+            key_at(175, a, 0),
+            key_at(200, b, 0),
+            key_at(300, a, 1),
+            key_at(310, a, 0),
+            key_at(500, KeyCode::BTN_LEFT, 1),
+            key_at(700, KeyCode::BTN_LEFT, 0),
+        ];
+        let keys: Vec<_> = typematic
+            .transform_events(events)
+            .filter(|ev| ev.event_type() == EventType::KEY)
+            .map(|ev| (KeyCode(ev.code()), ev.value(), ev.timestamp()))
+            .collect();
+        let t = |ms| key_at(ms, a, 0).timestamp();
+        assert_eq!(
+            keys,
+            [
+                (a, 1, t(0)),
+                (b, 1, t(50)),
+                (b, 2, t(150)),
+                (b, 2, t(170)),
+                (a, 0, t(175)),
+                (b, 2, t(190)),
+                (b, 0, t(200)),
+                (a, 1, t(300)),
+                (a, 0, t(310)),
+                (KeyCode::BTN_LEFT, 1, t(500)),
+                (KeyCode::BTN_LEFT, 0, t(700)),
+            ]
+        );
+    }
+}
@@ -5,9 +5,11 @@
 use crate::compat::{input_event, input_id, uinput_abs_setup, uinput_setup, UINPUT_MAX_NAME_SIZE};
 use crate::ff::FFEffectData;
 use crate::inputid::{BusType, InputId};
+use crate::raw_stream::RawDevice;
 use crate::{
-    sys, AttributeSetRef, FFEffectCode, InputEvent, KeyCode, MiscCode, PropType, RelativeAxisCode,
-    SwitchCode, SynchronizationEvent, UInputCode, UInputEvent, UinputAbsSetup,
+    sys, AttributeSetRef, CapabilitiesReport, FFEffectCode, InputEvent, KeyCode, MiscCode,
+    PropType, RelativeAxisCode, SwitchCode, SynchronizationEvent, UInputCode, UInputEvent,
+    UinputAbsSetup,
 };
 use std::ffi::{CStr, OsStr};
 use std::os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd};
@@ -35,6 +37,7 @@ impl<'a> VirtualDeviceBuilder<'a> {
     #[deprecated(note = "use `VirtualDevice::builder()` instead")]
     #[doc(hidden)]
     pub fn new() -> io::Result<Self> {
+        crate::check_supported()?;
         // Open in read-write mode.
         let fd = fs::OpenOptions::new()
             .read(true)
@@ -271,8 +274,41 @@ impl VirtualDevice {
         })
     }
 
+    /// Use an already created virtual device, given its uinput file descriptor.
+    ///
+    /// This allows using a virtual device created by a privileged helper process and received
+    /// over a Unix socket. Fails with [`InvalidInput`](io::ErrorKind::InvalidInput) if `fd` isn't
+    /// a uinput file descriptor or its device wasn't created yet.
+    pub fn from_fd(fd: OwnedFd) -> io::Result<Self> {
+        crate::check_supported()?;
+        let mut version = 0;
+        match unsafe { sys::ui_get_version(fd.as_raw_fd(), &mut version) } {
+            Err(nix::Error::ENOTTY | nix::Error::EINVAL) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "not a uinput file descriptor",
+                ))
+            }
+            res => res?,
+        };
+        let mut sysname = [0u8; 64];
+        match unsafe { sys::ui_get_sysname(fd.as_raw_fd(), &mut sysname) } {
+            Err(nix::Error::ENOENT) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "the uinput device was not created",
+                ))
+            }
+            res => res?,
+        };
+        Ok(VirtualDevice {
+            fd,
+            event_buf: vec![],
+        })
+    }
+
     #[inline]
-    fn write_raw(&mut self, events: &[InputEvent]) -> io::Result<()> {
+    pub(crate) fn write_raw(&mut self, events: &[InputEvent]) -> io::Result<()> {
         crate::write_events(self.fd.as_fd(), events)?;
         Ok(())
     }
@@ -282,6 +318,10 @@ impl VirtualDevice {
     /// The syspath returned is the one of the input node itself (e.g.
     /// `/sys/devices/virtual/input/input123`), not the syspath of the device node.
     pub fn get_syspath(&mut self) -> io::Result<PathBuf> {
+        self.syspath()
+    }
+
+    fn syspath(&self) -> io::Result<PathBuf> {
         let mut syspath = vec![0u8; 256];
         let len = unsafe { sys::ui_get_sysname(self.fd.as_raw_fd(), &mut syspath)? };
         syspath.truncate(len as usize - 1);
@@ -291,6 +331,19 @@ impl VirtualDevice {
         Ok(Path::new(SYSFS_PATH).join(syspath))
     }
 
+    /// Returns a report of everything the virtual device supports, as seen by its readers.
+    ///
+    /// The report is read from the event node of the device, which requires permission to open
+    /// it, and fails if udev didn't create the node yet.
+    pub fn capabilities_report(&self) -> io::Result<CapabilitiesReport> {
+        let node = DevNodesBlocking {
+            dir: fs::read_dir(self.syspath()?)?,
+        }
+        .next()
+        .unwrap_or_else(|| Err(io::Error::from(io::ErrorKind::NotFound)))?;
+        RawDevice::open(node)?.capabilities_report()
+    }
+
     /// Get the syspaths of the corresponding device nodes in /dev/input.
     pub fn enumerate_dev_nodes_blocking(&mut self) -> io::Result<DevNodesBlocking> {
         let path = self.get_syspath()?;
@@ -315,10 +368,15 @@ impl VirtualDevice {
     /// of a mouse triggers a movement events for the X and Y axes separately in a batch of 2 events.
     ///
     /// Single events such as a `KEY` event must still be followed by a `SYN_REPORT`.
+    ///
+    /// The batch and its `SYN_REPORT` are written with a single system call. If the kernel only
+    /// accepts part of them, an error is returned instead of writing the remainder separately.
+    ///
+    /// The timestamps of the events are replaced with the time they were written at. To replay
+    /// recorded events with their original timing, use an [`EventPacer`](crate::EventPacer).
     pub fn emit(&mut self, events: &[InputEvent]) -> io::Result<()> {
-        self.write_raw(events)?;
         let syn = *SynchronizationEvent::new(crate::SynchronizationCode::SYN_REPORT, 0);
-        self.write_raw(&[syn])
+        crate::write_events_vectored(self.fd.as_fd(), &[events, &[syn]])
     }
 
     /// Processes the given [`UInputEvent`] if it is a force feedback upload event, in which case
@@ -485,6 +543,23 @@ impl AsRawFd for VirtualDevice {
     }
 }
 
+/// Takes over a virtual device created through the uinput file descriptor `fd`, e.g. by another
+/// process that passed the file descriptor on. See [`VirtualDevice::from_fd`].
+impl TryFrom<OwnedFd> for VirtualDevice {
+    type Error = io::Error;
+
+    fn try_from(fd: OwnedFd) -> Result<Self, Self::Error> {
+        Self::from_fd(fd)
+    }
+}
+
+/// Returns the uinput file descriptor. The virtual device is destroyed once it is closed.
+impl From<VirtualDevice> for OwnedFd {
+    fn from(device: VirtualDevice) -> Self {
+        device.fd
+    }
+}
+
 /// Represents a force feedback upload event that we are currently processing.
 pub struct FFUploadEvent {
     fd: OwnedFd,
@@ -0,0 +1,338 @@
+//! Translation of key events into keysyms and text with xkbcommon.
+//!
+//! Key events only say which physical key was pressed. What that key means depends on the
+//! keyboard layout and on the modifiers and lock keys that are active. This module uses
+//! [libxkbcommon](https://xkbcommon.org/), the library used by Wayland compositors and most
+//! toolkits, to turn a stream of [`KeyEvent`]s into keysyms and UTF-8 text, which is enough to
+//! build a minimal input stack for a kiosk or a terminal without a display server.
+//!
+//! This module is only available with the `xkb` feature, which links against the system's
+//! `libxkbcommon`.
+//!
+//! ```no_run
+//! use evdev::xkb::{Keymap, RuleNames, Translator};
+//!
+//! let keymap = Keymap::new(&RuleNames {
+//!     layout: Some("de"),
+//!     ..RuleNames::default()
+//! })?;
+//! let mut translator = Translator::new(&keymap)?;
+//! let mut device = evdev::Device::open("/dev/input/event0")?;
+//! loop {
+//!     for key in translator.translate(device.fetch_events()?) {
+//!         print!("{}", key.utf8);
+//!     }
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! [`KeyEvent`]: crate::KeyEvent
+
+use crate::{EventSummary, InputEvent, KeyCode};
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::ptr::NonNull;
+use std::{fmt, io};
+
+#[allow(non_camel_case_types)]
+mod ffi {
+    use std::os::raw::{c_char, c_int, c_void};
+
+    pub type xkb_keycode_t = u32;
+    pub type xkb_keysym_t = u32;
+
+    #[repr(C)]
+    pub struct xkb_rule_names {
+        pub rules: *const c_char,
+        pub model: *const c_char,
+        pub layout: *const c_char,
+        pub variant: *const c_char,
+        pub options: *const c_char,
+    }
+
+    pub const XKB_KEY_UP: c_int = 0;
+    pub const XKB_KEY_DOWN: c_int = 1;
+    pub const XKB_KEYMAP_FORMAT_TEXT_V1: c_int = 1;
+
+    #[link(name = "xkbcommon")]
+    extern "C" {
+        pub fn xkb_context_new(flags: c_int) -> *mut c_void;
+        pub fn xkb_context_unref(context: *mut c_void);
+        pub fn xkb_keymap_new_from_names(
+            context: *mut c_void,
+            names: *const xkb_rule_names,
+            flags: c_int,
+        ) -> *mut c_void;
+        pub fn xkb_keymap_new_from_string(
+            context: *mut c_void,
+            string: *const c_char,
+            format: c_int,
+            flags: c_int,
+        ) -> *mut c_void;
+        pub fn xkb_keymap_unref(keymap: *mut c_void);
+        pub fn xkb_state_new(keymap: *mut c_void) -> *mut c_void;
+        pub fn xkb_state_unref(state: *mut c_void);
+        pub fn xkb_state_update_key(
+            state: *mut c_void,
+            key: xkb_keycode_t,
+            direction: c_int,
+        ) -> c_int;
+        pub fn xkb_state_key_get_one_sym(state: *mut c_void, key: xkb_keycode_t) -> xkb_keysym_t;
+        pub fn xkb_state_key_get_utf8(
+            state: *mut c_void,
+            key: xkb_keycode_t,
+            buffer: *mut c_char,
+            size: usize,
+        ) -> c_int;
+        pub fn xkb_keysym_get_name(keysym: xkb_keysym_t, buffer: *mut c_char, size: usize)
+            -> c_int;
+    }
+}
+
+/// xkbcommon's keycodes are the evdev key codes offset by 8, for historical X11 reasons.
+const EVDEV_OFFSET: u32 = 8;
+
+/// The names selecting a keymap from the system's XKB rules, as in `setxkbmap` or the
+/// `XKB_DEFAULT_*` environment variables.
+///
+/// Fields that are `None` use the defaults of xkbcommon, which can be overridden with the
+/// environment variables.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RuleNames<'a> {
+    pub rules: Option<&'a str>,
+    pub model: Option<&'a str>,
+    pub layout: Option<&'a str>,
+    pub variant: Option<&'a str>,
+    pub options: Option<&'a str>,
+}
+
+/// A compiled XKB keymap.
+pub struct Keymap {
+    context: NonNull<c_void>,
+    keymap: NonNull<c_void>,
+}
+
+fn new_context() -> io::Result<NonNull<c_void>> {
+    NonNull::new(unsafe { ffi::xkb_context_new(0) })
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "failed to create an xkb context"))
+}
+
+fn to_cstring(s: Option<&str>) -> io::Result<Option<CString>> {
+    s.map(|s| CString::new(s).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e)))
+        .transpose()
+}
+
+impl Keymap {
+    /// Compile the keymap selected by `names` from the system's XKB rules.
+    pub fn new(names: &RuleNames<'_>) -> io::Result<Self> {
+        let strings = [
+            to_cstring(names.rules)?,
+            to_cstring(names.model)?,
+            to_cstring(names.layout)?,
+            to_cstring(names.variant)?,
+            to_cstring(names.options)?,
+        ];
+        let ptr = |s: &Option<CString>| s.as_ref().map_or(std::ptr::null(), |s| s.as_ptr());
+        let raw = ffi::xkb_rule_names {
+            rules: ptr(&strings[0]),
+            model: ptr(&strings[1]),
+            layout: ptr(&strings[2]),
+            variant: ptr(&strings[3]),
+            options: ptr(&strings[4]),
+        };
+        let context = new_context()?;
+        let keymap = unsafe { ffi::xkb_keymap_new_from_names(context.as_ptr(), &raw, 0) };
+        Self::finish(context, keymap, "no keymap matches the rule names")
+    }
+
+    /// Compile a keymap from its text form, e.g. as written by `xkbcomp` or sent by a Wayland
+    /// compositor.
+    pub fn from_string(keymap: &str) -> io::Result<Self> {
+        let keymap =
+            CString::new(keymap).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let context = new_context()?;
+        let raw = unsafe {
+            ffi::xkb_keymap_new_from_string(
+                context.as_ptr(),
+                keymap.as_ptr(),
+                ffi::XKB_KEYMAP_FORMAT_TEXT_V1,
+                0,
+            )
+        };
+        Self::finish(context, raw, "failed to compile the keymap")
+    }
+
+    fn finish(context: NonNull<c_void>, keymap: *mut c_void, msg: &str) -> io::Result<Self> {
+        match NonNull::new(keymap) {
+            Some(keymap) => Ok(Self { context, keymap }),
+            None => {
+                unsafe { ffi::xkb_context_unref(context.as_ptr()) };
+                Err(io::Error::new(io::ErrorKind::InvalidData, msg))
+            }
+        }
+    }
+}
+
+impl Drop for Keymap {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::xkb_keymap_unref(self.keymap.as_ptr());
+            ffi::xkb_context_unref(self.context.as_ptr());
+        }
+    }
+}
+
+impl fmt::Debug for Keymap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Keymap").finish_non_exhaustive()
+    }
+}
+
+/// An XKB keysym, the symbol a key produces under a keymap, such as `a`, `A` or `Return`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Keysym(pub u32);
+
+impl Keysym {
+    /// The keysym of keys that don't produce a symbol.
+    pub const NO_SYMBOL: Self = Self(0);
+
+    /// Returns the name of the keysym, as in `xkbcommon-keysyms.h` without the `XKB_KEY_` prefix.
+    pub fn name(self) -> String {
+        let mut buf = [0 as c_char; 64];
+        let len = unsafe { ffi::xkb_keysym_get_name(self.0, buf.as_mut_ptr(), buf.len()) };
+        if len < 0 {
+            return format!("{:#x}", self.0);
+        }
+        unsafe { CStr::from_ptr(buf.as_ptr()) }
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+impl fmt::Debug for Keysym {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Keysym({})", self.name())
+    }
+}
+
+impl fmt::Display for Keysym {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name())
+    }
+}
+
+/// A key event translated by a [`Translator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranslatedKey {
+    pub key: KeyCode,
+    /// `0` for a release, `1` for a press and `2` for an autorepeat.
+    pub value: i32,
+    /// The keysym the key produced, taking the active modifiers into account.
+    pub keysym: Keysym,
+    /// The text the key produced, empty for releases and for keys that don't produce text.
+    pub utf8: String,
+}
+
+/// Tracks the modifiers and lock keys of a keyboard and translates its key events.
+pub struct Translator {
+    state: NonNull<c_void>,
+}
+
+impl Translator {
+    /// Create a translator for a keyboard using `keymap`, with no modifiers active.
+    pub fn new(keymap: &Keymap) -> io::Result<Self> {
+        // the state holds its own reference to the keymap
+        let state = unsafe { ffi::xkb_state_new(keymap.keymap.as_ptr()) };
+        NonNull::new(state)
+            .map(|state| Self { state })
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "failed to create an xkb state"))
+    }
+
+    /// Update the translator with an event.
+    ///
+    /// Returns the translation if `event` is a key event.
+    pub fn process(&mut self, event: &InputEvent) -> Option<TranslatedKey> {
+        let (key, value) = match event.destructure() {
+            EventSummary::Key(_, key, value) => (key, value),
+            _ => return None,
+        };
+        let code = u32::from(key.code()) + EVDEV_OFFSET;
+        let state = self.state.as_ptr();
+        let keysym = Keysym(unsafe { ffi::xkb_state_key_get_one_sym(state, code) });
+        let utf8 = if value == 0 {
+            String::new()
+        } else {
+            self.utf8(code)
+        };
+        match value {
+            0 => unsafe { ffi::xkb_state_update_key(state, code, ffi::XKB_KEY_UP) },
+            1 => unsafe { ffi::xkb_state_update_key(state, code, ffi::XKB_KEY_DOWN) },
+            _ => 0,
+        };
+        Some(TranslatedKey {
+            key,
+            value,
+            keysym,
+            utf8,
+        })
+    }
+
+    fn utf8(&self, code: u32) -> String {
+        let state = self.state.as_ptr();
+        let len = unsafe { ffi::xkb_state_key_get_utf8(state, code, std::ptr::null_mut(), 0) };
+        if len <= 0 {
+            return String::new();
+        }
+        let mut buf = vec![0u8; len as usize + 1];
+        unsafe { ffi::xkb_state_key_get_utf8(state, code, buf.as_mut_ptr().cast(), buf.len()) };
+        buf.truncate(len as usize);
+        String::from_utf8(buf).unwrap_or_default()
+    }
+
+    /// Apply the translator to an iterator of events, yielding the translation of every key
+    /// event.
+    pub fn translate<'a, I>(&'a mut self, events: I) -> impl Iterator<Item = TranslatedKey> + 'a
+    where
+        I: IntoIterator<Item = InputEvent>,
+        I::IntoIter: 'a,
+    {
+        events.into_iter().filter_map(move |ev| self.process(&ev))
+    }
+}
+
+impl Drop for Translator {
+    fn drop(&mut self) {
+        unsafe { ffi::xkb_state_unref(self.state.as_ptr()) };
+    }
+}
+
+impl fmt::Debug for Translator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Translator").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyEvent;
+
+    #[test]
+    fn shifted_letters() {
+        let keymap = Keymap::new(&RuleNames {
+            layout: Some("us"),
+            ..RuleNames::default()
+        })
+        .unwrap();
+        let mut translator = Translator::new(&keymap).unwrap();
+        let events = [
+            *KeyEvent::new(KeyCode::KEY_A, 1),
+            *KeyEvent::new(KeyCode::KEY_A, 0),
+            *KeyEvent::new(KeyCode::KEY_LEFTSHIFT, 1),
+            *KeyEvent::new(KeyCode::KEY_A, 1),
+        ];
+        let text: String = translator.translate(events).map(|k| k.utf8).collect();
+        assert_eq!(text, "aA");
+        assert_eq!(Keysym(0xff0d).name(), "Return");
+    }
+}
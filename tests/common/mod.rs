@@ -11,7 +11,7 @@ pub fn key_click(key: KeyCode) -> Vec<InputEvent> {
 }
 
 pub fn key_event(key: KeyCode, key_state: i32) -> InputEvent {
-    InputEvent::new(EventType::KEY.0, key.code(), key_state)
+    InputEvent::new(EventType::KEY, key.code(), key_state)
 }
 
 pub fn get_test_device() -> std::io::Result<(Device, VirtualDevice)> {
@@ -41,8 +41,8 @@ pub fn test_compensate_keys() -> std::io::Result<()> {
 pub fn test_compensate_with_key_down() -> std::io::Result<()> {
     let (mut input, mut output) = get_test_device()?;
 
-    output.emit(&[InputEvent::new(EventType::KEY.0, KeyCode::KEY_A.0, 1)])?;
-    output.emit(&[InputEvent::new(EventType::KEY.0, KeyCode::KEY_B.0, 1)])?;
+    output.emit(&[InputEvent::new(EventType::KEY, KeyCode::KEY_A.0, 1)])?;
+    output.emit(&[InputEvent::new(EventType::KEY, KeyCode::KEY_B.0, 1)])?;
 
     // Make overflow.
     for _ in 0..30 {
@@ -71,11 +71,7 @@ pub fn test_compensate_with_switch_down() -> std::io::Result<()> {
 
     let dot_state: i32 = 0;
 
-    output.emit(&[InputEvent::new(
-        EventType::SWITCH.0,
-        SwitchCode::SW_LID.0,
-        1,
-    )])?;
+    output.emit(&[InputEvent::new(EventType::SWITCH, SwitchCode::SW_LID.0, 1)])?;
 
     // Make overflow.
     for _ in 0..30 {
@@ -2,8 +2,8 @@
 
 mod common;
 
-use common::{get_test_device, key_event};
-use evdev::KeyCode;
+use common::{get_device, get_test_device, key_event};
+use evdev::{Device, KeyCode};
 
 #[test]
 pub fn test_get_key_state() -> Result<(), Box<dyn std::error::Error>> {
@@ -23,3 +23,184 @@ pub fn test_get_key_state() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+pub fn test_grab_released_on_drop() -> Result<(), Box<dyn std::error::Error>> {
+    let (path, _output) = get_device()?;
+
+    let mut first = Device::open(&path)?;
+    first.grab()?;
+    drop(first);
+    let mut second = Device::open(&path)?;
+    second.grab()?;
+
+    // a leaked device keeps its grab
+    let _fd = second.leak();
+    let mut third = Device::open(&path)?;
+    assert!(third.grab().is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_refresh_capabilities_keeps_grab() -> Result<(), Box<dyn std::error::Error>> {
+    let (path, _output) = get_device()?;
+
+    let mut device = Device::open(&path)?;
+    let before = device.capabilities_report()?;
+    device.grab()?;
+    device.refresh_capabilities()?;
+    assert!(device.capabilities_report()?.diff(&before).is_empty());
+    let mut other = Device::open(&path)?;
+    assert!(other.grab().is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_clone_keeps_grab() -> Result<(), Box<dyn std::error::Error>> {
+    let (path, _output) = get_device()?;
+
+    let mut device = Device::open(&path)?;
+    device.grab()?;
+    let clone = device.try_clone()?;
+    assert!(!clone.is_grabbed());
+    drop(clone);
+    let mut other = Device::open(&path)?;
+    assert!(other.grab().is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_owned_fd_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let (path, _output) = get_device()?;
+
+    let mut device = Device::open(&path)?;
+    device.grab()?;
+    let fd = std::os::fd::OwnedFd::from(device);
+    let mut other = Device::open(&path)?;
+    assert!(other.grab().is_err());
+    let device = Device::try_from(fd)?;
+    assert_eq!(device.name(), other.name());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_fetch_frames_keeps_split_frame() -> Result<(), Box<dyn std::error::Error>> {
+    let (mut input, mut output) = get_test_device()?;
+    input.set_sync_on_drop(false);
+    // read the frame of three events in two parts
+    input.set_read_buffer_capacity(2);
+
+    output.emit(&[key_event(KeyCode::KEY_A, 1), key_event(KeyCode::KEY_B, 1)])?;
+    assert_eq!(input.fetch_frames()?.count(), 0);
+    let frames: Vec<_> = input.fetch_frames()?.collect();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].len(), 2);
+
+    Ok(())
+}
+
+/// Answers the force feedback requests of a virtual device, as its driver would.
+#[cfg(feature = "test-util")]
+fn serve_ff(mut device: evdev::uinput::VirtualDevice) -> std::io::Result<()> {
+    use evdev::{EventSummary, UInputCode};
+    loop {
+        let events: Vec<_> = device.fetch_events()?.collect();
+        for event in events {
+            match event.destructure() {
+                EventSummary::UInput(event, UInputCode::UI_FF_UPLOAD, ..) => {
+                    device.process_ff_upload(event)?;
+                }
+                EventSummary::UInput(event, UInputCode::UI_FF_ERASE, ..) => {
+                    device.process_ff_erase(event)?;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+pub fn test_ff_manager_evict_keeps_other_effects() -> Result<(), Box<dyn std::error::Error>> {
+    use evdev::test_util::DevicePair;
+    use evdev::uinput::VirtualDevice;
+    use evdev::{
+        AttributeSet, FFEffectCode, FFEffectData, FFEffectKind, FFManager, FFReplay, FFTrigger,
+    };
+
+    let builder = VirtualDevice::builder()?
+        .name("test ff device")
+        .with_ff(&AttributeSet::from_iter([FFEffectCode::FF_RUMBLE]))?
+        .with_ff_effects_max(2);
+    let (virtual_device, device) = DevicePair::new(builder)?.into_inner();
+    std::thread::spawn(move || serve_ff(virtual_device));
+
+    let rumble = |strong_magnitude| FFEffectData {
+        direction: 0,
+        trigger: FFTrigger::default(),
+        replay: FFReplay {
+            length: 10,
+            delay: 0,
+        },
+        kind: FFEffectKind::Rumble {
+            strong_magnitude,
+            weak_magnitude: 0,
+        },
+    };
+    let mut effects = FFManager::new(&device);
+    effects.play(1, rumble(1), 1)?;
+    effects.play(2, rumble(2), 1)?;
+    // evicts the first effect
+    effects.play(3, rumble(3), 1)?;
+    assert!(!effects.contains(&1));
+    // updating the second effect fails if it was erased along with the first one
+    effects.play(2, rumble(4), 1)?;
+    assert!(effects.remove(&3));
+    effects.play(2, rumble(5), 1)?;
+
+    Ok(())
+}
+
+#[test]
+pub fn test_description_creates_ff_device() -> Result<(), Box<dyn std::error::Error>> {
+    use evdev::evemu::{Description, DEFAULT_MAX_FF_EFFECTS};
+    use evdev::{AttributeSet, BusType, EventType, FFEffectCode, InputId};
+
+    let mut description = Description {
+        name: "test ff description".to_owned(),
+        input_id: InputId::new(BusType::BUS_USB, 0x1234, 0x5678, 0x111),
+        properties: AttributeSet::new(),
+        event_types: [EventType::FORCEFEEDBACK].into_iter().collect(),
+        keys: AttributeSet::new(),
+        relative_axes: AttributeSet::new(),
+        absolute_axes: Vec::new(),
+        misc: AttributeSet::new(),
+        switches: AttributeSet::new(),
+        leds: AttributeSet::new(),
+        sounds: AttributeSet::new(),
+        ff: [FFEffectCode::FF_RUMBLE].into_iter().collect(),
+        max_ff_effects: DEFAULT_MAX_FF_EFFECTS,
+    };
+    let mut output = description.create_virtual_device()?;
+    let path = output.enumerate_dev_nodes_blocking()?.next().unwrap()?;
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    let input = Device::open(path)?;
+    assert_eq!(input.max_ff_effects(), DEFAULT_MAX_FF_EFFECTS);
+    assert!(input
+        .supported_ff()
+        .unwrap()
+        .contains(FFEffectCode::FF_RUMBLE));
+
+    // without room for effects, force feedback is left out
+    description.max_ff_effects = 0;
+    let mut output = description.create_virtual_device()?;
+    let path = output.enumerate_dev_nodes_blocking()?.next().unwrap()?;
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    assert!(Device::open(path)?.supported_ff().is_none());
+
+    Ok(())
+}
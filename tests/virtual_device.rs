@@ -43,7 +43,7 @@ async fn test_virtual_device_actually_emits() -> Result<(), Box<dyn Error>> {
     });
 
     // emit a key code through virtual device
-    let down_event = InputEvent::new(type_.0, code, 10);
+    let down_event = InputEvent::new(type_, code, 10);
     device.emit(&[down_event]).unwrap();
 
     let event = timeout(Duration::from_secs(1), listener).await???;